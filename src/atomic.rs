@@ -0,0 +1,127 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Replace a file's contents without ever leaving it half written.
+///
+/// The new contents are written to `.<name>.clack-tmp` beside the file and
+/// renamed over it, so a crash part of the way through leaves the old file
+/// as it was. The new contents are flushed to the disk before the rename,
+/// and the rename after it, so a power cut can't leave an empty file. The
+/// new file gets the old one's permissions and, where it's allowed, its
+/// owner. A symlink is followed, so the file it points to is replaced
+/// rather than the link.
+///
+/// # Arguments
+///
+/// * `file_name` - The file to write.
+/// * `keep_backup` - Whether to copy the old file to `<name>~` first.
+/// * `write` - Writes the new contents to the path it's given, which has
+///   already been created, empty, with the old file's permissions.
+///
+pub fn replace<F>(file_name: &str, keep_backup: bool, write: F) -> Result<(), io::Error>
+where
+    F: FnOnce(&Path) -> Result<(), io::Error>,
+{
+    let path = fs::canonicalize(file_name).unwrap_or_else(|_| PathBuf::from(file_name));
+    let original = fs::metadata(&path).ok();
+    let temporary = sibling(&path, ".", ".clack-tmp");
+    // A leftover from a crash would stop the temporary file being created.
+    let _ = fs::remove_file(&temporary);
+    // Creating the temporary file before anything is written to it means
+    // the contents are never readable by more people than the old file's
+    // were, and a symlink planted at its path is refused, not followed.
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if let Some(original) = &original {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(original.permissions().mode() & 0o777);
+    }
+    options.open(&temporary)?;
+    let replaced = write(&temporary).and_then(|()| {
+        if let Some(original) = &original {
+            fs::set_permissions(&temporary, original.permissions())?;
+            // Only root (or the owner, for the group) can change ownership;
+            // anyone else gets a file they own, as any editor would give them.
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::MetadataExt;
+                let _ = std::os::unix::fs::chown(
+                    &temporary,
+                    Some(original.uid()),
+                    Some(original.gid()),
+                );
+            }
+            if keep_backup {
+                fs::copy(&path, sibling(&path, "", "~"))?;
+            }
+        }
+        OpenOptions::new()
+            .write(true)
+            .open(&temporary)?
+            .sync_all()?;
+        fs::rename(&temporary, &path)?;
+        // The file is replaced either way; this only makes the rename
+        // survive a power cut, so there's nothing to report if it fails.
+        #[cfg(unix)]
+        if let Some(directory) = path.parent() {
+            let _ = fs::File::open(directory).and_then(|directory| directory.sync_all());
+        }
+        Ok(())
+    });
+    if replaced.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    replaced
+}
+
+/// Whether `replace` can write a file, found by creating its temporary
+/// file beside it and removing it again: the directory has to be writable,
+/// not just the file.
+pub fn can_replace(file_name: &str) -> bool {
+    let path = fs::canonicalize(file_name).unwrap_or_else(|_| PathBuf::from(file_name));
+    let temporary = sibling(&path, ".", ".clack-tmp");
+    match OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temporary)
+    {
+        Ok(_) => {
+            let _ = fs::remove_file(&temporary);
+            true
+        }
+        // A save is being written now.
+        Err(error) => error.kind() == io::ErrorKind::AlreadyExists,
+    }
+}
+
+/// Create a file that nobody can read who couldn't read `like`: it gets
+/// `like`'s permissions, or is private to the user if `like` doesn't exist.
+/// Like `replace`, this refuses to follow a symlink or reuse a file that's
+/// already at `path`.
+pub fn create_like(path: &Path, like: &Path) -> Result<File, io::Error> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(
+            fs::metadata(like).map_or(0o600, |metadata| metadata.permissions().mode() & 0o777),
+        );
+    }
+    #[cfg(not(unix))]
+    let _ = like;
+    options.open(path)
+}
+
+/// Get the path of a file beside `path`, named by adding to its name.
+fn sibling(path: &Path, prefix: &str, suffix: &str) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}{}{}", prefix, name, suffix))
+}
@@ -0,0 +1,111 @@
+use crate::{Document, Position};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Bracket pairs, with the names spoken for the opener and the closer.
+const PAIRS: &[(char, char, &str, &str)] = &[
+    ('(', ')', "open paren", "close paren"),
+    ('[', ']', "open bracket", "close bracket"),
+    ('{', '}', "open brace", "close brace"),
+];
+
+/// The pairs typed together when auto-pairing, with the name spoken for
+/// each pair as it's typed.
+const AUTO_PAIRS: &[(char, char, &str)] = &[
+    ('(', ')', "parens"),
+    ('[', ']', "brackets"),
+    ('{', '}', "braces"),
+    ('"', '"', "quotes"),
+    ('\'', '\'', "single quotes"),
+    ('`', '`', "backticks"),
+];
+
+/// Get the character that closes an auto-paired opener, and the pair's
+/// spoken name, e.g. `)` and "parens" for `(`.
+pub fn auto_pair(c: char) -> Option<(char, &'static str)> {
+    AUTO_PAIRS
+        .iter()
+        .find(|&&(open, _, _)| open == c)
+        .map(|&(_, close, name)| (close, name))
+}
+
+/// Get the spoken name of a bracket, e.g. "close paren".
+pub fn bracket_name(c: char) -> Option<&'static str> {
+    PAIRS
+        .iter()
+        .find_map(|&(open, close, open_name, close_name)| {
+            if c == open {
+                Some(open_name)
+            } else if c == close {
+                Some(close_name)
+            } else {
+                None
+            }
+        })
+}
+
+/// Check whether a character closes a bracket pair.
+pub fn is_closer(c: char) -> bool {
+    PAIRS.iter().any(|&(_, close, _, _)| c == close)
+}
+
+/// Check whether a character is a bracket of either kind.
+pub fn is_bracket(c: char) -> bool {
+    bracket_name(c).is_some()
+}
+
+/// Find the bracket that pairs with the one at a position.
+///
+/// Only brackets of the same kind are counted, so a stray `]` doesn't stop
+/// a `(` from finding its `)`. Brackets in strings and comments count too.
+///
+/// # Returns
+///
+/// The partner's position and character, or None if there's no bracket at
+/// the position or it has no partner.
+///
+pub fn matching_bracket(document: &Document, at: &Position) -> Option<(Position, char)> {
+    let c = document
+        .get_row(at.y)?
+        .as_str()
+        .graphemes(true)
+        .nth(at.x)?
+        .chars()
+        .next()?;
+    let (partner, forward) = PAIRS.iter().find_map(|&(open, close, _, _)| {
+        if c == open {
+            Some((close, true))
+        } else if c == close {
+            Some((open, false))
+        } else {
+            None
+        }
+    })?;
+    let mut depth = 0_usize;
+    let rows: Box<dyn Iterator<Item = usize>> = if forward {
+        Box::new(at.y..document.row_count())
+    } else {
+        Box::new((0..=at.y).rev())
+    };
+    for y in rows {
+        let row = document.get_row(y)?;
+        let mut graphemes: Vec<(usize, &str)> = row.as_str().graphemes(true).enumerate().collect();
+        if y == at.y {
+            graphemes.retain(|&(x, _)| if forward { x > at.x } else { x < at.x });
+        }
+        if !forward {
+            graphemes.reverse();
+        }
+        for (x, grapheme) in graphemes {
+            let g = grapheme.chars().next();
+            if g == Some(c) {
+                depth += 1;
+            } else if g == Some(partner) {
+                if depth == 0 {
+                    return Some((Position { x, y }, partner));
+                }
+                depth -= 1;
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,299 @@
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    net::TcpStream,
+    thread,
+    time::Instant,
+};
+
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+/// The version of the BrlAPI protocol spoken, as in BRLTTY 5 and 6.
+const PROTOCOL_VERSION: u32 = 8;
+
+/// The port BrlAPI listens on for display 0 over TCP; display n is on the
+/// port n after it.
+const BASE_PORT: u16 = 4101;
+
+/// Where BrlAPI's local sockets are, one per display, named by its number.
+const SOCKET_DIRECTORIES: &[&str] = &["/var/lib/brltty/BrlAPI", "/var/lib/BrlAPI"];
+
+/// The key file BrlAPI clients authenticate with.
+const DEFAULT_KEY_FILE: &str = "/etc/brlapi.key";
+
+/// The two ends of a connection to BrlAPI.
+type Connection = (Box<dyn Read + Send>, Box<dyn Write + Send>);
+
+// Packet types.
+const VERSION: u32 = b'v' as u32;
+const AUTH: u32 = b'a' as u32;
+const GET_DISPLAY_SIZE: u32 = b's' as u32;
+const ENTER_TTY_MODE: u32 = b't' as u32;
+const LEAVE_TTY_MODE: u32 = b'L' as u32;
+const IGNORE_KEY_RANGES: u32 = b'm' as u32;
+const WRITE: u32 = b'w' as u32;
+const ACK: u32 = b'A' as u32;
+const ERROR: u32 = b'e' as u32;
+const EXCEPTION: u32 = b'E' as u32;
+
+// Authentication methods.
+const AUTH_NONE: u32 = b'N' as u32;
+const AUTH_KEY: u32 = b'K' as u32;
+const AUTH_CREDENTIALS: u32 = b'C' as u32;
+
+// What a write packet carries.
+const WRITE_REGION: u32 = 0x02;
+const WRITE_TEXT: u32 = 0x04;
+const WRITE_CURSOR: u32 = 0x20;
+const WRITE_CHARSET: u32 = 0x40;
+
+/// A braille display, reached through BRLTTY's BrlAPI, that mirrors the
+/// line being edited with the cursor on it, or the latest status message.
+///
+/// Lines longer than the display are shown a display's width at a time,
+/// panning to keep the cursor in view. The display's own keys are left to
+/// BRLTTY.
+pub struct Braille {
+    writer: Box<dyn Write + Send>,
+    /// How many cells the display has.
+    width: usize,
+    /// The line and cursor column last shown, to skip writing them again.
+    shown_line: Option<(String, usize)>,
+    /// When the last status message shown was set.
+    shown_message: Option<Instant>,
+}
+
+impl Braille {
+    /// Connect to BRLTTY and take over the display for this terminal.
+    ///
+    /// The display is found as BrlAPI clients do: `BRLAPI_HOST` (like `:0`
+    /// or `host:1`), or else display 0 on this machine, with the key in
+    /// `BRLAPI_AUTH` or `/etc/brlapi.key` if BRLTTY asks for one.
+    ///
+    /// # Returns
+    ///
+    /// The display, or a description of why it couldn't be used.
+    ///
+    pub fn connect() -> Result<Self, String> {
+        let (mut reader, writer) = open_connection()?;
+        let mut braille = Self {
+            writer,
+            width: 0,
+            shown_line: None,
+            shown_message: None,
+        };
+        braille
+            .handshake(&mut reader)
+            .map_err(|error| error.to_string())?;
+        // Acknowledgements and keys arrive from now on; nothing waits for
+        // them, so they're read and dropped in the background.
+        thread::spawn(move || while read_packet(&mut reader).is_ok() {});
+        Ok(braille)
+    }
+
+    /// Agree on the protocol, authenticate, and enter tty mode.
+    fn handshake(&mut self, reader: &mut dyn Read) -> Result<(), io::Error> {
+        expect(reader, VERSION)?;
+        self.send(VERSION, &PROTOCOL_VERSION.to_be_bytes())?;
+        let methods = expect(reader, AUTH)?;
+        let methods: Vec<u32> = methods
+            .chunks_exact(4)
+            .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        if !methods.contains(&AUTH_NONE) {
+            if methods.contains(&AUTH_KEY) {
+                let key_file =
+                    env::var("BRLAPI_AUTH").unwrap_or_else(|_| String::from(DEFAULT_KEY_FILE));
+                let key_file = key_file.strip_prefix("keyfile:").unwrap_or(&key_file);
+                let mut packet = AUTH_KEY.to_be_bytes().to_vec();
+                packet.extend(fs::read(key_file)?);
+                self.send(AUTH, &packet)?;
+            } else if methods.contains(&AUTH_CREDENTIALS) {
+                self.send(AUTH, &AUTH_CREDENTIALS.to_be_bytes())?;
+            } else {
+                return Err(other(
+                    "BRLTTY asked for an unsupported kind of authentication",
+                ));
+            }
+            expect(reader, ACK)?;
+        }
+        self.send(GET_DISPLAY_SIZE, &[])?;
+        let size = expect(reader, GET_DISPLAY_SIZE)?;
+        if size.len() < 8 {
+            return Err(other("BRLTTY sent a short display size"));
+        }
+        let columns = u32::from_be_bytes([size[0], size[1], size[2], size[3]]);
+        let rows = u32::from_be_bytes([size[4], size[5], size[6], size[7]]);
+        self.width = (columns * rows.max(1)) as usize;
+        if self.width == 0 {
+            return Err(other("there's no braille display connected to BRLTTY"));
+        }
+        let ttys = tty_path();
+        let mut packet = (ttys.len() as u32).to_be_bytes().to_vec();
+        for tty in ttys {
+            packet.extend(tty.to_be_bytes());
+        }
+        // No driver name: keys would come as BRLTTY commands.
+        packet.push(0);
+        self.send(ENTER_TTY_MODE, &packet)?;
+        expect(reader, ACK)?;
+        // Leave every key to BRLTTY.
+        let mut ranges = Vec::new();
+        for bound in [0, u64::MAX] {
+            ranges.extend(((bound >> 32) as u32).to_be_bytes());
+            ranges.extend((bound as u32).to_be_bytes());
+        }
+        self.send(IGNORE_KEY_RANGES, &ranges)?;
+        expect(reader, ACK)?;
+        Ok(())
+    }
+
+    /// Show what's changed: a status message newer than the last one shown,
+    /// or else the line and cursor if they've moved or been edited. A
+    /// message stays up until the line or cursor changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The line the cursor is on.
+    /// * `cursor` - The cursor's column in it, counting from 0.
+    /// * `message` - The status message, and when it was set.
+    ///
+    pub fn update(&mut self, line: &str, cursor: usize, message: (&str, Instant)) {
+        let (message, message_time) = message;
+        let current = (line.to_string(), cursor);
+        if !message.is_empty() && self.shown_message != Some(message_time) {
+            self.shown_message = Some(message_time);
+            self.show(message, None);
+            self.shown_line = Some(current);
+            return;
+        }
+        if self.shown_line.as_ref() == Some(&current) {
+            return;
+        }
+        let start = cursor / self.width * self.width;
+        let window: String = line
+            .chars()
+            .map(|c| if c == '\t' { ' ' } else { c })
+            .skip(start)
+            .take(self.width)
+            .collect();
+        self.show(&window, Some(cursor - start));
+        self.shown_line = Some(current);
+    }
+
+    /// Write text across the display, with the cursor at a cell if given.
+    /// A display that's gone away is ignored, as speech carries on.
+    fn show(&mut self, text: &str, cursor: Option<usize>) {
+        let mut cells: String = text.chars().take(self.width).collect();
+        let len = cells.chars().count();
+        cells.extend(std::iter::repeat_n(' ', self.width - len));
+        let mut packet = Vec::new();
+        packet.extend((WRITE_REGION | WRITE_TEXT | WRITE_CURSOR | WRITE_CHARSET).to_be_bytes());
+        packet.extend(1_u32.to_be_bytes());
+        packet.extend((self.width as u32).to_be_bytes());
+        packet.extend((cells.len() as u32).to_be_bytes());
+        packet.extend(cells.as_bytes());
+        // Cells count from 1, and 0 hides the cursor.
+        packet.extend(cursor.map_or(0, |cursor| cursor as u32 + 1).to_be_bytes());
+        let charset = b"UTF-8";
+        packet.push(charset.len() as u8);
+        packet.extend(charset);
+        let _ = self.send(WRITE, &packet);
+    }
+
+    fn send(&mut self, packet_type: u32, payload: &[u8]) -> Result<(), io::Error> {
+        let mut packet = (payload.len() as u32).to_be_bytes().to_vec();
+        packet.extend(packet_type.to_be_bytes());
+        packet.extend(payload);
+        self.writer.write_all(&packet)
+    }
+}
+
+impl Drop for Braille {
+    /// Give the display back to BRLTTY.
+    fn drop(&mut self) {
+        let _ = self.send(LEAVE_TTY_MODE, &[]);
+    }
+}
+
+/// Open a connection to BrlAPI, as a reader and a writer.
+fn open_connection() -> Result<Connection, String> {
+    let host = env::var("BRLAPI_HOST").unwrap_or_else(|_| String::from(":0"));
+    let (name, display) = host.rsplit_once(':').unwrap_or((host.as_str(), "0"));
+    let display: u16 = display.parse().unwrap_or(0);
+    #[cfg(unix)]
+    if name.is_empty() {
+        let stream = SOCKET_DIRECTORIES
+            .iter()
+            .find_map(|dir| UnixStream::connect(format!("{}/{}", dir, display)).ok());
+        if let Some(stream) = stream {
+            let reader = stream.try_clone().map_err(|error| error.to_string())?;
+            return Ok((Box::new(reader), Box::new(stream)));
+        }
+    }
+    let name = if name.is_empty() { "localhost" } else { name };
+    let stream = TcpStream::connect((name, BASE_PORT + display))
+        .map_err(|error| format!("Couldn't connect to BRLTTY at {}: {}", host, error))?;
+    let reader = stream.try_clone().map_err(|error| error.to_string())?;
+    Ok((Box::new(reader), Box::new(stream)))
+}
+
+/// Get the path of this terminal for BRLTTY: the X display's virtual
+/// terminal from `WINDOWPATH`, then the terminal window (`WINDOWID`) or the
+/// console's virtual terminal.
+fn tty_path() -> Vec<u32> {
+    let mut path: Vec<u32> = env::var("WINDOWPATH")
+        .unwrap_or_default()
+        .split(':')
+        .filter_map(|part| part.parse().ok())
+        .collect();
+    let tty = env::var("CONTROLVT")
+        .or_else(|_| env::var("WINDOWID"))
+        .ok()
+        .and_then(|tty| tty.parse().ok())
+        .or_else(console_number);
+    path.extend(tty);
+    path
+}
+
+/// Get the number of the Linux virtual console clack is running on, if it
+/// is on one.
+fn console_number() -> Option<u32> {
+    let tty = fs::read_link("/proc/self/fd/0").ok()?;
+    tty.to_str()?.strip_prefix("/dev/tty")?.parse().ok()
+}
+
+/// Read one packet: its type and payload.
+fn read_packet(reader: &mut dyn Read) -> Result<(u32, Vec<u8>), io::Error> {
+    let mut header = [0; 8];
+    reader.read_exact(&mut header)?;
+    let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+    let packet_type = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+    let mut payload = vec![0; size as usize];
+    reader.read_exact(&mut payload)?;
+    Ok((packet_type, payload))
+}
+
+/// Read the next packet, which must be of a given type, and get its
+/// payload. An error packet from BRLTTY is turned into an error.
+fn expect(reader: &mut dyn Read, wanted: u32) -> Result<Vec<u8>, io::Error> {
+    let (packet_type, payload) = read_packet(reader)?;
+    match packet_type {
+        packet_type if packet_type == wanted => Ok(payload),
+        ERROR | EXCEPTION => {
+            let code = payload.get(..4).map_or(0, |bytes| {
+                u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+            });
+            Err(other(&format!(
+                "BRLTTY refused the connection (error {})",
+                code
+            )))
+        }
+        _ => Err(other("BRLTTY sent something unexpected")),
+    }
+}
+
+fn other(message: &str) -> io::Error {
+    io::Error::other(message.to_string())
+}
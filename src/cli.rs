@@ -0,0 +1,188 @@
+use std::path::Path;
+
+/// What `clack --help` prints, and what's printed after a command line
+/// clack can't read.
+pub const USAGE: &str = "\
+Usage: clack [options] [[+line[:column]] file[:line[:column]]]...
+
+Opens each file in its own buffer. A file can be opened at a line (and
+column), counting from 1, either with +line before it or :line after it, as
+compilers and grep -n print them. + alone opens the file at its last line.
+
+Options:
+  --silent                Start with sound off.
+  --screen-reader         Write speech for a screen reader instead of speaking.
+  --read-only             Open the files read only.
+  --replay                Make the changes a crashed session left unsaved.
+  --doctor                Check that speech, audio and the config work.
+  --write-default-config  Write a config file with every setting explained.
+  --help                  Print this.
+  --                      Treat everything after it as a file name.
+";
+
+/// What clack was asked to do on its command line.
+pub enum Command {
+    /// Edit files.
+    Edit(Options),
+    /// Check the setup, for `--doctor`.
+    Doctor,
+    /// Write the default config file, for `--write-default-config`.
+    WriteDefaultConfig,
+    /// Print the usage, for `--help`.
+    Help,
+}
+
+/// How to start editing.
+#[derive(Default)]
+pub struct Options {
+    pub files: Vec<FileArgument>,
+    /// Whether to start with sound off.
+    pub silent: bool,
+    /// Whether to write speech for a screen reader instead of speaking it.
+    pub screen_reader: bool,
+    /// Whether to open the files read only.
+    pub read_only: bool,
+    /// Whether to replay the changes left unsaved in the files' journals.
+    pub replay: bool,
+}
+
+/// A file named on the command line, with where to put the cursor in it.
+pub struct FileArgument {
+    pub file_name: String,
+    /// The line to start on, counting from 1, or None for the top.
+    pub line: Option<usize>,
+    /// The column to start at, counting from 1, or None for the start of
+    /// the line.
+    pub column: Option<usize>,
+}
+
+/// Read clack's command line.
+///
+/// # Arguments
+///
+/// * `arguments` - The arguments, without the program's name.
+///
+/// # Returns
+///
+/// What to do, or a description of what's wrong with the command line.
+///
+pub fn parse<I>(arguments: I) -> Result<Command, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut options = Options::default();
+    // A `+line` waiting for the file it goes with.
+    let mut pending: Option<(usize, Option<usize>)> = None;
+    let mut only_files = false;
+    for argument in arguments {
+        if !only_files {
+            match argument.as_str() {
+                "--" => {
+                    only_files = true;
+                    continue;
+                }
+                "--silent" => {
+                    options.silent = true;
+                    continue;
+                }
+                "--screen-reader" => {
+                    options.screen_reader = true;
+                    continue;
+                }
+                "--read-only" => {
+                    options.read_only = true;
+                    continue;
+                }
+                "--replay" => {
+                    options.replay = true;
+                    continue;
+                }
+                "--doctor" => return Ok(Command::Doctor),
+                "--write-default-config" => return Ok(Command::WriteDefaultConfig),
+                "--help" | "-h" => return Ok(Command::Help),
+                option if option.starts_with("--") => {
+                    return Err(format!("Unknown option {}.", option));
+                }
+                _ => (),
+            }
+            if let Some(target) = argument.strip_prefix('+') {
+                if pending.is_some() {
+                    return Err(format!("{} follows another +line.", argument));
+                }
+                pending = Some(
+                    parse_target(target)
+                        .ok_or_else(|| format!("Not a line number: {}.", argument))?,
+                );
+                continue;
+            }
+        }
+        let file = match pending.take() {
+            Some((line, column)) => FileArgument {
+                file_name: argument,
+                line: Some(line),
+                column,
+            },
+            None => split_position(argument),
+        };
+        options.files.push(file);
+    }
+    if pending.is_some() {
+        return Err(String::from("A +line must come before the file it's for."));
+    }
+    Ok(Command::Edit(options))
+}
+
+/// Read the `line` or `line:column` of a `+line` argument. An empty one
+/// means the last line.
+fn parse_target(text: &str) -> Option<(usize, Option<usize>)> {
+    if text.is_empty() {
+        return Some((usize::MAX, None));
+    }
+    match text.split_once(':') {
+        Some((line, column)) => Some((line.parse().ok()?, Some(column.parse().ok()?))),
+        None => Some((text.parse().ok()?, None)),
+    }
+}
+
+/// Split a `file:line` or `file:line:column` argument, as printed by
+/// compilers and `grep -n`, into the file and the position. A trailing
+/// colon is allowed, and a file whose name really ends in `:<number>` is
+/// left alone.
+fn split_position(argument: String) -> FileArgument {
+    let whole = |file_name: String| FileArgument {
+        file_name,
+        line: None,
+        column: None,
+    };
+    if Path::new(&argument).exists() {
+        return whole(argument);
+    }
+    let mut rest = argument.strip_suffix(':').unwrap_or(&argument);
+    let mut numbers = Vec::new();
+    while numbers.len() < 2 {
+        match rest.rsplit_once(':') {
+            Some((before, number)) if !before.is_empty() => match number.parse::<usize>() {
+                Ok(number) => {
+                    numbers.push(number);
+                    rest = before;
+                }
+                Err(_) => break,
+            },
+            _ => break,
+        }
+    }
+    let file_name = rest.to_string();
+    match numbers[..] {
+        [line] => FileArgument {
+            file_name,
+            line: Some(line),
+            column: None,
+        },
+        [column, line] => FileArgument {
+            file_name,
+            line: Some(line),
+            column: Some(column),
+        },
+        _ => whole(argument),
+    }
+}
@@ -0,0 +1,85 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use crate::utils::is_on_path;
+
+/// Commands that copy stdin to the system clipboard, in order of preference.
+const COPY_COMMANDS: &[&[&str]] = &[
+    &["pbcopy"],
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+    &["clip.exe"],
+];
+
+/// Commands that print the system clipboard, in order of preference.
+const PASTE_COMMANDS: &[&[&str]] = &[
+    &["pbpaste"],
+    &["wl-paste", "--no-newline"],
+    &["xclip", "-selection", "clipboard", "-out"],
+    &["xsel", "--clipboard", "--output"],
+    &["powershell.exe", "-NoProfile", "-Command", "Get-Clipboard"],
+];
+
+fn first_available(commands: &[&'static [&'static str]]) -> Option<Command> {
+    let args = commands.iter().find(|args| is_on_path(args[0]))?;
+    let mut command = Command::new(args[0]);
+    command.args(&args[1..]);
+    Some(command)
+}
+
+/// Get the name of the tool used to copy to the clipboard, if there is one.
+pub fn copy_tool() -> Option<&'static str> {
+    COPY_COMMANDS
+        .iter()
+        .map(|args| args[0])
+        .find(|program| is_on_path(program))
+}
+
+/// Copy text to the system clipboard.
+///
+/// # Returns
+///
+/// Whether a clipboard tool was found and accepted the text.
+///
+pub fn copy(text: &str) -> bool {
+    let mut command = match first_available(COPY_COMMANDS) {
+        Some(command) => command,
+        None => return false,
+    };
+    let child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(_) => return false,
+    };
+    let written = child
+        .stdin
+        .take()
+        .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+    child.wait().is_ok_and(|status| status.success()) && written
+}
+
+/// Get the text on the system clipboard.
+///
+/// # Returns
+///
+/// The text, or None if there's no clipboard tool or it failed.
+///
+pub fn paste() -> Option<String> {
+    let output = first_available(PASTE_COMMANDS)?
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|text| text.replace("\r\n", "\n"))
+}
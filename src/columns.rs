@@ -0,0 +1,89 @@
+use crate::Row;
+use serde::Deserialize;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A field in a fixed-width column layout, from the `[columns]` table in
+/// the config file: a range like `"12-18"` (or a single column, `"7"`), or
+/// a table naming the range.
+#[derive(Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ColumnField {
+    Range(String),
+    Named { columns: String, name: String },
+}
+
+impl ColumnField {
+    fn columns(&self) -> &str {
+        match self {
+            Self::Range(columns) | Self::Named { columns, .. } => columns,
+        }
+    }
+
+    fn name(&self) -> Option<&str> {
+        match self {
+            Self::Range(_) => None,
+            Self::Named { name, .. } => Some(name),
+        }
+    }
+}
+
+/// Parse a 1-based, inclusive column range like `"12-18"` or `"7"`.
+fn parse_range(columns: &str) -> Option<(usize, usize)> {
+    let (first, last) = match columns.split_once('-') {
+        Some((first, last)) => (first.trim().parse().ok()?, last.trim().parse().ok()?),
+        None => {
+            let column = columns.trim().parse().ok()?;
+            (column, column)
+        }
+    };
+    if first == 0 || last < first {
+        return None;
+    }
+    Some((first, last))
+}
+
+/// The text of one field of a line.
+pub struct ColumnReading<'a> {
+    /// "column 7" or "column 12 through 18".
+    pub label: String,
+    pub name: Option<&'a str>,
+    /// The field's text with trailing padding removed; empty if it's blank.
+    pub text: String,
+}
+
+/// Read the fields of a line according to a column layout.
+///
+/// # Returns
+///
+/// The fields in layout order, or Err naming the first range that couldn't
+/// be understood.
+///
+pub fn read_columns<'a>(
+    row: &Row,
+    layout: &'a [ColumnField],
+) -> Result<Vec<ColumnReading<'a>>, String> {
+    let graphemes: Vec<&str> = row.as_str().graphemes(true).collect();
+    layout
+        .iter()
+        .map(|field| {
+            let (first, last) = parse_range(field.columns())
+                .ok_or_else(|| format!("Bad column range \"{}\".", field.columns()))?;
+            let label = if first == last {
+                format!("column {}", first)
+            } else {
+                format!("column {} through {}", first, last)
+            };
+            let text: String = graphemes
+                .iter()
+                .skip(first - 1)
+                .take(last - first + 1)
+                .copied()
+                .collect();
+            Ok(ColumnReading {
+                label,
+                name: field.name(),
+                text: text.trim_end().to_string(),
+            })
+        })
+        .collect()
+}
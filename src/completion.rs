@@ -0,0 +1,74 @@
+use crate::{Document, Position};
+use std::collections::HashMap;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How far from the cursor words in other buffers count as being, so that
+/// they're offered after words from the document being edited.
+const OTHER_BUFFER_DISTANCE: usize = 1000;
+
+/// Find the words in a document, and then in the other open documents,
+/// that complete a prefix, best first.
+///
+/// Candidates are ranked by how often they're used, weighted towards words
+/// used near the cursor, so that the one wanted is usually spoken first.
+///
+/// # Arguments
+///
+/// * `document` - The document to take words from.
+/// * `at` - The cursor, at the end of the prefix.
+/// * `prefix` - The start of the word being typed.
+/// * `others` - The other open documents, whose words are offered too.
+///
+pub fn ranked_completions(
+    document: &Document,
+    at: &Position,
+    prefix: &str,
+    others: &[&Document],
+) -> Vec<String> {
+    // For each candidate: how many times it's used, and the nearest line it's
+    // used on.
+    let mut candidates: HashMap<&str, (usize, usize)> = HashMap::new();
+    for other in others {
+        for row in (0..other.row_count()).filter_map(|y| other.get_row(y)) {
+            for (_, word) in row.words() {
+                if word.len() > prefix.len() && word.starts_with(prefix) {
+                    let (count, _) = candidates.entry(word).or_insert((0, OTHER_BUFFER_DISTANCE));
+                    *count += 1;
+                }
+            }
+        }
+    }
+    for y in 0..document.row_count() {
+        let row = match document.get_row(y) {
+            Some(row) => row,
+            None => continue,
+        };
+        let distance = y.abs_diff(at.y);
+        for (start, word) in row.words() {
+            let is_word_being_typed =
+                y == at.y && start < at.x && at.x <= start + word.graphemes(true).count();
+            if is_word_being_typed || word.len() <= prefix.len() || !word.starts_with(prefix) {
+                continue;
+            }
+            let (count, nearest) = candidates.entry(word).or_insert((0, distance));
+            *count += 1;
+            *nearest = (*nearest).min(distance);
+        }
+    }
+    let mut ranked: Vec<(&str, f64)> = candidates
+        .into_iter()
+        .map(|(word, (count, nearest))| (word, score(count, nearest)))
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    ranked
+        .into_iter()
+        .map(|(word, _)| word.to_string())
+        .collect()
+}
+
+/// Score a candidate: one point per use, plus up to ten for being used
+/// close to the cursor.
+#[allow(clippy::cast_precision_loss)]
+fn score(count: usize, nearest: usize) -> f64 {
+    count as f64 + 10.0 / (1.0 + nearest as f64 / 10.0)
+}
@@ -1,47 +1,766 @@
-use std::{fs::File, io::Read};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 /// This module contains configuration logic for reading and writing
 /// a clack config file.
+use crate::columns::ColumnField;
+use crate::earcon::{Earcon, EarconTheme, SoundDefinition, THEME_NAMES};
+use crate::sound::DEFAULT_AUDIO_BUDGET_MS;
+use crate::utils::{
+    CapitalReading, ContextVoices, IdentifierReading, IndentReading, LinkReadingMode,
+    NumberReading, PunctuationLevel, SpeechOptions, TokenVoices, TypingEcho, WrappedLineReading,
+};
+use crate::verbosity::{AnnouncementClass, Verbosity, VerbositySettings};
 use dirs::home_dir;
-use toml::Value;
+use serde::Deserialize;
+use toml::value::Table;
 
 const DEFAULT_CONFIG_PATH: &str = ".config/clack/config.toml";
 
 pub(crate) const DEFAULT_RATE_WPM: i64 = 300;
 
-pub fn read_config() -> Value {
-    let config_path = home_dir().unwrap().join(DEFAULT_CONFIG_PATH);
+/// The slowest and fastest speech rates allowed, in words per minute.
+pub(crate) const MIN_RATE_WPM: i64 = 100;
+pub(crate) const MAX_RATE_WPM: i64 = 700;
 
-    // If the config file doesn't exist, create it with the default settings.
+/// A config file with every setting at its default, explained, written by
+/// `clack --write-default-config`.
+const DEFAULT_CONFIG_FILE: &str = include_str!("default_config.toml");
+
+/// The command used to record voice memos. `{file}` is replaced with the
+/// path to record into; the recording is stopped with an interrupt signal.
+pub(crate) const DEFAULT_MEMO_RECORD_COMMAND: &str = "rec -q {file}";
+
+/// The settings in the config file. Anything left out takes its default.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Speech rate, in words per minute.
+    pub rate_wpm: i64,
+    /// The voice (or language) to speak with, unless a file has its own.
+    pub voice: Option<String>,
+    /// The text-to-speech backend to use, or "auto" to detect one.
+    pub speech_backend: String,
+    /// The volume of tones and sound clips, where 1.0 is full volume.
+    pub volume: f32,
+    /// The volume of speech, as a fraction of the speech engine's usual
+    /// volume.
+    pub speech_volume: f32,
+    /// How loud tones and sound clips are while speech plays, as a fraction
+    /// of their volume.
+    pub earcon_ducking: f32,
+    /// How far apart the document's text and other announcements are
+    /// panned, from 0.0 to 1.0.
+    pub stereo_speech: f32,
+    /// How far tones and clips are panned by the cursor's column, from 0.0
+    /// (not at all) to 1.0 (from hard left to hard right).
+    pub column_panning: f32,
+    /// Whether speech is panned by the cursor's column too.
+    pub column_panning_speech: bool,
+    /// Whether moving up and down plays a tone pitched by how far through
+    /// the file the line is.
+    pub position_tones: bool,
+    /// Whether clack starts in night mode, with everything played quietly.
+    pub night_mode: bool,
+    /// Whether speech is written to the screen for a screen reader instead
+    /// of spoken.
+    pub screen_reader: bool,
+    /// Whether the current line and status messages are shown on a braille
+    /// display through BrlAPI.
+    pub braille: bool,
+    /// The name of the built-in earcon theme.
+    pub earcon_theme: String,
+    /// Sounds that replace the theme's, by event.
+    pub earcons: HashMap<Earcon, SoundDefinition>,
+    /// How long a low-priority sound may wait before it's dropped, in
+    /// milliseconds.
+    pub audio_budget_ms: u64,
+    /// Whether a keypress silences speech that is still playing.
+    pub interrupt_speech_on_keypress: bool,
+    /// What is spoken while typing.
+    pub typing_echo: TypingEcho,
+    /// How URLs and paths are read aloud.
+    pub link_reading: LinkReadingMode,
+    /// How many symbols are named when text is read aloud.
+    pub punctuation: PunctuationLevel,
+    /// Whether camelCase and snake_case identifiers are read word by word.
+    pub identifiers: IdentifierReading,
+    /// Whether numbers are read as values or digit by digit.
+    pub numbers: NumberReading,
+    /// Whether a run of one symbol is named once with how many there are.
+    pub count_repeated_symbols: bool,
+    /// How capital letters are pointed out when spelling and typing.
+    pub capitals: CapitalReading,
+    /// Voice settings for string literals and comments.
+    pub token_voices: TokenVoices,
+    /// Voice settings for messages, document text, and spelling.
+    pub context_voices: ContextVoices,
+    /// How many spaces make one level of indentation, and how far apart
+    /// tab stops are drawn.
+    pub tab_width: usize,
+    /// Whether Tab inserts spaces up to the next tab stop instead of a tab.
+    pub insert_spaces: bool,
+    /// Whether saving keeps the previous version of a file as `<name>~`.
+    pub keep_backups: bool,
+    /// How indentation is heard: as tones, as block changes, as the level
+    /// before each line or only when it changes, or not at all.
+    pub indentation: IndentReading,
+    /// Whether to edit vi-style, with normal, insert and visual modes.
+    pub modal_editing: bool,
+    /// Whether Left and Right wrap onto the previous and next lines.
+    pub wrap: bool,
+    /// Whether line numbers are drawn beside each line.
+    pub show_line_numbers: bool,
+    /// Whether a line's number is spoken before it's read.
+    pub speak_line_numbers: bool,
+    /// Whether long lines are broken across screen lines instead of
+    /// scrolling sideways.
+    pub soft_wrap: bool,
+    /// Whether reading a soft-wrapped line reads all of it or just the
+    /// screen line the cursor is on.
+    pub wrapped_line_reading: WrappedLineReading,
+    /// The command used to record voice memos.
+    pub memo_record_command: String,
+    /// The command run to build the project and list its errors.
+    pub build_command: String,
+    /// Whether notifications a command sends (OSC 9) are read out, as well
+    /// as played as a bell.
+    pub speak_notifications: bool,
+    /// The verbosity of each announcement class, by class name.
+    pub verbosity: HashMap<String, Verbosity>,
+    /// The chords bound to each action, by action name.
+    pub keys: BTreeMap<String, Chords>,
+    /// Spoken names for typed symbols.
+    pub echo: HashMap<String, String>,
+    /// Fixed-width column layouts, by file extension.
+    pub columns: HashMap<String, Vec<ColumnField>>,
+    /// The age identity file used to open and save `.age` files.
+    pub age_identity: Option<String>,
+    /// The word list used to check spelling.
+    pub dictionary: Option<String>,
+    /// Whether a word typed twice in a row in a prose file is pointed out.
+    pub repeated_words: bool,
+    /// The file extensions whose brackets and quotes are closed as they're
+    /// opened, or "code" for every file that isn't prose.
+    pub auto_pairs: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            rate_wpm: DEFAULT_RATE_WPM,
+            voice: None,
+            speech_backend: String::from("auto"),
+            volume: 1.0,
+            speech_volume: 1.0,
+            earcon_ducking: 0.5,
+            stereo_speech: 0.0,
+            column_panning: 0.0,
+            column_panning_speech: false,
+            position_tones: false,
+            night_mode: false,
+            screen_reader: false,
+            braille: false,
+            earcon_theme: String::from("pentatonic"),
+            earcons: HashMap::new(),
+            audio_budget_ms: DEFAULT_AUDIO_BUDGET_MS,
+            interrupt_speech_on_keypress: false,
+            typing_echo: TypingEcho::Words,
+            link_reading: LinkReadingMode::Terse,
+            punctuation: PunctuationLevel::All,
+            identifiers: IdentifierReading::Split,
+            numbers: NumberReading::Values,
+            count_repeated_symbols: true,
+            capitals: CapitalReading::None,
+            token_voices: TokenVoices::default(),
+            context_voices: ContextVoices::default(),
+            tab_width: 4,
+            insert_spaces: false,
+            keep_backups: false,
+            indentation: IndentReading::Tones,
+            modal_editing: false,
+            wrap: false,
+            show_line_numbers: false,
+            speak_line_numbers: false,
+            soft_wrap: false,
+            wrapped_line_reading: WrappedLineReading::Line,
+            memo_record_command: String::from(DEFAULT_MEMO_RECORD_COMMAND),
+            build_command: String::from("cargo check --message-format short"),
+            speak_notifications: true,
+            verbosity: HashMap::new(),
+            keys: BTreeMap::new(),
+            echo: HashMap::new(),
+            columns: HashMap::new(),
+            age_identity: None,
+            dictionary: None,
+            repeated_words: true,
+            auto_pairs: Vec::new(),
+        }
+    }
+}
+
+/// The chords bound to an action in the `[keys]` table: one, or a list.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum Chords {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Read the config file.
+///
+/// # Returns
+///
+/// The config (the defaults if there is no config file) and the settings
+/// the file gives, or a spoken description of why the file couldn't be
+/// used.
+///
+pub fn read_config() -> Result<(Config, Table), String> {
+    let config_path = match config_path() {
+        Some(path) => path,
+        None => return Ok((Config::default(), Table::new())),
+    };
     if !config_path.exists() {
-        return Value::from(DEFAULT_CONFIG_PATH);
+        return Ok((Config::default(), Table::new()));
+    }
+    let contents = fs::read_to_string(&config_path)
+        .map_err(|error| format!("Couldn't read the config file: {}.", error))?;
+    let config: Config = toml::from_str(&contents)
+        .map_err(|error| format!("Problem in the config file: {}.", error))?;
+    config
+        .validate()
+        .map_err(|problem| format!("Problem in the config file: {}", problem))?;
+    let settings = toml::from_str(&contents).unwrap_or_default();
+    Ok((config, settings))
+}
+
+impl Config {
+    /// Check that settings are in range.
+    ///
+    /// # Returns
+    ///
+    /// Ok, or a spoken description of the first setting that's out of range.
+    ///
+    fn validate(&self) -> Result<(), String> {
+        if !(MIN_RATE_WPM..=MAX_RATE_WPM).contains(&self.rate_wpm) {
+            return Err(format!(
+                "rate_wpm is {}, but must be from {} to {}.",
+                self.rate_wpm, MIN_RATE_WPM, MAX_RATE_WPM
+            ));
+        }
+        if !(0.0..=2.0).contains(&self.volume) {
+            return Err(format!(
+                "volume is {}, but must be from 0 to 2.",
+                self.volume
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.speech_volume) {
+            return Err(format!(
+                "speech_volume is {}, but must be from 0 to 1.",
+                self.speech_volume
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.earcon_ducking) {
+            return Err(format!(
+                "earcon_ducking is {}, but must be from 0 to 1.",
+                self.earcon_ducking
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.stereo_speech) {
+            return Err(format!(
+                "stereo_speech is {}, but must be from 0 to 1.",
+                self.stereo_speech
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.column_panning) {
+            return Err(format!(
+                "column_panning is {}, but must be from 0 to 1.",
+                self.column_panning
+            ));
+        }
+        if !THEME_NAMES.contains(&self.earcon_theme.as_str()) {
+            return Err(format!(
+                "earcon_theme is {}, but must be one of {}.",
+                self.earcon_theme,
+                THEME_NAMES.join(", ")
+            ));
+        }
+        if !(1..=16).contains(&self.tab_width) {
+            return Err(format!(
+                "tab_width is {}, but must be from 1 to 16.",
+                self.tab_width
+            ));
+        }
+        Ok(())
     }
+}
 
-    let mut file = File::open(config_path).unwrap();
-    let mut contents = String::new();
-    file.read_to_string(&mut contents).unwrap();
-    contents.parse::<Value>().unwrap()
+/// Write a config file with every setting at its default, explained, unless
+/// there's a config file already.
+///
+/// # Returns
+///
+/// Where the file was written, or why it wasn't.
+///
+pub fn write_default_config() -> Result<PathBuf, String> {
+    let path = config_path().ok_or("Couldn't find the home directory.")?;
+    if path.exists() {
+        return Err(format!(
+            "{} already exists; move it aside to write a new one.",
+            path.display()
+        ));
+    }
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)
+            .map_err(|error| format!("Couldn't write the config file: {}.", error))?;
+    }
+    fs::write(&path, DEFAULT_CONFIG_FILE)
+        .map_err(|error| format!("Couldn't write the config file: {}.", error))?;
+    Ok(path)
+}
+
+/// Get where the config file is, or None if there's no home directory.
+pub(crate) fn config_path() -> Option<PathBuf> {
+    home_dir().map(|home| home.join(DEFAULT_CONFIG_PATH))
+}
+
+/// Expand a leading `~/` in a path from the config file.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Set a top-level setting in the config file, keeping the rest of the file,
+/// comments included, as it is.
+fn write_setting(key: &str, value: &str) -> Result<(), String> {
+    let path = config_path().ok_or("Couldn't find the home directory.")?;
+    let contents = fs::read_to_string(&path).unwrap_or_default();
+    let setting = format!("{} = {}", key, value);
+    let mut lines: Vec<String> = contents.lines().map(String::from).collect();
+    // Top-level settings come before the first table.
+    let top_level = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+    let existing = lines[..top_level]
+        .iter()
+        .position(|line| is_setting(line, key));
+    match existing {
+        Some(index) => lines[index] = setting,
+        None => lines.insert(0, setting),
+    }
+    save_config_lines(&path, &lines)
+}
+
+/// Bind an action to one chord in the `[keys]` table of the config file,
+/// adding the table if there isn't one, and keeping the rest of the file as
+/// it is.
+fn write_key_binding(action: &str, chord: &str) -> Result<(), String> {
+    let path = config_path().ok_or("Couldn't find the home directory.")?;
+    let contents = fs::read_to_string(&path).unwrap_or_default();
+    let escaped = chord.replace('\\', "\\\\").replace('"', "\\\"");
+    let setting = format!("{} = \"{}\"", action, escaped);
+    let mut lines: Vec<String> = contents.lines().map(String::from).collect();
+    match lines.iter().position(|line| line.trim() == "[keys]") {
+        Some(header) => {
+            let end = lines[header + 1..]
+                .iter()
+                .position(|line| line.trim_start().starts_with('['))
+                .map_or(lines.len(), |index| header + 1 + index);
+            let existing = lines[header + 1..end]
+                .iter()
+                .position(|line| is_setting(line, action));
+            match existing {
+                Some(index) => lines[header + 1 + index] = setting,
+                None => lines.insert(header + 1, setting),
+            }
+        }
+        None => {
+            lines.push(String::new());
+            lines.push(String::from("[keys]"));
+            lines.push(setting);
+        }
+    }
+    save_config_lines(&path, &lines)
+}
+
+/// Whether a config file line sets a key (and isn't commented out).
+fn is_setting(line: &str, key: &str) -> bool {
+    line.trim_start()
+        .strip_prefix(key)
+        .is_some_and(|rest| rest.trim_start().starts_with('='))
+}
+
+/// Write the config file's lines back.
+fn save_config_lines(path: &Path, lines: &[String]) -> Result<(), String> {
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)
+            .map_err(|error| format!("Couldn't save the config file: {}.", error))?;
+    }
+    fs::write(path, contents).map_err(|error| format!("Couldn't save the config file: {}.", error))
+}
+
+/// Get when the config file was last changed, or None if there isn't one.
+fn config_modified() -> Option<SystemTime> {
+    fs::metadata(config_path()?).ok()?.modified().ok()
 }
 
 pub struct ConfigManager {
-    config: Value,
+    config: Config,
+    /// The settings the config file gave when it was last read, and the
+    /// time before, to tell which a reload changed.
+    settings: Table,
+    previous_settings: Table,
+    /// Why the config file couldn't be used, if it couldn't.
+    problem: Option<String>,
+    /// When the config file had last changed as of the last read.
+    modified: Option<SystemTime>,
 }
 
 impl ConfigManager {
     pub fn new() -> Self {
-        Self {
-            config: read_config(),
+        let modified = config_modified();
+        match read_config() {
+            Ok((config, settings)) => Self {
+                config,
+                settings,
+                previous_settings: Table::new(),
+                problem: None,
+                modified,
+            },
+            Err(problem) => Self {
+                config: Config::default(),
+                settings: Table::new(),
+                previous_settings: Table::new(),
+                problem: Some(format!("{} Using the default settings.", problem)),
+                modified,
+            },
+        }
+    }
+
+    /// Read the config file again if it has changed since it was last read.
+    ///
+    /// # Returns
+    ///
+    /// None if the file hasn't changed. Otherwise Ok if the new settings were
+    /// loaded, or Err with why they couldn't be, in which case the previous
+    /// settings are kept.
+    ///
+    pub fn reload_if_changed(&mut self) -> Option<Result<(), String>> {
+        let modified = config_modified();
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        Some(read_config().map(|(config, settings)| {
+            self.config = config;
+            self.previous_settings = std::mem::replace(&mut self.settings, settings);
+            self.problem = None;
+        }))
+    }
+
+    /// Whether the last reload changed a setting (or table, like `keys`),
+    /// so that settings changed while clack runs, like the earcon theme,
+    /// are only replaced when the file changes them.
+    pub fn changed(&self, key: &str) -> bool {
+        self.settings.get(key) != self.previous_settings.get(key)
+    }
+
+    /// Get why the config file couldn't be used, if it couldn't.
+    pub fn problem(&self) -> Option<&str> {
+        self.problem.as_deref()
+    }
+
+    pub fn get_rate_wpm(&self) -> i64 {
+        self.config.rate_wpm
+    }
+
+    /// Change the speech rate and save it to the config file.
+    ///
+    /// # Returns
+    ///
+    /// Ok, or why the config file couldn't be written. The new rate is used
+    /// for this session either way.
+    ///
+    pub fn set_rate_wpm(&mut self, rate_wpm: i64) -> Result<(), String> {
+        self.config.rate_wpm = rate_wpm;
+        write_setting("rate_wpm", &rate_wpm.to_string())?;
+        // Don't reload a change we made ourselves.
+        self.modified = config_modified();
+        Ok(())
+    }
+
+    /// Bind an action to a single chord, replacing any it had, and save the
+    /// binding in the config file's `[keys]` table.
+    pub fn set_key_binding(&mut self, action: &str, chord: &str) -> Result<(), String> {
+        self.config
+            .keys
+            .insert(action.to_string(), Chords::One(chord.to_string()));
+        write_key_binding(action, chord)?;
+        // Don't reload a change we made ourselves.
+        self.modified = config_modified();
+        Ok(())
+    }
+
+    /// Get the voice to speak with when a file doesn't have its own.
+    pub fn get_voice(&self) -> Option<String> {
+        self.config.voice.clone()
+    }
+
+    /// Get the volume of tones and sound clips, where 1.0 is full volume.
+    pub fn get_volume(&self) -> f32 {
+        self.config.volume
+    }
+
+    /// Change the volume of tones and sound clips and save it to the config
+    /// file. Like the rate, it's used for this session even if it can't be
+    /// saved.
+    pub fn set_volume(&mut self, volume: f32) -> Result<(), String> {
+        self.config.volume = volume;
+        write_setting("volume", &format!("{:.1}", volume))?;
+        self.modified = config_modified();
+        Ok(())
+    }
+
+    /// Get the volume of speech, as a fraction of the speech engine's usual
+    /// volume.
+    pub fn get_speech_volume(&self) -> f32 {
+        self.config.speech_volume
+    }
+
+    /// Change the volume of speech and save it to the config file.
+    pub fn set_speech_volume(&mut self, volume: f32) -> Result<(), String> {
+        self.config.speech_volume = volume;
+        write_setting("speech_volume", &format!("{:.1}", volume))?;
+        self.modified = config_modified();
+        Ok(())
+    }
+
+    /// Get how loud tones and sound clips are while speech plays, as a
+    /// fraction of their volume.
+    pub fn get_earcon_ducking(&self) -> f32 {
+        self.config.earcon_ducking
+    }
+
+    /// Get whether clack starts in night mode.
+    pub fn get_night_mode(&self) -> bool {
+        self.config.night_mode
+    }
+
+    /// Get how far tones and clips are panned by the cursor's column.
+    pub fn get_column_panning(&self) -> f32 {
+        self.config.column_panning
+    }
+
+    /// Get whether speech is panned by the cursor's column too.
+    pub fn get_column_panning_speech(&self) -> bool {
+        self.config.column_panning_speech
+    }
+
+    /// Get whether moving up and down plays a position tone.
+    pub fn get_position_tones(&self) -> bool {
+        self.config.position_tones
+    }
+
+    /// Get whether speech is written for a screen reader instead of spoken.
+    pub fn get_screen_reader(&self) -> bool {
+        self.config.screen_reader
+    }
+
+    /// Get whether to mirror the current line to a braille display.
+    pub fn get_braille(&self) -> bool {
+        self.config.braille
+    }
+
+    /// Get a built-in earcon theme, with the sounds from the `[earcons]`
+    /// table in place of its own.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The theme's name, or None for the configured theme.
+    ///
+    pub fn get_earcon_theme(&self, name: Option<&str>) -> EarconTheme {
+        EarconTheme::named(name.unwrap_or(&self.config.earcon_theme))
+            .unwrap_or_default()
+            .with_overrides(&self.config.earcons)
+    }
+
+    /// Get whether saving keeps the previous version of a file as `<name>~`.
+    pub fn get_keep_backups(&self) -> bool {
+        self.config.keep_backups
+    }
+
+    /// Get whether Tab inserts spaces instead of a tab.
+    pub fn get_insert_spaces(&self) -> bool {
+        self.config.insert_spaces
+    }
+
+    /// Get whether editing is vi-style, with normal, insert and visual
+    /// modes.
+    pub fn get_modal_editing(&self) -> bool {
+        self.config.modal_editing
+    }
+
+    /// Get whether Left and Right wrap onto the previous and next lines.
+    pub fn get_wrap(&self) -> bool {
+        self.config.wrap
+    }
+
+    /// Get whether line numbers are drawn beside each line.
+    pub fn get_show_line_numbers(&self) -> bool {
+        self.config.show_line_numbers
+    }
+
+    /// Get whether a line's number is spoken before it's read.
+    pub fn get_speak_line_numbers(&self) -> bool {
+        self.config.speak_line_numbers
+    }
+
+    /// Get whether long lines are broken across screen lines.
+    pub fn get_soft_wrap(&self) -> bool {
+        self.config.soft_wrap
+    }
+
+    /// Get how much of a soft-wrapped line is read.
+    pub fn get_wrapped_line_reading(&self) -> WrappedLineReading {
+        self.config.wrapped_line_reading
+    }
+
+    /// Get the audio budget per event loop iteration, in milliseconds.
+    pub fn get_audio_budget_ms(&self) -> u64 {
+        self.config.audio_budget_ms
+    }
+
+    /// Get whether a keypress should silence speech that is still playing.
+    pub fn get_interrupt_speech_on_keypress(&self) -> bool {
+        self.config.interrupt_speech_on_keypress
+    }
+
+    /// Get what should be spoken while typing.
+    pub fn get_typing_echo(&self) -> TypingEcho {
+        self.config.typing_echo
+    }
+
+    /// Get the name of the text-to-speech backend to use, or "auto" to
+    /// detect one.
+    pub fn get_speech_backend_name(&self) -> String {
+        self.config.speech_backend.clone()
+    }
+
+    /// Get how URLs and paths should be read aloud.
+    pub fn get_link_reading_mode(&self) -> LinkReadingMode {
+        self.config.link_reading
+    }
+
+    /// Get the options used to turn text into speech.
+    pub fn get_speech_options(&self) -> SpeechOptions {
+        SpeechOptions {
+            links: self.get_link_reading_mode(),
+            punctuation: self.config.punctuation,
+            identifiers: self.config.identifiers,
+            numbers: self.config.numbers,
+            rate_wpm: self.config.rate_wpm,
+            tab_width: self.config.tab_width,
+            indentation: self.config.indentation,
+            voice: self.config.voice.clone(),
+            token_voices: self.config.token_voices.clone(),
+            context_voices: self.config.context_voices.clone(),
+            stereo_speech: self.config.stereo_speech,
+            count_repeated_symbols: self.config.count_repeated_symbols,
+            capitals: self.config.capitals,
         }
     }
 
-    fn get(&mut self, key: &str) -> Option<&Value> {
-        self.config.get(key)
+    /// Get the announcement verbosity for each class from the `[verbosity]`
+    /// table. Classes that aren't configured are verbose.
+    pub fn get_verbosity_settings(&self) -> VerbositySettings {
+        let mut settings = VerbositySettings::default();
+        for class in AnnouncementClass::ALL {
+            if let Some(verbosity) = self.config.verbosity.get(class.name()) {
+                settings.set(class, *verbosity);
+            }
+        }
+        settings
+    }
+
+    /// Get the user's key bindings from the `[keys]` table, as (action name,
+    /// chord) pairs. An action may be given one chord or a list of them.
+    pub fn get_key_bindings(&self) -> Vec<(String, String)> {
+        let mut bindings = Vec::new();
+        for (action, chords) in &self.config.keys {
+            let chords = match chords {
+                Chords::One(chord) => std::slice::from_ref(chord),
+                Chords::Many(chords) => chords.as_slice(),
+            };
+            for chord in chords {
+                bindings.push((action.clone(), chord.clone()));
+            }
+        }
+        bindings
+    }
+
+    /// Get the command used to record voice memos.
+    pub fn get_memo_record_command(&self) -> String {
+        self.config.memo_record_command.clone()
+    }
+
+    /// Get the command run to build the project and list its errors.
+    pub fn get_build_command(&self) -> String {
+        self.config.build_command.clone()
+    }
+
+    /// Get whether the notifications a command sends are read out.
+    pub fn get_speak_notifications(&self) -> bool {
+        self.config.speak_notifications
+    }
+
+    /// Get the age identity file, with a leading `~/` expanded.
+    pub fn get_age_identity(&self) -> Option<PathBuf> {
+        self.config.age_identity.as_deref().map(expand_home)
+    }
+
+    /// Get the word list used to check spelling, with a leading `~/`
+    /// expanded, if one is set.
+    pub fn get_dictionary(&self) -> Option<PathBuf> {
+        self.config.dictionary.as_deref().map(expand_home)
+    }
+
+    /// Get whether a word typed twice in a row in a prose file is pointed
+    /// out.
+    pub fn get_repeated_words(&self) -> bool {
+        self.config.repeated_words
+    }
+
+    /// Get the spoken name to echo when a symbol is typed, if the user has
+    /// configured one in the `[echo]` table.
+    ///
+    /// # Arguments
+    ///
+    /// * `symbol` - The typed symbol, e.g. `"."`.
+    ///
+    /// # Returns
+    ///
+    /// The configured spoken name, or None if the symbol is not in the table.
+    ///
+    pub fn get_echo_name(&self, symbol: &str) -> Option<String> {
+        self.config.echo.get(symbol).cloned()
+    }
+
+    /// Get the column layout for a file extension from the `[columns]`
+    /// table, if there is one.
+    pub fn get_column_layout(&self, extension: &str) -> Option<&[ColumnField]> {
+        self.config.columns.get(extension).map(Vec::as_slice)
     }
 
-    pub fn get_rate_wpm(&mut self) -> i64 {
-        self.get("rate_wpm")
-            .unwrap_or(&Value::Integer(DEFAULT_RATE_WPM))
-            .as_integer()
-            .unwrap()
+    /// Get the file extensions (or "code") brackets and quotes are closed
+    /// in as they're opened.
+    pub fn get_auto_pairs(&self) -> &[String] {
+        &self.config.auto_pairs
     }
 }
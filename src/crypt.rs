@@ -0,0 +1,165 @@
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// The tools clack can decrypt and re-encrypt files with, picked by the
+/// file's extension.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Scheme {
+    /// `.gpg` files, encrypted symmetrically with a passphrase.
+    Gpg,
+    /// `.age` files, encrypted to the user's age identity.
+    Age,
+}
+
+impl Scheme {
+    /// Work out how a file is encrypted from its extension.
+    ///
+    /// # Returns
+    ///
+    /// The scheme, or None if the file isn't one clack treats as encrypted.
+    ///
+    pub fn for_file(file_name: &str) -> Option<Self> {
+        match Path::new(file_name)
+            .extension()
+            .and_then(|extension| extension.to_str())
+        {
+            Some("gpg") => Some(Self::Gpg),
+            Some("age") => Some(Self::Age),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Gpg => "gpg",
+            Self::Age => "age",
+        }
+    }
+}
+
+/// How an open document is encrypted on disk, and the secret needed to
+/// write it back.
+#[derive(Clone)]
+pub enum Encryption {
+    Gpg {
+        passphrase: String,
+    },
+    /// age reads passphrases only from the terminal itself, which clack is
+    /// using, so age files are decrypted with an identity file instead.
+    Age {
+        identity: PathBuf,
+    },
+}
+
+impl Encryption {
+    pub fn scheme(&self) -> Scheme {
+        match self {
+            Self::Gpg { .. } => Scheme::Gpg,
+            Self::Age { .. } => Scheme::Age,
+        }
+    }
+
+    /// Decrypt a file into memory. The plaintext is never written to disk.
+    pub fn decrypt(&self, file_name: &str) -> Result<String, io::Error> {
+        let mut command = match self {
+            Self::Gpg { .. } => {
+                let mut command = gpg();
+                command.arg("--decrypt").arg(file_name);
+                command
+            }
+            Self::Age { identity } => {
+                let mut command = Command::new("age");
+                command
+                    .arg("--decrypt")
+                    .arg("--identity")
+                    .arg(identity)
+                    .arg(file_name);
+                command
+            }
+        };
+        let plaintext = run(&mut command, &self.stdin_prefix(), b"")?;
+        String::from_utf8(plaintext)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "not a text file"))
+    }
+
+    /// Encrypt text and write it to a file, replacing the file.
+    pub fn encrypt(&self, file_name: &str, contents: &[u8]) -> Result<(), io::Error> {
+        let mut command = match self {
+            Self::Gpg { .. } => {
+                let mut command = gpg();
+                command.arg("--symmetric").arg("--output").arg(file_name);
+                command
+            }
+            Self::Age { identity } => {
+                let mut command = Command::new("age");
+                command
+                    .arg("--encrypt")
+                    .arg("--identity")
+                    .arg(identity)
+                    .arg("--output")
+                    .arg(file_name);
+                command
+            }
+        };
+        run(&mut command, &self.stdin_prefix(), contents).map(|_| ())
+    }
+
+    /// What to send on stdin ahead of the file contents: gpg reads the
+    /// passphrase from its first line.
+    fn stdin_prefix(&self) -> Vec<u8> {
+        match self {
+            Self::Gpg { passphrase } => format!("{}\n", passphrase).into_bytes(),
+            Self::Age { .. } => Vec::new(),
+        }
+    }
+}
+
+/// A gpg command that takes its passphrase from the first line of stdin,
+/// without prompting or caching it in the agent.
+fn gpg() -> Command {
+    let mut command = Command::new("gpg");
+    command.args([
+        "--batch",
+        "--quiet",
+        "--yes",
+        "--no-symkey-cache",
+        "--pinentry-mode",
+        "loopback",
+        "--passphrase-fd",
+        "0",
+    ]);
+    command
+}
+
+/// Run an encryption tool, feeding it `prefix` and then `input` on stdin.
+///
+/// # Returns
+///
+/// What the tool wrote to stdout, or an error carrying the first line of
+/// what it wrote to stderr.
+///
+fn run(command: &mut Command, prefix: &[u8], input: &[u8]) -> Result<Vec<u8>, io::Error> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(prefix)?;
+        stdin.write_all(input)?;
+    }
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        return Ok(output.stdout);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let reason = stderr
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("the tool failed");
+    Err(io::Error::other(reason.to_string()))
+}
@@ -0,0 +1,174 @@
+/// The largest number of line pairs compared with a full LCS table. Beyond
+/// this the changed region is reported as a single replacement.
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// A contiguous run of changed lines between two versions of a document.
+///
+/// Line indices are zero-based. `old_len` lines starting at `old_start` were
+/// replaced by `new_len` lines starting at `new_start`.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_len: usize,
+    pub new_start: usize,
+    pub new_len: usize,
+}
+
+impl Hunk {
+    /// Describe the hunk in a compact, speakable form.
+    ///
+    /// # Returns
+    ///
+    /// A description like "line 10 changed" or "2 lines added after line 40".
+    ///
+    pub fn describe(&self) -> String {
+        let first = self.new_start.saturating_add(1);
+        let last = self.new_start.saturating_add(self.new_len);
+        if self.old_len == 0 {
+            return format!(
+                "{} added after line {}",
+                count_lines(self.new_len),
+                self.new_start
+            );
+        }
+        if self.new_len == 0 {
+            return format!(
+                "{} removed after line {}",
+                count_lines(self.old_len),
+                self.new_start
+            );
+        }
+        if self.old_len == self.new_len {
+            if self.new_len == 1 {
+                return format!("line {} changed", first);
+            }
+            return format!("lines {} to {} changed", first, last);
+        }
+        format!(
+            "{} replaced with {} at line {}",
+            count_lines(self.old_len),
+            count_lines(self.new_len),
+            first
+        )
+    }
+}
+
+/// Say how many lines there are, e.g. "1 line" or "3 lines".
+pub fn count_lines(count: usize) -> String {
+    if count == 1 {
+        "1 line".to_string()
+    } else {
+        format!("{} lines", count)
+    }
+}
+
+/// Find where a line of the old version is in the new one.
+///
+/// A line in a changed hunk goes to the same place in the lines that
+/// replaced it, or to the line after them if there are fewer.
+///
+pub fn map_line(hunks: &[Hunk], line: usize) -> usize {
+    let mut mapped = line;
+    for hunk in hunks {
+        let old_end = hunk.old_start.saturating_add(hunk.old_len);
+        let new_end = hunk.new_start.saturating_add(hunk.new_len);
+        if old_end <= line {
+            mapped = new_end + (line - old_end);
+        } else if hunk.old_start <= line {
+            return (hunk.new_start + (line - hunk.old_start)).min(new_end);
+        } else {
+            break;
+        }
+    }
+    mapped
+}
+
+/// Compute the changed hunks between two versions of a document.
+///
+/// # Arguments
+///
+/// * `old` - The lines of the old version.
+/// * `new` - The lines of the new version.
+///
+/// # Returns
+///
+/// The hunks, in document order.
+///
+pub fn diff_lines(old: &[&str], new: &[&str]) -> Vec<Hunk> {
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+    if old_middle.is_empty() && new_middle.is_empty() {
+        return Vec::new();
+    }
+    if old_middle.len().saturating_mul(new_middle.len()) > MAX_LCS_CELLS {
+        return vec![Hunk {
+            old_start: prefix,
+            old_len: old_middle.len(),
+            new_start: prefix,
+            new_len: new_middle.len(),
+        }];
+    }
+    lcs_hunks(old_middle, new_middle)
+        .into_iter()
+        .map(|hunk| Hunk {
+            old_start: hunk.old_start + prefix,
+            new_start: hunk.new_start + prefix,
+            ..hunk
+        })
+        .collect()
+}
+
+fn lcs_hunks(old: &[&str], new: &[&str]) -> Vec<Hunk> {
+    let width = new.len() + 1;
+    // lengths[i * width + j] is the LCS length of old[i..] and new[j..].
+    let mut lengths = vec![0_u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let mut current: Option<Hunk> = None;
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            hunks.extend(current.take());
+            i += 1;
+            j += 1;
+            continue;
+        }
+        let hunk = current.get_or_insert(Hunk {
+            old_start: i,
+            old_len: 0,
+            new_start: j,
+            new_len: 0,
+        });
+        if j < new.len()
+            && (i == old.len() || lengths[i * width + j + 1] >= lengths[(i + 1) * width + j])
+        {
+            hunk.new_len += 1;
+            j += 1;
+        } else {
+            hunk.old_len += 1;
+            i += 1;
+        }
+    }
+    hunks.extend(current);
+    hunks
+}
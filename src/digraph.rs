@@ -0,0 +1,130 @@
+/// Two-character digraphs for typing accented and special characters,
+/// following vim's RFC 1345 conventions.
+///
+/// The second character of a letter digraph picks the accent:
+/// `'` acute, `!` grave, `>` circumflex, `:` diaeresis, `?` tilde,
+/// `,` cedilla, `/` stroke, `a` ring.
+const DIGRAPHS: &[(&str, char)] = &[
+    ("a'", 'á'),
+    ("e'", 'é'),
+    ("i'", 'í'),
+    ("o'", 'ó'),
+    ("u'", 'ú'),
+    ("y'", 'ý'),
+    ("A'", 'Á'),
+    ("E'", 'É'),
+    ("I'", 'Í'),
+    ("O'", 'Ó'),
+    ("U'", 'Ú'),
+    ("Y'", 'Ý'),
+    ("a!", 'à'),
+    ("e!", 'è'),
+    ("i!", 'ì'),
+    ("o!", 'ò'),
+    ("u!", 'ù'),
+    ("A!", 'À'),
+    ("E!", 'È'),
+    ("I!", 'Ì'),
+    ("O!", 'Ò'),
+    ("U!", 'Ù'),
+    ("a>", 'â'),
+    ("e>", 'ê'),
+    ("i>", 'î'),
+    ("o>", 'ô'),
+    ("u>", 'û'),
+    ("A>", 'Â'),
+    ("E>", 'Ê'),
+    ("I>", 'Î'),
+    ("O>", 'Ô'),
+    ("U>", 'Û'),
+    ("a:", 'ä'),
+    ("e:", 'ë'),
+    ("i:", 'ï'),
+    ("o:", 'ö'),
+    ("u:", 'ü'),
+    ("y:", 'ÿ'),
+    ("A:", 'Ä'),
+    ("E:", 'Ë'),
+    ("I:", 'Ï'),
+    ("O:", 'Ö'),
+    ("U:", 'Ü'),
+    ("a?", 'ã'),
+    ("n?", 'ñ'),
+    ("o?", 'õ'),
+    ("A?", 'Ã'),
+    ("N?", 'Ñ'),
+    ("O?", 'Õ'),
+    ("c,", 'ç'),
+    ("C,", 'Ç'),
+    ("o/", 'ø'),
+    ("O/", 'Ø'),
+    ("aa", 'å'),
+    ("AA", 'Å'),
+    ("ae", 'æ'),
+    ("AE", 'Æ'),
+    ("oe", 'œ'),
+    ("OE", 'Œ'),
+    ("ss", 'ß'),
+    ("Eu", '€'),
+    ("Pd", '£'),
+    ("Ye", '¥'),
+    ("Ct", '¢'),
+    ("Co", '©'),
+    ("Rg", '®'),
+    ("TM", '™'),
+    ("SE", '§'),
+    ("PI", '¶'),
+    ("DG", '°'),
+    ("+-", '±'),
+    ("*X", '×'),
+    ("-:", '÷'),
+    ("!=", '≠'),
+    ("=<", '≤'),
+    (">=", '≥'),
+    ("<<", '«'),
+    (">>", '»'),
+    ("!I", '¡'),
+    ("?I", '¿'),
+    ("<-", '←'),
+    ("->", '→'),
+    ("-!", '↑'),
+    ("-v", '↓'),
+    ("..", '…'),
+    ("-N", '–'),
+    ("-M", '—'),
+    ("'6", '‘'),
+    ("'9", '’'),
+    ("\"6", '“'),
+    ("\"9", '”'),
+    ("a*", 'α'),
+    ("b*", 'β'),
+    ("g*", 'γ'),
+    ("d*", 'δ'),
+    ("l*", 'λ'),
+    ("m*", 'μ'),
+    ("p*", 'π'),
+    ("s*", 'σ'),
+];
+
+/// Look up the character composed by a digraph.
+///
+/// Like vim, the two characters may be typed in either order.
+///
+/// # Arguments
+///
+/// * `first` - The first character typed.
+/// * `second` - The second character typed.
+///
+/// # Returns
+///
+/// The composed character, or None if the pair isn't a known digraph.
+///
+pub fn lookup(first: char, second: char) -> Option<char> {
+    let find = |pair: String| {
+        DIGRAPHS
+            .iter()
+            .find(|(candidate, _)| *candidate == pair)
+            .map(|(_, composed)| *composed)
+    };
+    find(format!("{}{}", first, second)).or_else(|| find(format!("{}{}", second, first)))
+}
@@ -0,0 +1,171 @@
+use std::io::ErrorKind;
+use std::process::Stdio;
+use std::thread;
+use std::time::Duration;
+
+use crate::clipboard;
+use crate::config::{config_path, ConfigManager};
+use crate::sound;
+use crate::speech::{select_backend, SpeechBackend};
+use crate::spelling::Dictionary;
+use crate::utils::is_on_path;
+
+/// How often to check whether speech spoken in-process has finished.
+const SPEECH_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Check that clack has what it needs to run, printing and speaking each
+/// result, for `clack --doctor`.
+///
+/// Problems that stop clack from talking at all (no speech engine, no audio
+/// output, a broken config file) are failures; missing optional tools are
+/// only warnings.
+///
+/// # Returns
+///
+/// Whether every check passed.
+///
+pub fn run() -> bool {
+    let config_manager = ConfigManager::new();
+    let speech = select_backend(&config_manager.get_speech_backend_name());
+    let mut doctor = Doctor {
+        speech,
+        rate_wpm: config_manager.get_rate_wpm(),
+        can_speak: true,
+        passed: true,
+    };
+
+    // Speech comes first, so that the rest of the results can be heard.
+    let backend = doctor.speech.name();
+    match doctor.speak("Clack speech check.") {
+        Ok(()) => doctor.pass(&format!("Speech engine {} works.", backend)),
+        Err(problem) => {
+            doctor.can_speak = false;
+            doctor.fail(&format!("Speech engine {} {}.", backend, problem));
+        }
+    }
+
+    if sound::has_output_device() {
+        doctor.pass("Found an audio output device.");
+    } else {
+        doctor.fail("No audio output device, so there will be no tones or sounds.");
+    }
+
+    match (config_manager.problem(), config_path()) {
+        (Some(problem), _) => doctor.fail(problem),
+        (None, Some(path)) if path.exists() => {
+            doctor.pass(&format!("Config file {} is fine.", path.display()));
+        }
+        (None, _) => doctor.pass("No config file, so the default settings are used."),
+    }
+
+    match clipboard::copy_tool() {
+        Some(tool) => doctor.pass(&format!("Clipboard tool {} found.", tool)),
+        None => doctor.warn("No clipboard tool, so copy and paste stay inside clack."),
+    }
+    let memo_command = config_manager.get_memo_record_command();
+    let recorder = memo_command.split_whitespace().next().unwrap_or_default();
+    if is_on_path(recorder) {
+        doctor.pass(&format!("Voice memo recorder {} found.", recorder));
+    } else {
+        doctor.warn(&format!(
+            "Voice memo recorder {} not found, so memos can't be recorded.",
+            recorder
+        ));
+    }
+    match config_manager.get_dictionary() {
+        Some(path) if !path.is_file() => {
+            doctor.fail(&format!("Dictionary {} doesn't exist.", path.display()));
+        }
+        None if Dictionary::default_path().is_none() => {
+            doctor.warn("No dictionary, so spelling isn't checked.");
+        }
+        _ => (),
+    }
+    if !is_on_path("gpg") {
+        doctor.warn("gpg not found, so .gpg files can't be opened.");
+    }
+    match config_manager.get_age_identity() {
+        Some(identity) if !is_on_path("age") => {
+            doctor.fail(&format!(
+                "age identity {} is set, but age isn't installed.",
+                identity.display()
+            ));
+        }
+        Some(identity) if !identity.is_file() => {
+            doctor.fail(&format!(
+                "age identity {} doesn't exist.",
+                identity.display()
+            ));
+        }
+        Some(_) => doctor.pass("age and its identity file found."),
+        None => (),
+    }
+
+    if doctor.passed {
+        doctor.say("ok", "All checks passed.");
+    } else {
+        doctor.say("FAIL", "Some checks failed.");
+    }
+    doctor.passed
+}
+
+struct Doctor {
+    speech: Box<dyn SpeechBackend>,
+    rate_wpm: i64,
+    /// Whether the speech engine worked; if not, results are only printed.
+    can_speak: bool,
+    /// Whether every check so far has passed.
+    passed: bool,
+}
+
+impl Doctor {
+    fn pass(&self, message: &str) {
+        self.say("ok", message);
+    }
+
+    fn warn(&self, message: &str) {
+        self.say("warn", message);
+    }
+
+    fn fail(&mut self, message: &str) {
+        self.passed = false;
+        self.say("FAIL", message);
+    }
+
+    /// Print a result, then speak it and wait for it to finish.
+    fn say(&self, label: &str, message: &str) {
+        println!("{:<4} {}", label, message);
+        if self.can_speak {
+            self.speak(message).ok();
+        }
+    }
+
+    /// Speak text and wait for it to finish.
+    ///
+    /// # Returns
+    ///
+    /// Ok, or why the speech engine didn't work, phrased to follow its name.
+    ///
+    fn speak(&self, text: &str) -> Result<(), String> {
+        if let Some(mut handle) = self.speech.speak(text, self.rate_wpm, None, None, None) {
+            while !handle.is_finished() {
+                thread::sleep(SPEECH_POLL_INTERVAL);
+            }
+            return Ok(());
+        }
+        let status = self
+            .speech
+            .command(text, self.rate_wpm, None, None, None)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        match status {
+            Ok(status) if status.success() => Ok(()),
+            Ok(status) => Err(format!("failed with {}", status)),
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                Err(String::from("isn't installed"))
+            }
+            Err(error) => Err(format!("couldn't start: {}", error)),
+        }
+    }
+}
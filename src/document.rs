@@ -1,22 +1,373 @@
-use crate::{utils::SearchDirection, Position, Row};
-use std::{fs, io::Write};
+use crate::{
+    atomic,
+    crypt::Encryption,
+    diff::{diff_lines, Hunk},
+    git,
+    journal::{Journal, Operation},
+    lock::{FileLock, LockStatus},
+    notes::{LineNotes, Note},
+    rows::Rows,
+    swap,
+    utils::SearchDirection,
+    Position, Row,
+};
+use std::{
+    cell::RefCell,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Markers that flag an annotation the user should come back to.
+const TODO_MARKERS: &[&str] = &["TODO", "FIXME"];
+
+/// The most edits kept to be undone.
+const MAX_UNDO_STEPS: usize = 1000;
+
+/// A change to a run of whole rows, kept so that it can be undone.
+struct RowEdit {
+    start: usize,
+    /// The rows that were replaced.
+    old_rows: Vec<String>,
+    /// How many rows replaced them.
+    new_len: usize,
+    old_notes: LineNotes,
+    description: String,
+}
 
 #[derive(Default)]
 pub struct Document {
-    rows: Vec<Row>,
+    rows: Rows,
     pub file_name: Option<String>,
     dirty: bool,
+    read_only: bool,
+    lock: Option<FileLock>,
+    locked_by: Option<u32>,
+    todo_count: usize,
+    notes: LineNotes,
+    voice: Option<String>,
+    history: Vec<RowEdit>,
+    /// How the file is encrypted on disk, if it is.
+    encryption: Option<Encryption>,
+    /// Whether the file's last line has no newline after it. New files get
+    /// one, as POSIX tools expect.
+    missing_final_newline: bool,
+    /// How the file's lines end, which is kept as it was when it's saved.
+    line_ending: LineEnding,
+    /// The file's lines as of the last git commit, if it's in a repository.
+    committed_lines: Option<Vec<String>>,
+    /// How the document differs from `committed_lines`, worked out when
+    /// it's next asked for after an edit.
+    git_hunks: RefCell<Option<Vec<Hunk>>>,
+    /// Where unsaved changes are logged, for replaying after a crash. Like the
+    /// crash-recovery file, it's only kept for plain files this session
+    /// holds the lock on.
+    journal: Option<Journal>,
+}
+
+/// A save running on a background thread, so that a slow disk (like a
+/// network mount) doesn't hold up editing.
+pub struct PendingSave {
+    pub file_name: String,
+    /// The text being written, to tell whether the document was edited
+    /// while it was being saved.
+    contents: Vec<u8>,
+    result: Receiver<Result<(), io::Error>>,
+}
+
+impl PendingSave {
+    /// Wait up to `timeout` for the write to finish.
+    ///
+    /// # Returns
+    ///
+    /// The write's result, or None if it's still going.
+    ///
+    pub fn wait(&self, timeout: Duration) -> Option<Result<(), io::Error>> {
+        match self.result.recv_timeout(timeout) {
+            Ok(result) => Some(result),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => Some(Err(io::Error::other(
+                "the save stopped part of the way through",
+            ))),
+        }
+    }
+}
+
+/// How a file's lines end.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`, as on Linux and macOS.
+    #[default]
+    Unix,
+    /// `\r\n`, as on Windows.
+    Windows,
+}
+
+impl LineEnding {
+    /// Tell how a file's lines end: Windows if every line break is `\r\n`.
+    /// A file with a mix keeps its carriage returns as part of its lines,
+    /// so it's saved as it was.
+    fn detect(contents: &str) -> Self {
+        let breaks = contents.matches('\n').count();
+        if breaks > 0 && contents.matches("\r\n").count() == breaks {
+            Self::Windows
+        } else {
+            Self::Unix
+        }
+    }
+
+    fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Self::Unix => b"\n",
+            Self::Windows => b"\r\n",
+        }
+    }
+
+    /// Get the short name shown in the status bar.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Unix => "LF",
+            Self::Windows => "CRLF",
+        }
+    }
+}
+
+/// Split a file's contents into lines, without their line endings.
+///
+/// # Returns
+///
+/// The lines, and whether the last one had no newline after it. A final
+/// newline ends the last line rather than starting an empty one.
+///
+fn split_lines(contents: &str) -> (Vec<&str>, bool) {
+    let line_ending = LineEnding::detect(contents);
+    let (contents, missing_final_newline) = match contents.strip_suffix('\n') {
+        Some(contents) => (contents, false),
+        None => (contents, true),
+    };
+    let lines = contents
+        .split('\n')
+        .map(|line| match line_ending {
+            LineEnding::Windows => line.strip_suffix('\r').unwrap_or(line),
+            LineEnding::Unix => line,
+        })
+        .collect();
+    (lines, missing_final_newline)
 }
 
 impl Document {
     pub fn open(filename: &str) -> Result<Self, std::io::Error> {
         let file = fs::read_to_string(filename)?;
-        let rows = file.split('\n').map(|row| Row::from(row)).collect();
-        Ok(Self {
+        Ok(Self::from_contents(filename, &file, None))
+    }
+
+    /// Make an unnamed document holding some text, like a generated list.
+    pub fn from_text(text: &str) -> Self {
+        let (lines, _) = split_lines(text);
+        Self {
+            rows: lines.into_iter().map(Row::from).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Open an encrypted file, decrypting it into memory. The document is
+    /// encrypted the same way again when it's saved.
+    pub fn open_encrypted(filename: &str, encryption: Encryption) -> Result<Self, std::io::Error> {
+        let file = encryption.decrypt(filename)?;
+        Ok(Self::from_contents(filename, &file, Some(encryption)))
+    }
+
+    fn from_contents(filename: &str, contents: &str, encryption: Option<Encryption>) -> Self {
+        let (lines, missing_final_newline) = split_lines(contents);
+        let rows = lines.into_iter().map(Row::from).collect();
+        let (lock, locked_by) = match FileLock::acquire(filename) {
+            Ok(LockStatus::Acquired(lock)) => (Some(lock), None),
+            Ok(LockStatus::HeldBy(pid)) => (None, Some(pid)),
+            // Locking is advisory; an unwritable directory shouldn't stop us.
+            Err(_) => (None, None),
+        };
+        // Notes are kept in a plain sidecar file, so an encrypted document's
+        // notes live only in memory.
+        let notes = if encryption.is_some() {
+            LineNotes::default()
+        } else {
+            LineNotes::load(filename)
+        };
+        let journal = match (&encryption, locked_by) {
+            (None, None) => Some(Journal::new(filename)),
+            _ => None,
+        };
+        let mut document = Self {
             rows,
             file_name: Some(filename.to_string()),
             dirty: false,
-        })
+            read_only: false,
+            lock,
+            locked_by,
+            todo_count: 0,
+            notes,
+            voice: None,
+            history: Vec::new(),
+            encryption,
+            missing_final_newline,
+            line_ending: LineEnding::detect(contents),
+            committed_lines: None,
+            git_hunks: RefCell::new(None),
+            journal,
+        };
+        document.refresh_todo_count();
+        document.refresh_committed_lines();
+        document
+    }
+
+    /// Read the file's last committed version from git again. Encrypted
+    /// files are skipped, since git holds them encrypted.
+    fn refresh_committed_lines(&mut self) {
+        self.git_hunks.get_mut().take();
+        self.committed_lines = match (&self.file_name, &self.encryption) {
+            (Some(file_name), None) => git::committed_version(file_name).map(|contents| {
+                split_lines(&contents)
+                    .0
+                    .into_iter()
+                    .map(String::from)
+                    .collect()
+            }),
+            _ => None,
+        };
+    }
+
+    /// Get how the document differs from its last git commit, unsaved
+    /// changes included.
+    ///
+    /// # Returns
+    ///
+    /// The hunks, in document order, or None if the file isn't in a git
+    /// repository or has never been committed.
+    ///
+    pub fn git_hunks(&self) -> Option<Vec<Hunk>> {
+        let committed = self.committed_lines.as_ref()?;
+        let mut cached = self.git_hunks.borrow_mut();
+        let hunks = cached.get_or_insert_with(|| {
+            let old: Vec<&str> = committed.iter().map(String::as_str).collect();
+            let new: Vec<&str> = self.rows.iter().map(Row::as_str).collect();
+            diff_lines(&old, &new)
+        });
+        Some(hunks.clone())
+    }
+
+    /// Note that the document's text has changed: it has unsaved changes,
+    /// and its git hunks have to be worked out again.
+    fn mark_edited(&mut self) {
+        self.dirty = true;
+        self.git_hunks.get_mut().take();
+    }
+
+    /// Get a line of the file as of its last git commit.
+    pub fn committed_line(&self, index: usize) -> Option<&str> {
+        self.committed_lines
+            .as_ref()?
+            .get(index)
+            .map(String::as_str)
+    }
+
+    /// Get how the file is encrypted on disk, if it is.
+    pub fn encryption(&self) -> Option<&Encryption> {
+        self.encryption.as_ref()
+    }
+
+    /// Encrypt the file from the next save on.
+    pub fn set_encryption(&mut self, encryption: Encryption) {
+        self.encryption = Some(encryption);
+    }
+
+    /// Replace the document's contents with the file's current contents on
+    /// disk, discarding any unsaved changes.
+    ///
+    /// # Returns
+    ///
+    /// The hunks that changed between the buffer and the file on disk.
+    ///
+    pub fn reload(&mut self) -> Result<Vec<Hunk>, std::io::Error> {
+        let file_name = match &self.file_name {
+            Some(file_name) => file_name,
+            None => return Ok(Vec::new()),
+        };
+        let file = match &self.encryption {
+            Some(encryption) => encryption.decrypt(file_name)?,
+            None => fs::read_to_string(file_name)?,
+        };
+        let old: Vec<&str> = self.rows.iter().map(Row::as_str).collect();
+        let (new, missing_final_newline) = split_lines(&file);
+        let hunks = diff_lines(&old, &new);
+        self.missing_final_newline = missing_final_newline;
+        self.line_ending = LineEnding::detect(&file);
+        self.rows = new.into_iter().map(Row::from).collect();
+        self.notes.lines_changed(&hunks);
+        self.dirty = false;
+        self.history.clear();
+        self.record(Operation::Saved);
+        self.refresh_todo_count();
+        self.refresh_committed_lines();
+        self.save_notes()?;
+        Ok(hunks)
+    }
+
+    /// Get the pid of another clack session that has this document open.
+    ///
+    /// # Returns
+    ///
+    /// The pid of the other session, or None if this session holds the lock.
+    ///
+    pub fn locked_by(&self) -> Option<u32> {
+        self.locked_by
+    }
+
+    /// Get whether the file ends with a newline, which is kept as it was
+    /// when the file is saved.
+    pub fn ends_with_newline(&self) -> bool {
+        !self.missing_final_newline
+    }
+
+    /// Get how the file's lines end, which is kept as it was when the file
+    /// is saved.
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+
+    /// Get the voice this document is read with, if it overrides the default.
+    pub fn voice(&self) -> Option<&String> {
+        self.voice.as_ref()
+    }
+
+    pub fn set_voice(&mut self, voice: Option<String>) {
+        self.voice = voice;
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Whether the user has permission to save the file, found by opening
+    /// it for writing without changing it. Saving replaces the file, so its
+    /// directory has to be writable too. A file that hasn't been named is
+    /// writable.
+    pub fn is_writable(&self) -> bool {
+        let file_name = match &self.file_name {
+            Some(file_name) => file_name,
+            None => return true,
+        };
+        let file_writable = match fs::OpenOptions::new().write(true).open(file_name) {
+            Ok(_) => true,
+            Err(error) => error.kind() == io::ErrorKind::NotFound,
+        };
+        file_writable && atomic::can_replace(file_name)
     }
 
     pub fn get_row(&self, index: usize) -> Option<&Row> {
@@ -27,6 +378,15 @@ impl Document {
         self.rows.len()
     }
 
+    /// Count the consecutive blank rows starting at `from`.
+    pub fn blank_run_length(&self, from: usize) -> usize {
+        self.rows
+            .iter()
+            .skip(from)
+            .take_while(|row| row.is_blank())
+            .count()
+    }
+
     fn insert_newline(&mut self, at: &Position) {
         if at.y > self.rows.len() {
             // The cursor is in a space that doesn't exist.
@@ -39,13 +399,21 @@ impl Document {
 
         let new_row = self.rows.get_mut(at.y).unwrap().split(at.x);
         self.rows.insert(at.y + 1, new_row);
+        self.notes.line_inserted_after(at.y);
     }
 
     pub fn insert(&mut self, at: &Position, c: char) {
         if at.y > self.row_count() {
             return;
         }
-        self.dirty = true;
+        self.record(Operation::Insert { at: at.clone(), c });
+        self.mark_edited();
+        let old_len = usize::from(at.y < self.row_count());
+        if c == '\n' {
+            self.push_row_edit(at.y, old_len, old_len + 1, "line break", false);
+        } else {
+            self.push_row_edit(at.y, old_len, 1, "typing", true);
+        }
         if c == '\n' {
             // The user wants to insert a newline; create a new row:
             self.insert_newline(at);
@@ -63,39 +431,617 @@ impl Document {
         }
     }
 
+    /// Remove a whole row from the document.
+    ///
+    /// # Returns
+    ///
+    /// The removed row, or None if `y` is past the end of the document.
+    ///
+    pub fn remove_row(&mut self, y: usize) -> Option<Row> {
+        if y >= self.row_count() {
+            return None;
+        }
+        self.record(Operation::RemoveRow { y });
+        self.mark_edited();
+        self.push_row_edit(y, 1, 0, "line deletion", false);
+        self.notes.line_removed(y);
+        Some(self.rows.remove(y))
+    }
+
+    /// Insert a whole row before row `y`, or at the end of the document if
+    /// `y` is past the end.
+    pub fn insert_row(&mut self, y: usize, row: Row) {
+        let y = y.min(self.row_count());
+        self.record(Operation::InsertRow {
+            y,
+            text: row.as_str().to_string(),
+        });
+        self.mark_edited();
+        self.push_row_edit(y, 0, 1, "line insertion", false);
+        self.notes.line_inserted_at(y);
+        self.rows.insert(y, row);
+    }
+
+    /// Insert a copy of row `y` below it.
+    ///
+    /// # Returns
+    ///
+    /// Whether there was a row to copy.
+    ///
+    pub fn duplicate_row(&mut self, y: usize) -> bool {
+        let copy = match self.rows.get(y) {
+            Some(row) => Row::from(row.as_str()),
+            None => return false,
+        };
+        self.record(Operation::DuplicateRow { y });
+        self.mark_edited();
+        self.push_row_edit(y + 1, 0, 1, "duplicated line", false);
+        self.notes.line_inserted_after(y);
+        self.rows.insert(y + 1, copy);
+        true
+    }
+
+    /// Swap two rows, along with their notes.
+    ///
+    /// # Returns
+    ///
+    /// Whether both rows exist.
+    ///
+    pub fn swap_rows(&mut self, a: usize, b: usize) -> bool {
+        let (first, last) = (a.min(b), a.max(b));
+        if last >= self.row_count() {
+            return false;
+        }
+        if first == last {
+            return true;
+        }
+        self.record(Operation::SwapRows { a, b });
+        let len = last - first + 1;
+        self.push_row_edit(first, len, len, "line move", false);
+        let last_row = self.rows.remove(last);
+        let first_row = self.rows.remove(first);
+        self.rows.insert(first, last_row);
+        self.rows.insert(last, first_row);
+        let mut order: Vec<usize> = (0..=last - first).collect();
+        order.swap(0, last - first);
+        self.notes.lines_reordered(first, &order);
+        self.mark_edited();
+        true
+    }
+
+    /// Get the text between two positions, with rows joined by newlines.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The first position, included.
+    /// * `end` - The last position, excluded.
+    ///
+    pub fn text_in_range(&self, start: &Position, end: &Position) -> String {
+        let mut text = String::new();
+        for y in start.y..=end.y {
+            let row = match self.rows.get(y) {
+                Some(row) => row,
+                None => break,
+            };
+            if y > start.y {
+                text.push('\n');
+            }
+            let from = if y == start.y { start.x } else { 0 };
+            let to = if y == end.y { end.x } else { row.len() };
+            text.push_str(&row.slice(from, to));
+        }
+        text
+    }
+
+    /// Delete the text between two positions.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The first position, included.
+    /// * `end` - The last position, excluded.
+    ///
+    pub fn delete_range(&mut self, start: &Position, end: &Position) {
+        if start.y >= self.row_count() {
+            return;
+        }
+        self.record(Operation::DeleteRange {
+            start: start.clone(),
+            end: end.clone(),
+        });
+        let end_y = end.y.min(self.row_count() - 1);
+        let end_x = if end_y == end.y { end.x } else { usize::MAX };
+        self.mark_edited();
+        self.push_row_edit(start.y, end_y + 1 - start.y, 1, "deletion", false);
+        let tail = self.rows[end_y].split(end_x);
+        self.rows[start.y].split(start.x);
+        self.rows[start.y].append(&tail);
+        for _ in start.y..end_y {
+            self.rows.remove(start.y + 1);
+            self.notes.line_joined_into(start.y);
+        }
+    }
+
+    /// Insert text, which may span several rows, at a position.
+    ///
+    /// # Returns
+    ///
+    /// The position just after the inserted text.
+    ///
+    pub fn insert_text(&mut self, at: &Position, text: &str) -> Position {
+        if at.y > self.row_count() {
+            return at.clone();
+        }
+        self.record(Operation::InsertText {
+            at: at.clone(),
+            text: text.to_string(),
+        });
+        let old_len = usize::from(at.y < self.row_count());
+        let new_len = text.split('\n').count();
+        self.push_row_edit(at.y, old_len, new_len, "insertion", false);
+        if at.y == self.row_count() {
+            self.rows.push(Row::default());
+        }
+        self.mark_edited();
+        let tail = self.rows[at.y].split(at.x);
+        let mut end = at.clone();
+        for (index, line) in text.split('\n').enumerate() {
+            let line = Row::from(line);
+            if index == 0 {
+                end.x = self.rows[at.y].len() + line.len();
+                self.rows[at.y].append(&line);
+            } else {
+                end.y += 1;
+                end.x = line.len();
+                self.notes.line_inserted_at(end.y);
+                self.rows.insert(end.y, line);
+            }
+        }
+        self.rows[end.y].append(&tail);
+        end
+    }
+
+    /// Replace `len` characters at a position with other text.
+    pub fn replace(&mut self, at: &Position, len: usize, with: &str) {
+        let end = Position {
+            x: at.x.saturating_add(len),
+            y: at.y,
+        };
+        self.delete_range(at, &end);
+        self.insert_text(at, with);
+    }
+
+    /// Reorder a run of rows as a single undoable edit.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The first row of the run.
+    /// * `order` - For each new position in the run, the offset of the row
+    ///   that moves there.
+    /// * `description` - What the edit did, spoken when it's undone.
+    ///
+    pub fn reorder_rows(&mut self, start: usize, order: &[usize], description: String) {
+        let end = start.saturating_add(order.len()).min(self.row_count());
+        if start >= end {
+            return;
+        }
+        self.record(Operation::ReorderRows {
+            start,
+            order: order.to_vec(),
+        });
+        let old = self.rows.remove_range(start..end);
+        let edit = RowEdit {
+            start,
+            old_rows: old.iter().map(|row| row.as_str().to_string()).collect(),
+            new_len: old.len(),
+            old_notes: self.notes.clone(),
+            description,
+        };
+        let reordered: Vec<Row> = order
+            .iter()
+            .filter_map(|from| old.get(*from))
+            .map(|row| Row::from(row.as_str()))
+            .collect();
+        self.rows.insert_many(start, reordered);
+        self.notes.lines_reordered(start, order);
+        self.mark_edited();
+        self.history.push(edit);
+    }
+
+    /// Remove adjacent duplicate rows in a run, like `uniq`, as a single
+    /// undoable edit.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The first row of the run.
+    /// * `len` - The number of rows in the run.
+    /// * `description` - What the edit did, spoken when it's undone.
+    ///
+    /// # Returns
+    ///
+    /// The number of rows removed.
+    ///
+    pub fn remove_adjacent_duplicates(
+        &mut self,
+        start: usize,
+        len: usize,
+        description: String,
+    ) -> usize {
+        let end = start.saturating_add(len).min(self.row_count());
+        let duplicates: Vec<usize> = (start.saturating_add(1)..end)
+            .filter(|&y| self.rows[y].as_str() == self.rows[y - 1].as_str())
+            .collect();
+        if duplicates.is_empty() {
+            return 0;
+        }
+        self.record(Operation::RemoveDuplicates { start, len });
+        let edit = RowEdit {
+            start,
+            old_rows: (start..end)
+                .map(|y| &self.rows[y])
+                .map(|row| row.as_str().to_string())
+                .collect(),
+            new_len: end - start - duplicates.len(),
+            old_notes: self.notes.clone(),
+            description,
+        };
+        // Remove from the bottom up so earlier indices stay valid.
+        for &y in duplicates.iter().rev() {
+            self.notes.line_removed(y);
+            self.rows.remove(y);
+        }
+        self.mark_edited();
+        self.history.push(edit);
+        duplicates.len()
+    }
+
+    /// Remember the rows an edit is about to replace, so that it can be
+    /// undone.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The first row the edit changes.
+    /// * `old_len` - How many rows it replaces.
+    /// * `new_len` - How many rows replace them.
+    /// * `description` - What the edit does, spoken when it's undone.
+    /// * `merge` - Whether the edit is undone along with the last one, if
+    ///   that was the same kind of edit to the same single row, so that a
+    ///   run of typing on a line is undone at once.
+    ///
+    fn push_row_edit(
+        &mut self,
+        start: usize,
+        old_len: usize,
+        new_len: usize,
+        description: &str,
+        merge: bool,
+    ) {
+        if merge {
+            if let Some(last) = self.history.last() {
+                if last.start == start && last.new_len == 1 && last.description == description {
+                    return;
+                }
+            }
+        }
+        if self.history.len() == MAX_UNDO_STEPS {
+            self.history.remove(0);
+        }
+        let end = start.saturating_add(old_len).min(self.row_count());
+        self.history.push(RowEdit {
+            start,
+            old_rows: (start.min(end)..end)
+                .filter_map(|y| self.rows.get(y))
+                .map(|row| row.as_str().to_string())
+                .collect(),
+            new_len,
+            old_notes: self.notes.clone(),
+            description: description.to_string(),
+        });
+    }
+
+    /// Undo the most recent edit: a run of typing on one line, a deletion,
+    /// or a change to whole rows, like a sort.
+    ///
+    /// # Returns
+    ///
+    /// The first row of the restored run and the edit's description, or
+    /// None if there is nothing to undo.
+    ///
+    pub fn undo(&mut self) -> Option<(usize, String)> {
+        let edit = self.history.pop()?;
+        self.record(Operation::Undo);
+        let end = edit
+            .start
+            .saturating_add(edit.new_len)
+            .min(self.row_count());
+        self.rows.replace_range(
+            edit.start..end,
+            edit.old_rows.iter().map(|row| Row::from(row.as_str())),
+        );
+        self.notes = edit.old_notes;
+        self.mark_edited();
+        Some((edit.start, edit.description))
+    }
+
     pub fn delete(&mut self, at: &Position) {
         let len = self.rows.len();
         if at.y >= len {
             return;
         }
-
-        self.dirty = true;
-        if at.x == self.rows.get_mut(at.y).unwrap().len() && at.y + 1 < len {
+        self.record(Operation::Delete { at: at.clone() });
+        self.mark_edited();
+        let joins = at.x == self.rows.get_mut(at.y).unwrap().len() && at.y + 1 < len;
+        if joins {
+            self.push_row_edit(at.y, 2, 1, "line join", false);
+        } else {
+            self.push_row_edit(at.y, 1, 1, "deletion", true);
+        }
+        if joins {
             let next_row = self.rows.remove(at.y + 1);
             let row = self.rows.get_mut(at.y).unwrap();
             row.append(&next_row);
+            self.notes.line_joined_into(at.y);
         } else {
             let row = self.rows.get_mut(at.y).unwrap();
             row.delete(at.x);
         }
     }
 
-    pub fn save(&mut self) -> Result<(), std::io::Error> {
-        if let Some(file_name) = &self.file_name {
-            let mut file = fs::File::create(file_name)?;
-            for row in &self.rows {
-                file.write_all(row.as_bytes())?;
-                file.write_all(b"\n")?;
+    /// Get the document's text as it's written to disk.
+    fn contents(&self) -> Result<Vec<u8>, std::io::Error> {
+        let mut contents = Vec::new();
+        for (y, row) in self.rows.iter().enumerate() {
+            if y > 0 {
+                contents.write_all(self.line_ending.as_bytes())?;
             }
+            contents.write_all(row.as_bytes())?;
+        }
+        if !self.rows.is_empty() && !self.missing_final_newline {
+            contents.write_all(self.line_ending.as_bytes())?;
+        }
+        Ok(contents)
+    }
+
+    /// Start writing the document to disk on a background thread. Once the
+    /// write has finished, pass it to `finish_save`.
+    ///
+    /// The file is replaced in one step, so a crash part of the way through
+    /// leaves it as it was; with `keep_backup`, the old file is kept as
+    /// `<name>~`.
+    pub fn start_save(&self, keep_backup: bool) -> Result<PendingSave, io::Error> {
+        let file_name = self
+            .file_name
+            .clone()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "the file has no name"))?;
+        let contents = self.contents()?;
+        let (sender, result) = mpsc::channel();
+        let encryption = self.encryption.clone();
+        let (path, data) = (file_name.clone(), contents.clone());
+        thread::spawn(move || {
+            let written = atomic::replace(&path, keep_backup, |temporary| match &encryption {
+                Some(encryption) => encryption.encrypt(&temporary.to_string_lossy(), &data),
+                // `replace` made the file, so it's only opened here.
+                None => OpenOptions::new()
+                    .write(true)
+                    .open(temporary)?
+                    .write_all(&data),
+            });
+            let _ = sender.send(written);
+        });
+        Ok(PendingSave {
+            file_name,
+            contents,
+            result,
+        })
+    }
+
+    /// Finish a save whose write succeeded. The document stays unsaved if
+    /// it was edited while the write was going on.
+    pub fn finish_save(&mut self, save: &PendingSave) -> Result<(), io::Error> {
+        if self.contents()? == save.contents {
             self.dirty = false;
+            // A save can come just after a commit made outside clack.
+            self.refresh_committed_lines();
+            self.remove_swap();
+            self.record(Operation::Saved);
+        }
+        // A newly named document should be locked like an opened one.
+        if self.lock.is_none() && self.locked_by.is_none() {
+            if let Ok(LockStatus::Acquired(lock)) = FileLock::acquire(&save.file_name) {
+                self.lock = Some(lock);
+            }
+        }
+        self.refresh_todo_count();
+        self.save_notes()
+    }
+
+    /// Get the note attached to a row, if any.
+    pub fn note_at(&self, y: usize) -> Option<&Note> {
+        self.notes.get(y)
+    }
+
+    /// Attach a note to a row, replacing any existing note, and save it.
+    /// While the document has unsaved changes, the note is saved with them.
+    pub fn set_note(&mut self, y: usize, note: Note) -> Result<(), std::io::Error> {
+        self.notes.set(y, note);
+        self.save_notes()
+    }
+
+    /// Remove the note attached to a row and save the change, or leave it
+    /// to be saved with the document's unsaved changes.
+    ///
+    /// # Returns
+    ///
+    /// Result<bool, std::io::Error> - Ok(true) if there was a note to remove.
+    ///
+    pub fn remove_note(&mut self, y: usize) -> Result<bool, std::io::Error> {
+        let removed = self.notes.remove(y).is_some();
+        self.save_notes()?;
+        Ok(removed)
+    }
+
+    /// Write the notes to their sidecar file, once the lines they're on
+    /// match the file's: the sidecar is numbered by the saved lines, so
+    /// notes moved by unsaved edits wait for the save.
+    fn save_notes(&self) -> Result<(), std::io::Error> {
+        match &self.file_name {
+            Some(file_name) if self.encryption.is_none() && !self.dirty => {
+                self.notes.save(file_name)
+            }
+            _ => Ok(()),
         }
-        Ok(())
     }
 
     pub fn is_dirty(&self) -> bool {
         self.dirty
     }
 
+    /// Get the file to keep a crash-recovery copy in, if this session may.
+    /// Encrypted files never get one, so their plain text stays off disk,
+    /// and a file open in another session is left to that session.
+    fn swap_file_name(&self) -> Option<&str> {
+        match (&self.file_name, &self.encryption, self.locked_by) {
+            (Some(file_name), None, None) => Some(file_name),
+            _ => None,
+        }
+    }
+
+    /// Write the document's unsaved text to its crash-recovery file.
+    pub fn write_swap(&self) -> Result<(), std::io::Error> {
+        match self.swap_file_name() {
+            Some(file_name) => swap::write(file_name, &self.contents()?),
+            None => Ok(()),
+        }
+    }
+
+    /// Remove the document's crash-recovery file, once its changes are
+    /// saved or deliberately thrown away.
+    pub fn remove_swap(&self) {
+        if let Some(file_name) = self.swap_file_name() {
+            swap::remove(file_name);
+        }
+    }
+
+    /// Remove the document's journal when clack quits, as its changes have
+    /// been saved or deliberately thrown away.
+    pub fn remove_journal(&mut self) {
+        if let Some(journal) = &mut self.journal {
+            journal.remove();
+        }
+    }
+
+    /// Get the text of a crash-recovery file left by a session that ended
+    /// without saving, if it differs from the file.
+    pub fn recoverable_text(&self) -> Option<String> {
+        let text = swap::read(self.swap_file_name()?)?;
+        match self.contents() {
+            Ok(contents) if contents == text.as_bytes() => {
+                self.remove_swap();
+                None
+            }
+            _ => Some(text),
+        }
+    }
+
+    /// Replace the document's text with recovered text. The document is
+    /// left unsaved, so that the file itself only changes when the user
+    /// saves.
+    pub fn recover(&mut self, text: &str) {
+        self.record(Operation::Replace {
+            text: text.to_string(),
+        });
+        let (lines, missing_final_newline) = split_lines(text);
+        self.missing_final_newline = missing_final_newline;
+        self.line_ending = LineEnding::detect(text);
+        self.rows = lines.into_iter().map(Row::from).collect();
+        self.mark_edited();
+        self.history.clear();
+        self.refresh_todo_count();
+    }
+
+    /// Write a change to the journal, if the document keeps one.
+    fn record(&mut self, operation: Operation) {
+        if let Some(journal) = &mut self.journal {
+            journal.record(&operation);
+        }
+    }
+
+    /// Make the changes left unsaved when the document's last session
+    /// ended, as logged in its journal. The document is left unsaved.
+    ///
+    /// # Returns
+    ///
+    /// How many changes were made.
+    ///
+    pub fn replay_previous_session(&mut self) -> usize {
+        let operations = match &self.journal {
+            Some(journal) => journal.unsaved_in_previous_session(),
+            None => return 0,
+        };
+        let count = operations.len();
+        for operation in operations {
+            self.apply(operation);
+        }
+        count
+    }
+
+    /// Make a change read from a journal.
+    fn apply(&mut self, operation: Operation) {
+        match operation {
+            Operation::Insert { at, c } => self.insert(&at, c),
+            Operation::Delete { at } => self.delete(&at),
+            Operation::RemoveRow { y } => {
+                self.remove_row(y);
+            }
+            Operation::InsertRow { y, text } => self.insert_row(y, Row::from(text.as_str())),
+            Operation::DuplicateRow { y } => {
+                self.duplicate_row(y);
+            }
+            Operation::SwapRows { a, b } => {
+                self.swap_rows(a, b);
+            }
+            Operation::DeleteRange { start, end } => self.delete_range(&start, &end),
+            Operation::InsertText { at, text } => {
+                self.insert_text(&at, &text);
+            }
+            Operation::ReorderRows { start, order } => {
+                self.reorder_rows(start, &order, String::from("Replayed reorder"));
+            }
+            Operation::RemoveDuplicates { start, len } => {
+                self.remove_adjacent_duplicates(start, len, String::from("Replayed uniq"));
+            }
+            Operation::Undo => {
+                self.undo();
+            }
+            Operation::Replace { text } => self.recover(&text),
+            Operation::Saved => (),
+        }
+    }
+
+    /// Get the number of TODO/FIXME annotations as of the last open or save.
+    pub fn todo_count(&self) -> usize {
+        self.todo_count
+    }
+
+    fn refresh_todo_count(&mut self) {
+        self.todo_count = self.rows.iter().filter(|row| row_has_todo(row)).count();
+    }
+
+    /// Find the next row containing a TODO/FIXME annotation.
+    ///
+    /// The search starts on the row after `after` and wraps around the end
+    /// of the document.
+    ///
+    /// # Returns
+    ///
+    /// The index of the matching row, or None if there are no annotations.
+    ///
+    pub fn find_next_todo(&self, after: usize) -> Option<usize> {
+        let count = self.row_count();
+        (1..=count)
+            .map(|step| after.saturating_add(step) % count)
+            .find(|&index| self.rows.get(index).is_some_and(row_has_todo))
+    }
+
     pub fn find(&self, query: &str, at: &Position, direction: SearchDirection) -> Option<Position> {
         if at.y > self.row_count() {
             return None;
@@ -136,4 +1082,26 @@ impl Document {
         }
         None
     }
+
+    /// Find every match for a query, from the top of the document down.
+    /// Matches don't overlap: each is looked for after the end of the last.
+    pub fn find_all(&self, query: &str) -> Vec<Position> {
+        let step = query.graphemes(true).count().max(1);
+        let mut matches = Vec::new();
+        let mut at = Position::default();
+        while let Some(position) = self.find(query, &at, SearchDirection::Forward) {
+            at = Position {
+                x: position.x.saturating_add(step),
+                y: position.y,
+            };
+            matches.push(position);
+        }
+        matches
+    }
+}
+
+fn row_has_todo(row: &Row) -> bool {
+    TODO_MARKERS
+        .iter()
+        .any(|marker| row.as_str().contains(marker))
 }
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::sound::{Audible, AudioClip, Tone, PENTATONIC_SCALE};
+
+/// The events that clack plays a sound for.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Earcon {
+    Startup,
+    Quit,
+    /// The cursor can't move, or an edit isn't allowed.
+    Blocked,
+    Saved,
+    /// A command had nothing to act on.
+    Error,
+    MatchFound,
+    /// The cursor moved onto a line with a note.
+    Note,
+    /// The view scrolled sideways, away from the start of the line.
+    ScrollRight,
+    /// The view scrolled back towards the start of the line.
+    ScrollLeft,
+    /// A voice memo is about to be recorded.
+    Recording,
+    /// The cursor moved onto a misspelled word.
+    Misspelling,
+    /// A word was typed twice in a row, like "the the".
+    RepeatedWord,
+    /// A closing bracket was typed that nothing opens.
+    Unmatched,
+    /// A command rang the terminal bell or sent a notification.
+    Bell,
+}
+
+/// How an earcon sounds: tones played one after another, or a sound file.
+#[derive(Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SoundDefinition {
+    Tones(Vec<Tone>),
+    Clip { file: PathBuf },
+}
+
+impl SoundDefinition {
+    /// Get the sounds to queue, in order.
+    pub fn audibles(&self) -> Vec<Box<dyn Audible>> {
+        match self {
+            Self::Tones(tones) => tones
+                .iter()
+                .map(|tone| Box::new(*tone) as Box<dyn Audible>)
+                .collect(),
+            Self::Clip { file } => vec![Box::new(AudioClip::new(file.clone()))],
+        }
+    }
+}
+
+/// The names of the built-in themes, in the order they're cycled through.
+pub const THEME_NAMES: &[&str] = &["pentatonic", "subtle"];
+
+/// A set of sounds for the events clack plays earcons for, and for the
+/// levels of indentation played before a line is read.
+#[derive(Clone)]
+pub struct EarconTheme {
+    name: &'static str,
+    sounds: HashMap<Earcon, SoundDefinition>,
+    /// One tone per indentation level, repeating when there are more levels.
+    indent: Vec<Tone>,
+}
+
+impl Default for EarconTheme {
+    fn default() -> Self {
+        Self::pentatonic()
+    }
+}
+
+impl EarconTheme {
+    /// Get a built-in theme by name.
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "pentatonic" => Some(Self::pentatonic()),
+            "subtle" => Some(Self::subtle()),
+            _ => None,
+        }
+    }
+
+    /// The original clack sounds: bright tones around A 440.
+    fn pentatonic() -> Self {
+        let tone = Tone::new;
+        let sounds = [
+            (
+                Earcon::Startup,
+                vec![tone(440.0, 0.06, 0.5), tone(660.0, 0.1, 0.5)],
+            ),
+            (
+                Earcon::Quit,
+                vec![tone(660.0, 0.1, 0.5), tone(440.0, 0.06, 0.5)],
+            ),
+            (Earcon::Blocked, vec![tone(440.0, 0.2, 0.5)]),
+            (
+                Earcon::Saved,
+                vec![tone(880.0, 0.05, 0.4), tone(1100.0, 0.08, 0.4)],
+            ),
+            (Earcon::Error, vec![tone(660.0, 0.01, 0.25); 3]),
+            (Earcon::MatchFound, vec![tone(880.0, 0.06, 0.5)]),
+            (Earcon::Note, vec![tone(550.0, 0.04, 0.3)]),
+            (
+                Earcon::ScrollRight,
+                vec![tone(330.0, 0.03, 0.2), tone(371.25, 0.03, 0.2)],
+            ),
+            (
+                Earcon::ScrollLeft,
+                vec![tone(371.25, 0.03, 0.2), tone(330.0, 0.03, 0.2)],
+            ),
+            (Earcon::Recording, vec![tone(880.0, 0.06, 0.5)]),
+            (
+                Earcon::Misspelling,
+                vec![tone(392.0, 0.03, 0.2), tone(370.0, 0.03, 0.2)],
+            ),
+            (
+                Earcon::RepeatedWord,
+                vec![tone(370.0, 0.03, 0.2), tone(370.0, 0.03, 0.2)],
+            ),
+            (
+                Earcon::Unmatched,
+                vec![tone(330.0, 0.03, 0.2), tone(262.0, 0.05, 0.2)],
+            ),
+            (
+                Earcon::Bell,
+                vec![tone(1319.0, 0.04, 0.3), tone(1760.0, 0.12, 0.25)],
+            ),
+        ];
+        // D, E, F#, A and B.
+        let indent = [293.33, 329.25, 371.25, 440.0, 495.0]
+            .iter()
+            .map(|&frequency| tone(frequency, 0.15, 0.5))
+            .collect();
+        Self {
+            name: "pentatonic",
+            sounds: sounds
+                .into_iter()
+                .map(|(earcon, tones)| (earcon, SoundDefinition::Tones(tones)))
+                .collect(),
+            indent,
+        }
+    }
+
+    /// Lower, quieter and shorter sounds, for long sessions.
+    fn subtle() -> Self {
+        let tone = Tone::new;
+        let sounds = [
+            (
+                Earcon::Startup,
+                vec![tone(220.0, 0.05, 0.25), tone(330.0, 0.08, 0.25)],
+            ),
+            (
+                Earcon::Quit,
+                vec![tone(330.0, 0.08, 0.25), tone(220.0, 0.05, 0.25)],
+            ),
+            (Earcon::Blocked, vec![tone(110.0, 0.12, 0.3)]),
+            (
+                Earcon::Saved,
+                vec![tone(247.0, 0.04, 0.2), tone(330.0, 0.06, 0.2)],
+            ),
+            (Earcon::Error, vec![tone(165.0, 0.02, 0.15); 2]),
+            (Earcon::MatchFound, vec![tone(330.0, 0.04, 0.25)]),
+            (Earcon::Note, vec![tone(275.0, 0.03, 0.2)]),
+            (
+                Earcon::ScrollRight,
+                vec![tone(165.0, 0.03, 0.12), tone(185.0, 0.03, 0.12)],
+            ),
+            (
+                Earcon::ScrollLeft,
+                vec![tone(185.0, 0.03, 0.12), tone(165.0, 0.03, 0.12)],
+            ),
+            (Earcon::Recording, vec![tone(220.0, 0.05, 0.25)]),
+            (
+                Earcon::Misspelling,
+                vec![tone(196.0, 0.02, 0.1), tone(185.0, 0.02, 0.1)],
+            ),
+            (
+                Earcon::RepeatedWord,
+                vec![tone(185.0, 0.02, 0.1), tone(185.0, 0.02, 0.1)],
+            ),
+            (
+                Earcon::Unmatched,
+                vec![tone(165.0, 0.02, 0.1), tone(131.0, 0.03, 0.1)],
+            ),
+            (
+                Earcon::Bell,
+                vec![tone(659.0, 0.03, 0.12), tone(880.0, 0.08, 0.1)],
+            ),
+        ];
+        // The pentatonic scale an octave down.
+        let indent = PENTATONIC_SCALE
+            .iter()
+            .map(|&frequency| tone(frequency / 2.0, 0.1, 0.3))
+            .collect();
+        Self {
+            name: "subtle",
+            sounds: sounds
+                .into_iter()
+                .map(|(earcon, tones)| (earcon, SoundDefinition::Tones(tones)))
+                .collect(),
+            indent,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Replace some of the theme's sounds, like those from the `[earcons]`
+    /// table in the config file.
+    pub fn with_overrides(mut self, overrides: &HashMap<Earcon, SoundDefinition>) -> Self {
+        for (earcon, sound) in overrides {
+            self.sounds.insert(*earcon, sound.clone());
+        }
+        self
+    }
+
+    /// Get the sounds to queue for an event, in order.
+    pub fn audibles(&self, earcon: Earcon) -> Vec<Box<dyn Audible>> {
+        self.sounds
+            .get(&earcon)
+            .map(SoundDefinition::audibles)
+            .unwrap_or_default()
+    }
+
+    /// Get the tone for a level of indentation, counting from 0.
+    pub fn indent_tone(&self, level: usize) -> Option<Tone> {
+        self.indent.get(level % self.indent.len().max(1)).copied()
+    }
+}
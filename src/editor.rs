@@ -1,19 +1,117 @@
+use crate::brackets;
+use crate::braille::Braille;
+use crate::cli::{FileArgument, Options};
+use crate::clipboard;
+use crate::columns;
+use crate::completion;
 use crate::config::{self, ConfigManager};
-use crate::sound::{SoundManager, Tone, Utterance};
-use crate::utils::{string_to_speakable_tokens, SearchDirection};
+use crate::crypt::{Encryption, Scheme};
+use crate::diff::{count_lines, map_line, Hunk};
+use crate::digraph;
+use crate::document::{LineEnding, PendingSave};
+use crate::earcon::{Earcon, THEME_NAMES};
+use crate::keymap::{self, Action, KeyMap};
+use crate::notes::{self, Note};
+use crate::notifications::{self, Notifications};
+use crate::outline::{self, Entry};
+use crate::overview;
+use crate::palette;
+use crate::paths;
+use crate::quickfix::{self, Diagnostic};
+use crate::session::{SessionStore, WorkspaceBuffer};
+use crate::sort::{self, SortKey};
+use crate::sound::{Audible, AudioClip, Priority, SoundManager, SoundSequence, Tone, Utterance};
+use crate::speech;
+use crate::spelling::{self, Dictionary};
+use crate::stats::{self, Summary};
+use crate::utils::{
+    default_echo_name, phonetic_name, split_identifier, split_tokens, string_to_speakable_tokens,
+    CapitalReading, IdentifierReading, IndentReading, LinkReadingMode, PunctuationLevel,
+    SearchDirection, SpeechOptions, TypingEcho, VoiceParameters, WrappedLineReading,
+};
+use crate::verbosity::{AnnouncementClass, VerbositySettings};
 use crate::Document;
 use crate::Row;
 use crate::Terminal;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
 use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::sync::mpsc::TryRecvError;
+use std::thread;
 use std::time::Duration;
 use std::time::Instant;
-use termion::color;
-use termion::event::Key;
+
+use crate::terminal::{Key, Rgb};
+use unicode_segmentation::UnicodeSegmentation;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-const STATUS_FG_COLOR: color::Rgb = color::Rgb(63, 63, 63);
-const STATUS_BG_COLOR: color::Rgb = color::Rgb(239, 239, 239);
+/// The number of changed regions spoken after a reload before summarizing.
+const MAX_SPOKEN_HUNKS: usize = 5;
+
+/// The number of lines of a git hunk read out before summarizing the rest.
+const MAX_SPOKEN_HUNK_LINES: usize = 3;
+
+/// Gaps between keypresses longer than this don't count as editing time.
+const IDLE_THRESHOLD: Duration = Duration::from_secs(120);
+
+/// How often continuous reading checks for a keypress.
+const READ_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// How often a running shell command is checked on, and for a key to stop it.
+const SHELL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How often the config file is checked for changes while waiting for a key.
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often a document with unsaved changes is copied to its crash-recovery
+/// file.
+const SWAP_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long saving waits for the write before leaving it to finish in the
+/// background. Most disks are done well within this, so the result is
+/// spoken straight away.
+const QUICK_SAVE_WAIT: Duration = Duration::from_millis(200);
+
+/// How often a save running in the background is checked on while waiting
+/// for a key.
+const SAVE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How much higher a search match is spoken than the rest of its line, from
+/// -100 to 100.
+const MATCH_PITCH: i64 = 50;
+
+/// How much higher capital letters are spoken, from -100 to 100, when
+/// capitals are read at a higher pitch.
+const CAPITAL_PITCH: i64 = 40;
+
+/// How soon spelling a word again spells it phonetically.
+const SPELL_AGAIN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How much the speech rate hotkeys change the rate by, in words per minute.
+const RATE_STEP_WPM: i64 = 25;
+/// How much the speech and tone volume hotkeys change the volume by.
+const VOLUME_STEP: f32 = 0.1;
+/// The loudest tones and clips can be made, as in the config file.
+const MAX_TONE_VOLUME: f32 = 2.0;
+
+/// The pitches, in Hz, of the position tone at the top and the bottom of a
+/// file: two octaves apart, falling as the cursor goes down.
+const POSITION_TONE_TOP: f32 = 1200.0;
+const POSITION_TONE_BOTTOM: f32 = 300.0;
+/// How long the position tone lasts, in seconds, and how loud it is.
+const POSITION_TONE_DURATION: f32 = 0.03;
+const POSITION_TONE_VOLUME: f32 = 0.3;
+
+const STATUS_FG_COLOR: Rgb = Rgb(63, 63, 63);
+const STATUS_BG_COLOR: Rgb = Rgb(239, 239, 239);
+/// Lines changed since the last git commit.
+const CHANGED_LINE_FG_COLOR: Rgb = Rgb(63, 63, 63);
+const CHANGED_LINE_BG_COLOR: Rgb = Rgb(214, 234, 214);
 
 #[derive(PartialEq)]
 enum QuitStatus {
@@ -41,12 +139,110 @@ pub struct Editor {
     should_draw_ui: bool,
     config_manager: config::ConfigManager,
     wrap_arrow_key_navigation: bool,
+    /// Whether long rows are broken across screen lines, which Up and Down
+    /// then move between.
+    soft_wrap: bool,
+    wrapped_line_reading: WrappedLineReading,
+    /// Whether any keypress silences speech before the key is acted on.
+    interrupt_speech_on_keypress: bool,
+    /// What is spoken while typing; cycled with Alt-E.
+    typing_echo: TypingEcho,
     terminal: Terminal,
     cursor_position: Position,
     offset: Position,
     document: Document,
     status_message: StatusMessage,
     sound_manager: SoundManager,
+    speech_options: SpeechOptions,
+    /// The indentation level of the last line read, for announcing blocks.
+    last_indent_level: Option<usize>,
+    /// The word list prose is spell-checked against, if one was found.
+    dictionary: Option<Dictionary>,
+    verbosity: VerbositySettings,
+    trash: Vec<String>,
+    compare_mark: Option<usize>,
+    /// The line that location announcements are measured from, if one is
+    /// set.
+    anchor_line: Option<usize>,
+    /// The other end of the selection from the cursor, if a mark is set.
+    selection_anchor: Option<Position>,
+    /// Text copied or cut within clack, used when there's no system
+    /// clipboard.
+    register: String,
+    session: SessionStore,
+    keymap: KeyMap,
+    last_activity: Instant,
+    /// When the active document's crash-recovery file was last written.
+    last_swap_write: Instant,
+    /// Saves still being written on background threads.
+    pending_saves: Vec<PendingSave>,
+    /// Editing time not yet written to the session store.
+    pending_editing_time: Duration,
+    /// Problems found in the config file and key bindings, spoken once at
+    /// startup.
+    config_problems: Vec<String>,
+    /// The braille display the current line is mirrored to, if one is
+    /// enabled and connected.
+    braille: Option<Braille>,
+    /// Encrypted files named on the command line. They're opened once the
+    /// editor is running, so that their passphrases can be asked for.
+    encrypted_files: Vec<String>,
+    /// The errors and other locations found in the last build or shell
+    /// command's output, stepped through with next and previous error.
+    diagnostics: Vec<Diagnostic>,
+    /// The diagnostic last gone to, if any.
+    diagnostic_index: Option<usize>,
+    /// Files named on the command line with a line to start on, like
+    /// `main.rs:120:4`. The cursor is put there once they're open.
+    start_positions: Vec<FileArgument>,
+    /// Whether the files named on the command line are opened read only.
+    open_read_only: bool,
+    /// Whether `--screen-reader` was given, which keeps screen reader mode
+    /// on whatever the config file says.
+    screen_reader_option: bool,
+    /// Whether the files named on the command line get their last session's
+    /// unsaved changes replayed from their journals.
+    replay: bool,
+    /// The keys pressed since recording a macro started, while one is being
+    /// recorded.
+    macro_recording: Option<Vec<Key>>,
+    /// The last macro recorded.
+    last_macro: Vec<Key>,
+    /// Keys from a macro being replayed, read before any the user presses.
+    macro_replay_keys: VecDeque<Key>,
+    /// How many times the next replay repeats the macro, typed with Alt and
+    /// digits before it.
+    macro_count: Option<usize>,
+    /// The vi-style mode, if modal editing is turned on.
+    vi_mode: Option<ViMode>,
+    /// Whether `d` was pressed in normal mode, waiting for a second `d`.
+    vi_pending_delete: bool,
+    /// How far back in the speech history the last utterance heard again
+    /// was (0 for the newest), while stepping through it.
+    speech_review: Option<usize>,
+    /// Where and when a word was last spelled, so that spelling it again
+    /// straight away spells it phonetically.
+    spelled_at: Option<(Position, Instant)>,
+    /// The closers auto-pairing put after the cursor on its line, innermost
+    /// last, to be typed over rather than doubled.
+    auto_closers: Vec<char>,
+    /// Every open buffer. The active buffer's state lives in the editor's
+    /// own fields (`document`, `cursor_position` and so on), and its slot
+    /// here is left empty until another buffer is switched to.
+    buffers: Vec<Buffer>,
+    active_buffer: usize,
+}
+
+/// An open document, with the view state kept for it while another buffer
+/// is active.
+#[derive(Default)]
+struct Buffer {
+    document: Document,
+    cursor_position: Position,
+    offset: Position,
+    selection_anchor: Option<Position>,
+    compare_mark: Option<usize>,
+    anchor_line: Option<usize>,
 }
 
 enum Mode {
@@ -54,6 +250,27 @@ enum Mode {
     Quitting,
 }
 
+/// The modes of vi-style modal editing, when it's turned on.
+#[derive(Clone, Copy, PartialEq)]
+enum ViMode {
+    /// Letters are commands: motions, deletions and mode changes.
+    Normal,
+    /// Letters are typed.
+    Insert,
+    /// Motions extend a selection from where visual mode started.
+    Visual,
+}
+
+impl ViMode {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Insert => "insert",
+            Self::Visual => "visual",
+        }
+    }
+}
+
 struct StatusMessage {
     text: String,
     time: Instant,
@@ -77,6 +294,31 @@ impl Editor {
     ///
     pub fn run(&mut self) {
         self.change_mode(Mode::Editing);
+        for file_name in std::mem::take(&mut self.encrypted_files) {
+            match self.open_buffer(&file_name) {
+                Ok(true) if self.open_read_only => self.document.set_read_only(true),
+                Ok(_) => (),
+                Err(error) => self.announce_open_error(&file_name, &error),
+            }
+        }
+        if let Err(error) = self.warn_if_locked() {
+            die(error);
+        }
+        let active = self.active_buffer;
+        for index in 0..self.buffers.len() {
+            self.switch_to_buffer(index);
+            self.warn_if_unwritable();
+            self.announce_line_ending();
+            if self.replay {
+                self.replay_journal();
+            } else if let Err(error) = self.offer_recovery() {
+                die(error);
+            }
+        }
+        self.switch_to_buffer(active);
+        self.go_to_start_positions();
+        self.announce_git_status();
+        self.announce_config_problems();
         loop {
             if let Err(error) = self.refresh_screen() {
                 die(error);
@@ -84,44 +326,187 @@ impl Editor {
             if self.should_quit == QuitStatus::Quitting {
                 break;
             }
+            self.sound_manager.begin_iteration();
             let input_handler = self.process_keypress();
+            self.update_column_pan();
             match input_handler {
                 Err(error) => die(error),
                 _ => (),
             };
-            self.sound_manager.play_next_or_wait();
         }
+        self.flush_editing_time();
+        // Don't exit part of the way through writing a file.
+        while !self.pending_saves.is_empty() {
+            self.finish_pending_saves(SAVE_POLL_INTERVAL);
+        }
+        // Quitting is the one way out that leaves no changes to recover:
+        // they were saved, or the user chose to throw them away.
+        self.document.remove_swap();
+        self.document.remove_journal();
+        for buffer in &mut self.buffers {
+            buffer.document.remove_swap();
+            buffer.document.remove_journal();
+        }
+        // Let the goodbye sounds finish before the process exits.
+        self.sound_manager.wait_until_idle();
     }
 
-    /// Create a new editor with default settings.
+    /// Create a new editor for the files and options given on the command
+    /// line.
     ///
-    pub fn default() -> Self {
-        let args: Vec<String> = env::args().collect();
+    pub fn new(options: Options) -> Self {
         let mut initial_status = String::from("Ctrl-S = save | Ctrl-Q = quit");
-        let document = if args.len() > 1 {
-            let file_name = &args[1];
-            let doc = Document::open(&file_name);
-            if doc.is_ok() {
-                doc.unwrap()
-            } else {
-                initial_status = format!("ERR: Could not open file: {}", file_name);
-                Document::default()
+        let session = SessionStore::load();
+        // Encrypted files are opened once the editor is running, so that
+        // their passphrases can be asked for.
+        let mut encrypted_files = Vec::new();
+        let mut documents = Vec::new();
+        let Options {
+            files,
+            silent,
+            screen_reader,
+            read_only: open_read_only,
+            replay,
+        } = options;
+        for file_name in files.iter().map(|file| &file.file_name) {
+            if Scheme::for_file(file_name).is_some() {
+                encrypted_files.push(file_name.clone());
+                continue;
+            }
+            match Document::open(file_name) {
+                Ok(mut document) => {
+                    document.set_voice(remembered_voice(&session, file_name));
+                    document.set_read_only(open_read_only);
+                    documents.push(document);
+                }
+                Err(_) => initial_status = format!("ERR: Could not open file: {}", file_name),
+            }
+        }
+        let start_positions = files
+            .into_iter()
+            .filter(|file| file.line.is_some())
+            .collect();
+        let mut documents = documents.into_iter();
+        let document = documents.next().unwrap_or_default();
+        // The active buffer's slot stays empty; see `Editor::buffers`.
+        let buffers = std::iter::once(Buffer::default())
+            .chain(documents.map(|document| Buffer {
+                document,
+                ..Buffer::default()
+            }))
+            .collect();
+
+        let config_manager = ConfigManager::new();
+        let mut speech_options = config_manager.get_speech_options();
+        speech_options.voice = document
+            .voice()
+            .cloned()
+            .or_else(|| config_manager.get_voice());
+        let verbosity = config_manager.get_verbosity_settings();
+        let interrupt_speech_on_keypress = config_manager.get_interrupt_speech_on_keypress();
+        let typing_echo = config_manager.get_typing_echo();
+        let (keymap, keymap_problems) =
+            KeyMap::with_user_bindings(&config_manager.get_key_bindings());
+        let mut config_problems: Vec<String> = config_manager
+            .problem()
+            .map(String::from)
+            .into_iter()
+            .chain(keymap_problems)
+            .collect();
+        let dictionary = match config_manager.get_dictionary() {
+            Some(path) => match Dictionary::load(&path) {
+                Ok(dictionary) => Some(dictionary),
+                Err(error) => {
+                    config_problems.push(format!(
+                        "Couldn't read the dictionary {}: {}.",
+                        path.display(),
+                        error
+                    ));
+                    None
+                }
+            },
+            None => Dictionary::default_path().and_then(|path| Dictionary::load(&path).ok()),
+        };
+        let mut sound_manager = SoundManager::new(speech::select_backend(
+            &config_manager.get_speech_backend_name(),
+        ));
+        sound_manager.set_budget(Duration::from_millis(config_manager.get_audio_budget_ms()));
+        sound_manager.set_volume(config_manager.get_volume());
+        sound_manager.set_speech_volume(config_manager.get_speech_volume());
+        sound_manager.set_ducking(config_manager.get_earcon_ducking());
+        sound_manager.set_theme(config_manager.get_earcon_theme(None));
+        sound_manager.set_silent(silent);
+        sound_manager.set_night_mode(config_manager.get_night_mode());
+        sound_manager.set_screen_reader(screen_reader || config_manager.get_screen_reader());
+        let braille = if config_manager.get_braille() {
+            match Braille::connect() {
+                Ok(braille) => Some(braille),
+                Err(error) => {
+                    config_problems.push(format!(
+                        "Couldn't connect to the braille display: {}.",
+                        error
+                    ));
+                    None
+                }
             }
         } else {
-            Document::default()
+            None
         };
+        let wrap_arrow_key_navigation = config_manager.get_wrap();
+        let modal_editing = config_manager.get_modal_editing();
+        let soft_wrap = config_manager.get_soft_wrap();
+        let wrapped_line_reading = config_manager.get_wrapped_line_reading();
 
         Self {
             should_quit: QuitStatus::Default,
             should_draw_ui: true,
-            config_manager: ConfigManager::new(),
-            wrap_arrow_key_navigation: false,
+            config_manager,
+            wrap_arrow_key_navigation,
+            soft_wrap,
+            wrapped_line_reading,
+            interrupt_speech_on_keypress,
+            typing_echo,
             terminal: Terminal::default().expect("Failed to initialize terminal"),
             cursor_position: Position::default(),
             document,
             offset: Position::default(),
             status_message: StatusMessage::from(initial_status),
-            sound_manager: SoundManager::new(),
+            sound_manager,
+            speech_options,
+            last_indent_level: None,
+            dictionary,
+            verbosity,
+            trash: Vec::new(),
+            compare_mark: None,
+            anchor_line: None,
+            selection_anchor: None,
+            register: String::new(),
+            session,
+            keymap,
+            last_activity: Instant::now(),
+            last_swap_write: Instant::now(),
+            pending_saves: Vec::new(),
+            pending_editing_time: Duration::ZERO,
+            config_problems,
+            braille,
+            encrypted_files,
+            start_positions,
+            diagnostics: Vec::new(),
+            diagnostic_index: None,
+            open_read_only,
+            screen_reader_option: screen_reader,
+            replay,
+            macro_recording: None,
+            last_macro: Vec::new(),
+            macro_replay_keys: VecDeque::new(),
+            macro_count: None,
+            vi_mode: modal_editing.then_some(ViMode::Normal),
+            vi_pending_delete: false,
+            speech_review: None,
+            spelled_at: None,
+            auto_closers: Vec::new(),
+            buffers,
+            active_buffer: 0,
         }
     }
 
@@ -137,9 +522,55 @@ impl Editor {
     /// The utterance to speak.
     ///
     fn create_utterance(&mut self, text: &str) -> Utterance {
-        let wpm = self.config_manager.get_rate_wpm();
-        let utterance = Utterance::from_text_and_wpm(text.to_string(), wpm);
-        utterance
+        self.speech_utterance(text)
+            .with_pan(-self.speech_options.stereo_speech)
+            .with_optional_parameters(self.speech_options.context_voices.messages.as_ref())
+    }
+
+    /// Create an utterance of the document's own text, like a word or a
+    /// selection, panned apart from announcements when stereo speech is on.
+    fn create_content_utterance(&mut self, text: &str) -> Utterance {
+        self.speech_utterance(text)
+            .with_pan(self.speech_options.stereo_speech)
+            .with_optional_parameters(self.speech_options.context_voices.content.as_ref())
+    }
+
+    /// Create an utterance of characters from the document, typed, moved
+    /// over, or spelled out, in the spelling voice if there is one.
+    fn create_spelling_utterance(&mut self, text: &str) -> Utterance {
+        self.speech_utterance(text)
+            .with_pan(self.speech_options.stereo_speech)
+            .with_optional_parameters(self.speech_options.context_voices.spelling.as_ref())
+    }
+
+    /// Create an utterance in the usual voice, before any context's voice
+    /// settings.
+    fn speech_utterance(&mut self, text: &str) -> Utterance {
+        self.update_column_pan();
+        Utterance::from_text_and_wpm(text.to_string(), self.speech_options.rate_wpm)
+            .with_voice(self.speech_options.voice.clone())
+    }
+
+    /// Create an utterance phrased for the verbosity of its class.
+    ///
+    /// # Arguments
+    ///
+    /// * `class` - The kind of announcement.
+    /// * `verbose` - The full phrasing.
+    /// * `terse` - The short phrasing.
+    ///
+    /// # Returns
+    ///
+    /// The utterance to speak.
+    ///
+    fn create_announcement(
+        &mut self,
+        class: AnnouncementClass,
+        verbose: &str,
+        terse: &str,
+    ) -> Utterance {
+        let text = self.verbosity.format(class, verbose, terse).to_string();
+        self.create_utterance(&text)
     }
 
     /// Redraw the editor screen.
@@ -161,18 +592,44 @@ impl Editor {
         if self.should_quit == QuitStatus::Quitting {
             Terminal::clear_screen();
         } else {
+            self.show_written_speech();
+            self.update_braille();
             self.draw_rows();
             self.draw_status_bar();
             self.draw_message_bar();
-            Terminal::cursor_position(&Position {
-                x: self.cursor_position.x.saturating_sub(self.offset.x),
-                y: self.cursor_position.y.saturating_sub(self.offset.y),
-            });
+            Terminal::cursor_position(&self.cursor_screen_position());
         }
         Terminal::cursor_show();
         Terminal::flush()
     }
 
+    /// In screen reader mode, put what would have been spoken on the
+    /// message line for the screen reader to read, unless a prompt has been
+    /// shown there since.
+    fn show_written_speech(&mut self) {
+        if let Some((text, time)) = self.sound_manager.take_written() {
+            if time >= self.status_message.time {
+                self.status_message = StatusMessage { text, time };
+            }
+        }
+    }
+
+    /// Mirror the current line and cursor, or a new status message, to the
+    /// braille display.
+    fn update_braille(&mut self) {
+        if let Some(braille) = &mut self.braille {
+            let line = self
+                .document
+                .get_row(self.cursor_position.y)
+                .map_or("", Row::as_str);
+            braille.update(
+                line,
+                self.cursor_position.x,
+                (&self.status_message.text, self.status_message.time),
+            );
+        }
+    }
+
     /// Process a single keypress in the document.
     ///
     /// This method also handles special key combinations with modifiers like
@@ -184,138 +641,584 @@ impl Editor {
     /// handled, or Err(error) if the keypress failed.
     ///
     fn process_keypress(&mut self) -> Result<bool, std::io::Error> {
-        // TODO: Modal editing.
-        let pressed_key = Terminal::read_key()?;
-        match pressed_key {
-            Key::Ctrl('q') => {
-                if self.document.is_dirty() && self.should_quit == QuitStatus::Default {
+        let pressed_key = match self.macro_replay_keys.pop_front() {
+            Some(key) => key,
+            None => loop {
+                self.write_swap_if_due();
+                let interval = if self.pending_saves.is_empty() {
+                    CONFIG_POLL_INTERVAL
+                } else {
+                    SAVE_POLL_INTERVAL
+                };
+                if let Some(key) = Terminal::poll_key(interval) {
+                    break key?;
+                }
+                let saved = self.finish_pending_saves(Duration::ZERO);
+                if self.reload_config_if_changed() || saved {
+                    self.refresh_screen()?;
+                }
+            },
+        };
+        self.record_activity();
+        if self.interrupt_speech_on_keypress {
+            self.sound_manager.silence();
+        }
+        let previous_y = self.cursor_position.y;
+        let previous_misspelling = self.misspelling_at_cursor();
+        let action = self.keymap.action_for(pressed_key);
+        // Only text typed into the document keeps the closing brackets
+        // typed for it; any other change or move may have left them behind.
+        let is_typing_text = action.is_none()
+            && matches!(pressed_key, Key::Char(_))
+            && !matches!(self.vi_mode, Some(ViMode::Normal | ViMode::Visual))
+            && !self.document.is_read_only();
+        let is_macro_count =
+            action.is_none() && matches!(pressed_key, Key::Alt(c) if c.is_ascii_digit());
+        let is_macro_command = matches!(
+            action,
+            Some(Action::StartMacro | Action::StopMacro | Action::ReplayMacro)
+        );
+        if !is_macro_count && !is_macro_command {
+            self.record_macro_key(pressed_key);
+        }
+        // Commands go in the speech history, but not typing, which would
+        // crowd everything else out, nor the keys that step through it.
+        let is_speech_review = matches!(
+            action,
+            Some(Action::PreviousSpoken | Action::NextSpoken | Action::RepeatLastSpoken)
+        );
+        if let Some(action) = action.filter(|_| !is_speech_review) {
+            self.sound_manager.remember_key(format!(
+                "Pressed {}, {}.",
+                keymap::chord_name(pressed_key),
+                action.spoken_name()
+            ));
+        }
+        match action {
+            Some(action) => self.perform(action)?,
+            None => match pressed_key {
+                Key::Alt(c) if is_macro_count => self.add_to_macro_count(c),
+
+                // Unbound Alt chords are swallowed rather than typed.
+                Key::Alt(_) => (),
+
+                Key::Esc if self.vi_mode.is_some() => self.vi_escape(),
+
+                Key::Char(c) if matches!(self.vi_mode, Some(ViMode::Normal | ViMode::Visual)) => {
+                    self.vi_command(c)?;
+                }
+
+                Key::Char(c) if self.document.is_read_only() => {
+                    if !self.review(c) {
+                        self.play_read_only_warning();
+                    }
+                }
+
+                Key::Char(c) => {
+                    if c == '\n' {
+                        // Enter finishes a word as much as a space does.
+                        self.warn_if_repeated_word();
+                        self.insert_carriage_return();
+                    } else if c == '\t' && self.config_manager.get_insert_spaces() {
+                        self.insert_soft_tab();
+                    } else if !self.type_auto_pair(c) {
+                        self.echo_typed(c);
+                        self.document.insert(&self.cursor_position, c);
+                        self.move_cursor(Key::Right, WrappingBehavior::Wrap);
+                        self.check_closing_bracket(c);
+                    }
+                }
+
+                _ => return Ok(false),
+            },
+        }
+        if !is_macro_count {
+            self.macro_count = None;
+        }
+        if !is_speech_review {
+            self.speech_review = None;
+        }
+        if self.cursor_position.y != previous_y || !is_typing_text {
+            self.auto_closers.clear();
+        }
+        if self.cursor_position.y != previous_y
+            && self.document.note_at(self.cursor_position.y).is_some()
+        {
+            self.play_note_earcon();
+        }
+        // Words are misspelled while they're being typed, so only moving
+        // onto a misspelling is worth a sound. Jumping to one plays its own.
+        let is_typing = matches!(pressed_key, Key::Char(_) | Key::Backspace | Key::Delete);
+        let jumped = matches!(
+            action,
+            Some(Action::NextMisspelling | Action::PreviousMisspelling)
+        );
+        let misspelling = self.misspelling_at_cursor();
+        if !is_typing && !jumped && misspelling.is_some() && misspelling != previous_misspelling {
+            self.play_earcon(Earcon::Misspelling);
+        }
+        let was_scrolled_right = self.offset.x > 0;
+        self.scroll();
+        let is_scrolled_right = self.offset.x > 0;
+        if is_scrolled_right != was_scrolled_right {
+            self.play_horizontal_scroll_earcon(is_scrolled_right);
+        }
+        Ok(true)
+    }
+
+    /// Carry out an action bound in the keymap.
+    fn perform(&mut self, action: Action) -> Result<(), std::io::Error> {
+        match action {
+            Action::Quit => {
+                if self.has_unsaved_changes() && self.should_quit == QuitStatus::Default {
                     self.should_quit = QuitStatus::Confirming;
                     self.status_message = StatusMessage::from("Quit? (Ctrl-Q)".to_string());
-                    let utt = self.create_utterance("Quit without saving?");
+                    let utt = self.create_announcement(
+                        AnnouncementClass::Prompts,
+                        "Quit without saving?",
+                        "Quit?",
+                    );
                     self.sound_manager.interrupt_and_play(Box::new(utt));
                 } else {
                     self.should_quit = QuitStatus::Quitting;
                     self.change_mode(Mode::Quitting);
                 }
             }
-            Key::Ctrl('s') => self.save(),
-
-            Key::Ctrl('f') => self.search(),
-
-            Key::Alt(';') => {
-                // Say the current location:
-                let utt = self.create_utterance(
-                    format!(
-                        "Row {}, column {}",
-                        self.cursor_position.y.saturating_add(1),
-                        self.cursor_position.x.saturating_add(1)
-                    )
-                    .as_str(),
-                );
+            Action::Save => self.save(),
+            Action::OpenFile => self.prompt_and_open_buffer()?,
+            Action::NextBuffer => self.cycle_buffer(),
+            Action::ListBuffers => self.list_buffers(),
+            Action::Search => self.search(),
+            Action::FindAll => self.find_all()?,
+            Action::Replace => self.replace()?,
+            Action::GoToLine => self.go_to_line()?,
+            Action::InsertDigraph => self.insert_digraph()?,
+            Action::CompleteWord => self.complete_word()?,
+            Action::SpeakLocation => {
+                let utt = self.create_location_announcement();
                 self.sound_manager.prepend(Box::new(utt));
             }
-            Key::Alt('l') => {
-                // Say the current line.
-                self.speak_current_row()
-            }
-
-            Key::Alt('.') => {
-                // Spell the current word.
-                let default = &Row::from("");
-                let row = self
-                    .document
-                    .get_row(self.cursor_position.y)
-                    .unwrap_or(default);
-                let word = row.get_word_at(self.cursor_position.x).unwrap_or_default();
-                // Add a space in between each letter.
-                let letters_with_spaces = word
-                    .chars()
-                    .map(|c| format!("{}, ", c))
-                    .collect::<Vec<String>>()
-                    .join("");
-                let utt = self.create_utterance(letters_with_spaces.as_str());
-                self.sound_manager.play_and_wait(Box::new(utt));
-            }
-
-            Key::Alt(c) => {
-                if c == 'j' {
-                    // Say the current line.
-                    self.speak_current_row();
-                    self.move_cursor(Key::Down, WrappingBehavior::Default);
-                }
-            }
-
-            Key::Char(c) => {
-                if c == '\n' {
-                    self.insert_carriage_return();
-                } else {
-                    if !c.is_alphanumeric() {
-                        if self
-                            .get_current_word()
-                            .chars()
-                            .map(|c| c.is_alphanumeric())
-                            .all(|c| c)
-                        {
-                            self.speak_current_word();
-                        }
-                        self.speak_character(&c.to_string());
-                    }
-                    self.document.insert(&self.cursor_position, c);
-                    self.move_cursor(Key::Right, WrappingBehavior::Wrap);
-                }
+            Action::SpeakStatusBar => self.speak_status_bar(),
+            Action::ToggleVerbosity => self.toggle_verbosity()?,
+            Action::CyclePunctuation => self.cycle_punctuation(),
+            Action::ToggleNumbers => self.toggle_numbers(),
+            Action::CycleTypingEcho => self.cycle_typing_echo(),
+            Action::SpeechFaster => self.change_rate(RATE_STEP_WPM),
+            Action::SpeechSlower => self.change_rate(-RATE_STEP_WPM),
+            Action::SpeechLouder => self.change_volume(true, VOLUME_STEP),
+            Action::SpeechQuieter => self.change_volume(true, -VOLUME_STEP),
+            Action::TonesLouder => self.change_volume(false, VOLUME_STEP),
+            Action::TonesQuieter => self.change_volume(false, -VOLUME_STEP),
+            Action::ToggleSilent => self.toggle_silent(),
+            Action::ToggleNightMode => self.toggle_night_mode(),
+            Action::ToggleSoftWrap => self.toggle_soft_wrap(),
+            Action::ToggleReadOnly => self.toggle_read_only(),
+            Action::CycleEarconTheme => self.cycle_earcon_theme(),
+            Action::NextTodo => self.jump_to_next_todo(),
+            Action::NextHunk => self.jump_to_hunk(SearchDirection::Forward),
+            Action::PreviousHunk => self.jump_to_hunk(SearchDirection::Backward),
+            Action::EditNote => self.edit_text_note()?,
+            Action::RecordMemo => self.record_voice_memo()?,
+            Action::PlayNote => self.play_note(),
+            Action::Reload => self.reload()?,
+            Action::SetVoice => self.set_document_voice()?,
+            Action::SpeakLine => self.speak_current_row(),
+            Action::SpellWord => self.spell_word(),
+            Action::SpeakColumns => self.speak_columns(),
+            Action::NextMisspelling => self.jump_to_misspelling(SearchDirection::Forward),
+            Action::PreviousMisspelling => self.jump_to_misspelling(SearchDirection::Backward),
+            Action::SpellingSuggestions => self.offer_spelling_suggestions()?,
+            Action::ReadAndAdvance => self.read_line_and_advance(),
+            Action::ReadToEnd => self.read_to_end()?,
+            Action::MarkCompare => self.mark_compare_row(),
+            Action::ToggleAnchor => self.toggle_anchor(),
+            Action::Compare => self.compare_with_mark(),
+            Action::TrashLine => self.trash_current_row(),
+            Action::RestoreLine => self.restore_from_trash()?,
+            Action::DuplicateLine => self.duplicate_current_row(),
+            Action::MoveLineUp => self.move_current_row(SearchDirection::Backward),
+            Action::MoveLineDown => self.move_current_row(SearchDirection::Forward),
+            Action::NextParagraph => self.move_paragraph(SearchDirection::Forward),
+            Action::PreviousParagraph => self.move_paragraph(SearchDirection::Backward),
+            Action::MoveUp => self.move_cursor(Key::Up, WrappingBehavior::Default),
+            Action::MoveDown => self.move_cursor(Key::Down, WrappingBehavior::Default),
+            Action::MoveLeft => self.move_cursor(Key::Left, WrappingBehavior::Default),
+            Action::MoveRight => self.move_cursor(Key::Right, WrappingBehavior::Default),
+            Action::PageUp => self.move_cursor(Key::PageUp, WrappingBehavior::Default),
+            Action::PageDown => self.move_cursor(Key::PageDown, WrappingBehavior::Default),
+            Action::LineStart => self.move_cursor(Key::Home, WrappingBehavior::Default),
+            Action::LineEnd => self.move_cursor(Key::End, WrappingBehavior::Default),
+            Action::DeleteForward | Action::DeleteBackward if self.document.is_read_only() => {
+                self.play_read_only_warning();
             }
-
-            // Deletion:
-            Key::Delete => self.document.delete(&self.cursor_position),
-            Key::Backspace => {
+            Action::DeleteForward => self.delete_at_cursor(),
+            Action::DeleteBackward => {
                 if self.cursor_position.x > 0 || self.cursor_position.y > 0 {
                     self.move_cursor(Key::Left, WrappingBehavior::Wrap);
-                    self.document.delete(&self.cursor_position);
+                    self.delete_at_cursor();
                 }
             }
+            Action::NextWord => self.move_word(SearchDirection::Forward, WrappingBehavior::Wrap),
+            Action::PreviousWord => {
+                self.move_word(SearchDirection::Backward, WrappingBehavior::Wrap);
+            }
+            Action::SetMark => self.set_mark(),
+            Action::SelectUp => self.extend_selection(Key::Up),
+            Action::SelectDown => self.extend_selection(Key::Down),
+            Action::SelectLeft => self.extend_selection(Key::Left),
+            Action::SelectRight => self.extend_selection(Key::Right),
+            Action::Copy => self.copy_selection(),
+            Action::Cut => self.cut_selection(),
+            Action::Paste => self.paste(),
+            Action::SpeakSelection => self.speak_selection(),
+            Action::SpeakSelectionSummary => self.speak_selection_summary(),
+            Action::SpeakOutline => self.speak_outline()?,
+            Action::ShellCommand => self.run_shell_command()?,
+            Action::RunBuild => self.run_build()?,
+            Action::NextError => self.step_through_diagnostics(SearchDirection::Forward)?,
+            Action::PreviousError => self.step_through_diagnostics(SearchDirection::Backward)?,
+            Action::SpeakScope => self.speak_scope(),
+            Action::MatchBracket => self.jump_to_matching_bracket(),
+            Action::PlayOverview => self.play_overview(),
+            Action::StartMacro => self.start_macro(),
+            Action::StopMacro => self.stop_macro(),
+            Action::ReplayMacro => self.replay_macro(),
+            Action::CommandPalette => self.command_palette()?,
+            Action::PreviousSpoken => self.review_speech(SearchDirection::Backward),
+            Action::NextSpoken => self.review_speech(SearchDirection::Forward),
+            Action::RepeatLastSpoken => {
+                self.speech_review = None;
+                self.review_speech(SearchDirection::Backward);
+            }
+            Action::RecordKeyBinding => self.record_key_binding()?,
+            Action::SpeakStatistics => self.speak_statistics(),
+            Action::SaveWorkspace => self.save_workspace()?,
+            Action::OpenWorkspace => self.open_workspace()?,
+            Action::SpeakEditingTime => self.speak_editing_time(),
+            Action::SortLines => self.sort_lines()?,
+            Action::DedupLines => self.dedup_lines(),
+            Action::Undo => self.undo(),
+        }
+        Ok(())
+    }
 
-            // TODO: Wordwise navigation.
-            Key::Up
-            | Key::Down
-            | Key::Left
-            | Key::Right
-            | Key::PageUp
-            | Key::PageDown
-            | Key::End
-            | Key::Home => self.move_cursor(pressed_key, WrappingBehavior::Default),
+    /// Announce problems found in the config file and key bindings, once,
+    /// at startup.
+    fn announce_config_problems(&mut self) {
+        if self.config_problems.is_empty() {
+            return;
+        }
+        let problems = std::mem::take(&mut self.config_problems);
+        self.status_message = StatusMessage::from(format!(
+            "{} config problem(s): {}",
+            problems.len(),
+            problems.join(" ")
+        ));
+        let summary = self.create_announcement(
+            AnnouncementClass::Errors,
+            &format!("{} problems with your configuration.", problems.len()),
+            "Config problems.",
+        );
+        self.sound_manager.append(Box::new(summary));
+        for problem in problems {
+            let utt = self.create_utterance(&problem);
+            self.sound_manager.append(Box::new(utt));
+        }
+    }
 
-            _ => return Ok(false),
+    /// Apply changes to the config file as soon as it's saved, since it's
+    /// usually edited in clack itself. Settings changed since startup, like
+    /// verbosity toggled with Alt-A, are replaced by the file's.
+    ///
+    /// # Returns
+    ///
+    /// Whether the config file had changed.
+    ///
+    fn reload_config_if_changed(&mut self) -> bool {
+        match self.config_manager.reload_if_changed() {
+            None => return false,
+            Some(Err(problem)) => {
+                self.status_message = StatusMessage::from(problem.clone());
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    &format!("{} Keeping the previous settings.", problem),
+                    "Config not reloaded.",
+                );
+                self.sound_manager.append(Box::new(utt));
+                return true;
+            }
+            Some(Ok(())) => (),
         }
-        self.scroll();
-        Ok(true)
+        // Only what the file changed is applied, so that settings changed
+        // while clack runs, like the punctuation level or the earcon theme,
+        // aren't undone by an edit to something else.
+        let config = &self.config_manager;
+        let options = config.get_speech_options();
+        if config.changed("link_reading") {
+            self.speech_options.links = options.links;
+        }
+        if config.changed("punctuation") {
+            self.speech_options.punctuation = options.punctuation;
+        }
+        if config.changed("identifiers") {
+            self.speech_options.identifiers = options.identifiers;
+        }
+        if config.changed("numbers") {
+            self.speech_options.numbers = options.numbers;
+        }
+        if config.changed("rate_wpm") {
+            self.speech_options.rate_wpm = options.rate_wpm;
+        }
+        if config.changed("tab_width") {
+            self.speech_options.tab_width = options.tab_width;
+        }
+        if config.changed("indentation") {
+            self.speech_options.indentation = options.indentation;
+        }
+        if config.changed("token_voices") {
+            self.speech_options.token_voices = options.token_voices;
+        }
+        if config.changed("context_voices") {
+            self.speech_options.context_voices = options.context_voices;
+        }
+        if config.changed("stereo_speech") {
+            self.speech_options.stereo_speech = options.stereo_speech;
+        }
+        if config.changed("count_repeated_symbols") {
+            self.speech_options.count_repeated_symbols = options.count_repeated_symbols;
+        }
+        if config.changed("capitals") {
+            self.speech_options.capitals = options.capitals;
+        }
+        if config.changed("voice") {
+            self.speech_options.voice = self.document_voice();
+        }
+        let config = &self.config_manager;
+        if config.changed("wrap") {
+            self.wrap_arrow_key_navigation = config.get_wrap();
+        }
+        if config.changed("modal_editing") && config.get_modal_editing() != self.vi_mode.is_some() {
+            self.vi_mode = config.get_modal_editing().then_some(ViMode::Normal);
+            self.vi_pending_delete = false;
+        }
+        if config.changed("soft_wrap") {
+            self.soft_wrap = config.get_soft_wrap();
+        }
+        if config.changed("wrapped_line_reading") {
+            self.wrapped_line_reading = config.get_wrapped_line_reading();
+        }
+        if config.changed("volume") {
+            self.sound_manager.set_volume(config.get_volume());
+        }
+        if config.changed("speech_volume") {
+            self.sound_manager
+                .set_speech_volume(config.get_speech_volume());
+        }
+        if config.changed("earcon_ducking") {
+            self.sound_manager.set_ducking(config.get_earcon_ducking());
+        }
+        if config.changed("earcon_theme") || config.changed("earcons") {
+            self.sound_manager.set_theme(config.get_earcon_theme(None));
+        }
+        if config.changed("screen_reader") {
+            let screen_reader = self.screen_reader_option || config.get_screen_reader();
+            if screen_reader != self.sound_manager.is_screen_reader() {
+                self.sound_manager.set_screen_reader(screen_reader);
+            }
+        }
+        if config.changed("verbosity") {
+            self.verbosity = config.get_verbosity_settings();
+        }
+        if config.changed("interrupt_speech_on_keypress") {
+            self.interrupt_speech_on_keypress = config.get_interrupt_speech_on_keypress();
+        }
+        if config.changed("typing_echo") {
+            self.typing_echo = config.get_typing_echo();
+        }
+        if config.changed("keys") {
+            let (keymap, keymap_problems) = KeyMap::with_user_bindings(&config.get_key_bindings());
+            self.keymap = keymap;
+            self.config_problems = keymap_problems;
+        }
+        if config.changed("speech_backend") {
+            self.sound_manager
+                .set_speech_backend(speech::select_backend(&config.get_speech_backend_name()));
+        }
+        if config.changed("audio_budget_ms") {
+            self.sound_manager
+                .set_budget(Duration::from_millis(config.get_audio_budget_ms()));
+        }
+        self.status_message = StatusMessage::from("Configuration reloaded.".to_string());
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            "Configuration reloaded.",
+            "Config reloaded.",
+        );
+        self.sound_manager.append(Box::new(utt));
+        self.announce_config_problems();
+        true
     }
 
     fn change_mode(&mut self, mode: Mode) {
         match mode {
-            Mode::Editing => {
-                self.sound_manager
-                    .play_and_wait(Box::new(Tone::new(440.0, 0.06, 0.5)));
-                self.sound_manager
-                    .play_and_wait(Box::new(Tone::new(440.0 * 3.0 / 2.0, 0.1, 0.5)));
-            }
-            Mode::Quitting => {
-                self.sound_manager
-                    .play_and_wait(Box::new(Tone::new(440.0 * 3.0 / 2.0, 0.1, 0.5)));
-                self.sound_manager
-                    .play_and_wait(Box::new(Tone::new(440.0, 0.06, 0.5)));
-            }
+            Mode::Editing => self.play_earcon(Earcon::Startup),
+            Mode::Quitting => self.play_earcon(Earcon::Quit),
         }
     }
 
+    /// Break the line at the cursor, starting the new line with the
+    /// indentation of the one broken (as much of it as is before the
+    /// cursor).
     fn insert_carriage_return(&mut self) {
+        let indentation: String = self
+            .document
+            .get_row(self.cursor_position.y)
+            .map(|row| {
+                row.leading_whitespace()
+                    .chars()
+                    .take(self.cursor_position.x)
+                    .collect()
+            })
+            .unwrap_or_default();
         self.document.insert(&self.cursor_position, '\n');
         self.move_cursor(Key::Right, WrappingBehavior::Wrap);
+        for c in indentation.chars() {
+            self.document.insert(&self.cursor_position, c);
+            self.move_cursor(Key::Right, WrappingBehavior::Wrap);
+        }
+    }
+
+    /// Insert spaces up to the next tab stop, in place of a tab.
+    fn insert_soft_tab(&mut self) {
+        let tab_width = self.speech_options.tab_width.max(1);
+        let spaces = tab_width - self.cursor_column() % tab_width;
+        self.echo_typed('\t');
+        for _ in 0..spaces {
+            self.document.insert(&self.cursor_position, ' ');
+            self.move_cursor(Key::Right, WrappingBehavior::Wrap);
+        }
+    }
+
+    /// Play a soft sound if a bracket just typed closes nothing.
+    fn check_closing_bracket(&mut self, c: char) {
+        if !brackets::is_closer(c) {
+            return;
+        }
+        let at = Position {
+            x: self.cursor_position.x.saturating_sub(1),
+            y: self.cursor_position.y,
+        };
+        if brackets::matching_bracket(&self.document, &at).is_none() {
+            self.play_earcon(Earcon::Unmatched);
+        }
+    }
+
+    /// Jump to the bracket that pairs with the one at the cursor (or just
+    /// before it, as after typing one), and say where it is: its line and
+    /// what it closes, or the line it opens.
+    fn jump_to_matching_bracket(&mut self) {
+        let bracket_at = |editor: &Self, x: usize| {
+            let at = Position {
+                x,
+                y: editor.cursor_position.y,
+            };
+            let c = editor
+                .document
+                .get_row(at.y)?
+                .as_str()
+                .graphemes(true)
+                .nth(at.x)?
+                .chars()
+                .next()?;
+            brackets::is_bracket(c).then_some((at, c))
+        };
+        let x = self.cursor_position.x;
+        let found = bracket_at(self, x).or_else(|| bracket_at(self, x.checked_sub(1)?));
+        let (at, c) = match found {
+            Some(found) => found,
+            None => {
+                self.play_noop_sound();
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    "Not on a bracket.",
+                    "No bracket.",
+                );
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return;
+            }
+        };
+        let name = brackets::bracket_name(c).unwrap_or("bracket");
+        let (position, partner) = match brackets::matching_bracket(&self.document, &at) {
+            Some(partner) => partner,
+            None => {
+                self.play_noop_sound();
+                self.status_message = StatusMessage::from(format!("Unmatched {}", name));
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    &format!("Unmatched {}.", name),
+                    "Unmatched.",
+                );
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return;
+            }
+        };
+        // A closer is described by the line that opens it, and an opener by
+        // its own line.
+        let context_line = if brackets::is_closer(partner) {
+            at.y
+        } else {
+            position.y
+        };
+        let context = self
+            .document
+            .get_row(context_line)
+            .map(|row| string_to_speakable_tokens(row.as_str().trim(), &self.speech_options))
+            .unwrap_or_default();
+        let partner_name = brackets::bracket_name(partner).unwrap_or("bracket");
+        let line = position.y + 1;
+        self.cursor_position = position;
+        let verbose = if brackets::is_closer(partner) {
+            format!("{}, line {}, end of {}", partner_name, line, context)
+        } else {
+            format!("{}, line {}: {}", partner_name, line, context)
+        };
+        let utt = self.create_announcement(
+            AnnouncementClass::Navigation,
+            &verbose,
+            &format!("{} {}", partner_name, line),
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Delete the character at the cursor. If it's a bracket whose partner
+    /// is still there, say where the partner is, since it's now unmatched.
+    fn delete_at_cursor(&mut self) {
+        let at = self.cursor_position.clone();
+        let partner = brackets::matching_bracket(&self.document, &at);
+        self.document.delete(&at);
+        let (mut position, c) = match partner {
+            Some(partner) => partner,
+            None => return,
+        };
+        if position.y == at.y && position.x > at.x {
+            position.x -= 1;
+        }
+        let name = brackets::bracket_name(c).unwrap_or("bracket");
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &format!("Unmatched {} now on line {}.", name, position.y + 1),
+            &format!("Unmatched {}, line {}.", name, position.y + 1),
+        );
+        self.sound_manager.append(Box::new(utt));
     }
 
     fn speak_current_word(&mut self) {
         let word = self.get_current_word();
-        let utt = self.create_utterance(string_to_speakable_tokens(&word, None).as_str());
-        self.sound_manager.play_and_wait(Box::new(utt));
+        let utt = self.create_content_utterance(
+            string_to_speakable_tokens(&word, &self.speech_options).as_str(),
+        );
+        self.sound_manager
+            .append_with_priority(Box::new(utt), Priority::Low);
     }
 
     fn get_current_word(&self) -> String {
@@ -331,161 +1234,4625 @@ impl Editor {
     }
 
     fn speak_character(&mut self, c: &str) {
-        let utt = self.create_utterance(string_to_speakable_tokens(c, None).as_str());
-        self.sound_manager.play_and_wait(Box::new(utt));
-    }
-
-    fn speak_current_row(&mut self) {
-        let default = &Row::from("");
-        let row = self
-            .document
-            .get_row(self.cursor_position.y)
-            .unwrap_or(default);
-        // row.play(&mut self.sound_manager);
-        self.sound_manager.play_row(row);
-    }
-
-    fn play_success_sound(&mut self) {
+        let is_capital = c.chars().next().is_some_and(char::is_uppercase);
+        let mut text = string_to_speakable_tokens(c, &self.speech_options);
+        if is_capital && self.speech_options.capitals == CapitalReading::Word {
+            text = format!("cap {}", text);
+        }
+        let mut utt = self.create_spelling_utterance(&text);
+        if is_capital && self.speech_options.capitals == CapitalReading::Pitch {
+            let pitch = utt.pitch();
+            utt = utt.with_parameters(&capital_voice(pitch));
+        }
         self.sound_manager
-            .play_and_wait(Box::new(Tone::new(440.0 * 2.0, 0.06, 0.5)));
+            .append_with_priority(Box::new(utt), Priority::Low);
     }
 
-    fn play_noop_sound(&mut self) {
-        self.sound_manager
-            .play_and_wait(Box::new(Tone::new(440.0 * 3.0 / 2.0, 0.01, 0.25)));
-        self.sound_manager
-            .play_and_wait(Box::new(Tone::new(440.0 * 3.0 / 2.0, 0.01, 0.25)));
-        self.sound_manager
-            .play_and_wait(Box::new(Tone::new(440.0 * 3.0 / 2.0, 0.01, 0.25)));
+    /// Speak what the typing echo mode asks for when a character is typed:
+    /// the word it finishes, the character itself, or both.
+    fn echo_typed(&mut self, c: char) {
+        self.echo_typed_as(c, None);
     }
 
-    fn search(&mut self) {
-        let old_position = self.cursor_position.clone();
-
-        let utt = self.create_utterance("Find.");
-        self.sound_manager.play_and_wait(Box::new(utt));
-
-        let mut direction = SearchDirection::Forward;
-        self.prompt("Find: ", |editor, key, query| {
-            let mut moved = false;
-            match key {
-                Key::Right | Key::Down | Key::Ctrl('f') => {
-                    direction = SearchDirection::Forward;
-                    editor.move_cursor(Key::Right, WrappingBehavior::Wrap);
-                    editor.speak_current_row();
-                    moved = true;
-                }
-                Key::Left | Key::Up | Key::Ctrl('b') => {
-                    direction = SearchDirection::Backward;
-                    editor.move_cursor(Key::Left, WrappingBehavior::Wrap);
-                    editor.speak_current_row();
-                    moved = true;
-                }
-                _ => (),
+    /// Echo a typed character like `echo_typed`, but say a symbol as `name`
+    /// if it's given, like "parens" for an auto-paired `(`.
+    fn echo_typed_as(&mut self, c: char, name: Option<&str>) {
+        if c.is_alphanumeric() {
+            if self.typing_echo.speaks_letters() {
+                self.echo_character(c);
             }
-            if let Some(position) = editor
-                .document
-                .find(&query, &editor.cursor_position, direction)
-            {
-                editor.cursor_position = position;
-                editor.scroll();
-                editor.play_success_sound();
-            } else if moved {
-                editor.move_cursor(Key::Left, WrappingBehavior::Wrap)
+            return;
+        }
+        if self.typing_echo.speaks_words()
+            && self.get_current_word().chars().all(char::is_alphanumeric)
+        {
+            self.speak_current_word();
+        }
+        if self.typing_echo != TypingEcho::None {
+            match name {
+                Some(name) => {
+                    let utt = self.create_spelling_utterance(name);
+                    self.sound_manager
+                        .append_with_priority(Box::new(utt), Priority::Low);
+                }
+                None => self.echo_character(c),
             }
+        }
+        self.warn_if_repeated_word();
+    }
+
+    /// Whether brackets and quotes are closed as they're opened in this
+    /// file, by the `auto_pairs` setting.
+    fn auto_pairs_enabled(&self) -> bool {
+        let file_name = self.document.file_name.as_deref();
+        let extension = file_name
+            .and_then(|name| Path::new(name).extension())
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase);
+        self.config_manager.get_auto_pairs().iter().any(|entry| {
+            (entry == "code" && !spelling::is_prose(file_name))
+                || Some(entry.to_lowercase()) == extension
         })
-        .unwrap_or(None);
-        self.cursor_position = old_position;
-        self.scroll();
-        self.play_noop_sound();
-        self.say_current_location();
     }
 
-    fn prompt<C>(&mut self, prompt: &str, mut callback: C) -> Result<Option<String>, std::io::Error>
-    where
-        C: FnMut(&mut Self, Key, &String),
-    {
-        let mut result = String::new();
+    /// Type a character with auto-pairing: type over a closer that was put
+    /// in automatically, or put in the closer after an opener, speaking the
+    /// pair as one ("parens"). Quotes are only paired where they can't be
+    /// an apostrophe, and nothing is paired just before a word.
+    ///
+    /// # Returns
+    ///
+    /// Whether the character was typed, or should be typed as usual.
+    ///
+    fn type_auto_pair(&mut self, c: char) -> bool {
+        if !self.auto_pairs_enabled() {
+            return false;
+        }
+        let Position { x, y } = self.cursor_position;
+        let char_at = |x: usize| {
+            self.document
+                .get_row(y)
+                .and_then(|row| row.as_str().graphemes(true).nth(x))
+                .and_then(|grapheme| grapheme.chars().next())
+        };
+        let (previous, next) = (x.checked_sub(1).and_then(char_at), char_at(x));
+        if next == Some(c) && self.auto_closers.last() == Some(&c) {
+            self.auto_closers.pop();
+            self.echo_typed(c);
+            self.move_cursor(Key::Right, WrappingBehavior::Wrap);
+            return true;
+        }
+        let (close, name) = match brackets::auto_pair(c) {
+            Some(pair) => pair,
+            None => return false,
+        };
+        let is_quote = close == c;
+        let before_word = next.is_some_and(|next| {
+            !next.is_whitespace() && !brackets::is_closer(next) && Some(next) != Some(close)
+        });
+        let after_word =
+            previous.is_some_and(|previous| previous.is_alphanumeric() || previous == c);
+        if before_word || (is_quote && after_word) {
+            return false;
+        }
+        self.echo_typed_as(c, Some(name));
+        self.document.insert(&self.cursor_position, c);
+        self.move_cursor(Key::Right, WrappingBehavior::Wrap);
+        self.document.insert(&self.cursor_position, close);
+        self.auto_closers.push(close);
+        true
+    }
+
+    /// Point out the word just finished if it repeats the one before it,
+    /// like "the the", in a prose file.
+    fn warn_if_repeated_word(&mut self) {
+        if !self.config_manager.get_repeated_words()
+            || !spelling::is_prose(self.document.file_name.as_deref())
+        {
+            return;
+        }
+        let Position { x, y } = self.cursor_position;
+        let word = match self
+            .document
+            .get_row(y)
+            .and_then(|row| row.repeated_word_ending_at(x))
+        {
+            Some(word) => word.to_string(),
+            None => return,
+        };
+        self.play_earcon(Earcon::RepeatedWord);
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &format!("Repeated word: {}.", word),
+            &format!("Repeated {}", word),
+        );
+        self.sound_manager.append(Box::new(utt));
+    }
+
+    /// Switch to the next typing echo mode and announce it.
+    fn cycle_typing_echo(&mut self) {
+        self.typing_echo = self.typing_echo.next();
+        let name = self.typing_echo.name();
+        self.status_message = StatusMessage::from(format!("Typing echo: {}", name));
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            &format!("Typing echo {}", name),
+            name,
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Turn all sound off, for visual-only use, or back on.
+    fn toggle_silent(&mut self) {
+        let silent = !self.sound_manager.is_silent();
+        if silent {
+            self.sound_manager.set_silent(true);
+            self.status_message = StatusMessage::from("Sound off".to_string());
+        } else {
+            self.sound_manager.set_silent(false);
+            self.status_message = StatusMessage::from("Sound on".to_string());
+            let utt = self.create_announcement(AnnouncementClass::Prompts, "Sound on.", "On.");
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+        }
+    }
+
+    /// Turn soft wrapping of long lines on or off.
+    fn toggle_soft_wrap(&mut self) {
+        self.soft_wrap = !self.soft_wrap;
+        self.scroll();
+        let (verbose, terse) = if self.soft_wrap {
+            ("Soft wrap on.", "Wrap on.")
+        } else {
+            ("Soft wrap off.", "Wrap off.")
+        };
+        self.status_message = StatusMessage::from(verbose.to_string());
+        let utt = self.create_announcement(AnnouncementClass::Prompts, verbose, terse);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Turn night mode on or off. The announcement comes after the switch,
+    /// so turning night mode on is announced quietly.
+    fn toggle_night_mode(&mut self) {
+        let night = !self.sound_manager.is_night_mode();
+        self.sound_manager.set_night_mode(night);
+        let (verbose, terse) = if night {
+            ("Night mode on.", "Night.")
+        } else {
+            ("Night mode off.", "Day.")
+        };
+        self.status_message = StatusMessage::from(verbose.to_string());
+        let utt = self.create_announcement(AnnouncementClass::Prompts, verbose, terse);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Switch to the next built-in earcon theme, and play its startup sound
+    /// as a sample.
+    fn cycle_earcon_theme(&mut self) {
+        let current = self.sound_manager.theme().name();
+        let index = THEME_NAMES
+            .iter()
+            .position(|name| *name == current)
+            .map_or(0, |index| (index + 1) % THEME_NAMES.len());
+        let theme = self
+            .config_manager
+            .get_earcon_theme(THEME_NAMES.get(index).copied());
+        let name = theme.name();
+        self.sound_manager.set_theme(theme);
+        self.status_message = StatusMessage::from(format!("Earcon theme: {}", name));
+        self.sound_manager.silence();
+        self.play_earcon(Earcon::Startup);
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            &format!("Earcon theme {}", name),
+            name,
+        );
+        self.sound_manager.append(Box::new(utt));
+    }
+
+    /// Speak faster or slower, and save the new rate to the config file.
+    ///
+    /// # Arguments
+    ///
+    /// * `change` - How much to change the rate by, in words per minute.
+    ///
+    fn change_rate(&mut self, change: i64) {
+        let old_rate = self.speech_options.rate_wpm;
+        let rate = (old_rate + change).clamp(config::MIN_RATE_WPM, config::MAX_RATE_WPM);
+        if rate == old_rate {
+            self.play_blocked_navigation_sound();
+            let (verbose, terse) = if change > 0 {
+                (format!("Fastest rate, {}.", rate), "Fastest.")
+            } else {
+                (format!("Slowest rate, {}.", rate), "Slowest.")
+            };
+            let utt = self.create_announcement(AnnouncementClass::Prompts, &verbose, terse);
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return;
+        }
+        self.speech_options.rate_wpm = rate;
+        self.status_message = StatusMessage::from(format!("Rate: {} wpm", rate));
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            &format!("Rate {}", rate),
+            &rate.to_string(),
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        if let Err(problem) = self.config_manager.set_rate_wpm(rate) {
+            self.status_message = StatusMessage::from(problem.clone());
+            let utt = self.create_announcement(
+                AnnouncementClass::Errors,
+                &format!("{} The rate is only changed until you quit.", problem),
+                "Rate not saved.",
+            );
+            self.sound_manager.append(Box::new(utt));
+        }
+    }
+
+    /// Make speech or tones louder or quieter, and save the new volume to
+    /// the config file.
+    ///
+    /// # Arguments
+    ///
+    /// * `speech` - Whether to change the speech volume rather than the
+    ///   volume of tones and clips.
+    /// * `change` - How much to change the volume by, where 1.0 is full
+    ///   volume.
+    ///
+    fn change_volume(&mut self, speech: bool, change: f32) {
+        let (name, old_volume, max_volume) = if speech {
+            ("Speech", self.config_manager.get_speech_volume(), 1.0)
+        } else {
+            ("Tone", self.config_manager.get_volume(), MAX_TONE_VOLUME)
+        };
+        // Round to the step, so repeated changes land on whole percentages.
+        let volume = ((old_volume + change) * 10.0).round() / 10.0;
+        let volume = volume.clamp(0.0, max_volume);
+        let percent = (volume * 100.0).round() as u32;
+        if (volume - old_volume).abs() < f32::EPSILON {
+            self.play_blocked_navigation_sound();
+            let (verbose, terse) = if change > 0.0 {
+                (
+                    format!(
+                        "Loudest {} volume, {} percent.",
+                        name.to_lowercase(),
+                        percent
+                    ),
+                    "Loudest.",
+                )
+            } else {
+                (format!("{} volume off.", name), "Off.")
+            };
+            let utt = self.create_announcement(AnnouncementClass::Prompts, &verbose, terse);
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return;
+        }
+        let saved = if speech {
+            self.sound_manager.set_speech_volume(volume);
+            self.config_manager.set_speech_volume(volume)
+        } else {
+            self.sound_manager.set_volume(volume);
+            self.config_manager.set_volume(volume)
+        };
+        self.status_message = StatusMessage::from(format!("{} volume: {}%", name, percent));
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            &format!("{} volume {} percent.", name, percent),
+            &percent.to_string(),
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        if let Err(problem) = saved {
+            self.status_message = StatusMessage::from(problem.clone());
+            let utt = self.create_announcement(
+                AnnouncementClass::Errors,
+                &format!("{} The volume is only changed until you quit.", problem),
+                "Volume not saved.",
+            );
+            self.sound_manager.append(Box::new(utt));
+        }
+    }
+
+    /// Rebind a command by pressing its new key, rather than writing the
+    /// key's name in the config file by hand. The binding is saved in the
+    /// config file's `[keys]` table.
+    fn record_key_binding(&mut self) -> Result<(), std::io::Error> {
+        let name = match self.prompt("Command to rebind: ", |_, _, _| {})? {
+            Some(name) => name.trim().to_lowercase().replace(' ', "_"),
+            None => return Ok(()),
+        };
+        let action = match Action::from_name(&name) {
+            Some(action) => action,
+            None => {
+                self.play_noop_sound();
+                self.status_message = StatusMessage::from(format!("No command {}.", name));
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    &format!("There's no command called {}.", name.replace('_', " ")),
+                    "No such command.",
+                );
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return Ok(());
+            }
+        };
+
+        self.status_message = StatusMessage::from(format!(
+            "Press the key for {} (Esc to cancel)",
+            action.name()
+        ));
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            &format!(
+                "Press the key you want to bind to {}.",
+                action.spoken_name()
+            ),
+            "Press the key.",
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        self.refresh_screen()?;
+        let key = self.read_key()?;
+        if key == Key::Esc {
+            self.status_message = StatusMessage::from(String::new());
+            self.play_noop_sound();
+            return Ok(());
+        }
+
+        // A key taken by another command has to be freed first; binding it
+        // twice would leave one of the two unreachable.
+        let chord = keymap::chord_config_name(key);
+        let bound = self.keymap.action_for(key).filter(|bound| *bound != action);
+        let problem = match (&chord, keymap::shadowing_reason(key), bound) {
+            (None, _, _) => Some(String::from("That key can't be bound.")),
+            (_, Some(reason), _) => Some(format!(
+                "{} never reaches clack: {}.",
+                keymap::chord_name(key),
+                reason
+            )),
+            (_, _, Some(bound)) => Some(format!(
+                "{} is already bound to {}.",
+                keymap::chord_name(key),
+                bound.spoken_name()
+            )),
+            _ => None,
+        };
+        let chord = match (chord, problem) {
+            (Some(chord), None) => chord,
+            (_, problem) => {
+                let problem = problem.unwrap_or_default();
+                self.play_noop_sound();
+                self.status_message = StatusMessage::from(problem.clone());
+                let utt =
+                    self.create_announcement(AnnouncementClass::Errors, &problem, "Not bound.");
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return Ok(());
+            }
+        };
+
+        if let Err(problem) = self.config_manager.set_key_binding(action.name(), &chord) {
+            self.status_message = StatusMessage::from(problem.clone());
+            let utt = self.create_announcement(
+                AnnouncementClass::Errors,
+                &format!("{} The key wasn't bound.", problem),
+                "Key not saved.",
+            );
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return Ok(());
+        }
+        let (keymap, keymap_problems) =
+            KeyMap::with_user_bindings(&self.config_manager.get_key_bindings());
+        self.keymap = keymap;
+        // Problems found elsewhere in the config are still waiting to be
+        // heard.
+        for problem in keymap_problems {
+            if !self.config_problems.contains(&problem) {
+                self.config_problems.push(problem);
+            }
+        }
+        self.play_success_sound();
+        self.status_message = StatusMessage::from(format!(
+            "{} = \"{}\" saved to the config file",
+            action.name(),
+            chord
+        ));
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            &format!(
+                "{} is now on {}.",
+                action.spoken_name(),
+                keymap::chord_name(key)
+            ),
+            &keymap::chord_name(key),
+        );
+        self.sound_manager.append(Box::new(utt));
+        Ok(())
+    }
+
+    /// Echo a typed symbol using the echo table.
+    ///
+    /// The user's `[echo]` config table takes precedence over the built-in
+    /// terse names; symbols in neither fall back to the reading replace map.
+    ///
+    fn echo_character(&mut self, c: char) {
+        let symbol = c.to_string();
+        let spoken = self
+            .config_manager
+            .get_echo_name(&symbol)
+            .or_else(|| default_echo_name(&symbol).map(String::from));
+        match spoken {
+            Some(name) => {
+                let utt = self.create_spelling_utterance(&name);
+                self.sound_manager
+                    .append_with_priority(Box::new(utt), Priority::Low);
+            }
+            None => self.speak_character(&symbol),
+        }
+    }
+
+    /// Prompt for a voice (or language) to read this document with.
+    ///
+    /// The override applies to this document only and is remembered in the
+    /// session store. An empty answer goes back to the default voice.
+    ///
+    fn set_document_voice(&mut self) -> Result<(), std::io::Error> {
+        let utt =
+            self.create_announcement(AnnouncementClass::Prompts, "Voice for this file:", "Voice");
+        self.sound_manager.append(Box::new(utt));
+        let voice = self.prompt("Voice for this file: ", |_, _, _| {})?;
+        self.document.set_voice(voice.clone());
+        self.speech_options.voice = self.document_voice();
+        if let Some(file_name) = self.document.file_name.clone() {
+            let value = voice.clone().map(toml::Value::String);
+            if self
+                .session
+                .set_file_value(&file_name, "voice", value)
+                .is_err()
+            {
+                self.status_message = StatusMessage::from("Could not save session.".to_string());
+            }
+        }
+        let message = match &voice {
+            Some(voice) => format!("Voice set to {}.", voice),
+            None => "Default voice.".to_string(),
+        };
+        let utt = self.create_utterance(&message);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        Ok(())
+    }
+
+    /// Get the voice for the open document: its own, or else the one in the
+    /// config file.
+    fn document_voice(&self) -> Option<String> {
+        self.document
+            .voice()
+            .cloned()
+            .or_else(|| self.config_manager.get_voice())
+    }
+
+    /// Open a file in a new buffer and make it active, or switch to it if
+    /// it's already open. An empty, unnamed buffer is reused.
+    ///
+    /// # Returns
+    ///
+    /// Whether the file was opened; false if the user cancelled.
+    ///
+    fn open_buffer(&mut self, file_name: &str) -> Result<bool, std::io::Error> {
+        if let Some(index) = self.buffer_index_for(file_name) {
+            self.switch_to_buffer(index);
+            return Ok(true);
+        }
+        let mut document = match Scheme::for_file(file_name) {
+            Some(scheme) => match self.ask_for_encryption(file_name, scheme)? {
+                Some(encryption) => Document::open_encrypted(file_name, encryption)?,
+                None => return Ok(false),
+            },
+            None => Document::open(file_name)?,
+        };
+        document.set_voice(remembered_voice(&self.session, file_name));
+        self.show_in_buffer(document);
+        self.warn_if_locked()?;
+        self.warn_if_unwritable();
+        self.announce_line_ending();
+        self.offer_recovery()?;
+        Ok(true)
+    }
+
+    /// Put the cursor where the command line asked in each file, like
+    /// `main.rs:120:4`, and read the line it lands on in the active one.
+    fn go_to_start_positions(&mut self) {
+        let active = self.active_buffer;
+        let mut landed_in_active = false;
+        for file in std::mem::take(&mut self.start_positions) {
+            let index = match self.buffer_index_for(&file.file_name) {
+                Some(index) => index,
+                None => continue,
+            };
+            self.switch_to_buffer(index);
+            self.place_cursor(file.line.unwrap_or(1), file.column);
+            landed_in_active |= index == active;
+        }
+        self.switch_to_buffer(active);
+        if landed_in_active {
+            self.speak_current_row();
+        }
+    }
+
+    /// Put the cursor at a line and column, counting from 1, as named on the
+    /// command line or by a compiler. Lines and columns past the end go to
+    /// the last one.
+    fn place_cursor(&mut self, line: usize, column: Option<usize>) {
+        let y = line
+            .saturating_sub(1)
+            .min(self.document.row_count().saturating_sub(1));
+        let len = self.document.get_row(y).map_or(0, Row::len);
+        let x = column.unwrap_or(1).saturating_sub(1).min(len);
+        self.cursor_position = Position { x, y };
+        self.scroll();
+    }
+
+    /// Make a document the active buffer. It takes the place of an empty,
+    /// unnamed active buffer, or else gets a new one. Unnamed buffers with
+    /// text in them, like a command's output, are kept.
+    fn show_in_buffer(&mut self, document: Document) {
+        let reuse_active = self.document.file_name.is_none()
+            && !self.document.is_dirty()
+            && self.document.row_count() == 0;
+        self.park_active_buffer();
+        let index = if reuse_active {
+            self.active_buffer
+        } else {
+            self.buffers.push(Buffer::default());
+            self.buffers.len() - 1
+        };
+        self.buffers[index].document = document;
+        self.activate_buffer(index);
+    }
+
+    /// Find the buffer a file is open in, if any.
+    fn buffer_index_for(&self, file_name: &str) -> Option<usize> {
+        let wanted = fs::canonicalize(file_name).ok();
+        (0..self.buffers.len()).find(|&index| {
+            self.buffer_document(index)
+                .file_name
+                .as_ref()
+                .is_some_and(|open| {
+                    open == file_name || (wanted.is_some() && fs::canonicalize(open).ok() == wanted)
+                })
+        })
+    }
+
+    /// Get the document in a buffer, whether or not it's the active one.
+    fn buffer_document(&self, index: usize) -> &Document {
+        if index == self.active_buffer {
+            &self.document
+        } else {
+            &self.buffers[index].document
+        }
+    }
+
+    /// Move the active buffer's state out of the editor into its slot.
+    fn park_active_buffer(&mut self) {
+        self.flush_editing_time();
+        if self.document.is_dirty() {
+            // A recovery file is a safety net; failing to write one
+            // shouldn't get in the way of editing.
+            let _ = self.document.write_swap();
+        }
+        self.buffers[self.active_buffer] = Buffer {
+            document: std::mem::take(&mut self.document),
+            cursor_position: std::mem::take(&mut self.cursor_position),
+            offset: std::mem::take(&mut self.offset),
+            selection_anchor: self.selection_anchor.take(),
+            compare_mark: self.compare_mark.take(),
+            anchor_line: self.anchor_line.take(),
+        };
+    }
+
+    /// Move a parked buffer's state into the editor, making it active.
+    fn activate_buffer(&mut self, index: usize) {
+        let buffer = std::mem::take(&mut self.buffers[index]);
+        self.document = buffer.document;
+        self.cursor_position = buffer.cursor_position;
+        self.offset = buffer.offset;
+        self.selection_anchor = buffer.selection_anchor;
+        self.compare_mark = buffer.compare_mark;
+        self.anchor_line = buffer.anchor_line;
+        self.active_buffer = index;
+        self.speech_options.voice = self.document_voice();
+        self.last_indent_level = None;
+    }
+
+    fn switch_to_buffer(&mut self, index: usize) {
+        if index != self.active_buffer {
+            self.park_active_buffer();
+            self.activate_buffer(index);
+        }
+    }
+
+    /// Whether any open buffer has unsaved changes.
+    fn has_unsaved_changes(&self) -> bool {
+        (0..self.buffers.len()).any(|index| self.buffer_document(index).is_dirty())
+    }
+
+    /// Describe a buffer for speech, e.g. "main.rs, 120 lines, modified".
+    fn describe_buffer(&self, index: usize) -> String {
+        let document = self.buffer_document(index);
+        let name = document
+            .file_name
+            .as_deref()
+            .map_or_else(|| String::from("no name"), short_file_name);
+        let modified = if document.is_dirty() {
+            ", modified"
+        } else {
+            ""
+        };
+        format!("{}, {} lines{}", name, document.row_count(), modified)
+    }
+
+    /// Announce the active buffer, e.g. "Buffer 2 of 3: main.rs, 120 lines."
+    fn announce_active_buffer(&mut self) {
+        let description = self.describe_buffer(self.active_buffer);
+        let verbose = format!(
+            "Buffer {} of {}: {}.",
+            self.active_buffer + 1,
+            self.buffers.len(),
+            description
+        );
+        self.status_message = StatusMessage::from(verbose.clone());
+        let utt = self.create_announcement(AnnouncementClass::Navigation, &verbose, &description);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Switch to the next buffer, wrapping around to the first.
+    fn cycle_buffer(&mut self) {
+        if self.buffers.len() < 2 {
+            self.play_noop_sound();
+            let utt = self.create_announcement(
+                AnnouncementClass::Navigation,
+                "Only one buffer open.",
+                "One buffer.",
+            );
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return;
+        }
+        self.switch_to_buffer((self.active_buffer + 1) % self.buffers.len());
+        self.scroll();
+        self.announce_active_buffer();
+    }
+
+    /// Speak every open buffer, in order.
+    fn list_buffers(&mut self) {
+        let descriptions: Vec<String> = (0..self.buffers.len())
+            .map(|index| {
+                let current = if index == self.active_buffer {
+                    ", current"
+                } else {
+                    ""
+                };
+                format!("{}: {}{}", index + 1, self.describe_buffer(index), current)
+            })
+            .collect();
+        let count = descriptions.len();
+        let text = format!(
+            "{} buffer{}. {}.",
+            count,
+            if count == 1 { "" } else { "s" },
+            descriptions.join(". ")
+        );
+        self.status_message = StatusMessage::from(descriptions.join(" | "));
+        let utt = self.create_utterance(&text);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Ask for a file name and open it in a new buffer.
+    fn prompt_and_open_buffer(&mut self) -> Result<(), std::io::Error> {
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            "Open file. Tab completes, down and up browse.",
+            "Open",
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        let file_name = match self.pick_file()? {
+            Some(file_name) => file_name,
+            None => return Ok(()),
+        };
+        match self.open_buffer(&file_name) {
+            Ok(true) => {
+                self.announce_active_buffer();
+                self.announce_git_status();
+            }
+            Ok(false) => (),
+            Err(error) => self.announce_open_error(&file_name, &error),
+        }
+        Ok(())
+    }
+
+    /// Ask for a file to open. A path can be typed, with Tab completing its
+    /// last part and saying what it completed to, or the entries of its
+    /// directory browsed with Down and Up, each read with whether it's a
+    /// directory or a file and its size. Right enters the directory being
+    /// browsed and Left goes up to the parent; Enter on a directory enters
+    /// it too.
+    ///
+    /// # Returns
+    ///
+    /// The path of the file, or None if the user pressed Esc.
+    ///
+    fn pick_file(&mut self) -> Result<Option<String>, std::io::Error> {
+        let mut typed = String::new();
+        // The entries being browsed with Down and Up, and which is selected.
+        let mut listing: Vec<paths::Entry> = Vec::new();
+        let mut selected: Option<usize> = None;
+        loop {
+            self.status_message = StatusMessage::from(format!("Open: {}", typed));
+            self.refresh_screen()?;
+            match self.read_key()? {
+                Key::Char('\t') => {
+                    listing.clear();
+                    selected = None;
+                    self.complete_path(&mut typed);
+                }
+                key @ (Key::Down | Key::Up) => {
+                    if selected.is_none() {
+                        let (dir, prefix) = paths::split(&typed);
+                        listing = paths::entries(&paths::resolve(dir), prefix).unwrap_or_default();
+                    }
+                    if listing.is_empty() {
+                        self.play_noop_sound();
+                        let utt = self.create_announcement(
+                            AnnouncementClass::Prompts,
+                            "Nothing here.",
+                            "Empty.",
+                        );
+                        self.sound_manager.interrupt_and_play(Box::new(utt));
+                        continue;
+                    }
+                    let last = listing.len() - 1;
+                    let next = match selected {
+                        None => 0,
+                        Some(index) if key == Key::Down => (index + 1).min(last),
+                        Some(index) => index.saturating_sub(1),
+                    };
+                    if selected == Some(next) {
+                        self.play_blocked_navigation_sound();
+                        continue;
+                    }
+                    selected = Some(next);
+                    let (dir, _) = paths::split(&typed);
+                    typed = format!("{}{}", dir, listing[next].name);
+                    let utt = self.create_utterance(&format!(
+                        "{} of {}: {}",
+                        next + 1,
+                        listing.len(),
+                        listing[next].describe()
+                    ));
+                    self.sound_manager.interrupt_and_play(Box::new(utt));
+                }
+                Key::Right if selected.is_some_and(|index| listing[index].is_dir) => {
+                    typed.push('/');
+                    listing.clear();
+                    selected = None;
+                    self.announce_directory(&typed);
+                }
+                Key::Left => {
+                    let (dir, _) = paths::split(&typed);
+                    let parent = fs::canonicalize(paths::resolve(dir))
+                        .ok()
+                        .and_then(|path| path.parent().map(Path::to_path_buf));
+                    let parent = match parent {
+                        Some(parent) => parent.display().to_string(),
+                        None => {
+                            self.play_blocked_navigation_sound();
+                            continue;
+                        }
+                    };
+                    typed = parent;
+                    if !typed.ends_with(std::path::MAIN_SEPARATOR) {
+                        typed.push(std::path::MAIN_SEPARATOR);
+                    }
+                    listing.clear();
+                    selected = None;
+                    self.announce_directory(&typed);
+                }
+                Key::Char('\n') if typed.is_empty() => self.play_noop_sound(),
+                Key::Char('\n') => {
+                    if !paths::resolve(&typed).is_dir() {
+                        break;
+                    }
+                    if !typed.ends_with('/') {
+                        typed.push('/');
+                    }
+                    listing.clear();
+                    selected = None;
+                    self.announce_directory(&typed);
+                }
+                Key::Backspace => {
+                    typed.pop();
+                    listing.clear();
+                    selected = None;
+                }
+                Key::Char(c) if !c.is_control() => {
+                    typed.push(c);
+                    listing.clear();
+                    selected = None;
+                }
+                Key::Esc => {
+                    self.status_message = StatusMessage::from(String::new());
+                    return Ok(None);
+                }
+                _ => (),
+            }
+        }
+        self.status_message = StatusMessage::from(String::new());
+        Ok(Some(typed))
+    }
+
+    /// Complete the last part of a path being typed in the file picker, and
+    /// say what it completed to, or how many entries it could still be.
+    fn complete_path(&mut self, typed: &mut String) {
+        let completion = paths::complete(typed);
+        let (_, component) = paths::split(completion.path.trim_end_matches('/'));
+        let component = component.to_string();
+        let utt = match &completion.candidates[..] {
+            [] => {
+                self.play_noop_sound();
+                self.create_announcement(AnnouncementClass::Prompts, "No matches.", "None.")
+            }
+            [only] => self.create_utterance(&only.describe()),
+            candidates if completion.path != *typed => {
+                self.create_utterance(&format!("{}, {} matches.", component, candidates.len()))
+            }
+            candidates => {
+                let names: Vec<&str> = candidates
+                    .iter()
+                    .take(5)
+                    .map(|entry| entry.name.as_str())
+                    .collect();
+                let more = if candidates.len() > names.len() {
+                    ", and more"
+                } else {
+                    ""
+                };
+                self.create_announcement(
+                    AnnouncementClass::Prompts,
+                    &format!(
+                        "{} matches: {}{}.",
+                        candidates.len(),
+                        names.join(", "),
+                        more
+                    ),
+                    &format!("{} matches.", candidates.len()),
+                )
+            }
+        };
+        *typed = completion.path;
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Say which directory the file picker has moved to, and how many
+    /// entries it has.
+    fn announce_directory(&mut self, dir: &str) {
+        let path = paths::resolve(dir);
+        let name = fs::canonicalize(&path)
+            .ok()
+            .and_then(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            })
+            .unwrap_or_else(|| path.display().to_string());
+        let count = paths::entries(&path, "").map_or(0, |entries| entries.len());
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            &format!(
+                "In {}, {} entr{}.",
+                name,
+                count,
+                if count == 1 { "y" } else { "ies" }
+            ),
+            &name,
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Get what's needed to decrypt a file: the passphrase for gpg, asked
+    /// for without echoing or speaking it, or the identity file for age.
+    ///
+    /// # Returns
+    ///
+    /// The encryption, or None if the user cancelled.
+    ///
+    fn ask_for_encryption(
+        &mut self,
+        file_name: &str,
+        scheme: Scheme,
+    ) -> Result<Option<Encryption>, std::io::Error> {
+        match scheme {
+            Scheme::Gpg => {
+                let utt = self.create_announcement(
+                    AnnouncementClass::Prompts,
+                    &format!("Passphrase for {}.", short_file_name(file_name)),
+                    "Passphrase.",
+                );
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                Ok(self
+                    .prompt_secret("Passphrase: ")?
+                    .map(|passphrase| Encryption::Gpg { passphrase }))
+            }
+            Scheme::Age => match self.config_manager.get_age_identity() {
+                Some(identity) => Ok(Some(Encryption::Age { identity })),
+                None => Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "set age_identity in the config file to open age files",
+                )),
+            },
+        }
+    }
+
+    /// Get what's needed to encrypt a file for the first time. A gpg
+    /// passphrase is asked for twice, so that a typo can't lock the file.
+    ///
+    /// # Returns
+    ///
+    /// The encryption, or None if the user cancelled or the passphrases
+    /// didn't match.
+    ///
+    fn ask_for_new_encryption(
+        &mut self,
+        file_name: &str,
+        scheme: Scheme,
+    ) -> Result<Option<Encryption>, std::io::Error> {
+        if scheme != Scheme::Gpg {
+            return self.ask_for_encryption(file_name, scheme);
+        }
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            &format!("New passphrase for {}.", short_file_name(file_name)),
+            "New passphrase.",
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        let passphrase = match self.prompt_secret("New passphrase: ")? {
+            Some(passphrase) => passphrase,
+            None => return Ok(None),
+        };
+        let utt = self.create_announcement(AnnouncementClass::Prompts, "Repeat it.", "Again.");
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        match self.prompt_secret("Repeat passphrase: ")? {
+            Some(repeated) if repeated == passphrase => Ok(Some(Encryption::Gpg { passphrase })),
+            Some(_) => {
+                self.play_noop_sound();
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    "The passphrases don't match.",
+                    "No match.",
+                );
+                self.sound_manager.append(Box::new(utt));
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Say that a file couldn't be opened, and why.
+    fn announce_open_error(&mut self, file_name: &str, error: &std::io::Error) {
+        self.play_noop_sound();
+        let message = format!("Could not open {}: {}.", short_file_name(file_name), error);
+        self.status_message = StatusMessage::from(message.clone());
+        let utt = self.create_announcement(AnnouncementClass::Errors, &message, "Could not open.");
+        self.sound_manager.append(Box::new(utt));
+    }
+
+    /// Count the time since the last keypress as editing time, unless the
+    /// editor sat idle.
+    fn record_activity(&mut self) {
+        let now = Instant::now();
+        let gap = now.duration_since(self.last_activity);
+        if gap < IDLE_THRESHOLD {
+            self.pending_editing_time += gap;
+        }
+        self.last_activity = now;
+    }
+
+    /// Write the editing time so far to the session store.
+    ///
+    /// Time spent on a file that hasn't been named yet is kept until it is.
+    ///
+    fn flush_editing_time(&mut self) {
+        let file_name = match self.document.file_name.clone() {
+            Some(file_name) => file_name,
+            None => return,
+        };
+        let seconds = self.pending_editing_time.as_secs();
+        if seconds > 0 && self.session.add_editing_time(&file_name, seconds).is_ok() {
+            self.pending_editing_time -= Duration::from_secs(seconds);
+        }
+    }
+
+    /// Speak how long this file has been edited today and in total.
+    fn speak_editing_time(&mut self) {
+        self.flush_editing_time();
+        let (today, total) = match &self.document.file_name {
+            Some(file_name) => self.session.editing_time(file_name),
+            None => (self.pending_editing_time.as_secs(), 0),
+        };
+        let total = total.max(today);
+        let message = if total == today {
+            format!(
+                "You have edited this file for {} today.",
+                describe_duration(today)
+            )
+        } else {
+            format!(
+                "You have edited this file for {} today, {} in total.",
+                describe_duration(today),
+                describe_duration(total)
+            )
+        };
+        self.status_message = StatusMessage::from(message.clone());
+        let terse = format!("{} today.", describe_duration(today));
+        let utt = self.create_announcement(AnnouncementClass::Navigation, &message, &terse);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Save every named buffer, with its cursor position, as a named
+    /// workspace.
+    fn save_workspace(&mut self) -> Result<(), std::io::Error> {
+        let buffers: Vec<WorkspaceBuffer> = (0..self.buffers.len())
+            .filter_map(|index| {
+                let file_name = self.buffer_document(index).file_name.clone()?;
+                let cursor = if index == self.active_buffer {
+                    &self.cursor_position
+                } else {
+                    &self.buffers[index].cursor_position
+                };
+                Some(WorkspaceBuffer {
+                    file_name,
+                    x: cursor.x,
+                    y: cursor.y,
+                })
+            })
+            .collect();
+        if buffers.is_empty() {
+            self.play_noop_sound();
+            let utt = self.create_announcement(
+                AnnouncementClass::Errors,
+                "Save the file before saving a workspace.",
+                "Save first.",
+            );
+            self.sound_manager.append(Box::new(utt));
+            return Ok(());
+        }
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            "Save workspace as:",
+            "Workspace name",
+        );
+        self.sound_manager.append(Box::new(utt));
+        let name = match self.prompt("Save workspace as: ", |_, _, _| {})? {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+        let message = match self.session.set_workspace(&name, &buffers) {
+            Ok(()) => format!(
+                "Saved workspace {} with {} file{}.",
+                name,
+                buffers.len(),
+                if buffers.len() == 1 { "" } else { "s" }
+            ),
+            Err(_) => String::from("Could not save session."),
+        };
+        self.status_message = StatusMessage::from(message.clone());
+        let utt = self.create_announcement(AnnouncementClass::Editing, &message, "Saved.");
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        Ok(())
+    }
+
+    /// Restore a named workspace, opening each of its files in a buffer at
+    /// its saved cursor and switching to the first.
+    fn open_workspace(&mut self) -> Result<(), std::io::Error> {
+        let names = self.session.workspace_names();
+        if names.is_empty() {
+            self.play_noop_sound();
+            let utt = self.create_announcement(
+                AnnouncementClass::Errors,
+                "No saved workspaces.",
+                "None saved.",
+            );
+            self.sound_manager.append(Box::new(utt));
+            return Ok(());
+        }
+        let verbose = format!("Open workspace: {}.", names.join(", "));
+        let utt = self.create_announcement(AnnouncementClass::Prompts, &verbose, "Workspace");
+        self.sound_manager.append(Box::new(utt));
+        let name = match self.prompt("Open workspace: ", |_, _, _| {})? {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+        let buffers = match self.session.workspace(&name) {
+            Some(buffers) if !buffers.is_empty() => buffers,
+            _ => {
+                self.play_noop_sound();
+                let utt = self.create_utterance(&format!("No workspace named {}.", name));
+                self.sound_manager.append(Box::new(utt));
+                return Ok(());
+            }
+        };
+        let mut first = None;
+        for buffer in &buffers {
+            match self.open_buffer(&buffer.file_name) {
+                Ok(true) => (),
+                Ok(false) => continue,
+                Err(error) => {
+                    self.announce_open_error(&buffer.file_name, &error);
+                    continue;
+                }
+            }
+            let y = buffer.y.min(self.document.row_count().saturating_sub(1));
+            let x = buffer.x.min(self.document.get_row(y).map_or(0, Row::len));
+            self.cursor_position = Position { x, y };
+            first.get_or_insert(self.active_buffer);
+        }
+        let first = match first {
+            Some(first) => first,
+            None => return Ok(()),
+        };
+        self.switch_to_buffer(first);
+        self.scroll();
+        let short_name = self
+            .document
+            .file_name
+            .as_deref()
+            .map(short_file_name)
+            .unwrap_or_default();
+        let line = self.cursor_position.y + 1;
+        let message = format!(
+            "Restored workspace {}: {} file{}. {}, line {}.",
+            name,
+            buffers.len(),
+            if buffers.len() == 1 { "" } else { "s" },
+            short_name,
+            line
+        );
+        self.status_message = StatusMessage::from(message.clone());
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &message,
+            &format!("{}, line {}.", short_name, line),
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        Ok(())
+    }
+
+    /// Set the mark at the cursor, starting a selection that runs from the
+    /// mark to wherever the cursor moves. Setting the mark where it already
+    /// is clears it.
+    fn set_mark(&mut self) {
+        let at = self.cursor_position.clone();
+        let already_here = self
+            .selection_anchor
+            .as_ref()
+            .is_some_and(|anchor| anchor.x == at.x && anchor.y == at.y);
+        let (message, terse) = if already_here {
+            self.selection_anchor = None;
+            (String::from("Mark cleared."), "Cleared.")
+        } else {
+            self.selection_anchor = Some(at.clone());
+            (
+                format!("Mark set at line {}, column {}.", at.y + 1, at.x + 1),
+                "Mark set.",
+            )
+        };
+        self.status_message = StatusMessage::from(message.clone());
+        let utt = self.create_announcement(AnnouncementClass::Editing, &message, terse);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Move the cursor with the selection following it, setting the mark
+    /// where the cursor was if there isn't one, and say the text moved over.
+    fn extend_selection(&mut self, key: Key) {
+        let from = self.cursor_position.clone();
+        if self.selection_anchor.is_none() {
+            self.selection_anchor = Some(from.clone());
+        }
+        self.move_cursor(key, WrappingBehavior::Wrap);
+        let to = self.cursor_position.clone();
+        let (start, end) = if (from.y, from.x) <= (to.y, to.x) {
+            (from, to)
+        } else {
+            (to, from)
+        };
+        let text = self.document.text_in_range(&start, &end);
+        if text.chars().count() == 1 {
+            if text == "\n" {
+                let utt = self.create_spelling_utterance("new line");
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+            } else {
+                self.speak_character(&text);
+            }
+        } else if !text.is_empty() {
+            let spoken = if text.trim().is_empty() {
+                String::from("blank")
+            } else {
+                string_to_speakable_tokens(&text, &self.speech_options)
+            };
+            let utt = self.create_content_utterance(&spoken);
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+        }
+    }
+
+    /// Get the selected range, from the earlier of the mark and the cursor
+    /// (included) to the later (excluded).
+    fn selection_range(&self) -> Option<(Position, Position)> {
+        let anchor = self.selection_anchor.clone()?;
+        let cursor = self.cursor_position.clone();
+        if (anchor.y, anchor.x) <= (cursor.y, cursor.x) {
+            Some((anchor, cursor))
+        } else {
+            Some((cursor, anchor))
+        }
+    }
+
+    /// Get the rows the selection touches, or the whole document if no mark
+    /// is set.
+    ///
+    /// # Returns
+    ///
+    /// The first row and the number of rows.
+    ///
+    fn selected_rows(&self) -> (usize, usize) {
+        match &self.selection_anchor {
+            Some(mark) => {
+                let y = self.cursor_position.y;
+                let end = mark
+                    .y
+                    .max(y)
+                    .min(self.document.row_count().saturating_sub(1));
+                let start = mark.y.min(y).min(end);
+                (start, end - start + 1)
+            }
+            None => (0, self.document.row_count()),
+        }
+    }
+
+    /// Describe an amount of text for speech, e.g. "3 lines, 42 characters".
+    fn describe_text_size(text: &str) -> String {
+        let lines = text.split('\n').count();
+        let characters = text.chars().filter(|c| *c != '\n').count();
+        match lines {
+            1 => format!("{} characters", characters),
+            _ => format!("{} lines, {} characters", lines, characters),
+        }
+    }
+
+    /// Get the selected text, or play the no-op sound if nothing is selected.
+    fn selected_text_or_complain(&mut self) -> Option<(Position, Position, String)> {
+        match self.selection_range() {
+            Some((start, end)) => {
+                let text = self.document.text_in_range(&start, &end);
+                Some((start, end, text))
+            }
+            None => {
+                self.play_noop_sound();
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    "Nothing selected. Set the mark with control space.",
+                    "No selection.",
+                );
+                self.sound_manager.append(Box::new(utt));
+                None
+            }
+        }
+    }
+
+    /// Copy text to the internal register and the system clipboard.
+    fn copy_to_clipboard(&mut self, text: String) {
+        clipboard::copy(&text);
+        self.register = text;
+    }
+
+    fn copy_selection(&mut self) {
+        let (_, _, text) = match self.selected_text_or_complain() {
+            Some(selection) => selection,
+            None => return,
+        };
+        let message = format!("Copied {}.", Self::describe_text_size(&text));
+        self.copy_to_clipboard(text);
+        self.selection_anchor = None;
+        self.status_message = StatusMessage::from(message.clone());
+        let utt = self.create_announcement(AnnouncementClass::Editing, &message, "Copied.");
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    fn cut_selection(&mut self) {
+        if self.document.is_read_only() {
+            self.play_read_only_warning();
+            return;
+        }
+        let (start, end, text) = match self.selected_text_or_complain() {
+            Some(selection) => selection,
+            None => return,
+        };
+        let message = format!("Cut {}.", Self::describe_text_size(&text));
+        self.copy_to_clipboard(text);
+        self.document.delete_range(&start, &end);
+        self.cursor_position = start;
+        self.selection_anchor = None;
+        self.status_message = StatusMessage::from(message.clone());
+        let utt = self.create_announcement(AnnouncementClass::Editing, &message, "Cut.");
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Paste from the system clipboard, or from the internal register if
+    /// there's no system clipboard.
+    fn paste(&mut self) {
+        if self.document.is_read_only() {
+            self.play_read_only_warning();
+            return;
+        }
+        let text = clipboard::paste().unwrap_or_else(|| self.register.clone());
+        if text.is_empty() {
+            self.play_noop_sound();
+            let utt = self.create_announcement(
+                AnnouncementClass::Errors,
+                "Clipboard is empty.",
+                "Empty.",
+            );
+            self.sound_manager.append(Box::new(utt));
+            return;
+        }
+        self.cursor_position = self.document.insert_text(&self.cursor_position, &text);
+        let message = format!("Pasted {}.", Self::describe_text_size(&text));
+        self.status_message = StatusMessage::from(message.clone());
+        let utt = self.create_announcement(AnnouncementClass::Editing, &message, "Pasted.");
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Speak the selected text, so it can be checked before cutting.
+    fn speak_selection(&mut self) {
+        let (_, _, text) = match self.selected_text_or_complain() {
+            Some(selection) => selection,
+            None => return,
+        };
+        let spoken = if text.trim().is_empty() {
+            String::from("blank")
+        } else {
+            string_to_speakable_tokens(&text, &self.speech_options)
+        };
+        let utt = self.create_content_utterance(&spoken);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Read a key pressed in a prompt or a question: the next one from the
+    /// macro being replayed, if there is one, or else from the terminal. The
+    /// key is added to the macro being recorded, if one is.
+    fn read_key(&mut self) -> Result<Key, std::io::Error> {
+        let key = match self.macro_replay_keys.pop_front() {
+            Some(key) => key,
+            None => Terminal::read_key()?,
+        };
+        self.record_macro_key(key);
+        Ok(key)
+    }
+
+    /// Add a key to the macro being recorded, if one is.
+    fn record_macro_key(&mut self, key: Key) {
+        if let Some(keys) = self.macro_recording.as_mut() {
+            keys.push(key);
+        }
+    }
+
+    /// Start recording a macro: every key pressed until it's stopped, to be
+    /// replayed with `replay_macro`.
+    fn start_macro(&mut self) {
+        if self.macro_recording.is_some() {
+            self.play_noop_sound();
+            let utt = self.create_announcement(
+                AnnouncementClass::Errors,
+                "Already recording a macro.",
+                "Already recording.",
+            );
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return;
+        }
+        self.macro_recording = Some(Vec::new());
+        self.play_earcon(Earcon::Recording);
+        self.status_message = StatusMessage::from("Recording macro...".to_string());
+        let utt =
+            self.create_announcement(AnnouncementClass::Editing, "Recording macro.", "Recording.");
+        self.sound_manager.append(Box::new(utt));
+    }
+
+    /// Stop recording a macro, keeping it to replay. An empty recording
+    /// leaves the last macro as it was.
+    fn stop_macro(&mut self) {
+        let keys = match self.macro_recording.take() {
+            Some(keys) => keys,
+            None => {
+                self.play_noop_sound();
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    "Not recording a macro.",
+                    "Not recording.",
+                );
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return;
+            }
+        };
+        if keys.is_empty() {
+            self.play_noop_sound();
+            self.status_message = StatusMessage::from("Macro empty".to_string());
+            let utt = self.create_announcement(
+                AnnouncementClass::Editing,
+                "Macro stopped with no keys; the last one is kept.",
+                "Empty macro.",
+            );
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return;
+        }
+        let count = keys.len();
+        self.last_macro = keys;
+        self.play_success_sound();
+        self.status_message = StatusMessage::from(format!("Macro recorded: {} keys", count));
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &format!(
+                "Macro recorded, {} {}.",
+                count,
+                if count == 1 { "key" } else { "keys" }
+            ),
+            "Recorded.",
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Add a digit to the number of times the next replay repeats the
+    /// macro.
+    fn add_to_macro_count(&mut self, digit: char) {
+        let digit = digit.to_digit(10).unwrap_or(0) as usize;
+        let count = self
+            .macro_count
+            .unwrap_or(0)
+            .saturating_mul(10)
+            .saturating_add(digit);
+        self.macro_count = Some(count);
+        self.status_message = StatusMessage::from(format!("Repeat {}", count));
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &format!("Repeat {}.", count),
+            &count.to_string(),
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Replay the last macro, as many times as the count typed before it
+    /// (once if there was none). Its keys are read before any pressed
+    /// while it replays.
+    fn replay_macro(&mut self) {
+        let count = self.macro_count.take().unwrap_or(1).max(1);
+        if self.macro_recording.is_some() {
+            self.play_noop_sound();
+            let utt = self.create_announcement(
+                AnnouncementClass::Errors,
+                "Stop recording the macro before replaying it.",
+                "Still recording.",
+            );
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return;
+        }
+        if self.last_macro.is_empty() {
+            self.play_noop_sound();
+            let utt = self.create_announcement(
+                AnnouncementClass::Errors,
+                "No macro recorded.",
+                "No macro.",
+            );
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return;
+        }
+        for _ in 0..count {
+            self.macro_replay_keys
+                .extend(self.last_macro.iter().copied());
+        }
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &if count == 1 {
+                "Replaying macro.".to_string()
+            } else {
+                format!("Replaying macro {} times.", count)
+            },
+            "Replaying.",
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// List every command by name, narrowed as a fuzzy query is typed, and
+    /// run the one chosen. Each command is spoken with its key as it's
+    /// highlighted; Down and Up browse, Enter runs, and Esc cancels.
+    fn command_palette(&mut self) -> Result<(), std::io::Error> {
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            "Command palette. Type to filter, down and up to browse, enter to run.",
+            "Commands.",
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        let mut query = String::new();
+        let mut matches = palette::matching(&query);
+        let mut selected = 0;
+        loop {
+            let highlighted = matches.get(selected).map_or("", |action| action.name());
+            self.status_message = StatusMessage::from(format!(
+                "Command: {} ({}/{}) {}",
+                query,
+                if matches.is_empty() { 0 } else { selected + 1 },
+                matches.len(),
+                highlighted
+            ));
+            self.refresh_screen()?;
+            let previous = selected;
+            let previous_query = query.clone();
+            match self.read_key()? {
+                Key::Down | Key::Ctrl('n') if selected + 1 < matches.len() => selected += 1,
+                Key::Up | Key::Ctrl('p') if selected > 0 => selected -= 1,
+                Key::Down | Key::Up | Key::Ctrl('n' | 'p') => self.play_blocked_navigation_sound(),
+                Key::Char('\n') if !matches.is_empty() => break,
+                Key::Char('\n') => self.play_noop_sound(),
+                Key::Char(c) if !c.is_control() => query.push(c),
+                Key::Backspace if !query.is_empty() => {
+                    query.pop();
+                }
+                Key::Backspace => self.play_blocked_navigation_sound(),
+                Key::Esc => {
+                    self.status_message = StatusMessage::from(String::new());
+                    self.play_noop_sound();
+                    return Ok(());
+                }
+                _ => (),
+            }
+            if query != previous_query {
+                matches = palette::matching(&query);
+                selected = 0;
+                if matches.is_empty() {
+                    self.play_noop_sound();
+                    let utt = self.create_announcement(
+                        AnnouncementClass::Prompts,
+                        &format!("No commands match {}.", query),
+                        "No matches.",
+                    );
+                    self.sound_manager.interrupt_and_play(Box::new(utt));
+                    continue;
+                }
+                let verbose = format!(
+                    "{}. {} {}.",
+                    self.describe_command(matches[0]),
+                    matches.len(),
+                    if matches.len() == 1 {
+                        "match"
+                    } else {
+                        "matches"
+                    }
+                );
+                let terse = matches[0].spoken_name();
+                let utt = self.create_announcement(AnnouncementClass::Prompts, &verbose, &terse);
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+            } else if selected != previous {
+                let utt = self.create_utterance(&format!(
+                    "{} of {}: {}",
+                    selected + 1,
+                    matches.len(),
+                    self.describe_command(matches[selected])
+                ));
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+            }
+        }
+        self.status_message = StatusMessage::from(String::new());
+        self.perform(matches[selected])
+    }
+
+    /// Describe a command for the palette: its name and its key, if it has
+    /// one ("speak line, alt L").
+    fn describe_command(&self, action: Action) -> String {
+        match self.keymap.key_for(action) {
+            Some(key) => format!("{}, {}", action.spoken_name(), keymap::chord_name(key)),
+            None => format!("{}, no key", action.spoken_name()),
+        }
+    }
+
+    /// Switch to a vi-style mode and say so ("Insert mode."), after anything
+    /// the command that switched said.
+    fn set_vi_mode(&mut self, mode: ViMode) {
+        self.vi_mode = Some(mode);
+        self.vi_pending_delete = false;
+        let name = mode.name();
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &format!("{}{} mode.", name[..1].to_uppercase(), &name[1..]),
+            name,
+        );
+        self.sound_manager.append(Box::new(utt));
+    }
+
+    /// Handle Esc in modal editing: back to normal mode, dropping the
+    /// selection if there was one.
+    fn vi_escape(&mut self) {
+        match self.vi_mode {
+            Some(ViMode::Insert) => self.set_vi_mode(ViMode::Normal),
+            Some(ViMode::Visual) => {
+                self.selection_anchor = None;
+                self.set_vi_mode(ViMode::Normal);
+            }
+            _ => {
+                self.vi_pending_delete = false;
+                self.play_noop_sound();
+            }
+        }
+    }
+
+    /// Carry out a normal or visual mode command. Motions are the editor's
+    /// own actions, so they're spoken just as they are with the arrow keys.
+    fn vi_command(&mut self, c: char) -> Result<(), std::io::Error> {
+        let pending_delete = std::mem::take(&mut self.vi_pending_delete);
+        let visual = self.vi_mode == Some(ViMode::Visual);
+        let is_edit = matches!(c, 'x' | 'd' | 'p' | 'i' | 'a' | 'o');
+        if is_edit && self.document.is_read_only() {
+            self.play_read_only_warning();
+            return Ok(());
+        }
+        match c {
+            'h' => self.perform(Action::MoveLeft)?,
+            'j' => self.perform(Action::MoveDown)?,
+            'k' => self.perform(Action::MoveUp)?,
+            'l' => self.perform(Action::MoveRight)?,
+            'w' => self.perform(Action::NextWord)?,
+            'b' => self.perform(Action::PreviousWord)?,
+            '0' => self.perform(Action::LineStart)?,
+            '$' => self.perform(Action::LineEnd)?,
+            '%' => self.perform(Action::MatchBracket)?,
+            'y' if visual => {
+                self.copy_selection();
+                self.set_vi_mode(ViMode::Normal);
+            }
+            'd' | 'x' if visual => {
+                self.cut_selection();
+                self.set_vi_mode(ViMode::Normal);
+            }
+            'v' if visual => {
+                self.selection_anchor = None;
+                self.set_vi_mode(ViMode::Normal);
+            }
+            'v' => {
+                self.selection_anchor = Some(self.cursor_position.clone());
+                self.set_vi_mode(ViMode::Visual);
+            }
+            'd' if pending_delete => self.vi_delete_line(),
+            'd' => self.vi_pending_delete = true,
+            'x' => self.perform(Action::DeleteForward)?,
+            'p' => self.vi_put(),
+            'i' => self.set_vi_mode(ViMode::Insert),
+            'a' => {
+                let len = self.current_row_len();
+                self.cursor_position.x = (self.cursor_position.x + 1).min(len);
+                self.set_vi_mode(ViMode::Insert);
+            }
+            'o' => {
+                self.cursor_position.x = self.current_row_len();
+                self.insert_carriage_return();
+                self.set_vi_mode(ViMode::Insert);
+            }
+            _ => self.play_noop_sound(),
+        }
+        Ok(())
+    }
+
+    /// Get the length of the row the cursor is on.
+    fn current_row_len(&self) -> usize {
+        self.document
+            .get_row(self.cursor_position.y)
+            .map_or(0, Row::len)
+    }
+
+    /// Delete the current line into the register, as vi's `dd` does, for
+    /// `p` to put back below another.
+    fn vi_delete_line(&mut self) {
+        let row = match self.document.remove_row(self.cursor_position.y) {
+            Some(row) => row,
+            None => {
+                self.play_blocked_navigation_sound();
+                return;
+            }
+        };
+        self.cursor_position.x = 0;
+        self.move_cursor(Key::Null, WrappingBehavior::Default);
+        let spoken = if row.is_blank() {
+            "blank line".to_string()
+        } else {
+            string_to_speakable_tokens(row.as_str(), &self.speech_options)
+        };
+        self.copy_to_clipboard(format!("{}\n", row.as_str()));
+        self.status_message = StatusMessage::from("Deleted line".to_string());
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &format!("Deleted: {}", spoken),
+            "Deleted.",
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Put the register after the cursor, as vi's `p` does: whole lines
+    /// (from `dd`) go below the current line, and other text after the
+    /// cursor.
+    fn vi_put(&mut self) {
+        let text = clipboard::paste().unwrap_or_else(|| self.register.clone());
+        let lines = match text.strip_suffix('\n') {
+            Some(lines) => lines,
+            None => {
+                let len = self.current_row_len();
+                self.cursor_position.x = (self.cursor_position.x + 1).min(len);
+                self.paste();
+                return;
+            }
+        };
+        let y = self.cursor_position.y;
+        let end = Position {
+            x: self.current_row_len(),
+            y,
+        };
+        self.document.insert_text(&end, &format!("\n{}", lines));
+        self.cursor_position = Position { x: 0, y: y + 1 };
+        let message = format!("Put {}.", Self::describe_text_size(lines));
+        self.status_message = StatusMessage::from(message.clone());
+        let spoken = self.spoken_row(y + 1);
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &format!("{} {}", message, spoken),
+            "Put.",
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Step back or forward through the speech history and say that entry
+    /// again. The first step back (or a repeat) says the newest; stepping
+    /// starts over once anything else is pressed.
+    fn review_speech(&mut self, direction: SearchDirection) {
+        let length = self.sound_manager.history().len();
+        if length == 0 {
+            self.play_noop_sound();
+            let utt = self.create_announcement(
+                AnnouncementClass::Errors,
+                "Nothing has been spoken yet.",
+                "No history.",
+            );
+            self.sound_manager.interrupt_and_repeat(Box::new(utt));
+            return;
+        }
+        let distance = match (direction, self.speech_review) {
+            (SearchDirection::Backward, None) => Some(0),
+            (SearchDirection::Backward, Some(distance)) if distance + 1 < length => {
+                Some(distance + 1)
+            }
+            (SearchDirection::Forward, Some(distance)) if distance > 0 => Some(distance - 1),
+            _ => None,
+        };
+        let distance = match distance {
+            Some(distance) => distance,
+            None => {
+                // At either end, say the entry there again after the bump.
+                self.play_blocked_navigation_sound();
+                self.speech_review.unwrap_or(0)
+            }
+        };
+        self.speech_review = Some(distance);
+        let text = self.sound_manager.history()[length - 1 - distance].clone();
+        self.status_message =
+            StatusMessage::from(format!("Spoken {}/{}: {}", length - distance, length, text));
+        let utt = self.create_utterance(&text);
+        self.sound_manager.interrupt_and_repeat(Box::new(utt));
+    }
+
+    /// Play the shape of the whole document as a quick run of tones, one for
+    /// each line or few lines: higher for deeper indentation, louder for
+    /// longer lines, and silent for blank ones.
+    fn play_overview(&mut self) {
+        let lines = self.document.row_count();
+        if lines == 0 {
+            self.play_noop_sound();
+            let utt =
+                self.create_announcement(AnnouncementClass::Errors, "The file is empty.", "Empty.");
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return;
+        }
+        let utt = self.create_announcement(
+            AnnouncementClass::Navigation,
+            &format!("Overview of {} lines.", lines),
+            "Overview.",
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        let overview = overview::overview(&self.document, &self.speech_options);
+        self.sound_manager.append(Box::new(overview));
+    }
+
+    /// Say where the cursor is in the document's structure: the headings
+    /// above it in Markdown, or the definitions around it in code.
+    fn speak_scope(&mut self) {
+        let chain =
+            outline::enclosing(&self.document, &self.speech_options, self.cursor_position.y);
+        if chain.is_empty() {
+            self.status_message = StatusMessage::from("Top level".to_string());
+            let utt = self.create_announcement(
+                AnnouncementClass::Navigation,
+                "At the top level.",
+                "Top level.",
+            );
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return;
+        }
+        self.status_message = StatusMessage::from(
+            chain
+                .iter()
+                .map(|entry| entry.title.as_str())
+                .collect::<Vec<&str>>()
+                .join(" > "),
+        );
+        let spoken = chain
+            .iter()
+            .map(|entry| string_to_speakable_tokens(&entry.title, &self.speech_options))
+            .collect::<Vec<String>>()
+            .join(", ");
+        let kind = if outline::is_markdown(&self.document) {
+            "Section"
+        } else {
+            "Inside"
+        };
+        let utt = self.create_announcement(
+            AnnouncementClass::Navigation,
+            &format!("{}: {}.", kind, spoken),
+            &spoken,
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Speak the document's outline: its headings, or the functions and
+    /// types it defines, with their line numbers. Down and Up go through the
+    /// entries one at a time, Enter jumps to one, and `b` opens the outline
+    /// in a new buffer.
+    fn speak_outline(&mut self) -> Result<(), std::io::Error> {
+        let entries = outline::outline(&self.document, &self.speech_options);
+        if entries.is_empty() {
+            self.play_noop_sound();
+            let utt = self.create_announcement(
+                AnnouncementClass::Errors,
+                "No headings or definitions found.",
+                "No outline.",
+            );
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return Ok(());
+        }
+        let describe = |editor: &Self, entry: &Entry| {
+            format!(
+                "Line {}: {}",
+                entry.y + 1,
+                string_to_speakable_tokens(&entry.title, &editor.speech_options)
+            )
+        };
+        let all = entries
+            .iter()
+            .map(|entry| describe(self, entry))
+            .collect::<Vec<String>>()
+            .join(". ");
+        let verbose = format!(
+            "Outline, {} entries. Down and up to browse, enter to jump, b for a buffer. {}.",
+            entries.len(),
+            all
+        );
+        let terse = format!("{} entries. {}.", entries.len(), all);
+        let utt = self.create_announcement(AnnouncementClass::Prompts, &verbose, &terse);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+
+        // Start from the entry the cursor is in.
+        let y = self.cursor_position.y;
+        let mut selected = entries.iter().rposition(|entry| entry.y <= y).unwrap_or(0);
+        loop {
+            self.status_message = StatusMessage::from(format!(
+                "Outline {}/{}: line {}: {}",
+                selected + 1,
+                entries.len(),
+                entries[selected].y + 1,
+                entries[selected].title
+            ));
+            self.refresh_screen()?;
+            let previous = selected;
+            match self.read_key()? {
+                Key::Down if selected + 1 < entries.len() => selected += 1,
+                Key::Up if selected > 0 => selected -= 1,
+                Key::Down | Key::Up => self.play_blocked_navigation_sound(),
+                Key::Char('\n') => break,
+                Key::Char('b') => {
+                    let text = entries
+                        .iter()
+                        .map(|entry| {
+                            format!(
+                                "{}{}: {}",
+                                "  ".repeat(entry.depth),
+                                entry.y + 1,
+                                entry.title
+                            )
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n");
+                    self.show_in_buffer(Document::from_text(&text));
+                    self.announce_active_buffer();
+                    return Ok(());
+                }
+                Key::Esc => {
+                    self.status_message = StatusMessage::from(String::new());
+                    self.play_noop_sound();
+                    return Ok(());
+                }
+                _ => (),
+            }
+            if selected != previous {
+                let utt = self.create_utterance(&format!(
+                    "{} of {}: {}",
+                    selected + 1,
+                    entries.len(),
+                    describe(self, &entries[selected])
+                ));
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+            }
+        }
+        self.status_message = StatusMessage::from(String::new());
+        self.cursor_position = Position {
+            x: 0,
+            y: entries[selected].y,
+        };
+        self.scroll();
+        self.speak_current_row();
+        Ok(())
+    }
+
+    /// Speak how big the selection is and where it runs, like "3 lines, 47
+    /// words selected, from line 10 column 2 to line 12 column 30", to check
+    /// it before acting on it.
+    fn speak_selection_summary(&mut self) {
+        let (start, end, text) = match self.selected_text_or_complain() {
+            Some(selection) => selection,
+            None => return,
+        };
+        let (verbose, terse) = if text.is_empty() {
+            (
+                format!(
+                    "Empty selection, at line {} column {}.",
+                    start.y + 1,
+                    start.x + 1
+                ),
+                String::from("Empty."),
+            )
+        } else {
+            // A selection ending at the start of a line doesn't take in any
+            // of that line.
+            let ends_at_line_start = end.x == 0 && end.y > start.y;
+            let lines = text.split('\n').count() - usize::from(ends_at_line_start);
+            let words = text.split_whitespace().count();
+            let plural = |count: usize, noun: &str| {
+                if count == 1 {
+                    format!("1 {}", noun)
+                } else {
+                    format!("{} {}s", count, noun)
+                }
+            };
+            // The end is the column of the last selected character, so a
+            // selection ending at the start of a line ends on the line before.
+            let (end_line, end_column) = if ends_at_line_start {
+                let y = end.y - 1;
+                (y + 1, self.document.get_row(y).map_or(0, Row::len).max(1))
+            } else {
+                (end.y + 1, end.x)
+            };
+            (
+                format!(
+                    "{}, {} selected, from line {} column {} to line {} column {}.",
+                    plural(lines, "line"),
+                    plural(words, "word"),
+                    start.y + 1,
+                    start.x + 1,
+                    end_line,
+                    end_column
+                ),
+                format!(
+                    "{} lines, {} words, {} {} to {} {}",
+                    lines,
+                    words,
+                    start.y + 1,
+                    start.x + 1,
+                    end_line,
+                    end_column
+                ),
+            )
+        };
+        self.status_message = StatusMessage::from(verbose.clone());
+        let utt = self.create_announcement(AnnouncementClass::Navigation, &verbose, &terse);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Speak the count, sum, minimum, maximum and mean of the numbers in the
+    /// selection, or on the current line if no mark is set.
+    fn speak_statistics(&mut self) {
+        let text = match self.selection_range() {
+            Some((start, end)) => self.document.text_in_range(&start, &end),
+            None => self
+                .document
+                .get_row(self.cursor_position.y)
+                .map(|row| row.slice(0, row.len()))
+                .unwrap_or_default(),
+        };
+        let utt = match Summary::of(&stats::numbers_in(&text)) {
+            Some(summary) => {
+                self.status_message = StatusMessage::from(summary.describe());
+                self.create_utterance(&summary.describe())
+            }
+            None => {
+                self.play_noop_sound();
+                self.create_announcement(
+                    AnnouncementClass::Errors,
+                    "No numbers here.",
+                    "No numbers.",
+                )
+            }
+        };
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Sort the selected lines (or the whole document) as a single undoable
+    /// edit.
+    fn sort_lines(&mut self) -> Result<(), std::io::Error> {
+        if self.document.is_read_only() {
+            self.play_read_only_warning();
+            return Ok(());
+        }
+        self.status_message = StatusMessage::from(
+            "Sort: (a)lphabetical (n)umeric (c)olumn; capital to reverse".to_string(),
+        );
+        self.refresh_screen()?;
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            "Sort: A alphabetical, N numeric, C by column. Capital letter to reverse.",
+            "Sort.",
+        );
+        self.sound_manager.append(Box::new(utt));
+        let (key, descending) = match self.read_key()? {
+            Key::Char(c @ ('a' | 'n' | 'c')) => (c, false),
+            Key::Char(c @ ('A' | 'N' | 'C')) => (c.to_ascii_lowercase(), true),
+            _ => {
+                self.status_message = StatusMessage::from(String::new());
+                self.play_noop_sound();
+                return Ok(());
+            }
+        };
+        let key = match key {
+            'a' => SortKey::Lexicographic,
+            'n' => SortKey::Numeric,
+            _ => {
+                let utt = self.create_announcement(
+                    AnnouncementClass::Prompts,
+                    "Column number, then an optional delimiter, like 2 comma.",
+                    "Column.",
+                );
+                self.sound_manager.append(Box::new(utt));
+                let answer = self
+                    .prompt("Column (e.g. 2 or 2,): ", |_, _, _| {})?
+                    .unwrap_or_default();
+                let digits: String = answer.chars().take_while(char::is_ascii_digit).collect();
+                match digits.parse::<usize>() {
+                    Ok(index) if index > 0 => SortKey::Column {
+                        index,
+                        delimiter: answer[digits.len()..].trim().chars().next(),
+                    },
+                    _ => {
+                        self.play_noop_sound();
+                        return Ok(());
+                    }
+                }
+            }
+        };
+
+        let (start, len) = self.selected_rows();
+        let lines: Vec<&str> = (start..start + len)
+            .filter_map(|y| self.document.get_row(y))
+            .map(Row::as_str)
+            .collect();
+        let order = sort::sorted_order(&lines, &key, descending);
+        let how = format!(
+            "{}{}",
+            key.describe(),
+            if descending { ", reversed" } else { "" }
+        );
+        let message = if order.iter().enumerate().all(|(to, from)| to == *from) {
+            format!("{} lines already sorted {}.", len, how)
+        } else {
+            self.document
+                .reorder_rows(start, &order, format!("sort of {} lines", len));
+            self.selection_anchor = None;
+            self.cursor_position = Position { x: 0, y: start };
+            format!("Sorted {} lines {}.", len, how)
+        };
+        self.status_message = StatusMessage::from(message.clone());
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &message,
+            &format!("Sorted {}.", len),
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        Ok(())
+    }
+
+    /// Remove adjacent duplicate lines from the selected lines (or the whole
+    /// document) as a single undoable edit.
+    fn dedup_lines(&mut self) {
+        if self.document.is_read_only() {
+            self.play_read_only_warning();
+            return;
+        }
+        let (start, len) = self.selected_rows();
+        let removed = self.document.remove_adjacent_duplicates(
+            start,
+            len,
+            format!("removing duplicates from {} lines", len),
+        );
+        let message = if removed == 0 {
+            format!("No adjacent duplicates in {} lines.", len)
+        } else {
+            self.selection_anchor = None;
+            self.cursor_position = Position { x: 0, y: start };
+            format!(
+                "Removed {} duplicate line{}.",
+                removed,
+                if removed == 1 { "" } else { "s" }
+            )
+        };
+        self.status_message = StatusMessage::from(message.clone());
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &message,
+            &format!("{} removed.", removed),
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Undo the last row edit, like a sort.
+    fn undo(&mut self) {
+        if self.document.is_read_only() {
+            self.play_read_only_warning();
+            return;
+        }
+        match self.document.undo() {
+            Some((y, description)) => {
+                self.cursor_position = Position { x: 0, y };
+                let message = format!("Undid {}.", description);
+                self.status_message = StatusMessage::from(message.clone());
+                let utt = self.create_announcement(AnnouncementClass::Editing, &message, "Undone.");
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+            }
+            None => {
+                self.play_noop_sound();
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    "Nothing to undo.",
+                    "Nothing.",
+                );
+                self.sound_manager.append(Box::new(utt));
+            }
+        }
+    }
+
+    /// Mark the current row as "A" for a later comparison.
+    fn mark_compare_row(&mut self) {
+        let y = self.cursor_position.y;
+        self.compare_mark = Some(y);
+        self.status_message = StatusMessage::from(format!("Line {} marked as A", y + 1));
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            format!("Line {} marked as A.", y + 1).as_str(),
+            "Marked A.",
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Set the anchor on the current line, or clear it if it's already
+    /// here. While it's set, location announcements say how far the cursor
+    /// is from it.
+    fn toggle_anchor(&mut self) {
+        let y = self.cursor_position.y;
+        let (verbose, terse) = if self.anchor_line == Some(y) {
+            self.anchor_line = None;
+            ("Anchor cleared.".to_string(), "Cleared.")
+        } else {
+            self.anchor_line = Some(y);
+            (format!("Anchor set on line {}.", y + 1), "Anchored.")
+        };
+        self.status_message = StatusMessage::from(verbose.clone());
+        let utt = self.create_announcement(AnnouncementClass::Editing, &verbose, terse);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Describe where the cursor is relative to the anchor line, like
+    /// ("12 lines below the anchor.", "+12"), if an anchor is set.
+    fn anchor_offset(&self) -> Option<(String, String)> {
+        let anchor = self.anchor_line?;
+        let y = self.cursor_position.y;
+        let lines = |count: usize| {
+            if count == 1 {
+                String::from("1 line")
+            } else {
+                format!("{} lines", count)
+            }
+        };
+        Some(match y.cmp(&anchor) {
+            Ordering::Greater => (
+                format!("{} below the anchor.", lines(y - anchor)),
+                format!("+{}", y - anchor),
+            ),
+            Ordering::Less => (
+                format!("{} above the anchor.", lines(anchor - y)),
+                format!("-{}", anchor - y),
+            ),
+            Ordering::Equal => (String::from("On the anchor line."), String::from("anchor")),
+        })
+    }
+
+    /// Speak the marked row and the current row back to back, followed by
+    /// the first token at which they differ.
+    fn compare_with_mark(&mut self) {
+        let mark = match self.compare_mark {
+            Some(mark) => mark,
+            None => {
+                self.play_noop_sound();
+                let utt = self.create_utterance("No line marked as A.");
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return;
+            }
+        };
+        let line_text = |editor: &Self, y: usize| {
+            editor
+                .document
+                .get_row(y)
+                .map(|row| row.as_str().to_string())
+                .unwrap_or_default()
+        };
+        let a = line_text(self, mark);
+        let b = line_text(self, self.cursor_position.y);
+        let a_tokens = split_tokens(&a);
+        let b_tokens = split_tokens(&b);
+        let first_difference = (0..a_tokens.len().max(b_tokens.len()))
+            .find(|&index| a_tokens.get(index) != b_tokens.get(index));
+        let speak = |token: Option<&&str>| {
+            token.map_or("nothing".to_string(), |token| {
+                string_to_speakable_tokens(token, &self.speech_options)
+            })
+        };
+        let verdict = match first_difference {
+            None => "Identical.".to_string(),
+            Some(index) => format!(
+                "Differs at token {}: {} versus {}.",
+                index + 1,
+                speak(a_tokens.get(index)),
+                speak(b_tokens.get(index))
+            ),
+        };
+        let a_spoken = string_to_speakable_tokens(&a, &self.speech_options);
+        let b_spoken = string_to_speakable_tokens(&b, &self.speech_options);
+        self.status_message = StatusMessage::from(verdict.clone());
+        for text in [
+            format!("A: {}", a_spoken),
+            format!("B: {}", b_spoken),
+            verdict,
+        ] {
+            let utt = self.create_utterance(&text);
+            self.sound_manager.append(Box::new(utt));
+        }
+    }
+
+    /// Move the current row into the session's trash.
+    fn trash_current_row(&mut self) {
+        if self.document.is_read_only() {
+            self.play_read_only_warning();
+            return;
+        }
+        let row = match self.document.remove_row(self.cursor_position.y) {
+            Some(row) => row,
+            None => {
+                self.play_blocked_navigation_sound();
+                return;
+            }
+        };
+        self.move_cursor(Key::Null, WrappingBehavior::Default);
+        let spoken = if row.is_blank() {
+            "blank line".to_string()
+        } else {
+            string_to_speakable_tokens(row.as_str(), &self.speech_options)
+        };
+        self.trash.push(row.as_str().to_string());
+        self.status_message =
+            StatusMessage::from(format!("Trashed ({} in trash)", self.trash.len()));
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            format!("Trashed: {}", spoken).as_str(),
+            "Trashed.",
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Copy the current line below itself and move down onto the copy.
+    fn duplicate_current_row(&mut self) {
+        if self.document.is_read_only() {
+            self.play_read_only_warning();
+            return;
+        }
+        let y = self.cursor_position.y;
+        if !self.document.duplicate_row(y) {
+            self.play_blocked_navigation_sound();
+            return;
+        }
+        self.cursor_position.y = y + 1;
+        self.scroll();
+        let spoken = self.spoken_row(y + 1);
+        self.status_message = StatusMessage::from(format!("Duplicated line {}.", y + 1));
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &format!("Duplicated line {}: {}", y + 1, spoken),
+            "Duplicated.",
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Swap the current line with the one above or below it, keeping the
+    /// cursor on the moved line.
+    fn move_current_row(&mut self, direction: SearchDirection) {
+        if self.document.is_read_only() {
+            self.play_read_only_warning();
+            return;
+        }
+        let y = self.cursor_position.y;
+        let target = match direction {
+            SearchDirection::Forward => y + 1,
+            SearchDirection::Backward if y > 0 => y - 1,
+            SearchDirection::Backward => {
+                self.play_blocked_navigation_sound();
+                return;
+            }
+        };
+        if !self.document.swap_rows(y, target) {
+            self.play_blocked_navigation_sound();
+            return;
+        }
+        self.cursor_position.y = target;
+        self.scroll();
+        let (way, neighbour) = match direction {
+            SearchDirection::Forward => ("down", "below"),
+            SearchDirection::Backward => ("up", "above"),
+        };
+        let passed = self.spoken_row(y);
+        self.status_message = StatusMessage::from(format!("Moved {} to line {}.", way, target + 1));
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &format!(
+                "Moved {} to line {}, {} {}",
+                way,
+                target + 1,
+                neighbour,
+                passed
+            ),
+            &format!("{} {}", way, target + 1),
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Get a line as it should be spoken, or "blank line".
+    fn spoken_row(&self, y: usize) -> String {
+        match self.document.get_row(y) {
+            Some(row) if !row.is_blank() => {
+                string_to_speakable_tokens(row.as_str(), &self.speech_options)
+            }
+            _ => "blank line".to_string(),
+        }
+    }
+
+    /// Browse the trash by speech and restore a chosen row at the cursor.
+    ///
+    /// Up/Down step through trashed rows (most recent first), Enter restores
+    /// the current one above the cursor, and Esc cancels.
+    ///
+    fn restore_from_trash(&mut self) -> Result<(), std::io::Error> {
+        if self.document.is_read_only() {
+            self.play_read_only_warning();
+            return Ok(());
+        }
+        if self.trash.is_empty() {
+            self.play_noop_sound();
+            let utt = self.create_utterance("Trash is empty.");
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return Ok(());
+        }
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            format!(
+                "Trash, {} lines. Up and down to browse, enter to restore.",
+                self.trash.len()
+            )
+            .as_str(),
+            "Trash.",
+        );
+        self.sound_manager.append(Box::new(utt));
+
+        // Index from the most recently trashed row.
+        let mut selected = 0;
+        loop {
+            let index = self.trash.len() - 1 - selected;
+            let text = self.trash[index].clone();
+            self.status_message = StatusMessage::from(format!(
+                "Trash {}/{}: {}",
+                selected + 1,
+                self.trash.len(),
+                text
+            ));
+            self.refresh_screen()?;
+            let spoken = if text.trim().is_empty() {
+                "blank line".to_string()
+            } else {
+                string_to_speakable_tokens(&text, &self.speech_options)
+            };
+            let utt = self.create_utterance(
+                format!("{} of {}: {}", selected + 1, self.trash.len(), spoken).as_str(),
+            );
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+
+            match self.read_key()? {
+                Key::Down => {
+                    if selected + 1 < self.trash.len() {
+                        selected += 1;
+                    } else {
+                        self.play_blocked_navigation_sound();
+                    }
+                }
+                Key::Up => {
+                    if selected > 0 {
+                        selected -= 1;
+                    } else {
+                        self.play_blocked_navigation_sound();
+                    }
+                }
+                Key::Char('\n') => {
+                    let text = self.trash.remove(index);
+                    self.document
+                        .insert_row(self.cursor_position.y, Row::from(text.as_str()));
+                    self.cursor_position.x = 0;
+                    self.status_message = StatusMessage::from("Restored.".to_string());
+                    let utt = self.create_announcement(
+                        AnnouncementClass::Editing,
+                        format!("Restored on line {}.", self.cursor_position.y + 1).as_str(),
+                        "Restored.",
+                    );
+                    self.sound_manager.interrupt_and_play(Box::new(utt));
+                    return Ok(());
+                }
+                Key::Esc => {
+                    self.status_message = StatusMessage::from(String::new());
+                    self.play_noop_sound();
+                    return Ok(());
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Say the current line and move down, as one step of reading through
+    /// the document.
+    ///
+    /// A run of blank lines is announced once by its length and skipped,
+    /// rather than read as silence line by line.
+    ///
+    fn read_line_and_advance(&mut self) {
+        let y = self.cursor_position.y;
+        let blank_lines = self.document.blank_run_length(y);
+        if blank_lines > 0 && y < self.document.row_count() {
+            self.announce_blank_lines(blank_lines);
+            self.cursor_position.y = y.saturating_add(blank_lines);
+            self.move_cursor(Key::Null, WrappingBehavior::Default);
+            return;
+        }
+        // Say the current line.
+        self.speak_current_row();
+        self.move_cursor(Key::Down, WrappingBehavior::Default);
+    }
+
+    /// Read from the current line to the end of the document, moving the
+    /// cursor down as each line finishes so that it follows the speech.
+    ///
+    /// Any key stops reading; the key is not otherwise acted on. When speech
+    /// is written for a screen reader, or is off, there's nothing to wait for
+    /// between lines, so only one line is read.
+    ///
+    fn read_to_end(&mut self) -> Result<(), std::io::Error> {
+        if self.sound_manager.is_screen_reader() || self.sound_manager.is_silent() {
+            self.read_line_and_advance();
+            return Ok(());
+        }
+        loop {
+            let y = self.cursor_position.y;
+            if y >= self.document.row_count() {
+                let utt = self.create_utterance("End of document");
+                self.sound_manager.append(Box::new(utt));
+                return Ok(());
+            }
+            let blank_lines = self.document.blank_run_length(y);
+            if blank_lines > 0 {
+                self.announce_blank_lines(blank_lines);
+            } else {
+                self.speak_current_row();
+            }
+            let finished = self.sound_manager.notify_when_played();
+            loop {
+                if let Some(key) = Terminal::poll_key(READ_POLL_INTERVAL) {
+                    key?;
+                    self.sound_manager.silence();
+                    return Ok(());
+                }
+                if let Err(TryRecvError::Disconnected) = finished.try_recv() {
+                    break;
+                }
+            }
+            self.cursor_position.y = y.saturating_add(blank_lines.max(1));
+            self.move_cursor(Key::Null, WrappingBehavior::Default);
+            self.refresh_screen()?;
+        }
+    }
+
+    /// Move to the start of the next or previous paragraph and speak it.
+    ///
+    /// Paragraphs are separated by blank lines; the number of blank lines
+    /// crossed is announced once before the landing line.
+    ///
+    fn move_paragraph(&mut self, direction: SearchDirection) {
+        let y = self.cursor_position.y;
+        let row_count = self.document.row_count();
+        let (target, blank_lines) = match direction {
+            SearchDirection::Forward => {
+                let mut target = y;
+                while target < row_count && self.document.blank_run_length(target) == 0 {
+                    target += 1;
+                }
+                let blank_lines = self.document.blank_run_length(target);
+                (target.saturating_add(blank_lines), blank_lines)
+            }
+            SearchDirection::Backward => {
+                let is_blank = |y: usize| self.document.blank_run_length(y) > 0;
+                let mut target = y;
+                // Within a paragraph, go back to its first line.
+                while target > 0 && !is_blank(target) && !is_blank(target - 1) {
+                    target -= 1;
+                }
+                if target < y {
+                    (target, 0)
+                } else {
+                    // Otherwise step over the blank lines above, then walk to
+                    // the first line of the paragraph before them.
+                    let mut blank_lines = 0;
+                    while target > 0 && is_blank(target - 1) {
+                        target -= 1;
+                        blank_lines += 1;
+                    }
+                    if target == 0 {
+                        (y, 0)
+                    } else {
+                        target -= 1;
+                        while target > 0 && !is_blank(target - 1) {
+                            target -= 1;
+                        }
+                        (target, blank_lines)
+                    }
+                }
+            }
+        };
+        if target == y || target >= row_count {
+            self.play_blocked_navigation_sound();
+            return;
+        }
+        self.cursor_position = Position { x: 0, y: target };
+        self.scroll();
+        if blank_lines > 0 {
+            self.announce_blank_lines(blank_lines);
+        }
+        self.speak_current_row();
+    }
+
+    /// Move the cursor to the start of the next or previous word and speak
+    /// it. If wrapping is allowed, the jump continues onto other lines when
+    /// there are no more words on this one.
+    fn move_word(&mut self, direction: SearchDirection, wrapping_behavior: WrappingBehavior) {
+        let should_wrap = self.should_wrap(wrapping_behavior);
+        let Position { x, y } = self.cursor_position;
+        let row_count = self.document.row_count();
+        let mut target = None;
+        let mut row_index = y;
+        while target.is_none() {
+            if let Some(row) = self.document.get_row(row_index) {
+                let starts = row.words().into_iter().map(|(start, _)| start);
+                target = match direction {
+                    SearchDirection::Forward => {
+                        starts.filter(|&start| row_index > y || start > x).min()
+                    }
+                    SearchDirection::Backward => {
+                        starts.filter(|&start| row_index < y || start < x).max()
+                    }
+                };
+            }
+            if target.is_some() || !should_wrap {
+                break;
+            }
+            match direction {
+                SearchDirection::Forward if row_index + 1 < row_count => row_index += 1,
+                SearchDirection::Backward if row_index > 0 => row_index -= 1,
+                _ => break,
+            }
+        }
+        let x = match target {
+            Some(x) => x,
+            None => {
+                self.play_blocked_navigation_sound();
+                return;
+            }
+        };
+        self.cursor_position = Position { x, y: row_index };
+        self.scroll();
+        let word = self
+            .document
+            .get_row(row_index)
+            .and_then(|row| {
+                row.words()
+                    .into_iter()
+                    .find(|&(start, _)| start == x)
+                    .map(|(_, word)| word.to_string())
+            })
+            .unwrap_or_default();
+        let utt = self.create_content_utterance(
+            string_to_speakable_tokens(&word, &self.speech_options).as_str(),
+        );
+        self.sound_manager
+            .append_with_priority(Box::new(utt), Priority::Low);
+    }
+
+    /// Handle a plain key in a read-only buffer. Nothing can be typed
+    /// there, so letters move a review cursor instead, like a screen
+    /// reader's browse mode: `j` and `k` for the next and previous non-blank
+    /// line, `l` and `h` for the next and previous cell of a table row, `J`
+    /// and `K` down and up a column, and `/` to search.
+    ///
+    /// # Returns
+    ///
+    /// Whether the key was a review key.
+    ///
+    fn review(&mut self, c: char) -> bool {
+        match c {
+            'j' => self.review_line(SearchDirection::Forward),
+            'k' => self.review_line(SearchDirection::Backward),
+            'l' => self.review_cell(SearchDirection::Forward),
+            'h' => self.review_cell(SearchDirection::Backward),
+            'J' => self.review_column(SearchDirection::Forward),
+            'K' => self.review_column(SearchDirection::Backward),
+            '/' => self.search(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Find the next or previous line that isn't blank.
+    fn next_non_blank_row(&self, direction: SearchDirection) -> Option<usize> {
+        let y = self.cursor_position.y;
+        let is_blank = |y: usize| self.document.get_row(y).is_none_or(Row::is_blank);
+        match direction {
+            SearchDirection::Forward => {
+                (y.saturating_add(1)..self.document.row_count()).find(|&y| !is_blank(y))
+            }
+            SearchDirection::Backward => (0..y).rev().find(|&y| !is_blank(y)),
+        }
+    }
+
+    /// Get the index of the table cell the cursor is in, if any.
+    fn current_cell(&self) -> Option<usize> {
+        let Position { x, y } = self.cursor_position;
+        self.document
+            .get_row(y)?
+            .cells()
+            .iter()
+            .rposition(|(start, _)| *start <= x)
+    }
+
+    /// Move to the next or previous non-blank line and speak it.
+    fn review_line(&mut self, direction: SearchDirection) {
+        let y = match self.next_non_blank_row(direction) {
+            Some(y) => y,
+            None => {
+                self.play_blocked_navigation_sound();
+                return;
+            }
+        };
+        let x = self
+            .document
+            .get_row(y)
+            .and_then(|row| row.cells().first().map(|(start, _)| *start))
+            .unwrap_or(0);
+        self.cursor_position = Position { x, y };
+        self.scroll();
+        self.speak_current_row();
+    }
+
+    /// Move to the next or previous cell in this table row and speak it.
+    fn review_cell(&mut self, direction: SearchDirection) {
+        let current = self.current_cell();
+        let target = match direction {
+            SearchDirection::Forward => Some(current.map_or(0, |index| index + 1)),
+            SearchDirection::Backward => current.and_then(|index| index.checked_sub(1)),
+        };
+        let y = self.cursor_position.y;
+        if target.is_none_or(|index| !self.move_to_cell(y, index)) {
+            self.play_blocked_navigation_sound();
+        }
+    }
+
+    /// Move to the same cell in the next or previous non-blank line and
+    /// speak it. If that line has fewer cells, the table has ended, so the
+    /// whole line is read instead.
+    fn review_column(&mut self, direction: SearchDirection) {
+        let index = self.current_cell().unwrap_or(0);
+        let y = match self.next_non_blank_row(direction) {
+            Some(y) => y,
+            None => {
+                self.play_blocked_navigation_sound();
+                return;
+            }
+        };
+        if !self.move_to_cell(y, index) {
+            self.cursor_position = Position { x: 0, y };
+            self.scroll();
+            self.speak_current_row();
+        }
+    }
+
+    /// Move the cursor to the start of a table cell and speak the cell.
+    ///
+    /// # Returns
+    ///
+    /// Whether the line has that cell.
+    ///
+    fn move_to_cell(&mut self, y: usize, index: usize) -> bool {
+        let cell = self
+            .document
+            .get_row(y)
+            .and_then(|row| row.cells().into_iter().nth(index));
+        let (x, text) = match cell {
+            Some(cell) => cell,
+            None => return false,
+        };
+        self.cursor_position = Position { x, y };
+        self.scroll();
+        let utt = self.create_content_utterance(
+            string_to_speakable_tokens(&text, &self.speech_options).as_str(),
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        true
+    }
+
+    fn announce_blank_lines(&mut self, count: usize) {
+        let text = if count == 1 {
+            "1 blank line".to_string()
+        } else {
+            format!("{} blank lines", count)
+        };
+        let utt = self.create_utterance(&text);
+        self.sound_manager.append(Box::new(utt));
+    }
+
+    fn speak_current_row(&mut self) {
+        self.update_column_pan();
+        let default = &Row::from("");
+        let row = self
+            .document
+            .get_row(self.cursor_position.y)
+            .unwrap_or(default);
+        // A blank line says nothing about the block it's in, so it keeps
+        // the level of the lines around it.
+        if !row.as_str().trim().is_empty() {
+            let level = row.indent_level(&self.speech_options);
+            self.announce_indentation(level);
+        }
+        if self.config_manager.get_speak_line_numbers() {
+            let number = format!("{}:", self.cursor_position.y + 1);
+            let utt = self.create_utterance(&number);
+            self.sound_manager.append(Box::new(utt));
+        }
+        let row = self
+            .document
+            .get_row(self.cursor_position.y)
+            .unwrap_or(default);
+        if self.soft_wrap && self.wrapped_line_reading == WrappedLineReading::Segment {
+            let width = self.wrap_width();
+            let tab_width = self.speech_options.tab_width;
+            let start = segment_of(
+                self.cursor_column(),
+                self.row_columns(self.cursor_position.y),
+                width,
+            ) * width;
+            let segment = Row::from(
+                row.slice(
+                    row.x_at_render_x(start, tab_width),
+                    row.x_at_render_x(start + width, tab_width),
+                )
+                .as_str(),
+            );
+            self.sound_manager.play_row(&segment, &self.speech_options);
+            return;
+        }
+        // row.play(&mut self.sound_manager);
+        self.sound_manager.play_row(row, &self.speech_options);
+    }
+
+    /// Say how a line about to be read is indented, as the indentation
+    /// setting asks: "Indent 3" before every indented line, or only when it
+    /// changes, or "Entering block, depth 3" and "Leaving block, depth 1" if
+    /// it's indented more or less than the last one.
+    fn announce_indentation(&mut self, level: usize) {
+        let previous = self.last_indent_level.replace(level);
+        let indentation = self.speech_options.indentation;
+        let (verbose, terse) = match previous {
+            _ if indentation == IndentReading::Level && level > 0 => {
+                (format!("Indent {}.", level), format!("Indent {}", level))
+            }
+            Some(previous) if indentation == IndentReading::Changes && level != previous => {
+                match level {
+                    0 => (String::from("No indent."), String::from("Indent 0")),
+                    _ => (format!("Indent {}.", level), format!("Indent {}", level)),
+                }
+            }
+            _ if !indentation.announces_blocks() => return,
+            Some(previous) if level > previous => (
+                format!("Entering block, depth {}.", level),
+                format!("In {}", level),
+            ),
+            Some(previous) if level < previous => (
+                format!("Leaving block, depth {}.", level),
+                format!("Out {}", level),
+            ),
+            _ => return,
+        };
+        let utt = self.create_announcement(AnnouncementClass::Navigation, &verbose, &terse);
+        self.sound_manager.append(Box::new(utt));
+    }
+
+    /// Read the fields of the current line, using the `[columns]` layout for
+    /// the file's extension, e.g. "column 7: J, column 12 through 18:
+    /// 2024-01-02".
+    fn speak_columns(&mut self) {
+        let extension = self
+            .document
+            .file_name
+            .as_deref()
+            .and_then(|name| Path::new(name).extension())
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        let layout = match self.config_manager.get_column_layout(&extension) {
+            Some(layout) => layout.to_vec(),
+            None => {
+                self.status_message =
+                    StatusMessage::from(format!("No column layout for .{} files", extension));
+                self.play_noop_sound();
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    "No column layout for this file type.",
+                    "No layout.",
+                );
+                self.sound_manager.append(Box::new(utt));
+                return;
+            }
+        };
+        let default = &Row::from("");
+        let row = self
+            .document
+            .get_row(self.cursor_position.y)
+            .unwrap_or(default);
+        let readings = match columns::read_columns(row, &layout) {
+            Ok(readings) => readings,
+            Err(problem) => {
+                self.status_message = StatusMessage::from(problem.clone());
+                self.play_noop_sound();
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    &format!("{} Check the columns table in the config file.", problem),
+                    &problem,
+                );
+                self.sound_manager.append(Box::new(utt));
+                return;
+            }
+        };
+        let mut verbose = Vec::new();
+        let mut terse = Vec::new();
+        for reading in readings {
+            let text = if reading.text.trim().is_empty() {
+                String::from("blank")
+            } else {
+                string_to_speakable_tokens(&reading.text, &self.speech_options)
+            };
+            match reading.name {
+                Some(name) => {
+                    verbose.push(format!("{}, {}: {}", name, reading.label, text));
+                    terse.push(format!("{}: {}", name, text));
+                }
+                None => {
+                    verbose.push(format!("{}: {}", reading.label, text));
+                    terse.push(format!(
+                        "{}: {}",
+                        reading.label.trim_start_matches("column "),
+                        text
+                    ));
+                }
+            }
+        }
+        let utt = self.create_announcement(
+            AnnouncementClass::Navigation,
+            &verbose.join(", "),
+            &terse.join(", "),
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Play an earcon, panned to where the cursor is on its line.
+    fn play_earcon(&mut self, earcon: Earcon) {
+        self.update_column_pan();
+        self.sound_manager.play_earcon(earcon);
+    }
+
+    /// Pan later sounds by the cursor's column, from the left at the start
+    /// of the line to the right at its end, when column panning is on. An
+    /// empty line is heard in the middle.
+    fn update_column_pan(&mut self) {
+        let width = self.config_manager.get_column_panning();
+        let len = self.current_row_len();
+        let pan = if len == 0 {
+            0.0
+        } else {
+            self.cursor_position.x.min(len) as f32 / len as f32 * 2.0 - 1.0
+        };
+        self.sound_manager
+            .set_pan(pan * width, self.config_manager.get_column_panning_speech());
+    }
+
+    fn play_success_sound(&mut self) {
+        self.play_earcon(Earcon::MatchFound);
+    }
+
+    fn play_noop_sound(&mut self) {
+        self.play_earcon(Earcon::Error);
+    }
+
+    /// Replace matches of a query one at a time, speaking the line of each
+    /// match and asking whether to replace it.
+    fn replace(&mut self) -> Result<(), std::io::Error> {
+        if self.document.is_read_only() {
+            self.play_read_only_warning();
+            return Ok(());
+        }
+        let utt = self.create_announcement(AnnouncementClass::Prompts, "Replace.", "Replace");
+        self.sound_manager.append(Box::new(utt));
+        let query = match self.prompt("Replace: ", |_, _, _| {})? {
+            Some(query) => query,
+            None => return Ok(()),
+        };
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            &format!("Replace {} with:", query),
+            "With",
+        );
+        self.sound_manager.append(Box::new(utt));
+        let with =
+            match self.prompt_allowing_empty(&format!("Replace {} with: ", query), |_, _, _| {})? {
+                Some(with) => with,
+                None => return Ok(()),
+            };
+        let query_len = query.graphemes(true).count();
+        let with_len = with.graphemes(true).count();
+
+        let mut replace_all = false;
+        let mut matches = 0;
+        let mut replaced = 0;
+        let mut at = self.cursor_position.clone();
+        while let Some(position) = self.document.find(&query, &at, SearchDirection::Forward) {
+            matches += 1;
+            self.cursor_position = position.clone();
+            self.scroll();
+            let replace = if replace_all {
+                true
+            } else {
+                self.status_message =
+                    StatusMessage::from("Replace? (y)es (n)o (a)ll (q)uit".to_string());
+                self.refresh_screen()?;
+                self.sound_manager.silence();
+                self.speak_current_row();
+                let utt = self.create_announcement(
+                    AnnouncementClass::Prompts,
+                    &format!("Replace at column {}?", position.x + 1),
+                    "Replace?",
+                );
+                self.sound_manager.append(Box::new(utt));
+                match self.read_key()? {
+                    Key::Char('y' | 'Y') => true,
+                    Key::Char('n' | 'N') => false,
+                    Key::Char('a' | 'A') => {
+                        replace_all = true;
+                        true
+                    }
+                    _ => break,
+                }
+            };
+            at = if replace {
+                self.document.replace(&position, query_len, &with);
+                replaced += 1;
+                Position {
+                    x: position.x + with_len,
+                    y: position.y,
+                }
+            } else {
+                Position {
+                    x: position.x + query_len,
+                    y: position.y,
+                }
+            };
+        }
+
+        let message = if matches == 0 {
+            self.play_noop_sound();
+            format!("No matches for {}.", query)
+        } else {
+            format!(
+                "Replaced {} of {} match{}.",
+                replaced,
+                matches,
+                if matches == 1 { "" } else { "es" }
+            )
+        };
+        self.status_message = StatusMessage::from(message.clone());
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &message,
+            &format!("{} replaced.", replaced),
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        Ok(())
+    }
+
+    /// Ask for a line number, or `line:column`, move there and speak the
+    /// line. Numbers past the end of the file are refused out loud.
+    fn go_to_line(&mut self) -> Result<(), std::io::Error> {
+        let utt = self.create_announcement(AnnouncementClass::Prompts, "Go to line", "Line");
+        self.sound_manager.append(Box::new(utt));
+        let answer = match self.prompt("Go to line: ", |_, _, _| {})? {
+            Some(answer) => answer,
+            None => {
+                self.play_noop_sound();
+                return Ok(());
+            }
+        };
+        let (line, column) = match parse_line_target(&answer) {
+            Some(target) => target,
+            None => {
+                let message = format!("Not a line number: {}.", answer);
+                self.refuse_go_to_line(&message, "Not a number.");
+                return Ok(());
+            }
+        };
+        let line_count = self.document.row_count().max(1);
+        if line == 0 || line > line_count {
+            let message = format!("Line {} of {}.", line, line_count);
+            self.refuse_go_to_line(&message, &message);
+            return Ok(());
+        }
+        let y = line - 1;
+        let width = self.document.get_row(y).map_or(0, Row::len) + 1;
+        let x = match column {
+            None => 0,
+            Some(column) if column == 0 || column > width => {
+                let message = format!("Column {} of {}.", column, width);
+                self.refuse_go_to_line(&message, &message);
+                return Ok(());
+            }
+            Some(column) => column - 1,
+        };
+        self.cursor_position = Position { x, y };
+        self.scroll();
+        self.speak_current_row();
+        Ok(())
+    }
+
+    /// Say why a go-to-line answer can't be followed.
+    fn refuse_go_to_line(&mut self, verbose: &str, terse: &str) {
+        self.status_message = StatusMessage::from(verbose.to_string());
+        self.play_noop_sound();
+        let utt = self.create_announcement(AnnouncementClass::Errors, verbose, terse);
+        self.sound_manager.append(Box::new(utt));
+    }
+
+    fn search(&mut self) {
+        let old_position = self.cursor_position.clone();
+        let old_offset = self.offset.clone();
+
+        let utt = self.create_announcement(AnnouncementClass::Prompts, "Find.", "Find");
+        self.sound_manager.append(Box::new(utt));
+
+        let mut direction = SearchDirection::Forward;
+        let query = self
+            .prompt("Find: ", |editor, key, query| {
+                let mut moved = false;
+                match key {
+                    Key::Right | Key::Down | Key::Ctrl('f') => {
+                        direction = SearchDirection::Forward;
+                        editor.move_cursor(Key::Right, WrappingBehavior::Wrap);
+                        moved = true;
+                    }
+                    Key::Left | Key::Up | Key::Ctrl('b') => {
+                        direction = SearchDirection::Backward;
+                        editor.move_cursor(Key::Left, WrappingBehavior::Wrap);
+                        moved = true;
+                    }
+                    _ => (),
+                }
+                // Only the newest result is worth hearing.
+                editor.sound_manager.silence();
+                if query.is_empty() {
+                    return;
+                }
+                match editor.find_wrapping(query, direction) {
+                    Some((position, wrapped)) => {
+                        editor.cursor_position = position.clone();
+                        editor.scroll();
+                        editor.play_success_sound();
+                        if wrapped {
+                            let (verbose, terse) = match direction {
+                                SearchDirection::Forward => ("Wrapped to top.", "Top."),
+                                SearchDirection::Backward => ("Wrapped to bottom.", "Bottom."),
+                            };
+                            let utt = editor.create_announcement(
+                                AnnouncementClass::Navigation,
+                                verbose,
+                                terse,
+                            );
+                            editor.sound_manager.append(Box::new(utt));
+                        }
+                        editor.speak_match(&position, query);
+                    }
+                    None => {
+                        if moved {
+                            editor.move_cursor(Key::Left, WrappingBehavior::Wrap);
+                        }
+                        editor.play_noop_sound();
+                        let utt = editor.create_announcement(
+                            AnnouncementClass::Errors,
+                            &format!("No match for {}.", query),
+                            "No match.",
+                        );
+                        editor.sound_manager.append(Box::new(utt));
+                    }
+                }
+            })
+            .unwrap_or(None);
+        if query.is_none() {
+            // Put the view back exactly as it was, rather than wherever
+            // scrolling to the old position would leave it.
+            self.cursor_position = old_position;
+            self.offset = old_offset;
+            let line = self.cursor_position.y.saturating_add(1);
+            let column = self.cursor_position.x.saturating_add(1);
+            let verbose = format!(
+                "Search cancelled, returned to line {} column {}.",
+                line, column
+            );
+            self.status_message = StatusMessage::from(verbose.clone());
+            let utt = self.create_announcement(
+                AnnouncementClass::Prompts,
+                &verbose,
+                &format!("Cancelled, {} {}", line, column),
+            );
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return;
+        }
+        self.say_current_location();
+    }
+
+    /// Find every match for a query and step through them: n (or Down) goes
+    /// to the next, p (or Up) to the previous, each saying which match it is
+    /// and reading its line. Enter stays at the match, and Esc goes back to
+    /// where the search started.
+    fn find_all(&mut self) -> Result<(), std::io::Error> {
+        let old_position = self.cursor_position.clone();
+        let old_offset = self.offset.clone();
+        let utt = self.create_announcement(AnnouncementClass::Prompts, "Find all.", "Find all");
+        self.sound_manager.append(Box::new(utt));
+        let query = match self.prompt("Find all: ", |_, _, _| {})? {
+            Some(query) => query,
+            None => {
+                self.play_noop_sound();
+                return Ok(());
+            }
+        };
+        let matches = self.document.find_all(&query);
+        if matches.is_empty() {
+            self.play_noop_sound();
+            let utt = self.create_announcement(
+                AnnouncementClass::Errors,
+                &format!("No match for {}.", query),
+                "No match.",
+            );
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return Ok(());
+        }
+        // Start at the first match at or after the cursor.
+        let mut selected = matches
+            .iter()
+            .position(|at| (at.y, at.x) >= (old_position.y, old_position.x))
+            .unwrap_or(0);
+        let mut moved = true;
+        loop {
+            if moved {
+                let at = matches[selected].clone();
+                self.cursor_position = at.clone();
+                self.scroll();
+                let utt = self.create_announcement(
+                    AnnouncementClass::Navigation,
+                    &format!("Match {} of {}.", selected + 1, matches.len()),
+                    &format!("{} of {}", selected + 1, matches.len()),
+                );
+                self.sound_manager.silence();
+                self.sound_manager.append(Box::new(utt));
+                self.speak_match(&at, &query);
+            }
+            self.status_message = StatusMessage::from(format!(
+                "Find all: {} ({}/{}) n next, p previous, Enter to stay, Esc to go back",
+                query,
+                selected + 1,
+                matches.len()
+            ));
+            self.refresh_screen()?;
+            moved = false;
+            match self.read_key()? {
+                Key::Char('n') | Key::Down if selected + 1 < matches.len() => {
+                    selected += 1;
+                    moved = true;
+                }
+                Key::Char('p') | Key::Up if selected > 0 => {
+                    selected -= 1;
+                    moved = true;
+                }
+                Key::Char('n' | 'p') | Key::Down | Key::Up => {
+                    self.play_blocked_navigation_sound();
+                }
+                Key::Char('\n') => break,
+                Key::Esc => {
+                    self.cursor_position = old_position;
+                    self.offset = old_offset;
+                    self.status_message = StatusMessage::from(String::new());
+                    let line = self.cursor_position.y.saturating_add(1);
+                    let column = self.cursor_position.x.saturating_add(1);
+                    let utt = self.create_announcement(
+                        AnnouncementClass::Prompts,
+                        &format!("Returned to line {} column {}.", line, column),
+                        &format!("Back, {} {}", line, column),
+                    );
+                    self.sound_manager.interrupt_and_play(Box::new(utt));
+                    return Ok(());
+                }
+                _ => self.play_noop_sound(),
+            }
+        }
+        self.status_message = StatusMessage::from(String::new());
+        self.say_current_location();
+        Ok(())
+    }
+
+    /// Find the next match for a search, going round to the other end of the
+    /// document if there are no more in the direction of the search.
+    ///
+    /// # Returns
+    ///
+    /// The match, and whether the search went round to find it; or None if
+    /// there's no match anywhere.
+    ///
+    fn find_wrapping(&self, query: &str, direction: SearchDirection) -> Option<(Position, bool)> {
+        if let Some(position) = self.document.find(query, &self.cursor_position, direction) {
+            return Some((position, false));
+        }
+        let restart = match direction {
+            SearchDirection::Forward => Position::default(),
+            SearchDirection::Backward => {
+                let y = self.document.row_count().saturating_sub(1);
+                let x = self.document.get_row(y).map_or(0, Row::len);
+                Position { x, y }
+            }
+        };
+        self.document
+            .find(query, &restart, direction)
+            .map(|position| (position, true))
+    }
+
+    /// Read the line of a search match, with the match itself spoken at a
+    /// higher pitch, then where on the line it is: "Line 42: let count
+    /// equals zero, match at column 5."
+    fn speak_match(&mut self, at: &Position, query: &str) {
+        let row = match self.document.get_row(at.y) {
+            Some(row) => row.as_str().to_string(),
+            None => return,
+        };
+        let graphemes: Vec<&str> = row.graphemes(true).collect();
+        let end =
+            at.x.saturating_add(query.graphemes(true).count())
+                .min(graphemes.len());
+        let start = at.x.min(end);
+        let line = at.y.saturating_add(1);
+        let column = at.x.saturating_add(1);
+        let prefix = self
+            .verbosity
+            .format(
+                AnnouncementClass::Navigation,
+                &format!("Line {}:", line),
+                &format!("{}:", line),
+            )
+            .to_string();
+        let suffix = self
+            .verbosity
+            .format(
+                AnnouncementClass::Navigation,
+                &format!("match at column {}.", column),
+                &format!("column {}", column),
+            )
+            .to_string();
+        let utt = self.create_utterance(&prefix);
+        self.sound_manager.append(Box::new(utt));
+        for (part, emphasized) in [
+            (graphemes[..start].concat(), false),
+            (graphemes[start..end].concat(), true),
+            (graphemes[end..].concat(), false),
+        ] {
+            if part.trim().is_empty() {
+                continue;
+            }
+            let spoken = string_to_speakable_tokens(&part, &self.speech_options);
+            let utt = self.create_content_utterance(&spoken);
+            let utt = if emphasized {
+                let pitch = utt.pitch();
+                utt.with_parameters(&match_voice(pitch))
+            } else {
+                utt
+            };
+            self.sound_manager.append(Box::new(utt));
+        }
+        let utt = self.create_utterance(&suffix);
+        self.sound_manager.append(Box::new(utt));
+    }
+
+    fn prompt<C>(&mut self, prompt: &str, callback: C) -> Result<Option<String>, std::io::Error>
+    where
+        C: FnMut(&mut Self, Key, &String),
+    {
+        Ok(self
+            .prompt_allowing_empty(prompt, callback)?
+            .filter(|result| !result.is_empty()))
+    }
+
+    /// Like `prompt`, but an empty answer is returned as Some(""), and only
+    /// Esc gives None.
+    fn prompt_allowing_empty<C>(
+        &mut self,
+        prompt: &str,
+        mut callback: C,
+    ) -> Result<Option<String>, std::io::Error>
+    where
+        C: FnMut(&mut Self, Key, &String),
+    {
+        let mut result = String::new();
+        let mut cancelled = false;
+        loop {
+            self.status_message = StatusMessage::from(format!("{}{}", prompt, result));
+            self.refresh_screen()?;
+            let key = self.read_key()?;
+            match key {
+                Key::Backspace => result.truncate(result.len().saturating_sub(1)),
+                Key::Char('\n') => break,
+                Key::Char(c) => {
+                    if !c.is_control() {
+                        result.push(c);
+                    }
+                }
+                Key::Esc => {
+                    result.truncate(0);
+                    cancelled = true;
+                    break;
+                }
+                _ => (),
+            }
+            callback(self, key, &result);
+        }
+        self.status_message = StatusMessage::from(String::new());
+        if cancelled {
+            return Ok(None);
+        }
+        Ok(Some(result))
+    }
+
+    /// Ask for a secret, like a passphrase. What's typed is shown as
+    /// asterisks and never spoken.
+    ///
+    /// # Returns
+    ///
+    /// The secret, or None if the user pressed Esc.
+    ///
+    fn prompt_secret(&mut self, prompt: &str) -> Result<Option<String>, std::io::Error> {
+        let mut secret = String::new();
+        let answer = loop {
+            self.status_message =
+                StatusMessage::from(format!("{}{}", prompt, "*".repeat(secret.chars().count())));
+            self.refresh_screen()?;
+            match self.read_key()? {
+                Key::Backspace => {
+                    secret.pop();
+                }
+                Key::Char('\n') => break Some(secret),
+                Key::Char(c) if !c.is_control() => secret.push(c),
+                Key::Esc => break None,
+                _ => (),
+            }
+        };
+        self.status_message = StatusMessage::from(String::new());
+        Ok(answer)
+    }
+
+    /// Ask the user a yes/no question and wait for the answer.
+    ///
+    /// # Arguments
+    ///
+    /// * `question` - The question to speak and show in the message bar.
+    ///
+    /// # Returns
+    ///
+    /// Result<bool, std::io::Error> - Ok(true) if the user pressed `y`,
+    /// Ok(false) for `n` or Esc.
+    ///
+    fn confirm(&mut self, question: &str) -> Result<bool, std::io::Error> {
+        self.status_message = StatusMessage::from(format!("{} (y/n)", question));
+        self.refresh_screen()?;
+        let utt = self.create_utterance(format!("{} Y or N.", question).as_str());
+        self.sound_manager.append(Box::new(utt));
+        let answer = loop {
+            match self.read_key()? {
+                Key::Char('y' | 'Y') => break true,
+                Key::Char('n' | 'N') | Key::Esc => break false,
+                _ => (),
+            }
+        };
+        self.status_message = StatusMessage::from(String::new());
+        Ok(answer)
+    }
+
+    /// Read back a path the user typed, spelled out in full, and wait for
+    /// them to confirm it. A mistyped character is easy to miss while
+    /// typing, so nothing is written until the whole path has been heard.
+    ///
+    /// # Returns
+    ///
+    /// Result<bool, std::io::Error> - Ok(true) if the user pressed Enter or
+    /// `y`, Ok(false) for `n` or Esc.
+    ///
+    fn confirm_path(&mut self, path: &str) -> Result<bool, std::io::Error> {
+        self.status_message =
+            StatusMessage::from(format!("Save to {}? (Enter to save, Esc to cancel)", path));
+        self.refresh_screen()?;
+        let options = SpeechOptions {
+            links: LinkReadingMode::Full,
+            punctuation: PunctuationLevel::All,
+            ..self.speech_options.clone()
+        };
+        let spoken = string_to_speakable_tokens(path, &options);
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            &format!("Save to {}? Enter to save, escape to cancel.", spoken),
+            &format!("Save to {}?", spoken),
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        let answer = loop {
+            match self.read_key()? {
+                Key::Char('\n' | 'y' | 'Y') => break true,
+                Key::Char('n' | 'N') | Key::Esc => break false,
+                _ => (),
+            }
+        };
+        self.status_message = StatusMessage::from(String::new());
+        Ok(answer)
+    }
+
+    /// Warn the user if the document is already open in another session.
+    ///
+    /// The user is offered read-only mode so that the two sessions can't
+    /// silently overwrite each other's changes.
+    ///
+    fn warn_if_locked(&mut self) -> Result<(), std::io::Error> {
+        let pid = match self.document.locked_by() {
+            Some(pid) if !self.document.is_read_only() => pid,
+            _ => return Ok(()),
+        };
+        self.play_blocked_navigation_sound();
+        let read_only = self.confirm(
+            format!(
+                "This file is already open in another clack session, process {}. Open read only?",
+                pid
+            )
+            .as_str(),
+        )?;
+        self.document.set_read_only(read_only);
+        let message = if read_only {
+            "Opened read only."
+        } else {
+            "Editing anyway. Changes may conflict."
+        };
+        self.status_message = StatusMessage::from(message.to_string());
+        let utt = self.create_utterance(message);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        Ok(())
+    }
+
+    /// Open the document read only if the user doesn't have permission to
+    /// write it, so that changes aren't made that can't be saved.
+    fn warn_if_unwritable(&mut self) {
+        if self.document.is_read_only() || self.document.is_writable() {
+            return;
+        }
+        self.document.set_read_only(true);
+        let name = self
+            .document
+            .file_name
+            .as_deref()
+            .map_or_else(String::new, short_file_name);
+        self.play_blocked_navigation_sound();
+        self.status_message = StatusMessage::from(format!(
+            "No permission to write {}. Opened read only; toggle read only from the command palette to edit anyway.",
+            name
+        ));
+        let verbose = format!(
+            "You don't have permission to write {}, so it's open read only. Run toggle read only from the command palette to edit it anyway.",
+            name
+        );
+        let utt = self.create_announcement(AnnouncementClass::Errors, &verbose, "Read only.");
+        self.sound_manager.append(Box::new(utt));
+    }
+
+    /// Point out a file with Windows line endings when it's opened. They're
+    /// kept when it's saved.
+    fn announce_line_ending(&mut self) {
+        if self.document.line_ending() != LineEnding::Windows {
+            return;
+        }
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            "This file uses Windows line endings.",
+            "Windows line endings.",
+        );
+        self.sound_manager.append(Box::new(utt));
+    }
+
+    /// Turn read-only mode off, to edit a file anyway, or back on.
+    fn toggle_read_only(&mut self) {
+        let read_only = !self.document.is_read_only();
+        self.document.set_read_only(read_only);
+        let (verbose, terse) = if read_only {
+            ("Read only.", "Read only.")
+        } else if !self.document.is_writable() {
+            (
+                "Editing anyway. You don't have permission to write this file, so saving it will fail.",
+                "Editing, can't save.",
+            )
+        } else if self.document.locked_by().is_some() {
+            ("Editing anyway. Changes may conflict.", "Editing.")
+        } else {
+            ("Editing.", "Editing.")
+        };
+        self.status_message = StatusMessage::from(verbose.to_string());
+        let utt = self.create_announcement(AnnouncementClass::Prompts, verbose, terse);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Copy the active document to its crash-recovery file, if it has
+    /// unsaved changes and the last copy is old enough.
+    fn write_swap_if_due(&mut self) {
+        if !self.document.is_dirty() || self.last_swap_write.elapsed() < SWAP_INTERVAL {
+            return;
+        }
+        // A recovery file is a safety net; failing to write one shouldn't
+        // get in the way of editing.
+        let _ = self.document.write_swap();
+        self.last_swap_write = Instant::now();
+    }
+
+    /// Offer to recover the active document's unsaved changes from a
+    /// session that ended without saving, if it left any.
+    fn offer_recovery(&mut self) -> Result<(), std::io::Error> {
+        let text = match self.document.recoverable_text() {
+            Some(text) => text,
+            None => return Ok(()),
+        };
+        let name = self
+            .document
+            .file_name
+            .as_deref()
+            .map_or_else(String::new, short_file_name);
+        self.play_noop_sound();
+        let recover = self.confirm(
+            format!(
+                "{} has unsaved changes from a session that ended without saving. Recover them?",
+                name
+            )
+            .as_str(),
+        )?;
+        let message = if recover {
+            self.document.recover(&text);
+            self.cursor_position = Position::default();
+            self.offset = Position::default();
+            "Recovered. Save to keep the changes."
+        } else {
+            self.document.remove_swap();
+            "Unsaved changes discarded."
+        };
+        self.status_message = StatusMessage::from(message.to_string());
+        let utt = self.create_utterance(message);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        Ok(())
+    }
+
+    /// Make the changes the document's last session left unsaved, from its
+    /// journal, for when that session crashed.
+    fn replay_journal(&mut self) {
+        let name = self
+            .document
+            .file_name
+            .as_deref()
+            .map_or_else(String::new, short_file_name);
+        let count = self.document.replay_previous_session();
+        let message = if count == 0 {
+            self.play_noop_sound();
+            format!("No unsaved changes to replay in {}.", name)
+        } else {
+            self.cursor_position = Position::default();
+            self.offset = Position::default();
+            format!(
+                "Replayed {} unsaved changes in {}. Save to keep them.",
+                count, name
+            )
+        };
+        self.status_message = StatusMessage::from(message.clone());
+        let utt = self.create_utterance(&message);
+        self.sound_manager.append(Box::new(utt));
+    }
+
+    /// Get the part of the word before the cursor.
+    fn word_before_cursor(&self) -> String {
+        let Position { x, y } = self.cursor_position;
+        self.document
+            .get_row(y)
+            .and_then(|row| {
+                row.words()
+                    .into_iter()
+                    .find(|(start, word)| *start < x && x <= start + word.graphemes(true).count())
+                    .map(|(start, word)| word.graphemes(true).take(x - start).collect())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Complete the word before the cursor with a word used elsewhere in the
+    /// document. The best candidate is spoken first, with how many others
+    /// there are; Down and Up browse them, and Enter or Tab accepts one.
+    fn complete_word(&mut self) -> Result<(), std::io::Error> {
+        if self.document.is_read_only() {
+            self.play_read_only_warning();
+            return Ok(());
+        }
+        let prefix = self.word_before_cursor();
+        let candidates = if prefix.is_empty() {
+            Vec::new()
+        } else {
+            let others: Vec<&Document> = (0..self.buffers.len())
+                .filter(|&index| index != self.active_buffer)
+                .map(|index| self.buffer_document(index))
+                .collect();
+            completion::ranked_completions(&self.document, &self.cursor_position, &prefix, &others)
+        };
+        if candidates.is_empty() {
+            self.play_noop_sound();
+            let utt =
+                self.create_announcement(AnnouncementClass::Errors, "No completions.", "None.");
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return Ok(());
+        }
+        let top = string_to_speakable_tokens(&candidates[0], &self.speech_options);
+        let verbose = match candidates.len() {
+            1 => format!("{}. Enter to accept.", top),
+            count => format!(
+                "Candidate 1 of {}: {}. Enter to accept, down for the next, escape to cancel.",
+                count, top
+            ),
+        };
+        let terse = match candidates.len() {
+            1 => top.clone(),
+            count => format!("{}, and {} more", top, count - 1),
+        };
+        let utt = self.create_announcement(AnnouncementClass::Prompts, &verbose, &terse);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+
+        let mut selected = 0;
         loop {
-            self.status_message = StatusMessage::from(format!("{}{}", prompt, result));
+            self.status_message = StatusMessage::from(format!(
+                "Complete {}/{}: {}",
+                selected + 1,
+                candidates.len(),
+                candidates[selected]
+            ));
             self.refresh_screen()?;
-            let key = Terminal::read_key()?;
-            match key {
-                Key::Backspace => result.truncate(result.len().saturating_sub(1)),
-                Key::Char('\n') => break,
-                Key::Char(c) => {
-                    if !c.is_control() {
-                        result.push(c);
-                    }
+            let previous = selected;
+            match self.read_key()? {
+                Key::Down | Key::Ctrl('n') if selected + 1 < candidates.len() => selected += 1,
+                Key::Up | Key::Ctrl('p') if selected > 0 => selected -= 1,
+                Key::Down | Key::Up | Key::Ctrl('n' | 'p') => self.play_blocked_navigation_sound(),
+                Key::Char('\n' | '\t') => break,
+                Key::Esc => {
+                    self.status_message = StatusMessage::from(String::new());
+                    self.play_noop_sound();
+                    return Ok(());
                 }
+                _ => (),
+            }
+            if selected != previous {
+                let spoken =
+                    string_to_speakable_tokens(&candidates[selected], &self.speech_options);
+                let utt = self.create_announcement(
+                    AnnouncementClass::Prompts,
+                    &format!(
+                        "Candidate {} of {}: {}",
+                        selected + 1,
+                        candidates.len(),
+                        spoken
+                    ),
+                    &format!("{} of {}: {}", selected + 1, candidates.len(), spoken),
+                );
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+            }
+        }
+        let word = &candidates[selected];
+        self.cursor_position = self
+            .document
+            .insert_text(&self.cursor_position, &word[prefix.len()..]);
+        self.status_message = StatusMessage::from(format!("Completed {}.", word));
+        let spoken = string_to_speakable_tokens(word, &self.speech_options);
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &format!("Completed {}.", spoken),
+            &spoken,
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        Ok(())
+    }
+
+    /// Read a two-character digraph and insert the character it composes.
+    ///
+    /// Esc at either step cancels without inserting anything.
+    ///
+    fn insert_digraph(&mut self) -> Result<(), std::io::Error> {
+        if self.document.is_read_only() {
+            self.play_read_only_warning();
+            return Ok(());
+        }
+        let utt = self.create_announcement(AnnouncementClass::Prompts, "Digraph.", "Dig");
+        self.sound_manager.append(Box::new(utt));
+        let mut pair = Vec::new();
+        while pair.len() < 2 {
+            self.status_message =
+                StatusMessage::from(format!("Digraph: {}", pair.iter().collect::<String>()));
+            self.refresh_screen()?;
+            match self.read_key()? {
+                Key::Char(c) if !c.is_control() => pair.push(c),
                 Key::Esc => {
-                    result.truncate(0);
-                    break;
+                    self.status_message = StatusMessage::from(String::new());
+                    self.play_noop_sound();
+                    return Ok(());
                 }
                 _ => (),
             }
-            callback(self, key, &result);
         }
         self.status_message = StatusMessage::from(String::new());
-        if result.is_empty() {
-            return Ok(None);
+        match digraph::lookup(pair[0], pair[1]) {
+            Some(composed) => {
+                self.document.insert(&self.cursor_position, composed);
+                self.move_cursor(Key::Right, WrappingBehavior::Wrap);
+                let spoken =
+                    string_to_speakable_tokens(&composed.to_string(), &self.speech_options);
+                let utt = self.create_utterance(&spoken);
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+            }
+            None => {
+                self.play_noop_sound();
+                let spoken = string_to_speakable_tokens(
+                    &format!("{} {}", pair[0], pair[1]),
+                    &self.speech_options,
+                );
+                let utt = self.create_utterance(format!("No digraph for {}.", spoken).as_str());
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+            }
         }
-        Ok(Some(result))
+        Ok(())
+    }
+
+    /// Reload the document from disk and speak what changed.
+    ///
+    /// The user is offered a jump to the first change afterwards.
+    ///
+    fn reload(&mut self) -> Result<(), std::io::Error> {
+        if self.document.file_name.is_none() {
+            self.play_noop_sound();
+            let utt = self.create_utterance("Nothing to reload.");
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return Ok(());
+        }
+        if self.document.is_dirty() && !self.confirm("Discard unsaved changes and reload?")? {
+            let utt = self.create_utterance("Reload cancelled.");
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return Ok(());
+        }
+        let hunks = match self.document.reload() {
+            Ok(hunks) => hunks,
+            Err(_) => {
+                self.status_message = StatusMessage::from("Error reading file!".to_string());
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    "Error reading file!",
+                    "Read error.",
+                );
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return Ok(());
+            }
+        };
+        // Keep the cursor on the line it was on, wherever that is now.
+        self.cursor_position.y =
+            map_line(&hunks, self.cursor_position.y).min(self.document.row_count());
+        self.move_cursor(Key::Null, WrappingBehavior::Default);
+        let first = match hunks.first() {
+            Some(hunk) => *hunk,
+            None => {
+                self.status_message = StatusMessage::from("Reloaded, no changes.".to_string());
+                let utt = self.create_utterance("Reloaded. No changes on disk.");
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return Ok(());
+            }
+        };
+
+        let mut summary = hunks
+            .iter()
+            .take(MAX_SPOKEN_HUNKS)
+            .map(Hunk::describe)
+            .collect::<Vec<String>>()
+            .join(", ");
+        if hunks.len() > MAX_SPOKEN_HUNKS {
+            summary = format!(
+                "{}, and {} more changes",
+                summary,
+                hunks.len() - MAX_SPOKEN_HUNKS
+            );
+        }
+        self.status_message = StatusMessage::from(format!("Reloaded: {}", summary));
+        let utt = self.create_utterance(format!("Reloaded. {}.", summary).as_str());
+        self.sound_manager.append(Box::new(utt));
+
+        if self.confirm("Jump to the first change?")? {
+            self.cursor_position = Position {
+                x: 0,
+                y: first.new_start,
+            };
+            self.move_cursor(Key::Null, WrappingBehavior::Default);
+            self.scroll();
+            self.speak_current_row();
+        }
+        Ok(())
+    }
+
+    /// Get the current file name, or tell the user that notes need one.
+    fn file_name_for_notes(&mut self) -> Option<String> {
+        if self.document.file_name.is_none() {
+            self.play_noop_sound();
+            let utt = self.create_utterance("Save the file before adding notes.");
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+        }
+        self.document.file_name.clone()
+    }
+
+    /// Prompt for a text note on the current line.
+    ///
+    /// An empty note removes the line's existing note.
+    ///
+    fn edit_text_note(&mut self) -> Result<(), std::io::Error> {
+        if self.file_name_for_notes().is_none() {
+            return Ok(());
+        }
+        let y = self.cursor_position.y;
+        let utt = self.create_announcement(AnnouncementClass::Prompts, "Note:", "Note");
+        self.sound_manager.append(Box::new(utt));
+        let message = match self.prompt("Note: ", |_, _, _| {})? {
+            Some(text) => {
+                self.document.set_note(y, Note::Text(text))?;
+                "Note added."
+            }
+            None => {
+                if self.document.remove_note(y)? {
+                    "Note removed."
+                } else {
+                    "No note added."
+                }
+            }
+        };
+        self.status_message = StatusMessage::from(message.to_string());
+        let utt = self.create_utterance(message);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        Ok(())
+    }
+
+    /// Run a shell command, like `cargo check` or `git status`, and open what
+    /// it prints (its output and errors, in the order they came) in a new
+    /// buffer to be read with the usual commands. Says whether it succeeded,
+    /// how much it printed, and reads the first line.
+    fn run_shell_command(&mut self) -> Result<(), std::io::Error> {
+        let utt = self.create_announcement(AnnouncementClass::Prompts, "Shell command.", "Shell");
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        let command_line = match self.prompt("Shell: ", |_, _, _| {})? {
+            Some(command_line) => command_line,
+            None => {
+                self.play_noop_sound();
+                return Ok(());
+            }
+        };
+        self.status_message =
+            StatusMessage::from(format!("Running: {} (any key to stop)", command_line));
+        self.refresh_screen()?;
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            &format!("Running {}.", command_line),
+            "Running.",
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        let output = match run_in_shell(&command_line) {
+            Ok(Some(output)) => output,
+            Ok(None) => {
+                self.announce_stopped_command();
+                return Ok(());
+            }
+            Err(error) => {
+                let message = format!("Couldn't run {}: {}.", command_line, error);
+                self.status_message = StatusMessage::from(message.clone());
+                self.play_noop_sound();
+                let utt =
+                    self.create_announcement(AnnouncementClass::Errors, &message, "Couldn't run.");
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return Ok(());
+            }
+        };
+        let (text, notifications) =
+            notifications::extract(&String::from_utf8_lossy(&output.stdout));
+        let (verbose, terse) = match output.status.code() {
+            Some(0) => (String::from("Finished"), String::from("Done")),
+            Some(code) => (
+                format!("Failed with status {}", code),
+                format!("Failed, {}", code),
+            ),
+            None => (String::from("Stopped"), String::from("Stopped")),
+        };
+        if output.status.success() {
+            self.play_success_sound();
+        } else {
+            self.play_noop_sound();
+        }
+        if text.trim().is_empty() {
+            self.status_message = StatusMessage::from(format!("{}: no output", verbose));
+            let utt = self.create_announcement(
+                AnnouncementClass::Prompts,
+                &format!("{}, with no output.", verbose),
+                &format!("{}, nothing", terse),
+            );
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            self.announce_notifications(&notifications);
+            return Ok(());
+        }
+        // Locations in the output, like grep's, can be gone to like errors.
+        self.set_diagnostics(quickfix::parse(&text));
+        self.show_in_buffer(Document::from_text(&text));
+        let lines = self.document.row_count();
+        let plural = if lines == 1 { "" } else { "s" };
+        self.status_message =
+            StatusMessage::from(format!("{}: {} line{} of output", verbose, lines, plural));
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            &format!("{}, {} line{} of output.", verbose, lines, plural),
+            &format!("{}, {}", terse, lines),
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        self.announce_notifications(&notifications);
+        self.speak_current_row();
+        Ok(())
+    }
+
+    /// Run the build command from the config file and list the errors and
+    /// warnings it prints, saying how many there are. A failed build with
+    /// nothing to list has its output opened in a new buffer instead, to be
+    /// read like a shell command's.
+    fn run_build(&mut self) -> Result<(), std::io::Error> {
+        let command_line = self.config_manager.get_build_command();
+        self.status_message =
+            StatusMessage::from(format!("Building: {} (any key to stop)", command_line));
+        self.refresh_screen()?;
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            &format!("Building with {}.", command_line),
+            "Building.",
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        let output = match run_in_shell(&command_line) {
+            Ok(Some(output)) => output,
+            Ok(None) => {
+                self.announce_stopped_command();
+                return Ok(());
+            }
+            Err(error) => {
+                let message = format!("Couldn't run {}: {}.", command_line, error);
+                self.status_message = StatusMessage::from(message.clone());
+                self.play_noop_sound();
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    &message,
+                    "Couldn't build.",
+                );
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return Ok(());
+            }
+        };
+        let (text, notifications) =
+            notifications::extract(&String::from_utf8_lossy(&output.stdout));
+        let diagnostics = quickfix::parse(&text);
+        let count = |kind: &str| {
+            diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.message.starts_with(kind))
+                .count()
+        };
+        let (errors, warnings) = (count("error"), count("warning"));
+        let show_output =
+            !output.status.success() && diagnostics.is_empty() && !text.trim().is_empty();
+        let (verbose, terse) = match (output.status.success(), diagnostics.len()) {
+            (true, 0) => (String::from("Build succeeded."), String::from("Built.")),
+            (false, 0) if show_output => (
+                String::from("Build failed, with no locations to go to. Its output is open."),
+                String::from("Failed."),
+            ),
+            (false, 0) => (
+                String::from("Build failed, with no output."),
+                String::from("Failed."),
+            ),
+            (succeeded, total) => (
+                format!(
+                    "Build {}: {} error{}, {} warning{}{}.",
+                    if succeeded { "succeeded" } else { "failed" },
+                    errors,
+                    if errors == 1 { "" } else { "s" },
+                    warnings,
+                    if warnings == 1 { "" } else { "s" },
+                    match total - errors - warnings {
+                        0 => String::new(),
+                        other => format!(", {} other", other),
+                    }
+                ),
+                format!("{} {}", errors, warnings),
+            ),
+        };
+        if output.status.success() {
+            self.play_success_sound();
+        } else {
+            self.play_noop_sound();
+        }
+        self.set_diagnostics(diagnostics);
+        if show_output {
+            self.show_in_buffer(Document::from_text(&text));
+        }
+        self.status_message = StatusMessage::from(verbose.clone());
+        let utt = self.create_announcement(AnnouncementClass::Prompts, &verbose, &terse);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        self.announce_notifications(&notifications);
+        if show_output {
+            self.speak_current_row();
+        }
+        Ok(())
+    }
+
+    /// Play the bell earcon for the bells and notifications in a command's
+    /// output, rather than leave them to the terminal, and read out what
+    /// the notifications say unless `speak_notifications` is off.
+    fn announce_notifications(&mut self, notifications: &Notifications) {
+        if notifications.is_empty() {
+            return;
+        }
+        self.play_earcon(Earcon::Bell);
+        if !self.config_manager.get_speak_notifications() {
+            return;
+        }
+        for message in &notifications.messages {
+            let utt = self.create_announcement(
+                AnnouncementClass::Prompts,
+                &format!("Notification: {}", message),
+                message,
+            );
+            self.sound_manager.append(Box::new(utt));
+        }
+    }
+
+    /// Say that a shell command was stopped by a keypress before it finished.
+    fn announce_stopped_command(&mut self) {
+        self.status_message = StatusMessage::from(String::from("Stopped."));
+        self.play_noop_sound();
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            "Stopped before it finished.",
+            "Stopped.",
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Replace the list of diagnostics stepped through with next and
+    /// previous error, starting again before the first.
+    fn set_diagnostics(&mut self, diagnostics: Vec<Diagnostic>) {
+        self.diagnostics = diagnostics;
+        self.diagnostic_index = None;
+    }
+
+    /// Go to the next or previous diagnostic from the last build or shell
+    /// command, opening its file if it isn't open, and say what it is
+    /// ("Error 2 of 5, main.rs line 12: ...") before reading its line.
+    fn step_through_diagnostics(
+        &mut self,
+        direction: SearchDirection,
+    ) -> Result<(), std::io::Error> {
+        if self.diagnostics.is_empty() {
+            self.play_noop_sound();
+            let utt = self.create_announcement(
+                AnnouncementClass::Errors,
+                "No errors listed. Alt+Shift+R builds.",
+                "No errors.",
+            );
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return Ok(());
+        }
+        let last = self.diagnostics.len() - 1;
+        let index = match (self.diagnostic_index, direction) {
+            (None, SearchDirection::Forward) => 0,
+            (None, SearchDirection::Backward) => last,
+            (Some(index), SearchDirection::Forward) if index < last => index + 1,
+            (Some(index), SearchDirection::Backward) if index > 0 => index - 1,
+            (Some(_), _) => {
+                self.play_blocked_navigation_sound();
+                let (verbose, terse) = match direction {
+                    SearchDirection::Forward => ("No more errors.", "Last."),
+                    SearchDirection::Backward => ("No earlier errors.", "First."),
+                };
+                let utt = self.create_announcement(AnnouncementClass::Navigation, verbose, terse);
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return Ok(());
+            }
+        };
+        self.diagnostic_index = Some(index);
+        let file_name = self.diagnostics[index].file_name.clone();
+        match self.open_buffer(&file_name) {
+            Ok(true) => (),
+            Ok(false) => return Ok(()),
+            Err(error) => {
+                self.announce_open_error(&file_name, &error);
+                return Ok(());
+            }
+        }
+        let diagnostic = &self.diagnostics[index];
+        let (line, column) = (diagnostic.line, diagnostic.column);
+        let verbose = format!(
+            "{} of {}, {} line {}: {}",
+            index + 1,
+            self.diagnostics.len(),
+            short_file_name(&file_name),
+            line,
+            diagnostic.message
+        );
+        let terse = format!("{} {}: {}", index + 1, line, diagnostic.message);
+        self.status_message = StatusMessage::from(verbose.clone());
+        self.place_cursor(line, column);
+        let utt = self.create_announcement(AnnouncementClass::Navigation, &verbose, &terse);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        self.speak_current_row();
+        Ok(())
+    }
+
+    /// Record a voice memo for the current line.
+    ///
+    /// Recording uses the configured external recorder and stops on the next
+    /// keypress.
+    ///
+    fn record_voice_memo(&mut self) -> Result<(), std::io::Error> {
+        let file_name = match self.file_name_for_notes() {
+            Some(file_name) => file_name,
+            None => return Ok(()),
+        };
+        let y = self.cursor_position.y;
+        let memo_path = notes::new_memo_path_for(&file_name)?;
+        // Split the template before filling in the path, so that a path
+        // with spaces in it stays one argument.
+        let memo_path_text = memo_path.to_string_lossy();
+        let command_line = self.config_manager.get_memo_record_command();
+        let mut parts = command_line
+            .split_whitespace()
+            .map(|part| part.replace("{file}", &memo_path_text));
+        let program = parts.next().unwrap_or_default();
+
+        let utt = self.create_utterance("Recording. Press any key to stop.");
+        self.sound_manager.append(Box::new(utt));
+        self.play_earcon(Earcon::Recording);
+        // Don't record our own prompt.
+        self.sound_manager.wait_until_idle();
+        let child = Command::new(&program).args(parts).spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(_) => {
+                self.status_message =
+                    StatusMessage::from(format!("Could not start recorder: {}", program));
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    "Could not start the recorder.",
+                    "Recorder error.",
+                );
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return Ok(());
+            }
+        };
+        self.status_message = StatusMessage::from("Recording... (any key to stop)".to_string());
+        self.refresh_screen()?;
+        self.read_key()?;
+        // Interrupt rather than kill, so the recorder finishes the file.
+        let interrupted = Command::new("kill")
+            .arg("-INT")
+            .arg(child.id().to_string())
+            .status()
+            .is_ok_and(|status| status.success());
+        if !interrupted {
+            child.kill()?;
+        }
+        child.wait()?;
+
+        self.document.set_note(y, Note::Audio(memo_path))?;
+        self.status_message = StatusMessage::from("Memo recorded.".to_string());
+        let utt = self.create_utterance("Memo recorded.");
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        Ok(())
+    }
+
+    /// Play the note attached to the current line.
+    fn play_note(&mut self) {
+        match self.document.note_at(self.cursor_position.y).cloned() {
+            Some(Note::Text(text)) => {
+                let utt = self.create_utterance(&text);
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+            }
+            Some(Note::Audio(path)) => {
+                self.sound_manager
+                    .interrupt_and_play(Box::new(AudioClip::new(path)));
+            }
+            None => {
+                self.play_noop_sound();
+                let utt = self.create_utterance("No note on this line.");
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+            }
+        }
+    }
+
+    /// Play a soft two-note glide when the view scrolls sideways to follow
+    /// the cursor, so the user knows that anyone watching the screen can no
+    /// longer see the start of the line: rising on the way out, falling on
+    /// the way back.
+    fn play_horizontal_scroll_earcon(&mut self, scrolled_right: bool) {
+        self.play_earcon(if scrolled_right {
+            Earcon::ScrollRight
+        } else {
+            Earcon::ScrollLeft
+        });
+    }
+
+    fn play_note_earcon(&mut self) {
+        self.play_earcon(Earcon::Note);
+    }
+
+    fn play_read_only_warning(&mut self) {
+        self.play_blocked_navigation_sound();
+        let utt = self.create_announcement(AnnouncementClass::Errors, "Read only.", "Read only");
+        self.sound_manager.interrupt_and_play(Box::new(utt));
     }
 
     fn save(&mut self) {
+        if self.document.is_read_only() {
+            self.status_message = StatusMessage::from("Read only. Not saved.".to_string());
+            self.play_read_only_warning();
+            return;
+        }
         if self.document.file_name.is_none() {
-            let utt = self.create_utterance("Save as ");
-            self.sound_manager.play_and_wait(Box::new(utt));
-            let new_name = self.prompt("Save as: ", |_, _, _| {}).unwrap_or(None);
+            let utt = self.create_announcement(AnnouncementClass::Prompts, "Save as ", "Save as");
+            self.sound_manager.append(Box::new(utt));
+            let new_name = self
+                .prompt("Save as: ", |_, _, _| {})
+                .unwrap_or(None)
+                .filter(|name| self.confirm_path(name).unwrap_or(false));
             if new_name.is_none() {
                 self.status_message = StatusMessage::from("Save aborted.".to_string());
-                let utt = self.create_utterance("Save aborted.");
+                let utt = self.create_announcement(
+                    AnnouncementClass::Editing,
+                    "Save aborted.",
+                    "Aborted.",
+                );
                 self.sound_manager.interrupt_and_play(Box::new(utt));
                 return;
             }
             self.document.file_name = new_name;
         }
+        if let Some(file_name) = self.document.file_name.clone() {
+            if let (None, Some(scheme)) = (self.document.encryption(), Scheme::for_file(&file_name))
+            {
+                match self.ask_for_new_encryption(&file_name, scheme) {
+                    Ok(Some(encryption)) => self.document.set_encryption(encryption),
+                    _ => {
+                        self.status_message = StatusMessage::from("Save aborted.".to_string());
+                        let utt = self.create_announcement(
+                            AnnouncementClass::Editing,
+                            "Not encrypted, so not saved.",
+                            "Aborted.",
+                        );
+                        self.sound_manager.interrupt_and_play(Box::new(utt));
+                        return;
+                    }
+                }
+            }
+        }
 
-        if self.document.save().is_ok() {
-            let utt = self.create_utterance("Saved. ");
-            self.sound_manager.interrupt_and_play(Box::new(utt));
-
-            self.status_message = StatusMessage::from("File saved successfully.".to_string());
-            let utt = self.create_utterance(
-                format!("Saved {}.", self.document.file_name.as_ref().unwrap()).as_str(),
+        // Two writes to one file at once could interleave.
+        let file_name = self.document.file_name.clone();
+        if self
+            .pending_saves
+            .iter()
+            .any(|save| Some(&save.file_name) == file_name.as_ref())
+        {
+            self.status_message = StatusMessage::from("Still saving.".to_string());
+            self.play_noop_sound();
+            let utt = self.create_announcement(
+                AnnouncementClass::Errors,
+                "Still saving the last change. Try again in a moment.",
+                "Still saving.",
             );
             self.sound_manager.interrupt_and_play(Box::new(utt));
+            return;
+        }
+        match self
+            .document
+            .start_save(self.config_manager.get_keep_backups())
+        {
+            Ok(save) => {
+                self.flush_editing_time();
+                let name = short_file_name(&save.file_name);
+                self.pending_saves.push(save);
+                if !self.finish_pending_saves(QUICK_SAVE_WAIT) {
+                    self.status_message = StatusMessage::from(format!("Saving {}...", name));
+                    let verbose = format!("Saving {} in the background.", name);
+                    let utt =
+                        self.create_announcement(AnnouncementClass::Editing, &verbose, "Saving.");
+                    self.sound_manager.interrupt_and_play(Box::new(utt));
+                }
+            }
+            Err(_) => {
+                self.status_message = StatusMessage::from("Error writing file!".to_string());
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    "Error writing file!",
+                    "Write error.",
+                );
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+            }
+        }
+    }
+
+    /// Finish any background saves whose writes are done, waiting up to
+    /// `timeout` for each, and announce how they went.
+    ///
+    /// # Returns
+    ///
+    /// Whether any save finished.
+    ///
+    fn finish_pending_saves(&mut self, timeout: Duration) -> bool {
+        let mut finished = false;
+        for save in std::mem::take(&mut self.pending_saves) {
+            match save.wait(timeout) {
+                Some(result) => {
+                    self.finish_save(&save, result);
+                    finished = true;
+                }
+                None => self.pending_saves.push(save),
+            }
+        }
+        finished
+    }
+
+    /// Update the saved document, which may no longer be the active one,
+    /// and announce a save's result with the file's name.
+    fn finish_save(&mut self, save: &PendingSave, result: Result<(), std::io::Error>) {
+        let result = result.and_then(|()| match self.buffer_index_for(&save.file_name) {
+            Some(index) if index == self.active_buffer => self.document.finish_save(save),
+            Some(index) => self.buffers[index].document.finish_save(save),
+            None => Ok(()),
+        });
+        let name = short_file_name(&save.file_name);
+        match result {
+            Ok(()) => {
+                self.status_message = StatusMessage::from(format!("Saved {}.", name));
+                self.play_earcon(Earcon::Saved);
+                let verbose = format!("Saved {}.", save.file_name);
+                let utt = self.create_announcement(AnnouncementClass::Editing, &verbose, "Saved.");
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+            }
+            Err(error) => {
+                self.status_message =
+                    StatusMessage::from(format!("Error writing {}: {}", name, error));
+                self.play_blocked_navigation_sound();
+                let verbose = format!("Couldn't save {}: {}.", name, error);
+                let terse = format!("{} not saved.", name);
+                let utt = self.create_announcement(AnnouncementClass::Errors, &verbose, &terse);
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+            }
+        }
+    }
+
+    fn scroll(&mut self) {
+        if self.soft_wrap {
+            self.scroll_wrapped();
+            return;
+        }
+        let y = self.cursor_position.y;
+        let x = self.cursor_column();
+        let width = self.wrap_width();
+        let height = self.terminal.size().height as usize;
+        let mut offset = &mut self.offset;
+        if y < offset.y {
+            offset.y = y;
+        } else if y >= offset.y.saturating_add(height) {
+            offset.y = y.saturating_sub(height).saturating_add(1);
+        }
+        if x < offset.x {
+            offset.x = x;
+        } else if x >= offset.x.saturating_add(width) {
+            offset.x = x.saturating_sub(width).saturating_add(1);
+        }
+    }
+
+    /// Scroll so that the cursor's screen line is in view, when long rows
+    /// are soft wrapped. The top of the screen is always the start of a row.
+    fn scroll_wrapped(&mut self) {
+        let height = self.terminal.size().height as usize;
+        let y = self.cursor_position.y;
+        self.offset.x = 0;
+        if y < self.offset.y {
+            self.offset.y = y;
+            return;
+        }
+        let mut lines = self.screen_lines_before_cursor();
+        while lines >= height && self.offset.y < y {
+            lines -= self.segment_count(self.offset.y);
+            self.offset.y += 1;
+        }
+    }
+
+    /// The width that soft-wrapped rows are broken at: the screen's, less
+    /// the line number gutter.
+    fn wrap_width(&self) -> usize {
+        usize::from(self.terminal.size().width)
+            .saturating_sub(self.gutter_width())
+            .max(1)
+    }
+
+    /// Get how many columns the line number gutter takes up, if it's shown:
+    /// room for the largest line number and a space.
+    fn gutter_width(&self) -> usize {
+        if self.config_manager.get_show_line_numbers() {
+            self.document.row_count().max(1).to_string().len() + 1
+        } else {
+            0
+        }
+    }
+
+    /// Get the screen column the cursor is drawn at, counting from the start
+    /// of its row, with tabs expanded.
+    fn cursor_column(&self) -> usize {
+        let Position { x, y } = self.cursor_position;
+        self.document
+            .get_row(y)
+            .map_or(x, |row| row.render_x(x, self.speech_options.tab_width))
+    }
+
+    /// Get how many screen columns a row takes up, with tabs expanded.
+    fn row_columns(&self, y: usize) -> usize {
+        self.document.get_row(y).map_or(0, |row| {
+            row.render_x(row.len(), self.speech_options.tab_width)
+        })
+    }
+
+    /// Get the character of a row drawn at a screen column.
+    fn x_at_column(&self, y: usize, column: usize) -> usize {
+        self.document.get_row(y).map_or(0, |row| {
+            row.x_at_render_x(column, self.speech_options.tab_width)
+        })
+    }
+
+    /// Get how many screen lines a row takes up. A row that isn't soft
+    /// wrapped takes up one.
+    fn segment_count(&self, y: usize) -> usize {
+        if !self.soft_wrap {
+            return 1;
+        }
+        let len = self.row_columns(y);
+        let width = self.wrap_width();
+        len.max(1).div_ceil(width)
+    }
+
+    /// Count the screen lines between the top of the screen and the
+    /// cursor's.
+    fn screen_lines_before_cursor(&self) -> usize {
+        let y = self.cursor_position.y;
+        let segment = if self.soft_wrap {
+            segment_of(self.cursor_column(), self.row_columns(y), self.wrap_width())
         } else {
-            self.status_message = StatusMessage::from("Error writing file!".to_string());
-            let utt = self.create_utterance("Error writing file!");
-            self.sound_manager.interrupt_and_play(Box::new(utt));
-        }
+            0
+        };
+        (self.offset.y..y)
+            .map(|y| self.segment_count(y))
+            .sum::<usize>()
+            + segment
     }
 
-    fn scroll(&mut self) {
-        let Position { x, y } = self.cursor_position;
-        let width = self.terminal.size().width as usize;
-        let height = self.terminal.size().height as usize;
-        let mut offset = &mut self.offset;
-        if y < offset.y {
-            offset.y = y;
-        } else if y >= offset.y.saturating_add(height) {
-            offset.y = y.saturating_sub(height).saturating_add(1);
+    /// Get where the cursor is drawn on the screen.
+    fn cursor_screen_position(&self) -> Position {
+        let y = self.cursor_position.y;
+        let x = self.cursor_column();
+        let gutter = self.gutter_width();
+        if !self.soft_wrap {
+            return Position {
+                x: x.saturating_sub(self.offset.x) + gutter,
+                y: y.saturating_sub(self.offset.y),
+            };
         }
-        if x < offset.x {
-            offset.x = x;
-        } else if x >= offset.x.saturating_add(width) {
-            offset.x = x.saturating_sub(width).saturating_add(1);
+        let width = self.wrap_width();
+        let len = self.row_columns(y);
+        Position {
+            x: x - segment_of(x, len, width) * width + gutter,
+            y: self.screen_lines_before_cursor(),
         }
     }
 
-    fn move_cursor(&mut self, key: Key, wrapping_behavior: WrappingBehavior) {
-        let should_wrap_operations = match wrapping_behavior {
+    fn should_wrap(&self, wrapping_behavior: WrappingBehavior) -> bool {
+        match wrapping_behavior {
             WrappingBehavior::Default => self.wrap_arrow_key_navigation,
             WrappingBehavior::Wrap => true,
             WrappingBehavior::NoWrap => false,
-        };
+        }
+    }
+
+    fn move_cursor(&mut self, key: Key, wrapping_behavior: WrappingBehavior) {
+        let should_wrap_operations = self.should_wrap(wrapping_behavior);
         let term_height = self.terminal.size().height as usize;
         let Position { mut y, mut x } = self.cursor_position;
         let height = self.document.row_count();
@@ -494,7 +5861,28 @@ impl Editor {
         } else {
             0
         };
+        // Soft-wrapped rows are moved through a screen line at a time,
+        // keeping the column on the screen.
+        let segment_width = self.wrap_width();
+        let column = self.cursor_column();
+        let segment = segment_of(column, self.row_columns(y), segment_width);
+        let screen_column = column - segment * segment_width;
         match key {
+            Key::Up if self.soft_wrap && segment > 0 => {
+                x = self.x_at_column(y, column - segment_width);
+            }
+            Key::Up if self.soft_wrap && y > 0 => {
+                y -= 1;
+                let segment = self.segment_count(y) - 1;
+                x = self.x_at_column(y, segment * segment_width + screen_column);
+            }
+            Key::Down if self.soft_wrap && segment + 1 < self.segment_count(y) => {
+                x = self.x_at_column(y, column + segment_width);
+            }
+            Key::Down if self.soft_wrap && y < height => {
+                y += 1;
+                x = self.x_at_column(y, screen_column);
+            }
             Key::Up => {
                 if y == 0 {
                     self.play_blocked_navigation_sound();
@@ -558,27 +5946,689 @@ impl Editor {
         }
 
         // let ending_y = y;
+        let moved_line = y != self.cursor_position.y;
         self.cursor_position = Position { x, y };
+        if moved_line && matches!(key, Key::Up | Key::Down | Key::PageUp | Key::PageDown) {
+            self.play_position_tone();
+        }
+    }
+
+    /// Play a short tone whose pitch says how far through the file the
+    /// cursor's line is, high at the top and low at the bottom, when
+    /// position tones are on. It's played straight away, so it comes
+    /// before anything said about the line.
+    fn play_position_tone(&mut self) {
+        if !self.config_manager.get_position_tones() {
+            return;
+        }
+        let fraction = self.cursor_position.y as f32 / self.document.row_count().max(1) as f32;
+        // Equal steps through the file are equal steps in pitch.
+        let frequency =
+            POSITION_TONE_TOP * (POSITION_TONE_BOTTOM / POSITION_TONE_TOP).powf(fraction.min(1.0));
+        self.update_column_pan();
+        self.sound_manager.play_tone(Tone::new(
+            frequency,
+            POSITION_TONE_DURATION,
+            POSITION_TONE_VOLUME,
+        ));
     }
 
     fn play_blocked_navigation_sound(&mut self) {
-        self.sound_manager.play_and_wait(Box::new(Tone {
-            frequency: 440.0,
-            duration: 0.2,
-            volume: 0.5,
-        }));
+        self.play_earcon(Earcon::Blocked);
     }
 
     fn say_current_location(&mut self) {
-        let utt = self.create_utterance(
+        let utt = self.create_location_announcement();
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    fn create_location_announcement(&mut self) -> Utterance {
+        let row = self.cursor_position.y.saturating_add(1);
+        let column = self.cursor_position.x.saturating_add(1);
+        let scrolled = match self.offset.x {
+            0 => String::new(),
+            columns => format!(" Screen scrolled right {} columns.", columns),
+        };
+        let segments = self.segment_count(self.cursor_position.y);
+        let wrapped = if segments > 1 {
+            let len = self.row_columns(self.cursor_position.y);
             format!(
-                "Row {}, Column {}.",
-                self.cursor_position.y + 1,
-                self.cursor_position.x + 1
+                " Wrapped line {} of {}.",
+                segment_of(self.cursor_column(), len, self.wrap_width()) + 1,
+                segments
             )
-            .as_str(),
+        } else {
+            String::new()
+        };
+        let encrypted = match self.document.encryption() {
+            Some(encryption) => format!(" Encrypted with {}.", encryption.scheme().name()),
+            None => String::new(),
+        };
+        let final_newline = if self.document.ends_with_newline() {
+            ""
+        } else {
+            " No newline at end of file."
+        };
+        let line_ending = match self.document.line_ending() {
+            LineEnding::Windows => " Windows line endings.",
+            LineEnding::Unix => "",
+        };
+        let (anchor, terse_anchor) = match self.anchor_offset() {
+            Some((verbose, terse)) => (format!(" {}", verbose), format!(", {}", terse)),
+            None => (String::new(), String::new()),
+        };
+        let verbose = format!(
+            "Row {}, column {}.{}{}{}{}{}{}{}",
+            row,
+            column,
+            anchor,
+            wrapped,
+            scrolled,
+            encrypted,
+            line_ending,
+            final_newline,
+            self.todo_summary()
+        );
+        let terse = if self.offset.x > 0 {
+            format!("{} {}{}, scrolled", row, column, terse_anchor)
+        } else {
+            format!("{} {}{}", row, column, terse_anchor)
+        };
+        self.create_announcement(AnnouncementClass::Navigation, &verbose, &terse)
+    }
+
+    /// Speak what the status bar shows (the file, whether it's modified,
+    /// its length, the modes that are on and where the cursor is) and then
+    /// the most recent status message, since these are otherwise only seen.
+    fn speak_status_bar(&mut self) {
+        let file_name = self
+            .document
+            .file_name
+            .as_deref()
+            .map_or_else(|| String::from("No name"), short_file_name);
+        let mut verbose = String::new();
+        if self.buffers.len() > 1 {
+            verbose.push_str(&format!(
+                "Buffer {} of {}: ",
+                self.active_buffer + 1,
+                self.buffers.len()
+            ));
+        }
+        let modified = if self.document.is_read_only() {
+            "read only"
+        } else if self.document.is_dirty() {
+            "modified"
+        } else {
+            "not modified"
+        };
+        let row_count = self.document.row_count();
+        verbose.push_str(&format!(
+            "{}, {}, {} line{}.",
+            file_name,
+            modified,
+            row_count,
+            if row_count == 1 { "" } else { "s" }
+        ));
+        if self.document.line_ending() == LineEnding::Windows {
+            verbose.push_str(" Windows line endings.");
+        }
+        if !self.document.ends_with_newline() {
+            verbose.push_str(" No newline at end of file.");
+        }
+        if let Some(mode) = self.vi_mode {
+            verbose.push_str(&format!(" In {} mode.", mode.name()));
+        }
+        if self.sound_manager.is_silent() {
+            verbose.push_str(" Silent.");
+        } else if self.sound_manager.is_night_mode() {
+            verbose.push_str(" Night mode.");
+        }
+        let row = self.cursor_position.y.saturating_add(1);
+        verbose.push_str(&format!(
+            " Line {} of {}, column {}{}.",
+            row,
+            row_count,
+            self.cursor_position.x.saturating_add(1),
+            self.todo_summary()
+        ));
+        let mut terse = format!(
+            "{}{}, {} of {}",
+            file_name,
+            if self.document.is_dirty() {
+                ", modified"
+            } else {
+                ""
+            },
+            row,
+            row_count
+        );
+        let message = self.status_message.text.trim();
+        if !message.is_empty() {
+            verbose.push_str(&format!(" Last message: {}", message));
+            terse.push_str(&format!(". {}", message));
+        }
+        let utt = self.create_announcement(AnnouncementClass::Navigation, &verbose, &terse);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Spell the word under the cursor. Spelling it again straight away
+    /// spells it with the phonetic alphabet ("f as in foxtrot"), for letters
+    /// that sound alike at speed.
+    fn spell_word(&mut self) {
+        let default = &Row::from("");
+        let row = self
+            .document
+            .get_row(self.cursor_position.y)
+            .unwrap_or(default);
+        let word = row.get_word_at(self.cursor_position.x).unwrap_or_default();
+        let phonetic = matches!(
+            &self.spelled_at,
+            Some((at, time)) if at.x == self.cursor_position.x
+                && at.y == self.cursor_position.y
+                && time.elapsed() < SPELL_AGAIN_INTERVAL
+        );
+        let letters_with_spaces = self.spell(word, phonetic);
+        let utt: Box<dyn Audible> =
+            if !phonetic && self.speech_options.capitals == CapitalReading::Pitch {
+                Box::new(self.pitch_capitals(&letters_with_spaces))
+            } else {
+                Box::new(self.create_spelling_utterance(letters_with_spaces.as_str()))
+            };
+        if phonetic {
+            // Cut the plain spelling short rather than wait for it.
+            self.sound_manager.interrupt_and_play(utt);
+            self.spelled_at = None;
+        } else {
+            self.sound_manager.append(utt);
+            self.spelled_at = Some((self.cursor_position.clone(), Instant::now()));
+        }
+    }
+
+    /// Speak a spelled word with its capitals at a higher pitch, as a
+    /// sequence of utterances that switch pitch at each capital.
+    fn pitch_capitals(&mut self, spelled: &str) -> SoundSequence {
+        let mut parts: Vec<(String, bool)> = Vec::new();
+        for c in spelled.chars() {
+            let is_capital = c.is_uppercase();
+            match parts.last_mut() {
+                Some((part, capital)) if *capital == is_capital => part.push(c),
+                _ => parts.push((c.to_string(), is_capital)),
+            }
+        }
+        let audibles = parts
+            .into_iter()
+            .filter(|(part, _)| !part.trim_matches(|c: char| c == ',' || c == ' ').is_empty())
+            .map(|(part, capital)| {
+                let utt = self.create_spelling_utterance(&part);
+                let utt = if capital {
+                    let pitch = utt.pitch();
+                    utt.with_parameters(&capital_voice(pitch))
+                } else {
+                    utt
+                };
+                Box::new(utt) as Box<dyn Audible>
+            })
+            .collect();
+        SoundSequence::new(audibles)
+    }
+
+    /// Spell a word out letter by letter, or with the phonetic alphabet. An
+    /// identifier is spelled one of its words at a time, unless identifiers
+    /// are read whole.
+    fn spell(&self, word: &str, phonetic: bool) -> String {
+        // Add a space in between each letter.
+        let capitals = self.speech_options.capitals;
+        let letters = |word: &str| {
+            word.chars()
+                .map(|c| {
+                    if phonetic {
+                        phonetic_name(c)
+                    } else if c.is_uppercase() && capitals == CapitalReading::Word {
+                        format!("cap {}", c)
+                    } else {
+                        c.to_string()
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        let identifiers = self.speech_options.identifiers;
+        let (style, words) = match split_identifier(word) {
+            Some(split) if identifiers != IdentifierReading::Whole => split,
+            _ => return letters(word),
+        };
+        let separator = if word.contains('_') {
+            ". underscore. "
+        } else {
+            ". "
+        };
+        let spelled = words
+            .into_iter()
+            .map(letters)
+            .collect::<Vec<String>>()
+            .join(separator);
+        if identifiers == IdentifierReading::Announce {
+            format!("{} case. {}", style, spelled)
+        } else {
+            spelled
+        }
+    }
+
+    /// Switch to the next punctuation level, so that fewer or more symbols
+    /// are named when text is read.
+    fn cycle_punctuation(&mut self) {
+        let level = self.speech_options.punctuation.next();
+        self.speech_options.punctuation = level;
+        self.status_message = StatusMessage::from(format!("Punctuation: {}", level.name()));
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            &format!("Punctuation {}", level.name()),
+            level.name(),
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Switch between reading numbers as values and digit by digit.
+    fn toggle_numbers(&mut self) {
+        let mode = self.speech_options.numbers.next();
+        self.speech_options.numbers = mode;
+        self.status_message = StatusMessage::from(format!("Numbers: {}", mode.name()));
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            &format!("Numbers as {}", mode.name()),
+            mode.name(),
+        );
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Toggle the verbosity of one announcement class.
+    ///
+    /// The class is picked with a follow-up key: `n`avigation, `e`diting,
+    /// `p`rompts, or e`r`rors.
+    fn toggle_verbosity(&mut self) -> Result<(), std::io::Error> {
+        self.status_message = StatusMessage::from(
+            "Verbosity for: (n)avigation (e)diting (p)rompts e(r)rors".to_string(),
+        );
+        self.refresh_screen()?;
+        let utt = self.create_announcement(
+            AnnouncementClass::Prompts,
+            "Verbosity for: N navigation, E editing, P prompts, R errors.",
+            "Verbosity.",
+        );
+        self.sound_manager.append(Box::new(utt));
+        let class = match self.read_key()? {
+            Key::Char('n') => AnnouncementClass::Navigation,
+            Key::Char('e') => AnnouncementClass::Editing,
+            Key::Char('p') => AnnouncementClass::Prompts,
+            Key::Char('r') => AnnouncementClass::Errors,
+            _ => {
+                self.status_message = StatusMessage::from(String::new());
+                self.play_noop_sound();
+                return Ok(());
+            }
+        };
+        let verbosity = self.verbosity.get(class).toggled();
+        self.verbosity.set(class, verbosity);
+        let message = format!("{} {}", class.name(), verbosity.name());
+        self.status_message = StatusMessage::from(format!("Verbosity: {}", message));
+        let utt = self.create_utterance(&message);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+        Ok(())
+    }
+
+    /// Describe the number of TODO annotations for orientation messages.
+    ///
+    /// # Returns
+    ///
+    /// A string like ", 3 TODOs", or an empty string if there are none.
+    ///
+    fn todo_summary(&self) -> String {
+        match self.document.todo_count() {
+            0 => String::new(),
+            1 => ", 1 TODO".to_string(),
+            count => format!(", {} TODOs", count),
+        }
+    }
+
+    /// Move the cursor to the next TODO annotation and speak its row.
+    fn jump_to_next_todo(&mut self) {
+        if let Some(y) = self.document.find_next_todo(self.cursor_position.y) {
+            self.cursor_position = Position { x: 0, y };
+            self.scroll();
+            self.speak_current_row();
+        } else {
+            self.play_noop_sound();
+            let utt = self.create_utterance("No TODOs.");
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+        }
+    }
+
+    /// Get the dictionary to check the current document's spelling with, or
+    /// None if it isn't prose or there's no dictionary.
+    fn spelling_dictionary(&self) -> Option<&Dictionary> {
+        self.dictionary
+            .as_ref()
+            .filter(|_| spelling::is_prose(self.document.file_name.as_deref()))
+    }
+
+    /// Find the misspelled word under the cursor.
+    ///
+    /// # Returns
+    ///
+    /// The word's row, the index of its first character, and the word.
+    ///
+    fn misspelling_at_cursor(&self) -> Option<(usize, usize, String)> {
+        let dictionary = self.spelling_dictionary()?;
+        let y = self.cursor_position.y;
+        let row = self.document.get_row(y)?;
+        spelling::misspelling_at(row, self.cursor_position.x, dictionary)
+            .map(|(x, word)| (y, x, word.to_string()))
+    }
+
+    /// Say why spelling can't be checked in the current document.
+    fn refuse_spelling(&mut self) {
+        let (verbose, terse) = if self.dictionary.is_none() {
+            (
+                "No dictionary, so spelling isn't checked.",
+                "No dictionary.",
+            )
+        } else {
+            ("Spelling is only checked in prose files.", "Not prose.")
+        };
+        self.play_noop_sound();
+        let utt = self.create_announcement(AnnouncementClass::Errors, verbose, terse);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+    }
+
+    /// Move to the next or previous misspelled word and read it, going round
+    /// to the other end of the document if need be.
+    fn jump_to_misspelling(&mut self, direction: SearchDirection) {
+        let dictionary = match self.spelling_dictionary() {
+            Some(dictionary) => dictionary,
+            None => return self.refuse_spelling(),
+        };
+        let (position, wrapped) = match spelling::find_misspelling(
+            &self.document,
+            &self.cursor_position,
+            direction,
+            dictionary,
+        ) {
+            Some(found) => found,
+            None => {
+                self.play_noop_sound();
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    "No misspellings.",
+                    "None.",
+                );
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return;
+            }
+        };
+        self.cursor_position = position.clone();
+        self.scroll();
+        self.sound_manager.silence();
+        self.play_earcon(Earcon::Misspelling);
+        if wrapped {
+            let (verbose, terse) = match direction {
+                SearchDirection::Forward => ("Wrapped to top.", "Top."),
+                SearchDirection::Backward => ("Wrapped to bottom.", "Bottom."),
+            };
+            let utt = self.create_announcement(AnnouncementClass::Navigation, verbose, terse);
+            self.sound_manager.append(Box::new(utt));
+        }
+        let word = self
+            .misspelling_at_cursor()
+            .map(|(_, _, word)| word)
+            .unwrap_or_default();
+        let spoken = string_to_speakable_tokens(&word, &self.speech_options);
+        self.status_message = StatusMessage::from(format!("Misspelled: {}", word));
+        let utt = self.create_announcement(
+            AnnouncementClass::Navigation,
+            &format!("{}, line {}.", spoken, position.y.saturating_add(1)),
+            &spoken,
+        );
+        self.sound_manager.append(Box::new(utt));
+    }
+
+    /// Offer corrections for the misspelled word under the cursor. The
+    /// first is spoken with how many others there are; Down and Up browse
+    /// them, and Enter or Tab replaces the word with one.
+    fn offer_spelling_suggestions(&mut self) -> Result<(), std::io::Error> {
+        if self.spelling_dictionary().is_none() {
+            self.refuse_spelling();
+            return Ok(());
+        }
+        if self.document.is_read_only() {
+            self.play_read_only_warning();
+            return Ok(());
+        }
+        let (y, x, word) = match self.misspelling_at_cursor() {
+            Some(found) => found,
+            None => {
+                self.play_noop_sound();
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    "No misspelled word here.",
+                    "Not misspelled.",
+                );
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return Ok(());
+            }
+        };
+        let suggestions = self
+            .dictionary
+            .as_ref()
+            .map(|dictionary| dictionary.suggestions(&word))
+            .unwrap_or_default();
+        if suggestions.is_empty() {
+            self.play_noop_sound();
+            let spoken = string_to_speakable_tokens(&word, &self.speech_options);
+            let utt = self.create_announcement(
+                AnnouncementClass::Errors,
+                &format!("No suggestions for {}.", spoken),
+                "None.",
+            );
+            self.sound_manager.interrupt_and_play(Box::new(utt));
+            return Ok(());
+        }
+        let top = string_to_speakable_tokens(&suggestions[0], &self.speech_options);
+        let verbose = match suggestions.len() {
+            1 => format!("{}. Enter to accept.", top),
+            count => format!(
+                "{}, and {} more. Enter to accept, down for the next.",
+                top,
+                count - 1
+            ),
+        };
+        let terse = match suggestions.len() {
+            1 => top.clone(),
+            count => format!("{}, and {} more", top, count - 1),
+        };
+        let utt = self.create_announcement(AnnouncementClass::Prompts, &verbose, &terse);
+        self.sound_manager.interrupt_and_play(Box::new(utt));
+
+        let mut selected = 0;
+        loop {
+            self.status_message = StatusMessage::from(format!(
+                "Correct {}/{}: {}",
+                selected + 1,
+                suggestions.len(),
+                suggestions[selected]
+            ));
+            self.refresh_screen()?;
+            let previous = selected;
+            match self.read_key()? {
+                Key::Down | Key::Ctrl('n') if selected + 1 < suggestions.len() => selected += 1,
+                Key::Up | Key::Ctrl('p') if selected > 0 => selected -= 1,
+                Key::Down | Key::Up | Key::Ctrl('n' | 'p') => self.play_blocked_navigation_sound(),
+                Key::Char('\n' | '\t') => break,
+                Key::Esc => {
+                    self.status_message = StatusMessage::from(String::new());
+                    self.play_noop_sound();
+                    return Ok(());
+                }
+                _ => (),
+            }
+            if selected != previous {
+                let spoken =
+                    string_to_speakable_tokens(&suggestions[selected], &self.speech_options);
+                let utt = self.create_utterance(
+                    format!("{} of {}: {}", selected + 1, suggestions.len(), spoken).as_str(),
+                );
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+            }
+        }
+        let correction = &suggestions[selected];
+        let at = Position { x, y };
+        self.document
+            .replace(&at, word.graphemes(true).count(), correction);
+        self.cursor_position = at;
+        self.status_message = StatusMessage::from(format!("Corrected to {}.", correction));
+        let spoken = string_to_speakable_tokens(correction, &self.speech_options);
+        let utt = self.create_announcement(
+            AnnouncementClass::Editing,
+            &format!("Corrected to {}.", spoken),
+            &spoken,
         );
         self.sound_manager.interrupt_and_play(Box::new(utt));
+        Ok(())
+    }
+
+    /// Say how the file differs from its last git commit, if it's in a
+    /// repository and has changed: "Modified since the last commit, 3
+    /// hunks."
+    fn announce_git_status(&mut self) {
+        let hunks = match self.document.git_hunks() {
+            Some(hunks) if hunks.len() == 1 => String::from("1 hunk"),
+            Some(hunks) if !hunks.is_empty() => format!("{} hunks", hunks.len()),
+            _ => return,
+        };
+        let utt = self.create_announcement(
+            AnnouncementClass::Navigation,
+            &format!("Modified since the last commit, {}.", hunks),
+            &format!("Modified, {}", hunks),
+        );
+        self.sound_manager.append(Box::new(utt));
+    }
+
+    /// Move to the next or previous hunk changed since the last git commit,
+    /// going round to the other end of the file if need be, and read what
+    /// changed: "Line 12, added 2 lines: ...".
+    fn jump_to_hunk(&mut self, direction: SearchDirection) {
+        let hunks = match self.document.git_hunks() {
+            Some(hunks) => hunks,
+            None => {
+                self.play_noop_sound();
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    "This file isn't committed to git.",
+                    "Not in git.",
+                );
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return;
+            }
+        };
+        // A hunk removed from the end of the file starts past its last line.
+        let last = self.document.row_count().saturating_sub(1);
+        let line = |hunk: &Hunk| hunk.new_start.min(last);
+        let y = self.cursor_position.y;
+        let found = match direction {
+            SearchDirection::Forward => hunks
+                .iter()
+                .find(|hunk| line(hunk) > y)
+                .map(|hunk| (*hunk, false))
+                .or_else(|| hunks.first().map(|hunk| (*hunk, true))),
+            SearchDirection::Backward => hunks
+                .iter()
+                .rev()
+                .find(|hunk| line(hunk) < y)
+                .map(|hunk| (*hunk, false))
+                .or_else(|| hunks.last().map(|hunk| (*hunk, true))),
+        };
+        let (hunk, wrapped) = match found {
+            Some(found) => found,
+            None => {
+                self.play_noop_sound();
+                let utt = self.create_announcement(
+                    AnnouncementClass::Errors,
+                    "No changes since the last commit.",
+                    "No changes.",
+                );
+                self.sound_manager.interrupt_and_play(Box::new(utt));
+                return;
+            }
+        };
+        self.cursor_position = Position {
+            x: 0,
+            y: line(&hunk),
+        };
+        self.scroll();
+        self.sound_manager.silence();
+        if wrapped {
+            let (verbose, terse) = match direction {
+                SearchDirection::Forward => ("Wrapped to top.", "Top."),
+                SearchDirection::Backward => ("Wrapped to bottom.", "Bottom."),
+            };
+            let utt = self.create_announcement(AnnouncementClass::Navigation, verbose, terse);
+            self.sound_manager.append(Box::new(utt));
+        }
+        let (verbose, terse) = self.describe_git_hunk(&hunk);
+        let utt = self.create_announcement(AnnouncementClass::Navigation, &verbose, &terse);
+        self.sound_manager.append(Box::new(utt));
+    }
+
+    /// Describe a git hunk and read its lines: the new ones, or the removed
+    /// ones if nothing replaced them.
+    ///
+    /// # Returns
+    ///
+    /// The verbose and terse descriptions.
+    ///
+    fn describe_git_hunk(&self, hunk: &Hunk) -> (String, String) {
+        let (change, lines): (String, Vec<&str>) = if hunk.old_len == 0 {
+            let lines = (hunk.new_start..hunk.new_start + hunk.new_len)
+                .filter_map(|y| self.document.get_row(y).map(Row::as_str))
+                .collect();
+            (format!("added {}", count_lines(hunk.new_len)), lines)
+        } else if hunk.new_len == 0 {
+            let lines = (hunk.old_start..hunk.old_start + hunk.old_len)
+                .filter_map(|y| self.document.committed_line(y))
+                .collect();
+            (format!("removed {}", count_lines(hunk.old_len)), lines)
+        } else {
+            let lines = (hunk.new_start..hunk.new_start + hunk.new_len)
+                .filter_map(|y| self.document.get_row(y).map(Row::as_str))
+                .collect();
+            let change = if hunk.old_len == hunk.new_len {
+                format!("changed {}", count_lines(hunk.new_len))
+            } else {
+                format!(
+                    "replaced {} with {}",
+                    count_lines(hunk.old_len),
+                    count_lines(hunk.new_len)
+                )
+            };
+            (change, lines)
+        };
+        let mut spoken = lines
+            .iter()
+            .take(MAX_SPOKEN_HUNK_LINES)
+            .map(|line| string_to_speakable_tokens(line, &self.speech_options))
+            .filter(|line| !line.trim().is_empty())
+            .collect::<Vec<String>>()
+            .join(". ");
+        if lines.len() > MAX_SPOKEN_HUNK_LINES {
+            spoken.push_str(&format!(
+                ", and {} more",
+                count_lines(lines.len() - MAX_SPOKEN_HUNK_LINES)
+            ));
+        }
+        let line = self.cursor_position.y.saturating_add(1);
+        (
+            format!("Line {}, {}: {}", line, change, spoken),
+            format!("{}, {}: {}", line, change, spoken),
+        )
     }
 
     fn draw_welcome_message(&self) {
@@ -594,13 +6644,28 @@ impl Editor {
 
     fn draw_rows(&self) {
         let height = self.terminal.size().height;
+        let changed = self.document.git_hunks().unwrap_or_default();
+        let is_changed = |y: usize| {
+            changed
+                .iter()
+                .any(|hunk| hunk.new_start <= y && y < hunk.new_start + hunk.new_len)
+        };
+        let lines = self.screen_lines(height.into());
         for terminal_row in 0..height {
             Terminal::clear_current_line();
-            if let Some(row) = self
-                .document
-                .get_row(self.offset.y.saturating_add(terminal_row.into()))
-            {
-                self.draw_row(row);
+            let line = lines
+                .get(usize::from(terminal_row))
+                .and_then(|&(y, start)| Some((y, self.document.get_row(y)?, start)));
+            if let Some((y, row, start)) = line {
+                if is_changed(y) {
+                    Terminal::set_bg_color(CHANGED_LINE_BG_COLOR);
+                    Terminal::set_fg_color(CHANGED_LINE_FG_COLOR);
+                    self.draw_row(y, row, start);
+                    Terminal::reset_bg_color();
+                    Terminal::reset_fg_color();
+                } else {
+                    self.draw_row(y, row, start);
+                }
             } else if self.document.row_count() == 0 && terminal_row == height / 3 {
                 self.draw_welcome_message();
             } else {
@@ -609,33 +6674,100 @@ impl Editor {
         }
     }
 
-    fn draw_row(&self, row: &Row) {
-        let width = self.terminal.size().width as usize;
-        let start = self.offset.x;
-        let end = self.offset.x.saturating_add(width);
-        println!("{}\r", row.render(start, end))
+    /// Get what each screen line shows, as the row and the index of the
+    /// first character of it shown.
+    fn screen_lines(&self, height: usize) -> Vec<(usize, usize)> {
+        let width = self.wrap_width();
+        (self.offset.y..self.document.row_count())
+            .flat_map(|y| {
+                let starts: Vec<usize> = if self.soft_wrap {
+                    (0..self.segment_count(y))
+                        .map(|segment| segment * width)
+                        .collect()
+                } else {
+                    vec![self.offset.x]
+                };
+                starts.into_iter().map(move |start| (y, start))
+            })
+            .take(height)
+            .collect()
+    }
+
+    fn draw_row(&self, y: usize, row: &Row, start: usize) {
+        let width = self.wrap_width();
+        let end = start.saturating_add(width);
+        // A soft-wrapped row's number is only drawn beside its first line.
+        let gutter = match self.gutter_width() {
+            0 => String::new(),
+            gutter if self.soft_wrap && start > 0 => " ".repeat(gutter),
+            gutter => format!("{:>1$} ", y + 1, gutter - 1),
+        };
+        println!(
+            "{}{}\r",
+            gutter,
+            row.render(start, end, self.speech_options.tab_width)
+        )
     }
 
     fn draw_status_bar(&self) {
         let mut status;
         let width = self.terminal.size().width as usize;
-        let modified_indicator = if self.document.is_dirty() { "*" } else { "" };
+        let silent_indicator = if self.sound_manager.is_silent() {
+            " [silent]"
+        } else if self.sound_manager.is_screen_reader() {
+            " [screen reader]"
+        } else if self.sound_manager.is_night_mode() {
+            " [night]"
+        } else {
+            ""
+        };
+        let modified_indicator = if self.document.is_read_only() {
+            " [read only]"
+        } else if self.document.is_dirty() {
+            "*"
+        } else {
+            ""
+        };
         let mut file_name = "[No Name]".to_string();
         if let Some(name) = &self.document.file_name {
             file_name = name.clone();
             file_name.truncate(20);
         }
+        // Only endings that differ from the usual are shown.
+        let mut ending_indicator = String::new();
+        if self.document.line_ending() != LineEnding::Unix {
+            ending_indicator.push_str(&format!(" [{}]", self.document.line_ending().name()));
+        }
+        if !self.document.ends_with_newline() {
+            ending_indicator.push_str(" [no final newline]");
+        }
+        let mode_indicator = self
+            .vi_mode
+            .map(|mode| format!(" [{}]", mode.name()))
+            .unwrap_or_default();
+        let buffer_indicator = if self.buffers.len() > 1 {
+            format!("[{}/{}] ", self.active_buffer + 1, self.buffers.len())
+        } else {
+            String::new()
+        };
         status = format!(
-            "{} - {} lines{}",
+            "{}{} - {} lines{}{}{}{}{}",
+            buffer_indicator,
             file_name,
             self.document.row_count(),
-            modified_indicator
+            ending_indicator,
+            modified_indicator,
+            mode_indicator,
+            silent_indicator,
+            self.todo_summary()
         );
         let line_indicator = format!(
             "{}/{}",
             self.cursor_position.y.saturating_add(1),
             self.document.row_count()
         );
+        #[cfg(feature = "audio-profiling")]
+        status.push_str(&format!(" | {}", self.sound_manager.profile_report()));
         let len = status.len() + line_indicator.len();
         status.push_str(&" ".repeat(width.saturating_sub(len)));
         status = format!("{}{}", status, line_indicator);
@@ -661,3 +6793,142 @@ fn die(e: std::io::Error) {
     Terminal::clear_screen();
     panic!("{}", e);
 }
+
+/// Run a command line in the shell, capturing what it prints. Errors are
+/// merged into the output by the shell, so the two stay in order.
+///
+/// It gets no input, so a command that reads some sees the end of it rather
+/// than waiting on the keyboard, and any key pressed while it runs stops it.
+///
+/// # Returns
+///
+/// What it printed and how it finished, or None if it was stopped.
+///
+fn run_in_shell(command_line: &str) -> Result<Option<Output>, std::io::Error> {
+    #[cfg(windows)]
+    let mut command = Command::new("cmd");
+    #[cfg(windows)]
+    command.arg("/C").arg(format!("({}) 2>&1", command_line));
+    #[cfg(not(windows))]
+    let mut command = Command::new("sh");
+    #[cfg(not(windows))]
+    {
+        use std::os::unix::process::CommandExt;
+        // In its own process group, so stopping it stops what it started.
+        command
+            .arg("-c")
+            .arg(format!("{{ {}\n}} 2>&1", command_line))
+            .process_group(0);
+    }
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let stdout = child.stdout.take();
+    // Read while it runs, so a full pipe can't hold it up.
+    let reader = thread::spawn(move || {
+        let mut output = Vec::new();
+        if let Some(mut stdout) = stdout {
+            let _ = stdout.read_to_end(&mut output);
+        }
+        output
+    });
+    loop {
+        if let Some(key) = Terminal::poll_key(SHELL_POLL_INTERVAL) {
+            key?;
+            stop_command(&mut child)?;
+            return Ok(None);
+        }
+        if reader.is_finished() {
+            if let Some(status) = child.try_wait()? {
+                return Ok(Some(Output {
+                    status,
+                    stdout: reader.join().unwrap_or_default(),
+                    stderr: Vec::new(),
+                }));
+            }
+        }
+    }
+}
+
+/// Stop a command started by `run_in_shell`, and everything it started.
+fn stop_command(child: &mut std::process::Child) -> Result<(), std::io::Error> {
+    #[cfg(not(windows))]
+    let stopped = Command::new("kill")
+        .arg("-TERM")
+        .arg(format!("-{}", child.id()))
+        .status()
+        .is_ok_and(|status| status.success());
+    #[cfg(windows)]
+    let stopped = false;
+    if !stopped {
+        child.kill()?;
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Read a go-to-line answer, `line` or `line:column`, counting from 1.
+fn parse_line_target(text: &str) -> Option<(usize, Option<usize>)> {
+    let text = text.trim();
+    match text.split_once(':') {
+        Some((line, column)) => {
+            Some((line.trim().parse().ok()?, Some(column.trim().parse().ok()?)))
+        }
+        None => Some((text.parse().ok()?, None)),
+    }
+}
+
+/// The voice capitals are spoken in when they're read at a higher pitch:
+/// higher than `pitch`, the pitch of the letters around them.
+fn capital_voice(pitch: i64) -> VoiceParameters {
+    VoiceParameters {
+        pitch: Some((pitch + CAPITAL_PITCH).min(100)),
+        ..VoiceParameters::default()
+    }
+}
+
+/// The voice a search match is spoken in: higher than `pitch`, the pitch
+/// of the rest of its line.
+fn match_voice(pitch: i64) -> VoiceParameters {
+    VoiceParameters {
+        pitch: Some((pitch + MATCH_PITCH).min(100)),
+        ..VoiceParameters::default()
+    }
+}
+
+/// Get which screen line of a soft-wrapped row a column is on. The end of a
+/// row that exactly fills its last screen line stays on that line.
+fn segment_of(x: usize, len: usize, width: usize) -> usize {
+    x.min(len.saturating_sub(1)) / width
+}
+
+/// Get the last part of a file's path, for speech.
+fn short_file_name(file_name: &str) -> String {
+    Path::new(file_name).file_name().map_or_else(
+        || file_name.to_string(),
+        |name| name.to_string_lossy().to_string(),
+    )
+}
+
+/// Get the voice remembered for a file in the session store, if any.
+fn remembered_voice(session: &SessionStore, file_name: &str) -> Option<String> {
+    session
+        .get_file_value(file_name, "voice")
+        .and_then(toml::Value::as_str)
+        .map(String::from)
+}
+
+/// Describe a number of seconds for speech, e.g. "1 hour 5 minutes".
+fn describe_duration(seconds: u64) -> String {
+    let minutes = seconds / 60;
+    let plural =
+        |count: u64, unit: &str| format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" });
+    match (minutes / 60, minutes % 60) {
+        (0, 0) => String::from("less than a minute"),
+        (0, minutes) => plural(minutes, "minute"),
+        (hours, 0) => plural(hours, "hour"),
+        (hours, minutes) => format!("{} {}", plural(hours, "hour"), plural(minutes, "minute")),
+    }
+}
@@ -0,0 +1,40 @@
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use crate::utils::is_on_path;
+
+/// Get a file's contents as of the last git commit.
+///
+/// # Returns
+///
+/// The committed contents, or None if git isn't installed, the file isn't
+/// in a repository, or it has never been committed.
+///
+pub fn committed_version(file_name: &str) -> Option<String> {
+    if !is_on_path("git") {
+        return None;
+    }
+    let path = Path::new(file_name);
+    let directory = path
+        .parent()
+        .filter(|directory| !directory.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let name = path.file_name()?.to_str()?;
+    // `HEAD:./name` is relative to the directory git runs in, so a symlinked
+    // or nested path still finds the right file.
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .arg("show")
+        .arg(format!("HEAD:./{}", name))
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout).ok()
+}
@@ -0,0 +1,340 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{atomic, Position};
+
+/// A change made to a document, as it's written to the journal.
+///
+/// Positions are the ones the change was made at, so replaying the changes
+/// in order on the text they were made to gives the same result.
+pub enum Operation {
+    Insert {
+        at: Position,
+        c: char,
+    },
+    Delete {
+        at: Position,
+    },
+    RemoveRow {
+        y: usize,
+    },
+    InsertRow {
+        y: usize,
+        text: String,
+    },
+    DuplicateRow {
+        y: usize,
+    },
+    SwapRows {
+        a: usize,
+        b: usize,
+    },
+    DeleteRange {
+        start: Position,
+        end: Position,
+    },
+    InsertText {
+        at: Position,
+        text: String,
+    },
+    ReorderRows {
+        start: usize,
+        order: Vec<usize>,
+    },
+    RemoveDuplicates {
+        start: usize,
+        len: usize,
+    },
+    Undo,
+    /// The whole text was replaced, as when recovering unsaved changes.
+    Replace {
+        text: String,
+    },
+    /// The text now matches the file on disk, after a save or a reload.
+    Saved,
+}
+
+impl Operation {
+    /// Write the operation as a journal line: its name, then its arguments,
+    /// separated by tabs. Positions are written `line:column`, counting
+    /// from 1, to be read by people as well as replayed.
+    fn to_line(&self) -> String {
+        let fields = match self {
+            Self::Insert { at, c } => {
+                vec!["insert".to_string(), position(at), escape(&c.to_string())]
+            }
+            Self::Delete { at } => vec!["delete".to_string(), position(at)],
+            Self::RemoveRow { y } => vec!["remove_row".to_string(), (y + 1).to_string()],
+            Self::InsertRow { y, text } => {
+                vec!["insert_row".to_string(), (y + 1).to_string(), escape(text)]
+            }
+            Self::DuplicateRow { y } => {
+                vec!["duplicate_row".to_string(), (y + 1).to_string()]
+            }
+            Self::SwapRows { a, b } => vec![
+                "swap_rows".to_string(),
+                (a + 1).to_string(),
+                (b + 1).to_string(),
+            ],
+            Self::DeleteRange { start, end } => {
+                vec!["delete_range".to_string(), position(start), position(end)]
+            }
+            Self::InsertText { at, text } => {
+                vec!["insert_text".to_string(), position(at), escape(text)]
+            }
+            Self::ReorderRows { start, order } => vec![
+                "reorder_rows".to_string(),
+                (start + 1).to_string(),
+                order
+                    .iter()
+                    .map(usize::to_string)
+                    .collect::<Vec<String>>()
+                    .join(","),
+            ],
+            Self::RemoveDuplicates { start, len } => vec![
+                "remove_duplicates".to_string(),
+                (start + 1).to_string(),
+                len.to_string(),
+            ],
+            Self::Undo => vec!["undo".to_string()],
+            Self::Replace { text } => vec!["replace".to_string(), escape(text)],
+            Self::Saved => vec!["saved".to_string()],
+        };
+        fields.join("\t")
+    }
+
+    /// Read an operation from a journal line.
+    ///
+    /// # Returns
+    ///
+    /// The operation, or None for comments and lines that can't be read.
+    ///
+    fn parse(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let row = |index: usize| -> Option<usize> {
+            fields.get(index)?.parse::<usize>().ok()?.checked_sub(1)
+        };
+        let at = |index: usize| parse_position(fields.get(index)?);
+        let text = |index: usize| fields.get(index).map(|text| unescape(text));
+        let operation = match *fields.first()? {
+            "insert" => Self::Insert {
+                at: at(1)?,
+                c: text(2)?.chars().next()?,
+            },
+            "delete" => Self::Delete { at: at(1)? },
+            "remove_row" => Self::RemoveRow { y: row(1)? },
+            "insert_row" => Self::InsertRow {
+                y: row(1)?,
+                text: text(2)?,
+            },
+            "duplicate_row" => Self::DuplicateRow { y: row(1)? },
+            "swap_rows" => Self::SwapRows {
+                a: row(1)?,
+                b: row(2)?,
+            },
+            "delete_range" => Self::DeleteRange {
+                start: at(1)?,
+                end: at(2)?,
+            },
+            "insert_text" => Self::InsertText {
+                at: at(1)?,
+                text: text(2)?,
+            },
+            "reorder_rows" => Self::ReorderRows {
+                start: row(1)?,
+                order: fields
+                    .get(2)?
+                    .split(',')
+                    .map(|offset| offset.parse().ok())
+                    .collect::<Option<Vec<usize>>>()?,
+            },
+            "remove_duplicates" => Self::RemoveDuplicates {
+                start: row(1)?,
+                len: fields.get(2)?.parse().ok()?,
+            },
+            "undo" => Self::Undo,
+            "replace" => Self::Replace { text: text(1)? },
+            "saved" => Self::Saved,
+            _ => return None,
+        };
+        Some(operation)
+    }
+}
+
+/// A log of the changes made to a document since it was last saved, kept
+/// in `.<name>.clack-journal` next to it.
+///
+/// Each change is written as soon as it's made, so after a crash the
+/// unsaved changes can be replayed exactly, and the journal can be read to
+/// see what was changed. Like the crash-recovery file, it's only there
+/// while there are unsaved changes: it's written from the first edit, and
+/// removed when the document is saved or clack quits. A journal left by a
+/// session that crashed is set aside as `.<name>.clack-journal.1` when this
+/// session starts its own.
+pub struct Journal {
+    path: PathBuf,
+    /// The document the journal is for.
+    document: PathBuf,
+    /// The journal file, once this session has written to it.
+    file: Option<File>,
+    /// Whether this session set a crashed session's journal aside.
+    kept_previous: bool,
+}
+
+impl Journal {
+    /// Get the journal for a document. Nothing is written until a change
+    /// is recorded.
+    pub fn new(file_name: &str) -> Self {
+        Self {
+            path: journal_path_for(file_name),
+            document: PathBuf::from(file_name),
+            file: None,
+            kept_previous: false,
+        }
+    }
+
+    /// Write a change to the journal, starting it if it's the first since
+    /// the last save. A save empties the journal. A journal that can't be
+    /// written to is no reason to stop editing, so failures are ignored.
+    pub fn record(&mut self, operation: &Operation) {
+        if let Operation::Saved = operation {
+            self.clear();
+            return;
+        }
+        if self.file.is_none() {
+            self.file = self.open().ok();
+        }
+        if let Some(file) = &mut self.file {
+            let _ = writeln!(file, "{}", operation.to_line());
+        }
+    }
+
+    /// Start this session's journal, setting aside one left by a session
+    /// that crashed.
+    fn open(&mut self) -> Result<File, io::Error> {
+        if self.path.exists() {
+            fs::rename(&self.path, previous_path(&self.path))?;
+            self.kept_previous = true;
+        }
+        // The journal holds the document's text, so it's kept as private as
+        // the document.
+        let mut file = atomic::create_like(&self.path, &self.document)?;
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        writeln!(
+            file,
+            "# clack session {} started at {} (seconds since 1970)",
+            process::id(),
+            started
+        )?;
+        Ok(file)
+    }
+
+    /// Remove this session's journal, once its changes are saved.
+    fn clear(&mut self) {
+        if self.file.take().is_some() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+
+    /// Remove this session's journal, and the crashed session's it set
+    /// aside, when clack quits: the changes in them were saved or thrown
+    /// away.
+    pub fn remove(&mut self) {
+        self.clear();
+        if self.kept_previous {
+            let _ = fs::remove_file(previous_path(&self.path));
+            self.kept_previous = false;
+        }
+    }
+
+    /// Read the changes left unsaved at the end of the document's previous
+    /// session: those after the last save in its journal, which is set
+    /// aside once this session has started its own.
+    pub fn unsaved_in_previous_session(&self) -> Vec<Operation> {
+        let path = if self.file.is_some() {
+            previous_path(&self.path)
+        } else {
+            self.path.clone()
+        };
+        unsaved_in(&path)
+    }
+}
+
+/// Read the changes after the last save in a journal.
+fn unsaved_in(path: &Path) -> Vec<Operation> {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    let mut operations = Vec::new();
+    for operation in contents.lines().filter_map(Operation::parse) {
+        match operation {
+            Operation::Saved => operations.clear(),
+            operation => operations.push(operation),
+        }
+    }
+    operations
+}
+
+fn journal_path_for(file_name: &str) -> PathBuf {
+    let path = Path::new(file_name);
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.clack-journal", name))
+}
+
+fn previous_path(path: &Path) -> PathBuf {
+    path.with_extension("clack-journal.1")
+}
+
+fn position(at: &Position) -> String {
+    format!("{}:{}", at.y + 1, at.x + 1)
+}
+
+fn parse_position(field: &str) -> Option<Position> {
+    let (line, column) = field.split_once(':')?;
+    Some(Position {
+        x: column.parse::<usize>().ok()?.checked_sub(1)?,
+        y: line.parse::<usize>().ok()?.checked_sub(1)?,
+    })
+}
+
+/// Escape text so that it fits in one field of one line.
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
@@ -0,0 +1,655 @@
+use std::env;
+
+use crate::terminal::Key;
+
+use crate::utils::default_echo_name;
+
+/// An editor command that can be bound to a key chord.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    PageUp,
+    PageDown,
+    LineStart,
+    LineEnd,
+    DeleteForward,
+    DeleteBackward,
+    Quit,
+    Save,
+    OpenFile,
+    NextBuffer,
+    ListBuffers,
+    Search,
+    FindAll,
+    Replace,
+    GoToLine,
+    InsertDigraph,
+    CompleteWord,
+    SpeakLocation,
+    SpeakStatusBar,
+    ToggleVerbosity,
+    CyclePunctuation,
+    ToggleNumbers,
+    CycleTypingEcho,
+    SpeechFaster,
+    SpeechSlower,
+    SpeechLouder,
+    SpeechQuieter,
+    TonesLouder,
+    TonesQuieter,
+    ToggleSilent,
+    ToggleNightMode,
+    ToggleSoftWrap,
+    ToggleReadOnly,
+    CycleEarconTheme,
+    NextTodo,
+    NextHunk,
+    PreviousHunk,
+    EditNote,
+    RecordMemo,
+    PlayNote,
+    Reload,
+    SetVoice,
+    SpeakLine,
+    SpellWord,
+    SpeakColumns,
+    NextMisspelling,
+    PreviousMisspelling,
+    SpellingSuggestions,
+    ReadAndAdvance,
+    ReadToEnd,
+    MarkCompare,
+    ToggleAnchor,
+    Compare,
+    TrashLine,
+    RestoreLine,
+    DuplicateLine,
+    MoveLineUp,
+    MoveLineDown,
+    NextParagraph,
+    PreviousParagraph,
+    NextWord,
+    PreviousWord,
+    SetMark,
+    SelectUp,
+    SelectDown,
+    SelectLeft,
+    SelectRight,
+    Copy,
+    Cut,
+    Paste,
+    SpeakSelection,
+    SpeakSelectionSummary,
+    SpeakOutline,
+    ShellCommand,
+    RunBuild,
+    NextError,
+    PreviousError,
+    RecordKeyBinding,
+    SpeakScope,
+    MatchBracket,
+    PlayOverview,
+    StartMacro,
+    StopMacro,
+    ReplayMacro,
+    CommandPalette,
+    PreviousSpoken,
+    NextSpoken,
+    RepeatLastSpoken,
+    SpeakStatistics,
+    SaveWorkspace,
+    OpenWorkspace,
+    SpeakEditingTime,
+    SortLines,
+    DedupLines,
+    Undo,
+}
+
+impl Action {
+    pub const ALL: [Self; 99] = [
+        Self::MoveUp,
+        Self::MoveDown,
+        Self::MoveLeft,
+        Self::MoveRight,
+        Self::PageUp,
+        Self::PageDown,
+        Self::LineStart,
+        Self::LineEnd,
+        Self::DeleteForward,
+        Self::DeleteBackward,
+        Self::Quit,
+        Self::Save,
+        Self::OpenFile,
+        Self::NextBuffer,
+        Self::ListBuffers,
+        Self::Search,
+        Self::FindAll,
+        Self::Replace,
+        Self::GoToLine,
+        Self::InsertDigraph,
+        Self::CompleteWord,
+        Self::SpeakLocation,
+        Self::SpeakStatusBar,
+        Self::ToggleVerbosity,
+        Self::CyclePunctuation,
+        Self::ToggleNumbers,
+        Self::CycleTypingEcho,
+        Self::SpeechFaster,
+        Self::SpeechSlower,
+        Self::SpeechLouder,
+        Self::SpeechQuieter,
+        Self::TonesLouder,
+        Self::TonesQuieter,
+        Self::ToggleSilent,
+        Self::ToggleNightMode,
+        Self::ToggleSoftWrap,
+        Self::ToggleReadOnly,
+        Self::CycleEarconTheme,
+        Self::NextTodo,
+        Self::NextHunk,
+        Self::PreviousHunk,
+        Self::EditNote,
+        Self::RecordMemo,
+        Self::PlayNote,
+        Self::Reload,
+        Self::SetVoice,
+        Self::SpeakLine,
+        Self::SpellWord,
+        Self::SpeakColumns,
+        Self::NextMisspelling,
+        Self::PreviousMisspelling,
+        Self::SpellingSuggestions,
+        Self::ReadAndAdvance,
+        Self::ReadToEnd,
+        Self::MarkCompare,
+        Self::ToggleAnchor,
+        Self::Compare,
+        Self::TrashLine,
+        Self::RestoreLine,
+        Self::DuplicateLine,
+        Self::MoveLineUp,
+        Self::MoveLineDown,
+        Self::NextParagraph,
+        Self::PreviousParagraph,
+        Self::NextWord,
+        Self::PreviousWord,
+        Self::SetMark,
+        Self::SelectUp,
+        Self::SelectDown,
+        Self::SelectLeft,
+        Self::SelectRight,
+        Self::Copy,
+        Self::Cut,
+        Self::Paste,
+        Self::SpeakSelection,
+        Self::SpeakSelectionSummary,
+        Self::SpeakOutline,
+        Self::ShellCommand,
+        Self::RunBuild,
+        Self::NextError,
+        Self::PreviousError,
+        Self::RecordKeyBinding,
+        Self::SpeakScope,
+        Self::MatchBracket,
+        Self::PlayOverview,
+        Self::StartMacro,
+        Self::StopMacro,
+        Self::ReplayMacro,
+        Self::CommandPalette,
+        Self::PreviousSpoken,
+        Self::NextSpoken,
+        Self::RepeatLastSpoken,
+        Self::SpeakStatistics,
+        Self::SaveWorkspace,
+        Self::OpenWorkspace,
+        Self::SpeakEditingTime,
+        Self::SortLines,
+        Self::DedupLines,
+        Self::Undo,
+    ];
+
+    /// The name used for this action in the `[keys]` config table.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::MoveUp => "move_up",
+            Self::MoveDown => "move_down",
+            Self::MoveLeft => "move_left",
+            Self::MoveRight => "move_right",
+            Self::PageUp => "page_up",
+            Self::PageDown => "page_down",
+            Self::LineStart => "line_start",
+            Self::LineEnd => "line_end",
+            Self::DeleteForward => "delete_forward",
+            Self::DeleteBackward => "delete_backward",
+            Self::Quit => "quit",
+            Self::Save => "save",
+            Self::OpenFile => "open_file",
+            Self::NextBuffer => "next_buffer",
+            Self::ListBuffers => "list_buffers",
+            Self::Search => "search",
+            Self::FindAll => "find_all",
+            Self::Replace => "replace",
+            Self::GoToLine => "go_to_line",
+            Self::InsertDigraph => "insert_digraph",
+            Self::CompleteWord => "complete_word",
+            Self::SpeakLocation => "speak_location",
+            Self::SpeakStatusBar => "speak_status_bar",
+            Self::ToggleVerbosity => "toggle_verbosity",
+            Self::CyclePunctuation => "cycle_punctuation",
+            Self::ToggleNumbers => "toggle_numbers",
+            Self::CycleTypingEcho => "cycle_typing_echo",
+            Self::SpeechFaster => "speech_faster",
+            Self::SpeechSlower => "speech_slower",
+            Self::SpeechLouder => "speech_louder",
+            Self::SpeechQuieter => "speech_quieter",
+            Self::TonesLouder => "tones_louder",
+            Self::TonesQuieter => "tones_quieter",
+            Self::ToggleSilent => "toggle_silent",
+            Self::ToggleNightMode => "toggle_night_mode",
+            Self::ToggleSoftWrap => "toggle_soft_wrap",
+            Self::ToggleReadOnly => "toggle_read_only",
+            Self::CycleEarconTheme => "cycle_earcon_theme",
+            Self::NextTodo => "next_todo",
+            Self::NextHunk => "next_hunk",
+            Self::PreviousHunk => "previous_hunk",
+            Self::EditNote => "edit_note",
+            Self::RecordMemo => "record_memo",
+            Self::PlayNote => "play_note",
+            Self::Reload => "reload",
+            Self::SetVoice => "set_voice",
+            Self::SpeakLine => "speak_line",
+            Self::SpellWord => "spell_word",
+            Self::SpeakColumns => "speak_columns",
+            Self::NextMisspelling => "next_misspelling",
+            Self::PreviousMisspelling => "previous_misspelling",
+            Self::SpellingSuggestions => "spelling_suggestions",
+            Self::ReadAndAdvance => "read_and_advance",
+            Self::ReadToEnd => "read_to_end",
+            Self::MarkCompare => "mark_compare",
+            Self::ToggleAnchor => "toggle_anchor",
+            Self::Compare => "compare",
+            Self::TrashLine => "trash_line",
+            Self::RestoreLine => "restore_line",
+            Self::DuplicateLine => "duplicate_line",
+            Self::MoveLineUp => "move_line_up",
+            Self::MoveLineDown => "move_line_down",
+            Self::NextParagraph => "next_paragraph",
+            Self::PreviousParagraph => "previous_paragraph",
+            Self::NextWord => "next_word",
+            Self::PreviousWord => "previous_word",
+            Self::SetMark => "set_mark",
+            Self::SelectUp => "select_up",
+            Self::SelectDown => "select_down",
+            Self::SelectLeft => "select_left",
+            Self::SelectRight => "select_right",
+            Self::Copy => "copy",
+            Self::Cut => "cut",
+            Self::Paste => "paste",
+            Self::SpeakSelection => "speak_selection",
+            Self::SpeakSelectionSummary => "speak_selection_summary",
+            Self::SpeakOutline => "speak_outline",
+            Self::ShellCommand => "shell_command",
+            Self::RunBuild => "run_build",
+            Self::NextError => "next_error",
+            Self::PreviousError => "previous_error",
+            Self::RecordKeyBinding => "record_key_binding",
+            Self::SpeakScope => "speak_scope",
+            Self::MatchBracket => "match_bracket",
+            Self::PlayOverview => "play_overview",
+            Self::StartMacro => "start_macro",
+            Self::StopMacro => "stop_macro",
+            Self::ReplayMacro => "replay_macro",
+            Self::CommandPalette => "command_palette",
+            Self::PreviousSpoken => "previous_spoken",
+            Self::NextSpoken => "next_spoken",
+            Self::RepeatLastSpoken => "repeat_last_spoken",
+            Self::SpeakStatistics => "speak_statistics",
+            Self::SaveWorkspace => "save_workspace",
+            Self::OpenWorkspace => "open_workspace",
+            Self::SpeakEditingTime => "speak_editing_time",
+            Self::SortLines => "sort_lines",
+            Self::DedupLines => "dedup_lines",
+            Self::Undo => "undo",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|action| action.name() == name)
+    }
+
+    /// The action's name as it should be spoken, e.g. "speak line".
+    pub fn spoken_name(self) -> String {
+        self.name().replace('_', " ")
+    }
+}
+
+const DEFAULT_BINDINGS: &[(Key, Action)] = &[
+    (Key::Up, Action::MoveUp),
+    (Key::Down, Action::MoveDown),
+    (Key::Left, Action::MoveLeft),
+    (Key::Right, Action::MoveRight),
+    (Key::PageUp, Action::PageUp),
+    (Key::PageDown, Action::PageDown),
+    (Key::Home, Action::LineStart),
+    (Key::End, Action::LineEnd),
+    (Key::Delete, Action::DeleteForward),
+    (Key::Backspace, Action::DeleteBackward),
+    (Key::Ctrl('q'), Action::Quit),
+    (Key::Ctrl('s'), Action::Save),
+    (Key::Ctrl('o'), Action::OpenFile),
+    (Key::Ctrl('b'), Action::NextBuffer),
+    (Key::Alt('B'), Action::ListBuffers),
+    (Key::Ctrl('f'), Action::Search),
+    (Key::Alt('F'), Action::FindAll),
+    (Key::Ctrl('r'), Action::Replace),
+    (Key::Ctrl('g'), Action::GoToLine),
+    (Key::Ctrl('k'), Action::InsertDigraph),
+    (Key::Ctrl('n'), Action::CompleteWord),
+    (Key::Alt(';'), Action::SpeakLocation),
+    (Key::Alt(','), Action::SpeakStatusBar),
+    (Key::Alt('a'), Action::ToggleVerbosity),
+    (Key::Alt('P'), Action::CyclePunctuation),
+    (Key::Alt('#'), Action::ToggleNumbers),
+    (Key::Alt('e'), Action::CycleTypingEcho),
+    (Key::Alt('+'), Action::SpeechFaster),
+    (Key::Alt('-'), Action::SpeechSlower),
+    (Key::Alt('Q'), Action::SpeechLouder),
+    (Key::Alt('q'), Action::SpeechQuieter),
+    (Key::Alt('G'), Action::TonesLouder),
+    (Key::Alt('g'), Action::TonesQuieter),
+    (Key::Alt('s'), Action::ToggleSilent),
+    (Key::Alt('N'), Action::ToggleNightMode),
+    (Key::Alt('z'), Action::ToggleSoftWrap),
+    (Key::Alt('T'), Action::CycleEarconTheme),
+    (Key::Alt('t'), Action::NextTodo),
+    (Key::Alt('h'), Action::NextHunk),
+    (Key::Alt('H'), Action::PreviousHunk),
+    (Key::Alt('n'), Action::EditNote),
+    (Key::Alt('m'), Action::RecordMemo),
+    (Key::Alt('p'), Action::PlayNote),
+    (Key::Alt('u'), Action::Reload),
+    (Key::Alt('L'), Action::SetVoice),
+    (Key::Alt('l'), Action::SpeakLine),
+    (Key::Alt('.'), Action::SpellWord),
+    (Key::Alt('|'), Action::SpeakColumns),
+    (Key::Alt('S'), Action::NextMisspelling),
+    (Key::Alt('A'), Action::PreviousMisspelling),
+    (Key::Alt('?'), Action::SpellingSuggestions),
+    (Key::Alt('j'), Action::ReadAndAdvance),
+    (Key::Alt('r'), Action::ReadToEnd),
+    (Key::Alt('C'), Action::MarkCompare),
+    (Key::Alt('X'), Action::ToggleAnchor),
+    (Key::Alt('c'), Action::Compare),
+    (Key::Alt('d'), Action::TrashLine),
+    (Key::Alt('D'), Action::RestoreLine),
+    (Key::Ctrl('d'), Action::DuplicateLine),
+    (Key::AltUp, Action::MoveLineUp),
+    (Key::AltDown, Action::MoveLineDown),
+    (Key::Alt('}'), Action::NextParagraph),
+    (Key::Alt('{'), Action::PreviousParagraph),
+    // Ctrl-Right and Ctrl-Left are read as these too; see the terminal backends.
+    (Key::Alt('f'), Action::NextWord),
+    (Key::Alt('b'), Action::PreviousWord),
+    // Terminals send Ctrl-Space as a null byte.
+    (Key::Null, Action::SetMark),
+    (Key::ShiftUp, Action::SelectUp),
+    (Key::ShiftDown, Action::SelectDown),
+    (Key::ShiftLeft, Action::SelectLeft),
+    (Key::ShiftRight, Action::SelectRight),
+    (Key::Ctrl('c'), Action::Copy),
+    (Key::Ctrl('x'), Action::Cut),
+    (Key::Ctrl('v'), Action::Paste),
+    (Key::Alt('v'), Action::SpeakSelection),
+    (Key::Alt('V'), Action::SpeakSelectionSummary),
+    // Alt-Shift-O would start a function key sequence; I is for index.
+    (Key::Alt('I'), Action::SpeakOutline),
+    (Key::Alt('!'), Action::ShellCommand),
+    (Key::Alt('R'), Action::RunBuild),
+    (Key::Alt('E'), Action::NextError),
+    (Key::Alt('Y'), Action::PreviousError),
+    (Key::Alt('K'), Action::RecordKeyBinding),
+    (Key::Alt('k'), Action::SpeakScope),
+    (Key::Ctrl(']'), Action::MatchBracket),
+    (Key::Alt('M'), Action::PlayOverview),
+    // Terminals can't send Ctrl-( or Ctrl-): they have no control code, so
+    // the Alt versions start and stop a macro instead.
+    (Key::Alt('('), Action::StartMacro),
+    (Key::Alt(')'), Action::StopMacro),
+    (Key::Ctrl('e'), Action::ReplayMacro),
+    (Key::Alt('x'), Action::CommandPalette),
+    (Key::Alt('<'), Action::PreviousSpoken),
+    (Key::Alt('>'), Action::NextSpoken),
+    (Key::Alt('\\'), Action::RepeatLastSpoken),
+    (Key::Alt('='), Action::SpeakStatistics),
+    (Key::Alt('w'), Action::SaveWorkspace),
+    (Key::Alt('W'), Action::OpenWorkspace),
+    (Key::Alt('i'), Action::SpeakEditingTime),
+    (Key::Alt('o'), Action::SortLines),
+    (Key::Alt('U'), Action::DedupLines),
+    (Key::Ctrl('z'), Action::Undo),
+    // Toggling read-only mode is rare enough to be left to the command
+    // palette, though a key can still be bound to it.
+];
+
+/// The key chords bound to each editor action.
+pub struct KeyMap {
+    bindings: Vec<(Key, Action)>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self {
+            bindings: DEFAULT_BINDINGS.to_vec(),
+        }
+    }
+}
+
+impl KeyMap {
+    /// Build a keymap from the defaults and the user's bindings.
+    ///
+    /// A user binding replaces the action's default chord. When two actions
+    /// end up on the same chord, the user's binding wins over a default, and
+    /// otherwise the first binding wins.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_bindings` - (action name, chord) pairs from the `[keys]` table.
+    ///
+    /// # Returns
+    ///
+    /// The keymap, and a spoken description of each problem found, so that
+    /// no binding is silently ignored.
+    ///
+    pub fn with_user_bindings(user_bindings: &[(String, String)]) -> (Self, Vec<String>) {
+        let mut problems = Vec::new();
+        let mut user = Vec::new();
+        for (action_name, chord) in user_bindings {
+            let action = match Action::from_name(action_name) {
+                Some(action) => action,
+                None => {
+                    problems.push(format!("Unknown key binding action {}.", action_name));
+                    continue;
+                }
+            };
+            let key = match parse_chord(chord) {
+                Some(key) => key,
+                None => {
+                    problems.push(format!(
+                        "Can't read the key {} for {}.",
+                        chord,
+                        action.spoken_name()
+                    ));
+                    continue;
+                }
+            };
+            user.push((key, action));
+        }
+
+        let defaults = DEFAULT_BINDINGS
+            .iter()
+            .filter(|(_, action)| !user.iter().any(|(_, bound)| bound == action))
+            .copied();
+        let mut bindings: Vec<(Key, Action)> = Vec::new();
+        for (key, action) in user.iter().copied().chain(defaults) {
+            match bindings.iter().find(|(bound, _)| *bound == key) {
+                Some((_, winner)) => problems.push(format!(
+                    "{} is bound to both {} and {}. Using {}.",
+                    chord_name(key),
+                    winner.spoken_name(),
+                    action.spoken_name(),
+                    winner.spoken_name()
+                )),
+                None => {
+                    // The defaults are checked too: tmux, for one, takes
+                    // a key clack binds.
+                    if let Some(reason) = shadowing_reason(key) {
+                        problems.push(format!(
+                            "{} for {} never reaches clack: {}.",
+                            chord_name(key),
+                            action.spoken_name(),
+                            reason
+                        ));
+                    }
+                    bindings.push((key, action));
+                }
+            }
+        }
+        (Self { bindings }, problems)
+    }
+
+    /// Get the key an action is bound to, if any.
+    pub fn key_for(&self, action: Action) -> Option<Key> {
+        self.bindings
+            .iter()
+            .find(|(_, bound)| *bound == action)
+            .map(|(key, _)| *key)
+    }
+
+    /// Get the action bound to a key, if any.
+    pub fn action_for(&self, key: Key) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, action)| *action)
+    }
+}
+
+/// Keys that are bound by name alone, with the names used in the config
+/// file and in speech.
+const NAMED_KEYS: &[(&str, &str, Key)] = &[
+    ("up", "up arrow", Key::Up),
+    ("down", "down arrow", Key::Down),
+    ("left", "left arrow", Key::Left),
+    ("right", "right arrow", Key::Right),
+    ("pageup", "page up", Key::PageUp),
+    ("pagedown", "page down", Key::PageDown),
+    ("home", "home", Key::Home),
+    ("end", "end", Key::End),
+    ("delete", "delete", Key::Delete),
+    ("backspace", "backspace", Key::Backspace),
+    ("alt-up", "alt up arrow", Key::AltUp),
+    ("alt-down", "alt down arrow", Key::AltDown),
+    ("shift-up", "shift up arrow", Key::ShiftUp),
+    ("shift-down", "shift down arrow", Key::ShiftDown),
+    ("shift-left", "shift left arrow", Key::ShiftLeft),
+    ("shift-right", "shift right arrow", Key::ShiftRight),
+];
+
+/// Parse a chord like `ctrl-s`, `alt-L`, `ctrl-space` or `pageup` from the
+/// config file.
+///
+/// Control chords are case-insensitive, since terminals can't tell them
+/// apart; Alt chords are case-sensitive, so `alt-L` is Alt-Shift-L.
+///
+/// # Returns
+///
+/// The key, or None if the chord isn't one clack can bind.
+///
+pub fn parse_chord(chord: &str) -> Option<Key> {
+    if let Some((_, _, key)) = NAMED_KEYS
+        .iter()
+        .find(|(name, _, _)| name.eq_ignore_ascii_case(chord))
+    {
+        return Some(*key);
+    }
+    let (modifier, rest) = chord.split_once('-')?;
+    let rest = if rest.eq_ignore_ascii_case("space") {
+        " "
+    } else {
+        rest
+    };
+    let mut chars = rest.chars();
+    let c = chars.next()?;
+    if chars.next().is_some() {
+        return None;
+    }
+    match modifier.to_lowercase().as_str() {
+        "ctrl" | "control" if c == ' ' => Some(Key::Null),
+        "ctrl" | "control" => Some(Key::Ctrl(c.to_ascii_lowercase())),
+        "alt" | "meta" => Some(Key::Alt(c)),
+        _ => None,
+    }
+}
+
+/// Write a key the way the `[keys]` table does, e.g. "ctrl-s" or "alt-L".
+///
+/// # Returns
+///
+/// The chord, or None if the key isn't one clack can bind.
+///
+pub fn chord_config_name(key: Key) -> Option<String> {
+    match key {
+        Key::Null => Some(String::from("ctrl-space")),
+        Key::Ctrl(c) => Some(format!("ctrl-{}", c)),
+        Key::Alt(' ') => Some(String::from("alt-space")),
+        Key::Alt(c) => Some(format!("alt-{}", c)),
+        key => NAMED_KEYS
+            .iter()
+            .find(|(_, _, named)| *named == key)
+            .map(|(name, _, _)| name.to_string()),
+    }
+}
+
+/// Describe a chord for speech, e.g. "control S" or "alt shift L".
+pub fn chord_name(key: Key) -> String {
+    let spoken = |c: char| {
+        if c == ' ' {
+            return String::from("space");
+        }
+        default_echo_name(&c.to_string())
+            .map(String::from)
+            .unwrap_or_else(|| c.to_uppercase().to_string())
+    };
+    match key {
+        Key::Null => String::from("control space"),
+        Key::Ctrl(c) => format!("control {}", spoken(c)),
+        Key::Alt(c) if c.is_uppercase() => format!("alt shift {}", spoken(c)),
+        Key::Alt(c) => format!("alt {}", spoken(c)),
+        key => NAMED_KEYS
+            .iter()
+            .find(|(_, _, named)| *named == key)
+            .map_or_else(
+                || String::from("that key"),
+                |(_, spoken, _)| spoken.to_string(),
+            ),
+    }
+}
+
+/// Explain why a chord would never reach the editor, if it wouldn't.
+pub fn shadowing_reason(key: Key) -> Option<&'static str> {
+    match key {
+        Key::Ctrl('i') => Some("the terminal sends it as tab"),
+        Key::Ctrl('j' | 'm') => Some("the terminal sends it as enter"),
+        Key::Ctrl('[') => Some("the terminal sends it as escape"),
+        Key::Alt('[' | 'O') => Some("the terminal uses it to start other keys"),
+        Key::Ctrl('b') if env::var_os("TMUX").is_some() => Some("tmux uses it as its prefix"),
+        Key::Ctrl('a') if env::var_os("STY").is_some() => Some("screen uses it as its prefix"),
+        _ => None,
+    }
+}
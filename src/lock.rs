@@ -0,0 +1,102 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{self, Command},
+};
+
+/// An advisory lock file marking a document as open in this clack session.
+///
+/// The lock lives next to the document as `.<name>.clack-lock` and holds the
+/// owning process id. It is removed when the lock is dropped.
+pub struct FileLock {
+    path: PathBuf,
+}
+
+/// The outcome of trying to lock a document.
+pub enum LockStatus {
+    /// The lock was acquired by this session.
+    Acquired(FileLock),
+    /// Another running clack process (with this pid) holds the lock.
+    HeldBy(u32),
+}
+
+impl FileLock {
+    /// Try to lock a document.
+    ///
+    /// Stale locks left behind by processes that are no longer running are
+    /// silently taken over.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - The path of the document to lock.
+    ///
+    /// # Returns
+    ///
+    /// The lock status, or an error if the lock file could not be written.
+    ///
+    pub fn acquire(file_name: &str) -> Result<LockStatus, io::Error> {
+        let path = lock_path_for(file_name);
+        // Creating the lock only succeeds if nobody else has one, so two
+        // sessions opening the same document can't both think they won.
+        // A stale lock is removed and creation retried once.
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(process::id().to_string().as_bytes())?;
+                    return Ok(LockStatus::Acquired(Self { path }));
+                }
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+                    let holder = fs::read_to_string(&path)
+                        .ok()
+                        .and_then(|contents| contents.trim().parse::<u32>().ok());
+                    if let Some(pid) = holder {
+                        if pid != process::id() && process_is_running(pid) {
+                            return Ok(LockStatus::HeldBy(pid));
+                        }
+                    }
+                    match fs::remove_file(&path) {
+                        Ok(()) => {}
+                        Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                        Err(error) => return Err(error),
+                    }
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "the lock file keeps reappearing",
+        ))
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn lock_path_for(file_name: &str) -> PathBuf {
+    let path = Path::new(file_name);
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.clack-lock", name))
+}
+
+fn process_is_running(pid: u32) -> bool {
+    let proc_dir = Path::new("/proc");
+    if proc_dir.exists() {
+        return proc_dir.join(pid.to_string()).exists();
+    }
+    // Without procfs, ask the OS. If we can't tell, assume the owner is alive
+    // rather than risk clobbering someone else's session.
+    Command::new("kill")
+        .arg("-0")
+        .arg(pid.to_string())
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(true)
+}
@@ -7,19 +7,68 @@
     clippy::wildcard_enum_match_arm,
     clippy::else_if_without_else
 )]
+mod atomic;
+mod brackets;
+mod braille;
+mod cli;
+mod clipboard;
+mod columns;
+mod completion;
 mod config;
+mod crypt;
+mod diff;
+mod digraph;
+mod doctor;
 mod document;
+mod earcon;
 mod editor;
+mod git;
+mod journal;
+mod keymap;
+mod lock;
+mod notes;
+mod notifications;
+mod outline;
+mod overview;
+mod palette;
+mod paths;
+mod quickfix;
 mod row;
+mod rows;
+mod session;
+mod sort;
 mod sound;
+mod speech;
+mod spelling;
+mod stats;
+mod swap;
+mod symbols;
 mod terminal;
 mod utils;
+mod verbosity;
+use cli::Command;
 pub use document::Document;
 use editor::Editor;
 pub use editor::Position;
 pub use row::Row;
+use std::{env, process};
 pub use terminal::Terminal;
 
 fn main() {
-    Editor::default().run();
+    match cli::parse(env::args().skip(1)) {
+        Ok(Command::Edit(options)) => Editor::new(options).run(),
+        Ok(Command::Doctor) => process::exit(if doctor::run() { 0 } else { 1 }),
+        Ok(Command::WriteDefaultConfig) => match config::write_default_config() {
+            Ok(path) => println!("Wrote the default settings to {}.", path.display()),
+            Err(problem) => {
+                eprintln!("{}", problem);
+                process::exit(1);
+            }
+        },
+        Ok(Command::Help) => print!("{}", cli::USAGE),
+        Err(problem) => {
+            eprintln!("{}\n\n{}", problem, cli::USAGE);
+            process::exit(2);
+        }
+    }
 }
@@ -0,0 +1,236 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use toml::{value::Table, Value};
+
+use crate::diff::{map_line, Hunk};
+
+/// A note attached to a single line of a document.
+#[derive(Clone)]
+pub enum Note {
+    /// A text note, spoken back with the speech engine.
+    Text(String),
+    /// A recorded audio memo, stored as a file next to the document.
+    Audio(PathBuf),
+}
+
+/// The notes attached to the lines of a document.
+///
+/// Notes are stored in a sidecar file named `.<name>.clack-notes` next to
+/// the document, and recorded memos in a `.<name>.clack-memos` directory.
+#[derive(Clone, Default)]
+pub struct LineNotes {
+    notes: BTreeMap<usize, Note>,
+}
+
+impl LineNotes {
+    /// Load the notes for a document, if it has a sidecar file.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - The path of the document.
+    ///
+    /// # Returns
+    ///
+    /// The notes, or an empty set if there is no sidecar or it can't be read.
+    ///
+    pub fn load(file_name: &str) -> Self {
+        let mut notes = BTreeMap::new();
+        let contents = fs::read_to_string(sidecar_path_for(file_name)).unwrap_or_default();
+        let parsed = contents
+            .parse::<Value>()
+            .unwrap_or(Value::Table(Table::new()));
+        let entries = parsed
+            .get("note")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        for entry in entries {
+            let line = match entry
+                .get("line")
+                .and_then(Value::as_integer)
+                .and_then(|line| usize::try_from(line).ok())
+            {
+                Some(line) => line,
+                None => continue,
+            };
+            if let Some(text) = entry.get("text").and_then(Value::as_str) {
+                notes.insert(line, Note::Text(text.to_string()));
+            } else if let Some(audio) = entry.get("audio").and_then(Value::as_str) {
+                notes.insert(line, Note::Audio(PathBuf::from(audio)));
+            }
+        }
+        Self { notes }
+    }
+
+    /// Write the notes to the document's sidecar file.
+    ///
+    /// The sidecar is removed when there are no notes left.
+    ///
+    pub fn save(&self, file_name: &str) -> Result<(), io::Error> {
+        let path = sidecar_path_for(file_name);
+        if self.notes.is_empty() {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+            return Ok(());
+        }
+        let entries = self
+            .notes
+            .iter()
+            .map(|(line, note)| {
+                let mut entry = Table::new();
+                #[allow(clippy::cast_possible_wrap)]
+                entry.insert("line".to_string(), Value::Integer(*line as i64));
+                match note {
+                    Note::Text(text) => {
+                        entry.insert("text".to_string(), Value::String(text.clone()));
+                    }
+                    Note::Audio(audio) => {
+                        entry.insert(
+                            "audio".to_string(),
+                            Value::String(audio.to_string_lossy().to_string()),
+                        );
+                    }
+                }
+                Value::Table(entry)
+            })
+            .collect();
+        let mut root = Table::new();
+        root.insert("note".to_string(), Value::Array(entries));
+        fs::write(path, Value::Table(root).to_string())
+    }
+
+    pub fn get(&self, line: usize) -> Option<&Note> {
+        self.notes.get(&line)
+    }
+
+    pub fn set(&mut self, line: usize, note: Note) {
+        self.notes.insert(line, note);
+    }
+
+    pub fn remove(&mut self, line: usize) -> Option<Note> {
+        self.notes.remove(&line)
+    }
+
+    /// Shift notes to follow a line inserted after `line`.
+    pub fn line_inserted_after(&mut self, line: usize) {
+        self.line_inserted_at(line.saturating_add(1));
+    }
+
+    /// Shift notes to follow a line inserted at `line`, pushing the line
+    /// that was there down.
+    pub fn line_inserted_at(&mut self, line: usize) {
+        self.notes = std::mem::take(&mut self.notes)
+            .into_iter()
+            .map(|(index, note)| {
+                if index >= line {
+                    (index.saturating_add(1), note)
+                } else {
+                    (index, note)
+                }
+            })
+            .collect();
+    }
+
+    /// Drop the note on a removed line and shift the notes below it up.
+    pub fn line_removed(&mut self, line: usize) {
+        self.notes.remove(&line);
+        self.notes = std::mem::take(&mut self.notes)
+            .into_iter()
+            .map(|(index, note)| {
+                if index > line {
+                    (index.saturating_sub(1), note)
+                } else {
+                    (index, note)
+                }
+            })
+            .collect();
+    }
+
+    /// Move notes to follow a run of lines being reordered.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The first line of the run.
+    /// * `order` - For each new position in the run, the offset of the line
+    ///   that moved there.
+    ///
+    pub fn lines_reordered(&mut self, start: usize, order: &[usize]) {
+        let moved: Vec<(usize, Note)> = order
+            .iter()
+            .enumerate()
+            .filter_map(|(to, from)| {
+                self.notes
+                    .get(&start.saturating_add(*from))
+                    .map(|note| (start.saturating_add(to), note.clone()))
+            })
+            .collect();
+        for offset in 0..order.len() {
+            self.notes.remove(&start.saturating_add(offset));
+        }
+        self.notes.extend(moved);
+    }
+
+    /// Move notes to follow the lines of a document being replaced, as
+    /// when it's reloaded. A note on a changed line stays near it.
+    ///
+    /// If two notes land on one line, the first is kept.
+    ///
+    pub fn lines_changed(&mut self, hunks: &[Hunk]) {
+        let mut moved = BTreeMap::new();
+        for (index, note) in std::mem::take(&mut self.notes) {
+            moved.entry(map_line(hunks, index)).or_insert(note);
+        }
+        self.notes = moved;
+    }
+
+    /// Shift notes to follow the line after `line` being joined onto it.
+    ///
+    /// If both lines had a note, the note on `line` is kept.
+    ///
+    pub fn line_joined_into(&mut self, line: usize) {
+        let mut shifted = BTreeMap::new();
+        for (index, note) in std::mem::take(&mut self.notes) {
+            let index = if index > line {
+                index.saturating_sub(1)
+            } else {
+                index
+            };
+            shifted.entry(index).or_insert(note);
+        }
+        self.notes = shifted;
+    }
+}
+
+fn sidecar_path_for(file_name: &str) -> PathBuf {
+    dotfile_path_for(file_name, "clack-notes")
+}
+
+/// Get a fresh path to record a memo for a document into.
+///
+/// Memos are named by recording time rather than line number, since the
+/// line a memo is attached to moves as the document is edited.
+///
+pub fn new_memo_path_for(file_name: &str) -> Result<PathBuf, io::Error> {
+    let dir = dotfile_path_for(file_name, "clack-memos");
+    fs::create_dir_all(&dir)?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    Ok(dir.join(format!("memo-{}.wav", timestamp)))
+}
+
+fn dotfile_path_for(file_name: &str, suffix: &str) -> PathBuf {
+    let path = Path::new(file_name);
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.{}", name, suffix))
+}
@@ -0,0 +1,72 @@
+/// The terminal notifications found in a command's output: bells, and the
+/// messages sent with OSC 9 (as `printf '\e]9;Done\a'` does), which
+/// terminal emulators show as desktop notifications.
+#[derive(Default)]
+pub struct Notifications {
+    /// How many bells rang outside of an escape sequence.
+    pub bells: usize,
+    /// The text of each OSC 9 notification, in order.
+    pub messages: Vec<String>,
+}
+
+impl Notifications {
+    pub fn is_empty(&self) -> bool {
+        self.bells == 0 && self.messages.is_empty()
+    }
+}
+
+/// Take the terminal escape sequences out of a command's output, keeping
+/// the bells and notifications in it to be played instead.
+///
+/// Colours and other CSI sequences, window titles and other OSC sequences
+/// are dropped, as they'd otherwise be read out or shown as junk. OSC 9;4,
+/// the progress bar some terminals draw, isn't a notification.
+///
+/// # Returns
+///
+/// The text without escape sequences, and the notifications found.
+///
+pub fn extract(output: &str) -> (String, Notifications) {
+    let mut text = String::with_capacity(output.len());
+    let mut notifications = Notifications::default();
+    let mut chars = output.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x07' => notifications.bells += 1,
+            '\x1b' => match chars.next() {
+                Some('[') => {
+                    // Parameters and intermediates, up to the final byte.
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                Some(']') => {
+                    let mut body = String::new();
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '\x07' => break,
+                            '\x1b' => {
+                                if chars.peek() == Some(&'\\') {
+                                    chars.next();
+                                }
+                                break;
+                            }
+                            c => body.push(c),
+                        }
+                    }
+                    if let Some(message) = body.strip_prefix("9;") {
+                        if !message.starts_with("4;") && !message.trim().is_empty() {
+                            notifications.messages.push(message.trim().to_string());
+                        }
+                    }
+                }
+                // A two-character sequence, like ESC 7.
+                _ => {}
+            },
+            c => text.push(c),
+        }
+    }
+    (text, notifications)
+}
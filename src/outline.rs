@@ -0,0 +1,184 @@
+use std::path::Path;
+
+use crate::utils::SpeechOptions;
+use crate::Document;
+
+/// The extensions of files outlined by their headings rather than their
+/// definitions.
+const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown"];
+
+/// Keywords that start a definition worth listing, across the languages
+/// clack is most used with.
+const DEFINITION_KEYWORDS: &[&str] = &[
+    "fn",
+    "struct",
+    "enum",
+    "trait",
+    "impl",
+    "mod",
+    "macro_rules!",
+    "def",
+    "class",
+    "function",
+    "func",
+    "interface",
+    "type",
+];
+
+/// Words that can come before a definition's keyword, like `pub` or
+/// `async`.
+const MODIFIERS: &[&str] = &[
+    "pub",
+    "pub(crate)",
+    "pub(super)",
+    "async",
+    "unsafe",
+    "const",
+    "extern",
+    "export",
+    "default",
+    "static",
+    "public",
+    "private",
+    "protected",
+    "abstract",
+    "final",
+];
+
+/// A heading or definition in a document's outline.
+pub struct Entry {
+    /// The row it's on.
+    pub y: usize,
+    /// How deeply it's nested, counting from 0: a heading's level, or a
+    /// definition's indentation.
+    pub depth: usize,
+    pub title: String,
+}
+
+/// Get a document's outline: its headings if it's Markdown, or else the
+/// functions, types and classes it defines.
+pub fn outline(document: &Document, options: &SpeechOptions) -> Vec<Entry> {
+    if is_markdown(document) {
+        headings(document)
+    } else {
+        definitions(document, options)
+    }
+}
+
+/// Get the entries of the outline that row `y` is inside, outermost first:
+/// the chain of headings above it, or the definitions enclosing it.
+///
+/// A definition encloses the rows after it until one is indented no deeper
+/// than it, like its closing brace or the next definition beside it.
+pub fn enclosing(document: &Document, options: &SpeechOptions, y: usize) -> Vec<Entry> {
+    let markdown = is_markdown(document);
+    let mut entries = outline(document, options)
+        .into_iter()
+        .filter(|entry| entry.y <= y)
+        .rev()
+        .peekable();
+    let mut chain = Vec::new();
+    // Only an entry shallower than everything between it and row `y` can
+    // enclose it.
+    let mut depth_limit = usize::MAX;
+    for y in (0..=y).rev() {
+        if let Some(entry) = entries.next_if(|entry| entry.y == y) {
+            if entry.depth < depth_limit {
+                depth_limit = entry.depth;
+                chain.push(entry);
+            }
+        }
+        if markdown {
+            continue;
+        }
+        if let Some(row) = document.get_row(y).filter(|row| !row.is_blank()) {
+            depth_limit = depth_limit.min(row.indent_level(options));
+        }
+    }
+    chain.reverse();
+    chain
+}
+
+/// Whether a document is outlined by its Markdown headings.
+pub fn is_markdown(document: &Document) -> bool {
+    document
+        .file_name
+        .as_deref()
+        .and_then(|file_name| Path::new(file_name).extension())
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| MARKDOWN_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+/// Find the Markdown headings, both `# Title` and underlined ones, outside
+/// of fenced code blocks.
+fn headings(document: &Document) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut in_fence = false;
+    let mut previous: Option<&str> = None;
+    for y in 0..document.row_count() {
+        let line = document.get_row(y).map_or("", |row| row.as_str());
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            previous = None;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        let rest = &trimmed[level..];
+        if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' ')) {
+            entries.push(Entry {
+                y,
+                depth: level - 1,
+                title: rest.trim().trim_end_matches('#').trim().to_string(),
+            });
+            previous = None;
+            continue;
+        }
+        // An underline of = or - makes the line above it a heading.
+        let underline = !trimmed.is_empty()
+            && (trimmed.chars().all(|c| c == '=') || trimmed.chars().all(|c| c == '-'));
+        match previous {
+            Some(title) if underline && y > 0 => {
+                entries.push(Entry {
+                    y: y - 1,
+                    depth: usize::from(trimmed.starts_with('-')),
+                    title: title.to_string(),
+                });
+                previous = None;
+            }
+            _ => previous = Some(trimmed).filter(|line| !line.is_empty()),
+        }
+    }
+    entries
+}
+
+/// Find the lines that define a function, type or class.
+fn definitions(document: &Document, options: &SpeechOptions) -> Vec<Entry> {
+    (0..document.row_count())
+        .filter_map(|y| {
+            let row = document.get_row(y)?;
+            let mut words = row.as_str().split_whitespace().peekable();
+            while words.next_if(|word| MODIFIERS.contains(word)).is_some() {}
+            let keyword = words
+                .next()
+                .filter(|word| DEFINITION_KEYWORDS.contains(word))?;
+            // A keyword alone on its line, or followed by punctuation, isn't
+            // defining anything.
+            words.peek().filter(|name| {
+                name.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '<')
+            })?;
+            let title = std::iter::once(keyword)
+                .chain(words)
+                .collect::<Vec<&str>>()
+                .join(" ");
+            Some(Entry {
+                y,
+                depth: row.indent_level(options),
+                title: title.trim_end_matches(['{', ':']).trim_end().to_string(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,56 @@
+use crate::sound::{Audible, SoundSequence, Tone, PENTATONIC_SCALE};
+use crate::utils::SpeechOptions;
+use crate::Document;
+
+/// The most tones an overview plays; longer files have several lines to a
+/// tone, so that any file takes a couple of seconds.
+const MAX_TONES: usize = 120;
+
+/// How long each tone lasts, in seconds.
+const TONE_DURATION: f32 = 0.02;
+
+/// The line length, in characters, that's played at full volume.
+const FULL_VOLUME_LENGTH: usize = 80;
+
+/// The volume of the longest lines, and of the shortest that aren't blank.
+const MAX_VOLUME: f32 = 0.5;
+const MIN_VOLUME: f32 = 0.08;
+
+/// The deepest indentation given its own pitch.
+const MAX_DEPTH: usize = 12;
+
+/// Sonify a whole document, a tone for each line (or run of lines, in a
+/// long file): higher for deeper indentation, louder for longer lines, and
+/// silent for blank ones, to hear the shape of the file at a glance.
+pub fn overview(document: &Document, options: &SpeechOptions) -> SoundSequence {
+    let lines = document.row_count();
+    let group = lines.div_ceil(MAX_TONES).max(1);
+    let tones = (0..lines)
+        .step_by(group)
+        .map(|start| {
+            let rows = (start..(start + group).min(lines))
+                .filter_map(|y| document.get_row(y))
+                .filter(|row| !row.is_blank())
+                .collect::<Vec<_>>();
+            if rows.is_empty() {
+                return Tone::new(PENTATONIC_SCALE[0], TONE_DURATION, 0.0);
+            }
+            let depth = rows
+                .iter()
+                .map(|row| row.indent_level(options))
+                .max()
+                .unwrap_or(0)
+                .min(MAX_DEPTH);
+            let length = rows.iter().map(|row| row.len()).sum::<usize>() / rows.len();
+            // Climb the pentatonic scale a step for each level, an octave
+            // every five levels.
+            let octave = 2.0_f32.powi((depth / PENTATONIC_SCALE.len()) as i32);
+            let frequency = PENTATONIC_SCALE[depth % PENTATONIC_SCALE.len()] * octave;
+            let loudness = length.min(FULL_VOLUME_LENGTH) as f32 / FULL_VOLUME_LENGTH as f32;
+            let volume = MIN_VOLUME + (MAX_VOLUME - MIN_VOLUME) * loudness;
+            Tone::new(frequency, TONE_DURATION, volume)
+        })
+        .map(|tone| Box::new(tone) as Box<dyn Audible>)
+        .collect();
+    SoundSequence::new(tones)
+}
@@ -0,0 +1,41 @@
+use crate::keymap::Action;
+
+/// Find the commands whose names match a fuzzy query, best first.
+///
+/// A name matches if it has the query's letters in order, not necessarily
+/// together, so "spln" finds "speak line". Matches with the letters
+/// together, or at the starts of words, come first; ties keep the order
+/// of `Action::ALL`.
+pub fn matching(query: &str) -> Vec<Action> {
+    let mut scored: Vec<(usize, Action)> = Action::ALL
+        .iter()
+        .filter(|action| **action != Action::CommandPalette)
+        .filter_map(|action| Some((score(query, &action.spoken_name())?, *action)))
+        .collect();
+    scored.sort_by_key(|(score, _)| *score);
+    scored.into_iter().map(|(_, action)| action).collect()
+}
+
+/// Score how well a name matches a query, lower being better.
+///
+/// # Returns
+///
+/// The score, or None if the name doesn't have the query's letters in
+/// order.
+///
+fn score(query: &str, name: &str) -> Option<usize> {
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    let mut chars = name.char_indices();
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let (i, _) = chars.find(|(_, c)| c.eq_ignore_ascii_case(&wanted))?;
+        let starts_word = i == 0 || name[..i].ends_with(' ');
+        score += match previous {
+            Some(previous) if previous + 1 == i => 0,
+            _ if starts_word => 1,
+            _ => 3,
+        };
+        previous = Some(i);
+    }
+    Some(score)
+}
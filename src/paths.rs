@@ -0,0 +1,131 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// A file or directory in a directory listing.
+pub struct Entry {
+    pub name: String,
+    pub is_dir: bool,
+    /// The size in bytes, or 0 for a directory.
+    pub size: u64,
+}
+
+impl Entry {
+    /// Describe the entry for speech: "src, directory" or "main.rs, file,
+    /// 3 kilobytes".
+    pub fn describe(&self) -> String {
+        if self.is_dir {
+            format!("{}, directory", self.name)
+        } else {
+            format!("{}, file, {}", self.name, describe_size(self.size))
+        }
+    }
+}
+
+/// The result of completing a path with Tab.
+pub struct Completion {
+    /// The path with its last component completed as far as it can be.
+    pub path: String,
+    /// The entries the last component could still become.
+    pub candidates: Vec<Entry>,
+}
+
+/// Split a path as it's being typed into its directory, ending in a
+/// separator if there is one, and the start of the last component.
+pub fn split(typed: &str) -> (&str, &str) {
+    match typed.rfind(is_separator) {
+        Some(index) => typed.split_at(index + 1),
+        None => ("", typed),
+    }
+}
+
+/// Get the directory a typed directory part refers to: the working
+/// directory if it's empty, with `~` standing for the home directory.
+pub fn resolve(dir: &str) -> PathBuf {
+    if dir.is_empty() {
+        return PathBuf::from(".");
+    }
+    match dir
+        .strip_prefix('~')
+        .and_then(|rest| Some((dirs::home_dir()?, rest)))
+    {
+        Some((home, rest)) => home.join(rest.trim_start_matches(is_separator)),
+        None => PathBuf::from(dir),
+    }
+}
+
+/// List a directory's entries whose names start with a prefix, directories
+/// first and then files, each in alphabetical order ignoring case. Hidden
+/// entries are only listed when the prefix starts with a dot.
+pub fn entries(dir: &Path, prefix: &str) -> Result<Vec<Entry>, io::Error> {
+    let mut entries: Vec<Entry> = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            // Follow symlinks, so a link to a directory can be entered.
+            let metadata = fs::metadata(entry.path()).ok()?;
+            Some(Entry {
+                name,
+                is_dir: metadata.is_dir(),
+                size: if metadata.is_dir() { 0 } else { metadata.len() },
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    Ok(entries)
+}
+
+/// Complete the last component of a typed path as far as the entries it
+/// could be agree. A single match is completed whole, with a separator
+/// after it if it's a directory.
+pub fn complete(typed: &str) -> Completion {
+    let (dir, prefix) = split(typed);
+    let candidates = entries(&resolve(dir), prefix).unwrap_or_default();
+    let path = match &candidates[..] {
+        [] => typed.to_string(),
+        [only] if only.is_dir => format!("{}{}/", dir, only.name),
+        [only] => format!("{}{}", dir, only.name),
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.name.as_str(), |common, entry| {
+                let len = common
+                    .char_indices()
+                    .zip(entry.name.chars())
+                    .take_while(|((_, a), b)| a == b)
+                    .last()
+                    .map_or(0, |((index, c), _)| index + c.len_utf8());
+                &common[..len]
+            });
+            format!("{}{}", dir, common)
+        }
+    };
+    Completion { path, candidates }
+}
+
+/// Describe a size in bytes for speech, e.g. "3 kilobytes".
+pub fn describe_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["bytes", "kilobytes", "megabytes", "gigabytes"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match (unit, bytes) {
+        (_, 1) => String::from("1 byte"),
+        (0, _) => format!("{} bytes", bytes),
+        _ if size < 10.0 => format!("{:.1} {}", size, UNITS[unit]),
+        _ => format!("{:.0} {}", size, UNITS[unit]),
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || c == std::path::MAIN_SEPARATOR
+}
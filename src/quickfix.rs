@@ -0,0 +1,74 @@
+/// A problem reported at a place in a file, like a compiler error, or a
+/// match printed by `grep -n`.
+pub struct Diagnostic {
+    pub file_name: String,
+    /// The line, counting from 1.
+    pub line: usize,
+    /// The column, counting from 1, if one was given.
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+/// Find the diagnostics in a command's output.
+///
+/// Lines like `src/main.rs:12:5: error: ...`, as printed by `cargo check
+/// --message-format short`, gcc, eslint's unix format and `grep -n`, are
+/// read as a diagnostic each. rustc's long format, where the message comes
+/// on the line before `--> src/main.rs:12:5`, is read too.
+pub fn parse(output: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    // The last `error: ...` or `warning: ...` line, for rustc's long format.
+    let mut heading: Option<&str> = None;
+    for line in output.lines() {
+        let trimmed = line.trim_start();
+        if let Some(location) = trimmed.strip_prefix("--> ") {
+            if let Some(mut diagnostic) = parse_location(location) {
+                diagnostic.message = heading.take().unwrap_or_default().to_string();
+                diagnostics.push(diagnostic);
+            }
+            continue;
+        }
+        if trimmed.starts_with("error") || trimmed.starts_with("warning") {
+            heading = Some(trimmed);
+        }
+        if line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        if let Some(diagnostic) = parse_location(line).filter(|found| !found.message.is_empty()) {
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+/// Read `file:line`, `file:line:column`, and any message after another
+/// colon.
+fn parse_location(text: &str) -> Option<Diagnostic> {
+    // Skip a Windows drive letter, so `C:\src\main.c:3:1` splits after it.
+    let drive = match text.as_bytes() {
+        [letter, b':', b'\\' | b'/', ..] if letter.is_ascii_alphabetic() => 2,
+        _ => 0,
+    };
+    let (file_name, rest) = text[drive..].split_once(':')?;
+    let file_name = format!("{}{}", &text[..drive], file_name);
+    if file_name.trim().is_empty() || file_name.contains(char::is_whitespace) {
+        return None;
+    }
+    let (line, after) = rest.split_once(':').unwrap_or((rest, ""));
+    let line = line.trim().parse::<usize>().ok()?;
+    let (column, message) = match after.split_once(':') {
+        Some((column, message)) if column.trim().parse::<usize>().is_ok() => {
+            (column.trim().parse().ok(), message)
+        }
+        _ => match after.trim().parse::<usize>() {
+            Ok(column) => (Some(column), ""),
+            Err(_) => (None, after),
+        },
+    };
+    Some(Diagnostic {
+        file_name,
+        line,
+        column,
+        message: message.trim().to_string(),
+    })
+}
@@ -1,8 +1,7 @@
 use crate::{
-    sound::{self, SoundManager, Tone, Utterance},
-    utils::{string_to_speakable_tokens, SearchDirection},
+    sound::{Audible, SoundManager, SoundSequence, Utterance},
+    utils::{split_token_classes, string_to_speakable_tokens, SearchDirection, SpeechOptions},
 };
-use std::cmp;
 use unicode_segmentation::UnicodeSegmentation;
 
 #[derive(Default)]
@@ -23,36 +22,77 @@ impl From<&str> for Row {
 }
 
 impl Row {
-    /// Render a row to a string.
+    /// Render a row to a string, with tabs expanded to spaces.
     ///
     /// # Arguments
     ///
-    /// * `start` - The index of the first character to render.
-    /// * `end` - The index of the last character to render.
+    /// * `start` - The first screen column to render.
+    /// * `end` - The screen column to stop before.
+    /// * `tab_width` - How many columns apart tab stops are.
     ///
     /// # Returns
     ///
     /// A string containing the rendered row.
     ///
-    pub fn render(&self, start: usize, end: usize) -> String {
-        let end = cmp::min(end, self.string.len());
-        let start = cmp::min(start, end);
+    pub fn render(&self, start: usize, end: usize, tab_width: usize) -> String {
         let mut result = String::new();
-        for grapheme in self.string[..]
-            .graphemes(true)
-            .skip(start)
-            .take(end - start)
-        {
+        let mut column = 0;
+        for grapheme in self.string[..].graphemes(true) {
+            if column >= end {
+                break;
+            }
+            let width = grapheme_width(grapheme, column, tab_width);
             if grapheme == "\t" {
-                // TODO: This is bad
-                result.push_str(" ");
-            } else {
+                // A tab cut by the edge of the screen shows the part in view.
+                for space in column..column + width {
+                    if (start..end).contains(&space) {
+                        result.push(' ');
+                    }
+                }
+            } else if column >= start {
                 result.push_str(grapheme);
             }
+            column += width;
         }
         result
     }
 
+    /// Get the screen column a character is drawn at, counting from the
+    /// start of the row, with tabs expanded.
+    pub fn render_x(&self, x: usize, tab_width: usize) -> usize {
+        self.string[..]
+            .graphemes(true)
+            .take(x)
+            .fold(0, |column, grapheme| {
+                column + grapheme_width(grapheme, column, tab_width)
+            })
+    }
+
+    /// Get the character drawn at a screen column, the inverse of
+    /// `render_x`. A column past the end of the row gives its length.
+    pub fn x_at_render_x(&self, render_x: usize, tab_width: usize) -> usize {
+        let mut column = 0;
+        for (x, grapheme) in self.string[..].graphemes(true).enumerate() {
+            column += grapheme_width(grapheme, column, tab_width);
+            if column > render_x {
+                return x;
+            }
+        }
+        self.len
+    }
+
+    /// Get the text between two character indices.
+    ///
+    /// Unlike `render`, the text is returned exactly as it is in the row.
+    ///
+    pub fn slice(&self, start: usize, end: usize) -> String {
+        self.string[..]
+            .graphemes(true)
+            .skip(start)
+            .take(end.saturating_sub(start))
+            .collect()
+    }
+
     /// Get the length of the row (cached)
     ///
     /// # Returns
@@ -77,74 +117,63 @@ impl Row {
         self.len == 0
     }
 
+    /// Whether the row is empty or contains only whitespace.
+    pub fn is_blank(&self) -> bool {
+        self.string.trim().is_empty()
+    }
+
     pub fn update_len(&mut self) {
         self.len = self.string[..].graphemes(true).count();
     }
 
+    /// Get the byte offset of the character at an index, or the end of
+    /// the row if the index is past it.
+    fn byte_index(&self, at: usize) -> usize {
+        self.string
+            .grapheme_indices(true)
+            .nth(at)
+            .map_or(self.string.len(), |(index, _)| index)
+    }
+
     pub fn insert(&mut self, at: usize, c: char) {
         if at >= self.len() {
             self.string.push(c);
-            // let mut result: String = self.string[..].graphemes(true).take(at).collect();
-            self.len += 1;
-            return;
-        }
-        let mut result: String = String::new();
-        let mut length = 0;
-        for (index, grapheme) in self.string[..].graphemes(true).enumerate() {
-            length += 1;
-            if index == at {
-                length += 1;
-                result.push(c);
-            }
-            result.push_str(grapheme);
+        } else {
+            let index = self.byte_index(at);
+            self.string.insert(index, c);
         }
-        self.len = length;
-        self.string = result;
+        // A combining character joins the grapheme before it, so count again
+        // rather than assuming the row grew by one.
+        self.update_len();
     }
 
     pub fn delete(&mut self, at: usize) {
         if at >= self.len() {
             return;
         }
-        let mut result: String = String::new();
-        let mut length = 0;
-        for (index, grapheme) in self.string[..].graphemes(true).enumerate() {
-            if index != at {
-                length += 1;
-                result.push_str(grapheme);
-            }
-        }
-        self.len = length;
-        self.string = result;
+        let start = self.byte_index(at);
+        let end = self.string[start..]
+            .graphemes(true)
+            .next()
+            .map_or(start, |grapheme| start + grapheme.len());
+        self.string.replace_range(start..end, "");
+        self.len -= 1;
     }
 
     pub fn append(&mut self, new: &Self) {
-        self.string = format!("{}{}", self.string, new.string);
+        self.string.push_str(&new.string);
         self.update_len();
     }
 
     pub fn split(&mut self, at: usize) -> Self {
-        let mut row: String = String::new();
-        let mut length = 0;
-        let mut split_row: String = String::new();
-        let mut split_length = 0;
-
-        for (index, grapheme) in self.string[..].graphemes(true).enumerate() {
-            if index < at {
-                length += 1;
-                row.push_str(grapheme);
-            } else {
-                split_length += 1;
-                split_row.push_str(grapheme);
-            }
-        }
-
-        self.len = length;
-        self.string = row;
-        Self {
-            string: split_row,
-            len: split_length,
-        }
+        let index = self.byte_index(at);
+        let mut split_row = Self {
+            string: self.string.split_off(index),
+            len: 0,
+        };
+        split_row.update_len();
+        self.len -= split_row.len;
+        split_row
     }
 
     pub fn as_bytes(&self) -> &[u8] {
@@ -167,53 +196,165 @@ impl Row {
         None
     }
 
-    pub fn play_blocking(&self, manager: &mut SoundManager) {
+    /// Get the words in the row, skipping whitespace and punctuation.
+    ///
+    /// # Returns
+    ///
+    /// Each word, with the index of its first character.
+    ///
+    pub fn words(&self) -> Vec<(usize, &str)> {
+        let mut words = Vec::new();
+        let mut index = 0;
+        for (_, token) in self.get_tokens_and_indices() {
+            if token.chars().any(char::is_alphanumeric) {
+                words.push((index, token));
+            }
+            index += token.graphemes(true).count();
+        }
+        words
+    }
+
+    /// Find the word ending at a column if it repeats the word before it,
+    /// with only whitespace between them, like "the the". Numbers aren't
+    /// counted, since they often repeat on purpose.
+    pub fn repeated_word_ending_at(&self, x: usize) -> Option<&str> {
+        let mut tokens = Vec::new();
+        let mut end = 0;
+        for (_, token) in self.get_tokens_and_indices() {
+            end += token.graphemes(true).count();
+            tokens.push((end, token));
+        }
+        let is_space = |token: &str| token.chars().all(char::is_whitespace);
+        let at = tokens
+            .iter()
+            .position(|(end, token)| *end == x && token.chars().any(char::is_alphabetic))?;
+        let word = tokens[at].1;
+        let before = &tokens[..at];
+        if !before.last().is_some_and(|(_, token)| is_space(token)) {
+            return None;
+        }
+        let previous = before.iter().rev().find(|(_, token)| !is_space(token))?.1;
+        (previous.to_lowercase() == word.to_lowercase()).then_some(word)
+    }
+
+    /// Get the cells of the row, read as a table row: cells are separated
+    /// by tabs, pipes, or runs of two or more spaces.
+    ///
+    /// # Returns
+    ///
+    /// Each non-empty cell, with the index of its first character.
+    ///
+    pub fn cells(&self) -> Vec<(usize, String)> {
+        let graphemes: Vec<&str> = self.string.graphemes(true).collect();
+        let is_gap = |grapheme: &str| matches!(grapheme, " " | "\t" | "|");
+        let mut cells = Vec::new();
+        let mut start = None;
+        let mut index = 0;
+        while index < graphemes.len() {
+            if !is_gap(graphemes[index]) {
+                start.get_or_insert(index);
+                index += 1;
+                continue;
+            }
+            let gap_start = index;
+            while index < graphemes.len() && is_gap(graphemes[index]) {
+                index += 1;
+            }
+            // A single space is just a space inside the cell.
+            let separates = index - gap_start > 1 || graphemes[gap_start] != " ";
+            if separates {
+                if let Some(start) = start.take() {
+                    cells.push((start, graphemes[start..gap_start].concat()));
+                }
+            }
+        }
+        if let Some(start) = start {
+            cells.push((start, graphemes[start..].concat()));
+        }
+        cells
+    }
+
+    pub fn play_blocking(&self, manager: &mut SoundManager, options: &SpeechOptions) {
         // Represent leading tabs with tones.
-        let indent_level = self.string.chars().take_while(|c| *c == '\t').count();
-        // TODO: Space indent fixed size:
-        let indent_space_level = self.string.chars().take_while(|c| *c == ' ').count() / 4;
-        let indent_level = indent_level + indent_space_level;
-        let duration = 0.15;
-        let volume: f32 = 0.5;
-        for indent in 0..indent_level {
-            manager.play_and_wait(Box::new(Tone::new(
-                *sound::PENTATONIC_SCALE
-                    .get(indent % sound::PENTATONIC_SCALE.len())
-                    .unwrap(),
-                duration,
-                volume,
-            )));
+        let levels = if options.indentation.plays_tones() {
+            self.indent_level(options)
+        } else {
+            0
+        };
+        for indent in 0..levels {
+            if let Some(tone) = manager.indent_tone(indent) {
+                manager.play_and_wait(Box::new(tone));
+            }
         }
 
         // Play the rest of the row:
-        let utterance = Utterance::new(string_to_speakable_tokens(&self.string, None));
-        manager.play_and_wait(Box::new(utterance))
+        for utterance in self.utterances(options) {
+            manager.play_and_wait(Box::new(utterance));
+        }
     }
 
-    pub fn play(&self, manager: &mut SoundManager) {
+    pub fn play(&self, manager: &mut SoundManager, options: &SpeechOptions) {
         // Represent leading tabs with tones.
-        let indent_level = self.string.chars().take_while(|c| *c == '\t').count();
-        // TODO: Space indent fixed size:
-        let indent_space_level = self.string.chars().take_while(|c| *c == ' ').count() / 4;
-        let indent_level = indent_level + indent_space_level;
-        // TONES:
-        // D: 36.6666 E: 41.15625 F#: 46.40625 A: 55 B: 61.875
-        let duration = 0.15;
-        let volume: f32 = 0.5;
-        let tones = vec![
-            Tone::new(8.0 * 36.6666, duration, volume),
-            Tone::new(8.0 * 41.15625, duration, volume),
-            Tone::new(8.0 * 46.40625, duration, volume),
-            Tone::new(8.0 * 55.0, duration, volume),
-            Tone::new(8.0 * 61.875, duration, volume),
-        ];
-        for indent in 0..indent_level {
-            manager.play_and_wait(Box::new(*tones.get(indent % tones.len()).unwrap()));
+        let levels = if options.indentation.plays_tones() {
+            self.indent_level(options)
+        } else {
+            0
+        };
+        for indent in 0..levels {
+            if let Some(tone) = manager.indent_tone(indent) {
+                manager.append(Box::new(tone));
+            }
         }
 
-        // Play the rest of the row:
-        let utterance = Utterance::new(string_to_speakable_tokens(&self.string, None));
-        manager.append(Box::new(utterance))
+        // Play the rest of the row, as one sound so that it's one entry in
+        // the speech history:
+        let utterances = self
+            .utterances(options)
+            .into_iter()
+            .map(|utterance| Box::new(utterance) as Box<dyn Audible>)
+            .collect();
+        manager.append(Box::new(SoundSequence::new(utterances)));
+    }
+
+    /// Count the row's levels of indentation: a tab, or `tab_width` spaces,
+    /// for each. Tabs after spaces reach the next tab stop, as they're drawn.
+    pub fn indent_level(&self, options: &SpeechOptions) -> usize {
+        let tab_width = options.tab_width.max(1);
+        self.render_x(self.leading_whitespace().chars().count(), tab_width) / tab_width
+    }
+
+    /// Get the spaces and tabs the row starts with.
+    pub fn leading_whitespace(&self) -> &str {
+        let end = self
+            .string
+            .find(|c| c != ' ' && c != '\t')
+            .unwrap_or(self.string.len());
+        &self.string[..end]
+    }
+
+    /// Build the speech for the row: one utterance, or, if strings or
+    /// comments have their own voices, one for each run of them.
+    fn utterances(&self, options: &SpeechOptions) -> Vec<Utterance> {
+        let speak = |text: &str| {
+            Utterance::from_text_and_wpm(
+                string_to_speakable_tokens(text, options),
+                options.rate_wpm,
+            )
+            .with_voice(options.voice.clone())
+            .with_pan(options.stereo_speech)
+            .with_optional_parameters(options.context_voices.content.as_ref())
+        };
+        if options.token_voices.is_empty() {
+            return vec![speak(&self.string)];
+        }
+        split_token_classes(&self.string)
+            .into_iter()
+            .filter(|(_, text)| !text.trim().is_empty())
+            .map(|(class, text)| match options.token_voices.get(class) {
+                Some(parameters) => speak(text).with_parameters(parameters),
+                None => speak(text),
+            })
+            .collect()
     }
 
     pub fn find(&self, query: &str, at: usize, direction: SearchDirection) -> Option<usize> {
@@ -253,3 +394,14 @@ impl Row {
         None
     }
 }
+
+/// Get how many screen columns a grapheme takes up at a column: a tab
+/// reaches the next tab stop, and anything else takes one.
+fn grapheme_width(grapheme: &str, column: usize, tab_width: usize) -> usize {
+    if grapheme == "\t" {
+        let tab_width = tab_width.max(1);
+        tab_width - column % tab_width
+    } else {
+        1
+    }
+}
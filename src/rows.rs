@@ -0,0 +1,230 @@
+use crate::Row;
+use std::ops::{Index, IndexMut, Range};
+
+/// The most rows a chunk holds before it is split in two.
+const MAX_CHUNK_LEN: usize = 512;
+
+/// The rows of a document, kept in small chunks with a Fenwick tree over
+/// the chunk lengths.
+///
+/// Finding a row takes O(log n) steps, and inserting or removing one only
+/// shifts the rows in its own chunk, so editing near the top of a very
+/// large file doesn't move every row below it.
+///
+#[derive(Default)]
+pub struct Rows {
+    chunks: Vec<Vec<Row>>,
+    /// The Fenwick tree of chunk lengths, indexed from 1.
+    tree: Vec<usize>,
+    len: usize,
+}
+
+impl Rows {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, y: usize) -> Option<&Row> {
+        let (chunk, offset) = self.locate(y)?;
+        self.chunks.get(chunk)?.get(offset)
+    }
+
+    pub fn get_mut(&mut self, y: usize) -> Option<&mut Row> {
+        let (chunk, offset) = self.locate(y)?;
+        self.chunks.get_mut(chunk)?.get_mut(offset)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Row> {
+        self.chunks.iter().flatten()
+    }
+
+    pub fn push(&mut self, row: Row) {
+        self.insert(self.len, row);
+    }
+
+    /// Insert a row, shifting the rows after it down.
+    ///
+    /// # Panics
+    ///
+    /// If `y` is past the end of the rows.
+    ///
+    pub fn insert(&mut self, y: usize, row: Row) {
+        assert!(y <= self.len, "row index {} out of range", y);
+        let (chunk, offset) = if y == self.len {
+            if self.chunks.is_empty() {
+                self.chunks.push(Vec::new());
+                self.rebuild();
+            }
+            let last = self.chunks.len() - 1;
+            (last, self.chunks[last].len())
+        } else {
+            self.locate(y).unwrap_or_default()
+        };
+        self.chunks[chunk].insert(offset, row);
+        self.len += 1;
+        if self.chunks[chunk].len() > MAX_CHUNK_LEN {
+            let half = self.chunks[chunk].split_off(MAX_CHUNK_LEN / 2);
+            self.chunks.insert(chunk + 1, half);
+            self.rebuild();
+        } else {
+            self.grow(chunk);
+        }
+    }
+
+    /// Insert several rows, the first of them at `y`.
+    pub fn insert_many<I: IntoIterator<Item = Row>>(&mut self, y: usize, rows: I) {
+        for (index, row) in rows.into_iter().enumerate() {
+            self.insert(y + index, row);
+        }
+    }
+
+    /// Remove a row, shifting the rows after it up.
+    ///
+    /// # Panics
+    ///
+    /// If `y` is past the end of the rows.
+    ///
+    pub fn remove(&mut self, y: usize) -> Row {
+        let (chunk, offset) = match self.locate(y) {
+            Some(location) => location,
+            None => panic!("row index {} out of range", y),
+        };
+        let row = self.chunks[chunk].remove(offset);
+        self.len -= 1;
+        if self.chunks[chunk].is_empty() {
+            self.chunks.remove(chunk);
+            self.rebuild();
+        } else {
+            self.shrink(chunk);
+        }
+        row
+    }
+
+    /// Remove a run of rows.
+    ///
+    /// # Returns
+    ///
+    /// The removed rows, in order.
+    ///
+    pub fn remove_range(&mut self, range: Range<usize>) -> Vec<Row> {
+        let start = range.start;
+        range.map(|_| self.remove(start)).collect()
+    }
+
+    /// Replace a run of rows with others, which needn't be as many.
+    pub fn replace_range<I: IntoIterator<Item = Row>>(&mut self, range: Range<usize>, rows: I) {
+        let start = range.start;
+        self.remove_range(range);
+        self.insert_many(start, rows);
+    }
+
+    /// Find the chunk holding a row, and the row's offset within it.
+    fn locate(&self, y: usize) -> Option<(usize, usize)> {
+        if y >= self.len {
+            return None;
+        }
+        // Walk down the tree to the last chunk boundary at or before `y`.
+        let mut chunk = 0;
+        let mut offset = y;
+        let mut step = self.chunks.len().checked_next_power_of_two()?;
+        while step > 0 {
+            let next = chunk + step;
+            if next <= self.chunks.len() && self.tree[next] <= offset {
+                chunk = next;
+                offset -= self.tree[next];
+            }
+            step /= 2;
+        }
+        Some((chunk, offset))
+    }
+
+    /// Count a row added to a chunk.
+    fn grow(&mut self, chunk: usize) {
+        let mut index = chunk + 1;
+        while index < self.tree.len() {
+            self.tree[index] += 1;
+            index += index & index.wrapping_neg();
+        }
+    }
+
+    /// Count a row removed from a chunk.
+    fn shrink(&mut self, chunk: usize) {
+        let mut index = chunk + 1;
+        while index < self.tree.len() {
+            self.tree[index] -= 1;
+            index += index & index.wrapping_neg();
+        }
+    }
+
+    /// Rebuild the tree after chunks are added or removed.
+    fn rebuild(&mut self) {
+        let count = self.chunks.len();
+        self.tree = vec![0; count + 1];
+        for index in 1..=count {
+            self.tree[index] += self.chunks[index - 1].len();
+            let parent = index + (index & index.wrapping_neg());
+            if parent <= count {
+                self.tree[parent] += self.tree[index];
+            }
+        }
+    }
+}
+
+impl FromIterator<Row> for Rows {
+    fn from_iter<I: IntoIterator<Item = Row>>(rows: I) -> Self {
+        // Fill chunks halfway, leaving room to type before one splits.
+        let mut chunks = Vec::new();
+        let mut chunk = Vec::new();
+        let mut len = 0;
+        for row in rows {
+            chunk.push(row);
+            len += 1;
+            if chunk.len() == MAX_CHUNK_LEN / 2 {
+                chunks.push(std::mem::take(&mut chunk));
+            }
+        }
+        if !chunk.is_empty() {
+            chunks.push(chunk);
+        }
+        let mut rows = Self {
+            chunks,
+            tree: Vec::new(),
+            len,
+        };
+        rows.rebuild();
+        rows
+    }
+}
+
+impl<'a> IntoIterator for &'a Rows {
+    type Item = &'a Row;
+    type IntoIter = std::iter::Flatten<std::slice::Iter<'a, Vec<Row>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.chunks.iter().flatten()
+    }
+}
+
+impl Index<usize> for Rows {
+    type Output = Row;
+
+    fn index(&self, y: usize) -> &Row {
+        match self.get(y) {
+            Some(row) => row,
+            None => panic!("row index {} out of range", y),
+        }
+    }
+}
+
+impl IndexMut<usize> for Rows {
+    fn index_mut(&mut self, y: usize) -> &mut Row {
+        match self.get_mut(y) {
+            Some(row) => row,
+            None => panic!("row index {} out of range", y),
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use dirs::home_dir;
+use toml::{value::Table, Value};
+
+const SESSION_PATH: &str = ".config/clack/session.toml";
+
+/// State that clack remembers between runs, like per-file settings.
+///
+/// The store is a TOML file with one table per file, keyed by the file's
+/// absolute path:
+///
+/// ```toml
+/// [files."/home/me/notes/README.md"]
+/// voice = "Anna"
+///
+/// [files."/home/me/notes/README.md".editing_seconds]
+/// 2022-09-14 = 2880
+///
+/// [[workspaces.thesis.buffers]]
+/// file = "/home/me/thesis/chapter1.md"
+/// x = 0
+/// y = 40
+/// ```
+pub struct SessionStore {
+    path: Option<PathBuf>,
+    data: Table,
+}
+
+/// A buffer remembered as part of a named workspace.
+pub struct WorkspaceBuffer {
+    /// The absolute path of the file.
+    pub file_name: String,
+    /// The cursor position in the buffer.
+    pub x: usize,
+    pub y: usize,
+}
+
+impl SessionStore {
+    /// Load the session store from the user's config directory.
+    ///
+    /// A missing or unreadable store is treated as empty.
+    ///
+    pub fn load() -> Self {
+        let path = home_dir().map(|home| home.join(SESSION_PATH));
+        let data = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| contents.parse::<Value>().ok())
+            .and_then(|value| match value {
+                Value::Table(table) => Some(table),
+                _ => None,
+            })
+            .unwrap_or_default();
+        Self { path, data }
+    }
+
+    /// Write the session store back to disk.
+    pub fn save(&self) -> Result<(), io::Error> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, Value::Table(self.data.clone()).to_string())
+    }
+
+    /// Get a remembered value for a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - The path of the file, as given to clack.
+    /// * `key` - The name of the setting.
+    ///
+    /// # Returns
+    ///
+    /// The value, or None if nothing is remembered.
+    ///
+    pub fn get_file_value(&self, file_name: &str, key: &str) -> Option<&Value> {
+        self.data
+            .get("files")
+            .and_then(|files| files.get(file_key(file_name)))
+            .and_then(|file| file.get(key))
+    }
+
+    /// Remember (or, with None, forget) a value for a file and save the store.
+    pub fn set_file_value(
+        &mut self,
+        file_name: &str,
+        key: &str,
+        value: Option<Value>,
+    ) -> Result<(), io::Error> {
+        let files = self
+            .data
+            .entry("files")
+            .or_insert_with(|| Value::Table(Table::new()));
+        if let Value::Table(files) = files {
+            let file = files
+                .entry(file_key(file_name))
+                .or_insert_with(|| Value::Table(Table::new()));
+            if let Value::Table(file) = file {
+                match value {
+                    Some(value) => {
+                        file.insert(key.to_string(), value);
+                    }
+                    None => {
+                        file.remove(key);
+                    }
+                }
+            }
+        }
+        self.save()
+    }
+
+    /// Add active editing time for a file to today's total, and save the
+    /// store.
+    pub fn add_editing_time(&mut self, file_name: &str, seconds: u64) -> Result<(), io::Error> {
+        let mut days = self
+            .get_file_value(file_name, "editing_seconds")
+            .and_then(Value::as_table)
+            .cloned()
+            .unwrap_or_default();
+        let today = today();
+        let total = days
+            .get(&today)
+            .and_then(Value::as_integer)
+            .unwrap_or_default()
+            .saturating_add(i64::try_from(seconds).unwrap_or(i64::MAX));
+        days.insert(today, Value::Integer(total));
+        self.set_file_value(file_name, "editing_seconds", Some(Value::Table(days)))
+    }
+
+    /// Get the active editing time recorded for a file.
+    ///
+    /// # Returns
+    ///
+    /// The seconds spent today, and the seconds spent in total.
+    ///
+    pub fn editing_time(&self, file_name: &str) -> (u64, u64) {
+        let days = match self
+            .get_file_value(file_name, "editing_seconds")
+            .and_then(Value::as_table)
+        {
+            Some(days) => days,
+            None => return (0, 0),
+        };
+        let seconds = |value: &Value| {
+            value
+                .as_integer()
+                .and_then(|seconds| u64::try_from(seconds).ok())
+                .unwrap_or_default()
+        };
+        let today = days.get(&today()).map(seconds).unwrap_or_default();
+        let total = days.values().map(seconds).sum();
+        (today, total)
+    }
+
+    /// Get the names of the saved workspaces.
+    pub fn workspace_names(&self) -> Vec<String> {
+        self.data
+            .get("workspaces")
+            .and_then(Value::as_table)
+            .map(|workspaces| workspaces.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the buffers saved in a named workspace.
+    ///
+    /// # Returns
+    ///
+    /// The buffers, or None if there is no workspace with that name.
+    ///
+    pub fn workspace(&self, name: &str) -> Option<Vec<WorkspaceBuffer>> {
+        let buffers = self
+            .data
+            .get("workspaces")
+            .and_then(|workspaces| workspaces.get(name))
+            .and_then(|workspace| workspace.get("buffers"))
+            .and_then(Value::as_array)?;
+        let position = |buffer: &Value, key: &str| {
+            buffer
+                .get(key)
+                .and_then(Value::as_integer)
+                .and_then(|value| usize::try_from(value).ok())
+                .unwrap_or_default()
+        };
+        Some(
+            buffers
+                .iter()
+                .filter_map(|buffer| {
+                    Some(WorkspaceBuffer {
+                        file_name: buffer.get("file")?.as_str()?.to_string(),
+                        x: position(buffer, "x"),
+                        y: position(buffer, "y"),
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// Save a named workspace, replacing any with the same name, and save the
+    /// store.
+    pub fn set_workspace(
+        &mut self,
+        name: &str,
+        buffers: &[WorkspaceBuffer],
+    ) -> Result<(), io::Error> {
+        let buffers = buffers
+            .iter()
+            .map(|buffer| {
+                let mut entry = Table::new();
+                entry.insert(
+                    "file".to_string(),
+                    Value::String(file_key(&buffer.file_name)),
+                );
+                for (key, value) in [("x", buffer.x), ("y", buffer.y)] {
+                    entry.insert(
+                        key.to_string(),
+                        Value::Integer(i64::try_from(value).unwrap_or(i64::MAX)),
+                    );
+                }
+                Value::Table(entry)
+            })
+            .collect();
+        let mut workspace = Table::new();
+        workspace.insert("buffers".to_string(), Value::Array(buffers));
+        let workspaces = self
+            .data
+            .entry("workspaces")
+            .or_insert_with(|| Value::Table(Table::new()));
+        if let Value::Table(workspaces) = workspaces {
+            workspaces.insert(name.to_string(), Value::Table(workspace));
+        }
+        self.save()
+    }
+}
+
+/// Get today's date in the local time zone as YYYY-MM-DD, so that a day's
+/// editing time runs from the user's midnight to midnight. `date` knows the
+/// time zone; where it can't be run, the UTC date is used.
+fn today() -> String {
+    Command::new("date")
+        .arg("+%Y-%m-%d")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|date| date.trim().to_string())
+        .filter(|date| date.len() == 10)
+        .unwrap_or_else(utc_today)
+}
+
+/// Get today's date in UTC as YYYY-MM-DD.
+fn utc_today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let days = i64::try_from(seconds / 86_400).unwrap_or_default();
+    // Convert days since 1970-01-01 to a civil date (Howard Hinnant's
+    // days_from_civil, run backwards).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Get the key a file is stored under: its absolute path where possible.
+fn file_key(file_name: &str) -> String {
+    fs::canonicalize(file_name)
+        .unwrap_or_else(|_| Path::new(file_name).to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
@@ -0,0 +1,99 @@
+use std::cmp::Ordering;
+
+/// How lines are compared when sorting.
+#[derive(Clone, PartialEq)]
+pub enum SortKey {
+    /// By the whole line, character by character.
+    Lexicographic,
+    /// By the number at the start of the line. Lines without one sort last.
+    Numeric,
+    /// By one field of the line (counting from 1), split on `delimiter` or
+    /// on whitespace. Fields that are both numbers compare as numbers.
+    Column {
+        index: usize,
+        delimiter: Option<char>,
+    },
+}
+
+impl SortKey {
+    /// Describe the sort for speech, e.g. "numerically" or "by column 2".
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Lexicographic => String::from("alphabetically"),
+            Self::Numeric => String::from("numerically"),
+            Self::Column { index, .. } => format!("by column {}", index),
+        }
+    }
+}
+
+/// Work out the sorted order of some lines.
+///
+/// The sort is stable, so lines that compare equal keep their order.
+///
+/// # Arguments
+///
+/// * `lines` - The lines to sort.
+/// * `key` - How to compare lines.
+/// * `descending` - Whether to put the largest line first.
+///
+/// # Returns
+///
+/// The indices of `lines` in sorted order.
+///
+pub fn sorted_order(lines: &[&str], key: &SortKey, descending: bool) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..lines.len()).collect();
+    order.sort_by(|&a, &b| {
+        let ordering = compare(lines[a], lines[b], key);
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    order
+}
+
+fn compare(a: &str, b: &str, key: &SortKey) -> Ordering {
+    match key {
+        SortKey::Lexicographic => a.cmp(b),
+        SortKey::Numeric => compare_numbers(leading_number(a), leading_number(b)),
+        SortKey::Column { index, delimiter } => {
+            let a = field(a, *index, *delimiter);
+            let b = field(b, *index, *delimiter);
+            match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+                (Ok(x), Ok(y)) => compare_numbers(Some(x), Some(y)),
+                _ => a.cmp(b),
+            }
+        }
+    }
+}
+
+/// Compare two optional numbers, putting missing numbers last.
+fn compare_numbers(a: Option<f64>, b: Option<f64>) -> Ordering {
+    match (a, b) {
+        (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Parse the number at the start of a line, like `sort -n`.
+fn leading_number(line: &str) -> Option<f64> {
+    let line = line.trim_start();
+    let end = line
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && (c == '-' || c == '+'))))
+        .map_or(line.len(), |(i, _)| i);
+    line[..end].parse().ok()
+}
+
+/// Get a field of a line, counting from 1, or "" if there aren't enough.
+fn field(line: &str, index: usize, delimiter: Option<char>) -> &str {
+    let position = index.saturating_sub(1);
+    match delimiter {
+        Some(delimiter) => line.split(delimiter).nth(position),
+        None => line.split_whitespace().nth(position),
+    }
+    .unwrap_or("")
+}
@@ -1,12 +1,49 @@
 use std::{
     collections::VecDeque,
-    process::{Child, Command},
+    env,
+    fs::{self, File},
+    io::BufReader,
+    path::PathBuf,
+    process::{self, Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread,
     time::{Duration, Instant},
 };
 
-use rodio::{source::SineWave, OutputStream, Sink, Source};
+use serde::Deserialize;
 
-use crate::Row;
+use rodio::{
+    source::{ChannelVolume, SineWave},
+    Decoder, OutputStream, OutputStreamHandle, Sink, Source,
+};
+
+use crate::{
+    earcon::{Earcon, EarconTheme},
+    speech::{SpeechBackend, SpeechHandle},
+    utils::{SpeechOptions, VoiceParameters},
+    Row,
+};
+
+/// The default time, in milliseconds, that a low-priority sound may wait in
+/// the queue before it is considered stale and dropped.
+pub const DEFAULT_AUDIO_BUDGET_MS: u64 = 750;
+
+/// How often the audio worker checks whether the current sound has finished.
+const WORKER_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How loud everything is in night mode, as a fraction of the usual volume.
+const NIGHT_VOLUME: f32 = 0.3;
+
+/// How many utterances and keys the speech history keeps.
+const SPEECH_HISTORY_LENGTH: usize = 100;
+
+/// The shortest time a tone takes to fade in, in seconds, in night mode, so
+/// that earcons don't start with a click.
+const NIGHT_ATTACK: f32 = 0.02;
 
 pub const SCALE_NOTES_MAP: &[f32] = &[
     262.0, /* C  */
@@ -33,25 +70,273 @@ pub const PENTATONIC_SCALE: &[f32] = &[
 
 /// A trait for objects that can be played by the sound system.
 /// This is used to abstract away the underlying sound players.
-pub trait Audible {
-    /// Start playing the sound.
-    fn play(&self);
+pub trait Audible: Send {
+    /// Start playing the sound and return a handle to poll or stop it.
+    ///
+    /// This is what the SoundManager's worker thread uses, so that a sound
+    /// can be interrupted part of the way through.
+    ///
+    fn start(self: Box<Self>, context: &AudioContext) -> Box<dyn Playback>;
+
+    /// The text the sound speaks, if it's speech, for the speech history.
+    fn spoken_text(&self) -> Option<String> {
+        None
+    }
+}
+
+/// What the audio worker thread plays sounds with.
+pub struct AudioContext {
+    speech: Box<dyn SpeechBackend>,
+    /// The output stream is opened once and kept for the life of the worker:
+    /// opening one per sound adds latency, and tearing it down early cuts
+    /// sounds off.
+    output: Option<(OutputStream, OutputStreamHandle)>,
+    /// The volume of tones and clips, where 1.0 is full volume.
+    volume: f32,
+    /// The volume of speech, as a fraction of the speech engine's usual
+    /// volume.
+    speech_volume: f32,
+    /// How loud tones and clips are while speech is playing, as a fraction
+    /// of their volume.
+    ducking: f32,
+    /// Whether speech is playing, so tones and clips are ducked.
+    speaking: bool,
+    /// Where tones and clips are heard, from -1.0 (left) to 1.0 (right),
+    /// following the cursor along its line.
+    pan: f32,
+    /// Whether speech is panned with the cursor too.
+    pan_speech: bool,
+    /// Whether everything is played quietly and tones fade in softly.
+    night: bool,
+}
+
+impl AudioContext {
+    fn new(speech: Box<dyn SpeechBackend>) -> Self {
+        Self {
+            speech,
+            output: OutputStream::try_default().ok(),
+            volume: 1.0,
+            speech_volume: 1.0,
+            ducking: 1.0,
+            speaking: false,
+            pan: 0.0,
+            pan_speech: false,
+            night: false,
+        }
+    }
+
+    /// Get the volume to play tones and clips at, allowing for night mode
+    /// and for ducking under speech.
+    fn output_volume(&self) -> f32 {
+        let mut volume = self.volume;
+        if self.night {
+            volume *= NIGHT_VOLUME;
+        }
+        if self.speaking {
+            volume *= self.ducking;
+        }
+        volume
+    }
+
+    /// Get the volume to ask the speech engine for, or None for its usual
+    /// volume.
+    fn speech_volume(&self) -> Option<f32> {
+        let volume = if self.night {
+            self.speech_volume * NIGHT_VOLUME
+        } else {
+            self.speech_volume
+        };
+        (volume < 1.0).then_some(volume)
+    }
+
+    /// Get where speech that's panned by `pan` on its own is heard, once
+    /// it's moved with the cursor too.
+    fn speech_pan(&self, pan: f32) -> f32 {
+        if self.pan_speech {
+            (pan + self.pan).clamp(-1.0, 1.0)
+        } else {
+            pan
+        }
+    }
+}
+
+/// Get the left and right channel volumes that pan a sound, from -1.0
+/// (left) to 1.0 (right). Panning one way quietens the other side rather
+/// than boosting this one, so nothing clips.
+fn channel_volumes(pan: f32) -> Vec<f32> {
+    vec![1.0 - pan.max(0.0), 1.0 + pan.min(0.0)]
+}
+
+/// Check whether there's an audio output device to play tones and sounds on.
+pub fn has_output_device() -> bool {
+    OutputStream::try_default().is_ok()
+}
+
+/// A sound that is currently playing.
+pub trait Playback {
+    /// Whether the sound has finished playing.
+    fn is_finished(&mut self, context: &AudioContext) -> bool;
+
+    /// Stop playing the sound immediately.
+    fn stop(&mut self);
+
+    /// Change the volume of a tone or clip part of the way through, as
+    /// it's ducked under speech. Other sounds keep theirs.
+    fn set_volume(&mut self, _volume: f32) {}
+}
+
+/// A sound played through its own rodio Sink on the shared output stream.
+///
+/// Sinks on the same stream are mixed, so a tone can play over speech.
+///
+struct SinkPlayback {
+    sink: Sink,
+}
+
+impl SinkPlayback {
+    /// Create a Sink on the worker's output stream.
+    ///
+    /// # Returns
+    ///
+    /// The playback, or None if there is no usable audio device.
+    ///
+    fn open(context: &AudioContext) -> Option<Self> {
+        let (_, stream_handle) = context.output.as_ref()?;
+        let sink = Sink::try_new(stream_handle).ok()?;
+        sink.set_volume(context.output_volume());
+        Some(Self { sink })
+    }
+}
+
+impl Playback for SinkPlayback {
+    fn is_finished(&mut self, _context: &AudioContext) -> bool {
+        self.sink.empty()
+    }
 
-    /// Play the sound and wait for it to finish.
-    fn play_and_wait(&self);
+    fn stop(&mut self) {
+        self.sink.stop();
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        self.sink.set_volume(volume);
+    }
+}
+
+/// A sound played by a child process, like the `say` command.
+struct ChildPlayback {
+    child: Child,
+    /// Run after killing the child, for speech engines that keep talking
+    /// without it.
+    stop_command: Option<Command>,
+}
+
+impl Playback for ChildPlayback {
+    fn is_finished(&mut self, _context: &AudioContext) -> bool {
+        self.child
+            .try_wait()
+            .map_or(true, |status| status.is_some())
+    }
+
+    fn stop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        if let Some(command) = &mut self.stop_command {
+            let _ = command.stdout(Stdio::null()).stderr(Stdio::null()).status();
+        }
+    }
 }
 
-/// A trait for Audibles that can be cancelled.
-pub trait CancellableAudible: Audible {
-    /// Stop playing the sound.
-    fn stop(&self);
+/// How many panned utterances have been written to files, to give each its
+/// own file.
+static PANNED_SPEECH_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Speech written to a WAV file by the speech engine, then played panned
+/// through a Sink once it's been written.
+struct PannedSpeechPlayback {
+    child: Child,
+    path: PathBuf,
+    pan: f32,
+    sink: Option<SinkPlayback>,
 }
 
-#[derive(Clone, Copy)]
+impl PannedSpeechPlayback {
+    /// Start playing the written file, panned.
+    ///
+    /// # Returns
+    ///
+    /// The playback, or None if the file couldn't be played.
+    ///
+    fn play_file(&self, context: &AudioContext) -> Option<SinkPlayback> {
+        let playback = SinkPlayback::open(context)?;
+        // The engine has already spoken it at the speech volume.
+        playback.sink.set_volume(1.0);
+        let file = File::open(&self.path).ok()?;
+        let source = Decoder::new(BufReader::new(file)).ok()?;
+        playback
+            .sink
+            .append(ChannelVolume::new(source, channel_volumes(self.pan)));
+        Some(playback)
+    }
+}
+
+impl Playback for PannedSpeechPlayback {
+    fn is_finished(&mut self, context: &AudioContext) -> bool {
+        if self.sink.is_none() {
+            match self.child.try_wait() {
+                Ok(None) => return false,
+                Ok(Some(status)) if status.success() => self.sink = self.play_file(context),
+                _ => (),
+            }
+        }
+        let finished = self
+            .sink
+            .as_mut()
+            .is_none_or(|sink| sink.is_finished(context));
+        if finished {
+            let _ = fs::remove_file(&self.path);
+        }
+        finished
+    }
+
+    fn stop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        if let Some(sink) = &mut self.sink {
+            sink.stop();
+        }
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// A sound that couldn't be started, and so is already finished.
+struct FinishedPlayback;
+
+impl Playback for FinishedPlayback {
+    fn is_finished(&mut self, _context: &AudioContext) -> bool {
+        true
+    }
+
+    fn stop(&mut self) {}
+}
+
+/// A silent sound, queued so that its completion marks the sounds before it
+/// as done.
+struct Marker;
+
+impl Audible for Marker {
+    fn start(self: Box<Self>, _context: &AudioContext) -> Box<dyn Playback> {
+        Box::new(FinishedPlayback)
+    }
+}
+
+#[derive(Clone, Copy, Deserialize)]
 pub struct Tone {
     pub frequency: f32,
     pub duration: f32,
     pub volume: f32,
+    /// How long the tone takes to fade in from silence, in seconds.
+    #[serde(default)]
+    pub attack: f32,
 }
 
 impl Tone {
@@ -60,36 +345,74 @@ impl Tone {
             frequency,
             duration,
             volume,
+            attack: 0.0,
         }
     }
 }
 
 impl Audible for Tone {
-    fn play(&self) {
-        let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-        let sink = Sink::try_new(&stream_handle).unwrap();
-
+    fn start(self: Box<Self>, context: &AudioContext) -> Box<dyn Playback> {
+        let playback = match SinkPlayback::open(context) {
+            Some(playback) => playback,
+            None => return Box::new(FinishedPlayback),
+        };
+        let attack = if context.night {
+            self.attack.max(NIGHT_ATTACK)
+        } else {
+            self.attack
+        };
+        // Leave at least half the tone at full volume.
+        let attack = attack.clamp(0.0, self.duration / 2.0);
         let mut source = SineWave::new(self.frequency)
             .amplify(self.volume)
+            .fade_in(Duration::from_secs_f32(attack))
             .take_duration(Duration::from_secs_f32(self.duration));
-
         source.set_filter_fadeout();
-
-        sink.append(source);
+        if context.pan == 0.0 {
+            playback.sink.append(source);
+        } else {
+            playback
+                .sink
+                .append(ChannelVolume::new(source, channel_volumes(context.pan)));
+        }
+        Box::new(playback)
     }
+}
 
-    fn play_and_wait(&self) {
-        let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-        let sink = Sink::try_new(&stream_handle).unwrap();
+/// A recorded sound clip, played from an audio file on disk.
+pub struct AudioClip {
+    path: PathBuf,
+}
 
-        let mut source = SineWave::new(self.frequency)
-            .amplify(self.volume)
-            .take_duration(Duration::from_secs_f32(self.duration));
+impl AudioClip {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
 
-        source.set_filter_fadeout();
+    fn play_into(&self, sink: &Sink, pan: f32) {
+        // A missing or unreadable clip is skipped rather than taking the
+        // editor down with it.
+        if let Ok(file) = File::open(&self.path) {
+            if let Ok(source) = Decoder::new(BufReader::new(file)) {
+                if pan == 0.0 {
+                    sink.append(source);
+                } else {
+                    sink.append(ChannelVolume::new(source, channel_volumes(pan)));
+                }
+            }
+        }
+    }
+}
 
-        sink.append(source);
-        sink.sleep_until_end();
+impl Audible for AudioClip {
+    fn start(self: Box<Self>, context: &AudioContext) -> Box<dyn Playback> {
+        match SinkPlayback::open(context) {
+            Some(playback) => {
+                self.play_into(&playback.sink, context.pan);
+                Box::new(playback)
+            }
+            None => Box::new(FinishedPlayback),
+        }
     }
 }
 
@@ -98,6 +421,10 @@ impl Audible for Tone {
 pub struct Utterance {
     text: String,
     rate_wpm: i64,
+    voice: Option<String>,
+    pitch: Option<i64>,
+    /// Where the speech is heard, from -1.0 (left) to 1.0 (right).
+    pan: f32,
 }
 
 impl Utterance {
@@ -116,32 +443,57 @@ impl Utterance {
         Self {
             text,
             rate_wpm: 300,
+            voice: None,
+            pitch: None,
+            pan: 0.0,
         }
     }
 
     pub fn from_text_and_wpm(text: String, rate_wpm: i64) -> Self {
-        Self { text, rate_wpm }
+        Self {
+            text,
+            rate_wpm,
+            voice: None,
+            pitch: None,
+            pan: 0.0,
+        }
     }
 
-    /// Speak the utterance and wait for the speech to finish.
-    pub fn speak_and_wait(&self) {
-        let mut command = Command::new("say");
-        command.arg("-r").arg(self.rate_wpm.to_string());
-        command.arg(&self.text);
-        command.output().unwrap();
+    /// Speak the utterance with a specific voice (or language), rather than
+    /// the system default.
+    pub fn with_voice(mut self, voice: Option<String>) -> Self {
+        self.voice = voice;
+        self
     }
 
-    /// Speak the utterance and return a Child of the subprocess.
-    ///
-    /// # Returns
-    ///
-    /// A Child of the subprocess.
-    ///
-    pub fn speak(&self) -> Child {
-        let mut command = Command::new("say");
-        command.arg("-r").arg(self.rate_wpm.to_string());
-        command.arg(&self.text);
-        command.spawn().unwrap()
+    /// Speak the utterance panned to one side, from -1.0 (left) to 1.0
+    /// (right). Panned speech is written to a file before it's played, so
+    /// it's only panned with engines that can do that.
+    pub fn with_pan(mut self, pan: f32) -> Self {
+        self.pan = pan.clamp(-1.0, 1.0);
+        self
+    }
+
+    /// Speak the utterance with some voice settings overridden.
+    pub fn with_parameters(mut self, parameters: &VoiceParameters) -> Self {
+        self.voice = parameters.voice.clone().or(self.voice);
+        self.rate_wpm = parameters.rate_wpm.unwrap_or(self.rate_wpm);
+        self.pitch = parameters.pitch.or(self.pitch);
+        self
+    }
+
+    /// Speak the utterance with some voice settings overridden, if there
+    /// are any.
+    pub fn with_optional_parameters(self, parameters: Option<&VoiceParameters>) -> Self {
+        match parameters {
+            Some(parameters) => self.with_parameters(parameters),
+            None => self,
+        }
+    }
+
+    /// Get the pitch the utterance will be spoken at, from -100 to 100.
+    pub fn pitch(&self) -> i64 {
+        self.pitch.unwrap_or(0)
     }
 }
 
@@ -161,12 +513,86 @@ impl From<&str> for Utterance {
 }
 
 impl Audible for Utterance {
-    fn play_and_wait(&self) {
-        self.speak_and_wait();
+    fn spoken_text(&self) -> Option<String> {
+        Some(self.text.clone())
     }
 
-    fn play(&self) {
-        self.speak();
+    fn start(self: Box<Self>, context: &AudioContext) -> Box<dyn Playback> {
+        let speech = &context.speech;
+        let pan = context.speech_pan(self.pan);
+        if pan != 0.0 && context.output.is_some() {
+            let path = env::temp_dir().join(format!(
+                "clack-speech-{}-{}.wav",
+                process::id(),
+                PANNED_SPEECH_COUNT.fetch_add(1, Ordering::Relaxed)
+            ));
+            let spawned = speech
+                .wav_command(
+                    &self.text,
+                    self.rate_wpm,
+                    self.voice.as_deref(),
+                    self.pitch,
+                    context.speech_volume(),
+                    &path,
+                )
+                .and_then(|mut command| {
+                    command
+                        .stdout(Stdio::null())
+                        .stderr(Stdio::null())
+                        .spawn()
+                        .ok()
+                });
+            if let Some(child) = spawned {
+                return Box::new(PannedSpeechPlayback {
+                    child,
+                    path,
+                    pan,
+                    sink: None,
+                });
+            }
+        }
+        if let Some(handle) = speech.speak(
+            &self.text,
+            self.rate_wpm,
+            self.voice.as_deref(),
+            self.pitch,
+            context.speech_volume(),
+        ) {
+            return Box::new(InProcessSpeechPlayback { handle });
+        }
+        let spawned = speech
+            .command(
+                &self.text,
+                self.rate_wpm,
+                self.voice.as_deref(),
+                self.pitch,
+                context.speech_volume(),
+            )
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        match spawned {
+            Ok(child) => Box::new(ChildPlayback {
+                child,
+                stop_command: speech.stop_command(),
+            }),
+            Err(_) => Box::new(FinishedPlayback),
+        }
+    }
+}
+
+/// Speech spoken in clack's own process, by an engine that's a library.
+struct InProcessSpeechPlayback {
+    handle: Box<dyn SpeechHandle>,
+}
+
+impl Playback for InProcessSpeechPlayback {
+    fn is_finished(&mut self, _context: &AudioContext) -> bool {
+        self.handle.is_finished()
+    }
+
+    fn stop(&mut self) {
+        self.handle.stop();
     }
 }
 
@@ -192,61 +618,531 @@ impl SoundSequence {
 }
 
 impl Audible for SoundSequence {
-    fn play(&self) {
-        todo!()
+    fn start(self: Box<Self>, _context: &AudioContext) -> Box<dyn Playback> {
+        Box::new(SequencePlayback {
+            pending: self.audibles.into(),
+            current: None,
+        })
     }
 
-    fn play_and_wait(&self) {
-        for audible in &self.audibles {
-            audible.play_and_wait();
+    fn spoken_text(&self) -> Option<String> {
+        let parts: Vec<String> = self
+            .audibles
+            .iter()
+            .filter_map(|audible| audible.spoken_text())
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+}
+
+/// Plays the parts of a SoundSequence one after another.
+struct SequencePlayback {
+    pending: VecDeque<Box<dyn Audible>>,
+    current: Option<Box<dyn Playback>>,
+}
+
+impl Playback for SequencePlayback {
+    fn is_finished(&mut self, context: &AudioContext) -> bool {
+        loop {
+            if let Some(current) = &mut self.current {
+                if !current.is_finished(context) {
+                    return false;
+                }
+            }
+            match self.pending.pop_front() {
+                Some(next) => self.current = Some(next.start(context)),
+                None => return true,
+            }
+        }
+    }
+
+    fn stop(&mut self) {
+        self.pending.clear();
+        if let Some(current) = &mut self.current {
+            current.stop();
+        }
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        if let Some(current) = &mut self.current {
+            current.set_volume(volume);
+        }
+    }
+}
+/// How important a queued sound is when the audio budget runs out.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Priority {
+    /// Feedback that can be skipped when audio falls behind, like typing echo.
+    Low,
+    /// Everything else.
+    Normal,
+}
+
+struct QueuedSound {
+    audible: Box<dyn Audible>,
+    priority: Priority,
+    queued_at: Instant,
+    /// Dropped when the sound finishes (or is discarded), to wake a caller
+    /// that is waiting for it.
+    done: Option<Sender<()>>,
+}
+
+impl QueuedSound {
+    fn new(audible: Box<dyn Audible>, priority: Priority) -> Self {
+        Self {
+            audible,
+            priority,
+            queued_at: Instant::now(),
+            done: None,
         }
     }
 }
+
+/// Messages from the SoundManager to its worker thread.
+enum Message {
+    Append(QueuedSound),
+    Prepend(QueuedSound),
+    /// Play an earcon straight away, mixed over whatever is playing.
+    Earcon(Box<dyn Audible>),
+    /// Stop the sound that is currently playing.
+    Interrupt,
+    /// Drop every queued sound.
+    Clear,
+    SetBudget(Duration),
+    SetVolume(f32),
+    SetSpeechVolume(f32),
+    /// Set how loud tones and clips are while speech plays.
+    SetDucking(f32),
+    /// Pan later tones and clips, and speech if asked to, to follow the
+    /// cursor.
+    SetPan(f32, bool),
+    /// Play everything quietly, with tones fading in, or stop doing so.
+    SetNight(bool),
+    /// Speak later utterances with a different text-to-speech engine.
+    SetSpeech(Box<dyn SpeechBackend>),
+    /// Drop the sender once nothing is queued or playing.
+    WhenIdle(Sender<()>),
+}
+
+/// Time spent on audio since the last `begin_iteration`.
+#[derive(Default)]
+struct AudioStats {
+    busy: Duration,
+    dropped: usize,
+}
+
+/// The sound that the worker thread is playing right now.
+struct CurrentSound {
+    playback: Box<dyn Playback>,
+    /// Whether it's speech, which earcons are ducked under.
+    speech: bool,
+    started: Instant,
+    _done: Option<Sender<()>>,
+}
+
+/// Plays queued sounds on a background thread, so that speech never blocks
+/// the editor's event loop.
+struct Worker {
+    context: AudioContext,
+    queue: VecDeque<QueuedSound>,
+    current: Option<CurrentSound>,
+    /// Earcons playing alongside the queue.
+    earcons: Vec<Box<dyn Playback>>,
+    budget: Duration,
+    idle_waiters: Vec<Sender<()>>,
+    stats: Arc<Mutex<AudioStats>>,
+}
+
+impl Worker {
+    fn run(mut self, receiver: Receiver<Message>) {
+        loop {
+            let busy = self.current.is_some() || !self.queue.is_empty() || !self.earcons.is_empty();
+            let message = if busy {
+                match receiver.recv_timeout(WORKER_POLL_INTERVAL) {
+                    Ok(message) => Some(message),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            } else {
+                match receiver.recv() {
+                    Ok(message) => Some(message),
+                    Err(_) => return,
+                }
+            };
+            if let Some(message) = message {
+                self.handle(message);
+                while let Ok(message) = receiver.try_recv() {
+                    self.handle(message);
+                }
+            }
+            self.advance();
+        }
+    }
+
+    fn handle(&mut self, message: Message) {
+        match message {
+            Message::Append(sound) => self.queue.push_back(sound),
+            Message::Prepend(sound) => self.queue.push_front(sound),
+            Message::Earcon(sound) => self.earcons.push(sound.start(&self.context)),
+            Message::Interrupt => {
+                if let Some(mut current) = self.current.take() {
+                    current.playback.stop();
+                    self.record_busy(current.started);
+                }
+            }
+            Message::Clear => {
+                self.queue.clear();
+                for mut earcon in self.earcons.drain(..) {
+                    earcon.stop();
+                }
+            }
+            Message::SetBudget(budget) => self.budget = budget,
+            Message::SetVolume(volume) => self.context.volume = volume,
+            Message::SetSpeechVolume(volume) => self.context.speech_volume = volume,
+            Message::SetDucking(ducking) => self.context.ducking = ducking,
+            Message::SetPan(pan, pan_speech) => {
+                self.context.pan = pan;
+                self.context.pan_speech = pan_speech;
+            }
+            Message::SetNight(night) => self.context.night = night,
+            Message::SetSpeech(speech) => self.context.speech = speech,
+            Message::WhenIdle(waiter) => self.idle_waiters.push(waiter),
+        }
+    }
+
+    /// Finish the current sound if it's done, and start the next one.
+    fn advance(&mut self) {
+        self.mix_earcons();
+        if let Some(current) = &mut self.current {
+            if !current.playback.is_finished(&self.context) {
+                return;
+            }
+            let started = current.started;
+            self.current = None;
+            self.record_busy(started);
+        }
+        while let Some(next) = self.queue.pop_front() {
+            if next.priority == Priority::Low && next.queued_at.elapsed() > self.budget {
+                if let Ok(mut stats) = self.stats.lock() {
+                    stats.dropped += 1;
+                }
+                continue;
+            }
+            let speech = next.audible.spoken_text().is_some();
+            self.current = Some(CurrentSound {
+                playback: next.audible.start(&self.context),
+                speech,
+                started: Instant::now(),
+                _done: next.done,
+            });
+            return;
+        }
+        if self.earcons.is_empty() {
+            self.idle_waiters.clear();
+        }
+    }
+
+    /// Drop the earcons that have finished, and duck the rest while speech
+    /// plays (or bring them back up once it's done).
+    fn mix_earcons(&mut self) {
+        self.context.speaking = self.current.as_ref().is_some_and(|current| current.speech);
+        let context = &self.context;
+        self.earcons
+            .retain_mut(|earcon| !earcon.is_finished(context));
+        let volume = self.context.output_volume();
+        for earcon in &mut self.earcons {
+            earcon.set_volume(volume);
+        }
+    }
+
+    fn record_busy(&self, started: Instant) {
+        if let Ok(mut stats) = self.stats.lock() {
+            stats.busy += started.elapsed();
+        }
+    }
+}
+
 pub struct SoundManager {
-    queue: VecDeque<Box<dyn Audible>>,
-    current_sound: Option<Box<dyn Audible>>,
-    current_sound_start: Option<Instant>,
-    current_child_process: Option<Child>,
+    sender: Sender<Message>,
+    /// Whether sounds are dropped instead of played.
+    silent: bool,
+    /// Whether sounds are played quietly, for night mode.
+    night: bool,
+    theme: EarconTheme,
+    stats: Arc<Mutex<AudioStats>>,
+    #[cfg_attr(not(feature = "audio-profiling"), allow(dead_code))]
+    budget: Duration,
+    /// What was last spoken, oldest first, to be heard again.
+    history: VecDeque<String>,
+    /// Whether speech is written to the screen for a screen reader to read,
+    /// instead of spoken. Tones and clips still play.
+    screen_reader: bool,
+    /// Speech written instead of spoken since the screen was last drawn,
+    /// and when the last of it was.
+    written: Vec<String>,
+    written_at: Instant,
+    /// The pan last sent to the worker, and whether it applies to speech.
+    pan: (f32, bool),
 }
 
 impl SoundManager {
-    pub fn new() -> Self {
+    /// Start the audio worker thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `speech` - The text-to-speech engine to speak Utterances with.
+    ///
+    pub fn new(speech: Box<dyn SpeechBackend>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let stats = Arc::new(Mutex::new(AudioStats::default()));
+        let worker_stats = Arc::clone(&stats);
+        // Playback handles (like rodio's OutputStream) can't cross threads,
+        // so the worker and its output stream are built on its own thread.
+        thread::spawn(move || {
+            Worker {
+                context: AudioContext::new(speech),
+                queue: VecDeque::new(),
+                current: None,
+                earcons: Vec::new(),
+                budget: Duration::from_millis(DEFAULT_AUDIO_BUDGET_MS),
+                idle_waiters: Vec::new(),
+                stats: worker_stats,
+            }
+            .run(receiver);
+        });
         Self {
-            queue: VecDeque::new(),
-            current_sound: None,
-            current_sound_start: None,
-            current_child_process: None,
+            sender,
+            silent: false,
+            night: false,
+            theme: EarconTheme::default(),
+            stats,
+            budget: Duration::from_millis(DEFAULT_AUDIO_BUDGET_MS),
+            history: VecDeque::new(),
+            screen_reader: false,
+            written: Vec::new(),
+            written_at: Instant::now(),
+            pan: (0.0, false),
+        }
+    }
+
+    fn send(&mut self, message: Message) {
+        // Speech for a screen reader is written rather than queued, and
+        // dropping it wakes anyone waiting for it like a silenced sound.
+        if self.screen_reader {
+            let speech = match &message {
+                Message::Append(queued) | Message::Prepend(queued) => queued
+                    .audible
+                    .spoken_text()
+                    .map(|text| (text, queued.priority)),
+                _ => None,
+            };
+            if let Some((text, priority)) = speech {
+                if priority != Priority::Low && !self.silent {
+                    self.written.push(text);
+                    self.written_at = Instant::now();
+                }
+                return;
+            }
+        }
+        // Dropping a silenced sound also drops its `done` sender, so anyone
+        // waiting for it to play carries on straight away.
+        if self.silent
+            && matches!(
+                message,
+                Message::Append(_) | Message::Prepend(_) | Message::Earcon(_)
+            )
+        {
+            return;
+        }
+        // If the worker has gone away there's nothing left to play sounds
+        // on; carry on silently rather than take the editor down.
+        let _ = self.sender.send(message);
+    }
+
+    /// Turn all sound off or back on. While silent, sounds are dropped as
+    /// they're queued, and whatever was playing is stopped.
+    pub fn set_silent(&mut self, silent: bool) {
+        if silent {
+            self.silence();
+        }
+        self.silent = silent;
+    }
+
+    pub fn is_silent(&self) -> bool {
+        self.silent
+    }
+
+    /// Turn screen reader mode on or off. In it, speech isn't spoken but
+    /// collected to be written on the screen, where a screen reader like
+    /// Orca or VoiceOver reads it in its own voice. Tones and clips still
+    /// play, and low-priority feedback like typing echo is left to the
+    /// screen reader.
+    pub fn set_screen_reader(&mut self, screen_reader: bool) {
+        if screen_reader {
+            self.silence();
+        }
+        self.screen_reader = screen_reader;
+    }
+
+    pub fn is_screen_reader(&self) -> bool {
+        self.screen_reader
+    }
+
+    /// Take the speech written instead of spoken since this was last
+    /// called, with when the last of it was, for the editor to show.
+    pub fn take_written(&mut self) -> Option<(String, Instant)> {
+        if self.written.is_empty() {
+            return None;
         }
+        let text = std::mem::take(&mut self.written).join(" ");
+        Some((text, self.written_at))
+    }
+
+    /// Turn night mode on or off. In night mode tones, clips and speech are
+    /// all much quieter, and tones fade in rather than starting abruptly.
+    /// Sounds already playing keep their volume.
+    pub fn set_night_mode(&mut self, night: bool) {
+        self.night = night;
+        self.send(Message::SetNight(night));
+    }
+
+    pub fn is_night_mode(&self) -> bool {
+        self.night
+    }
+
+    /// Set how long a low-priority sound may wait in the queue before it's
+    /// dropped as stale.
+    pub fn set_budget(&mut self, budget: Duration) {
+        self.budget = budget;
+        self.send(Message::SetBudget(budget));
+    }
+
+    /// Play later earcons and indentation tones from a different theme.
+    pub fn set_theme(&mut self, theme: EarconTheme) {
+        self.theme = theme;
+    }
+
+    pub fn theme(&self) -> &EarconTheme {
+        &self.theme
+    }
+
+    /// Play the current theme's sound for an event straight away, over
+    /// any speech (ducked under it) rather than after it.
+    pub fn play_earcon(&mut self, earcon: Earcon) {
+        let audibles = self.theme.audibles(earcon);
+        if !audibles.is_empty() {
+            self.send(Message::Earcon(Box::new(SoundSequence::new(audibles))));
+        }
+    }
+
+    /// Play a tone straight away, over any speech like an earcon.
+    pub fn play_tone(&mut self, tone: Tone) {
+        self.send(Message::Earcon(Box::new(tone)));
+    }
+
+    /// Get the current theme's tone for a level of indentation.
+    pub fn indent_tone(&self, level: usize) -> Option<Tone> {
+        self.theme.indent_tone(level)
+    }
+
+    /// Set the volume of later tones and sound clips, where 1.0 is full
+    /// volume. Speech volume is up to the speech engine.
+    pub fn set_volume(&mut self, volume: f32) {
+        self.send(Message::SetVolume(volume));
+    }
+
+    /// Set the volume of later speech, as a fraction of the speech engine's
+    /// usual volume.
+    pub fn set_speech_volume(&mut self, volume: f32) {
+        self.send(Message::SetSpeechVolume(volume));
+    }
+
+    /// Set how loud tones and clips are while speech plays, as a fraction of
+    /// their volume.
+    pub fn set_ducking(&mut self, ducking: f32) {
+        self.send(Message::SetDucking(ducking));
+    }
+
+    /// Pan later tones and clips, from -1.0 (left) to 1.0 (right), so they
+    /// follow the cursor along its line. Speech is panned along with them
+    /// (on top of stereo speech) if `pan_speech` is set.
+    pub fn set_pan(&mut self, pan: f32, pan_speech: bool) {
+        let pan = (pan.clamp(-1.0, 1.0), pan_speech);
+        if pan != self.pan {
+            self.pan = pan;
+            self.send(Message::SetPan(pan.0, pan.1));
+        }
+    }
+
+    /// Speak later utterances with a different text-to-speech engine. The
+    /// sound that is playing now finishes with the old one.
+    pub fn set_speech_backend(&mut self, speech: Box<dyn SpeechBackend>) {
+        self.send(Message::SetSpeech(speech));
+    }
+
+    /// Reset the per-iteration audio accounting.
+    ///
+    /// Call this once at the start of each event loop iteration.
+    ///
+    pub fn begin_iteration(&mut self) {
+        if let Ok(mut stats) = self.stats.lock() {
+            *stats = AudioStats::default();
+        }
+    }
+
+    /// Describe the audio time spent since the last event loop iteration.
+    #[cfg(feature = "audio-profiling")]
+    pub fn profile_report(&self) -> String {
+        let (busy, dropped) = self
+            .stats
+            .lock()
+            .map(|stats| (stats.busy, stats.dropped))
+            .unwrap_or_default();
+        format!(
+            "audio {}ms (budget {}ms), {} dropped",
+            busy.as_millis(),
+            self.budget.as_millis(),
+            dropped
+        )
     }
 
     pub fn prepend(&mut self, sound: Box<dyn Audible>) {
-        self.queue.push_front(sound);
+        self.remember(sound.as_ref(), Priority::Normal);
+        self.send(Message::Prepend(QueuedSound::new(sound, Priority::Normal)));
     }
 
     pub fn append(&mut self, sound: Box<dyn Audible>) {
-        self.queue.push_back(sound);
+        self.append_with_priority(sound, Priority::Normal);
+    }
+
+    /// Queue a sound that may be dropped if it waits too long to play.
+    pub fn append_with_priority(&mut self, sound: Box<dyn Audible>, priority: Priority) {
+        self.remember(sound.as_ref(), priority);
+        self.send(Message::Append(QueuedSound::new(sound, priority)));
     }
 
     pub fn clear(&mut self) {
-        self.queue.clear();
+        self.send(Message::Clear);
     }
 
-    pub fn play_next_or_wait(&mut self) {
-        while let Some(sound) = self.queue.pop_front() {
-            sound.as_ref().play_and_wait();
-            self.current_sound = Some(sound);
-            self.current_sound_start = Some(Instant::now());
-        }
-        self.current_sound = None;
-        self.current_child_process = None;
+    /// Block until every queued sound has finished playing.
+    pub fn wait_until_idle(&mut self) {
+        let (sender, receiver) = mpsc::channel();
+        self.send(Message::WhenIdle(sender));
+        let _ = receiver.recv();
     }
 
+    /// Stop the sound that is currently playing.
     pub fn kill(&mut self) {
-        if let Some(child_process) = &mut self.current_child_process {
-            child_process.kill().unwrap();
-        }
-        self.current_sound = None;
-        self.current_child_process = None;
+        // What it interrupts wouldn't have been heard, so isn't shown.
+        self.written.clear();
+        self.send(Message::Interrupt);
+    }
+
+    /// Stop the current sound and drop everything queued after it.
+    pub fn silence(&mut self) {
+        self.clear();
+        self.kill();
     }
 
     pub fn interrupt_and_play(&mut self, interrupt_sound: Box<dyn Audible>) {
@@ -259,15 +1155,78 @@ impl SoundManager {
         self.prepend(sound);
     }
 
+    /// Queue a sound and block until it has finished playing.
     pub fn play_and_wait(&mut self, sound: Box<dyn Audible>) {
-        sound.play_and_wait();
+        self.remember(sound.as_ref(), Priority::Normal);
+        let (sender, receiver) = mpsc::channel();
+        let mut queued = QueuedSound::new(sound, Priority::Normal);
+        queued.done = Some(sender);
+        self.send(Message::Append(queued));
+        let _ = receiver.recv();
+    }
+
+    /// Find out when every sound queued so far has finished playing,
+    /// without blocking.
+    ///
+    /// # Returns
+    ///
+    /// A receiver that disconnects once those sounds have finished, or have
+    /// been cleared from the queue.
+    ///
+    pub fn notify_when_played(&mut self) -> Receiver<()> {
+        let (sender, receiver) = mpsc::channel();
+        let mut queued = QueuedSound::new(Box::new(Marker), Priority::Normal);
+        queued.done = Some(sender);
+        self.send(Message::Append(queued));
+        receiver
+    }
+
+    /// Add what a sound says to the speech history. Feedback that may be
+    /// skipped, like typing echo, would crowd out the announcements worth
+    /// hearing again, so it's left out.
+    fn remember(&mut self, sound: &dyn Audible, priority: Priority) {
+        if self.silent || priority == Priority::Low {
+            return;
+        }
+        let text = match sound.spoken_text() {
+            Some(text) if !text.trim().is_empty() => text,
+            _ => return,
+        };
+        self.add_to_history(text);
+    }
+
+    /// Add a key pressed to the speech history, so that what was said can
+    /// be heard in order with what it answered.
+    pub fn remember_key(&mut self, description: String) {
+        if !self.silent {
+            self.add_to_history(description);
+        }
+    }
+
+    fn add_to_history(&mut self, text: String) {
+        if self.history.len() == SPEECH_HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+        self.history.push_back(text);
+    }
+
+    /// Get what was last spoken, oldest first.
+    pub fn history(&self) -> &VecDeque<String> {
+        &self.history
+    }
+
+    /// Stop the current sound to say something from the speech history
+    /// again, without adding it to the history a second time.
+    pub fn interrupt_and_repeat(&mut self, sound: Box<dyn Audible>) {
+        self.kill();
+        self.send(Message::Prepend(QueuedSound::new(sound, Priority::Normal)));
     }
 
-    pub fn play_row(&mut self, row: &Row) {
-        row.play(self);
+    pub fn play_row(&mut self, row: &Row, options: &SpeechOptions) {
+        row.play(self, options);
     }
 
-    pub fn play_row_and_wait(&mut self, row: Row) {
-        row.play_blocking(self);
+    pub fn play_row_and_wait(&mut self, row: Row, options: &SpeechOptions) {
+        row.play_blocking(self, options);
     }
 }
@@ -0,0 +1,621 @@
+use std::{path::Path, process::Command};
+
+#[cfg(feature = "native-speech")]
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use crate::utils::is_on_path;
+
+/// Speech in progress in clack's own process, for engines that are a
+/// library rather than a program.
+pub trait SpeechHandle {
+    /// Whether the speech has finished.
+    fn is_finished(&mut self) -> bool;
+
+    /// Stop speaking immediately.
+    fn stop(&mut self);
+}
+
+/// A text-to-speech engine that clack can speak through.
+///
+/// Most backends drive an external program, so that speech can be
+/// interrupted by killing the child process (or, for engines with a daemon,
+/// by asking the daemon to stop). Backends that speak from inside clack
+/// return a `SpeechHandle` from `speak` instead.
+pub trait SpeechBackend: Send {
+    /// The name used for this backend in the config file.
+    fn name(&self) -> &'static str;
+
+    /// Build the command that speaks `text` and exits when it's done.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to speak.
+    /// * `rate_wpm` - The speech rate in words per minute.
+    /// * `voice` - The voice or language to speak with, if not the default.
+    /// * `pitch` - The pitch from -100 to 100, if not the voice's usual one.
+    ///   Engines without a pitch setting ignore it.
+    /// * `volume` - The volume as a fraction of the engine's usual volume,
+    ///   from 0.0 to 1.0, if not the usual one.
+    ///
+    fn command(
+        &self,
+        text: &str,
+        rate_wpm: i64,
+        voice: Option<&str>,
+        pitch: Option<i64>,
+        volume: Option<f32>,
+    ) -> Command;
+
+    /// Build the command that writes `text`'s speech to a WAV file instead
+    /// of playing it, so that clack can play it panned to one side. The
+    /// arguments are as for `command`.
+    ///
+    /// # Returns
+    ///
+    /// The command, or None for engines that can only speak aloud.
+    ///
+    fn wav_command(
+        &self,
+        _text: &str,
+        _rate_wpm: i64,
+        _voice: Option<&str>,
+        _pitch: Option<i64>,
+        _volume: Option<f32>,
+        _path: &Path,
+    ) -> Option<Command> {
+        None
+    }
+
+    /// Build a command that silences speech in progress, for engines where
+    /// killing the speaking process isn't enough.
+    fn stop_command(&self) -> Option<Command> {
+        None
+    }
+
+    /// Start speaking `text` in clack's own process, for engines that are a
+    /// library rather than a program. The arguments are as for `command`.
+    ///
+    /// # Returns
+    ///
+    /// The speech in progress, or None to speak by running `command`.
+    ///
+    fn speak(
+        &self,
+        _text: &str,
+        _rate_wpm: i64,
+        _voice: Option<&str>,
+        _pitch: Option<i64>,
+        _volume: Option<f32>,
+    ) -> Option<Box<dyn SpeechHandle>> {
+        None
+    }
+}
+
+/// The macOS `say` command.
+pub struct Say;
+
+impl SpeechBackend for Say {
+    fn name(&self) -> &'static str {
+        "say"
+    }
+
+    fn command(
+        &self,
+        text: &str,
+        rate_wpm: i64,
+        voice: Option<&str>,
+        pitch: Option<i64>,
+        volume: Option<f32>,
+    ) -> Command {
+        self.build(text, rate_wpm, voice, pitch, volume, None)
+    }
+
+    fn wav_command(
+        &self,
+        text: &str,
+        rate_wpm: i64,
+        voice: Option<&str>,
+        pitch: Option<i64>,
+        volume: Option<f32>,
+        path: &Path,
+    ) -> Option<Command> {
+        Some(self.build(text, rate_wpm, voice, pitch, volume, Some(path)))
+    }
+}
+
+impl Say {
+    fn build(
+        &self,
+        text: &str,
+        rate_wpm: i64,
+        voice: Option<&str>,
+        pitch: Option<i64>,
+        volume: Option<f32>,
+        wav: Option<&Path>,
+    ) -> Command {
+        let mut command = Command::new("say");
+        command.arg("-r").arg(rate_wpm.to_string());
+        if let Some(path) = wav {
+            command.arg("-o").arg(path).arg("--data-format=LEI16@22050");
+        }
+        if let Some(voice) = voice {
+            command.arg("-v").arg(voice);
+        }
+        // Pitch and volume are set with embedded commands: the baseline
+        // pitch shifts by up to about an octave either way.
+        let mut embedded = String::new();
+        if let Some(pitch) = pitch {
+            embedded.push_str(&format!("[[pbas {:+}]] ", pitch / 8));
+        }
+        if let Some(volume) = volume {
+            embedded.push_str(&format!("[[volm {:.2}]] ", volume.clamp(0.0, 1.0)));
+        }
+        command.arg("--").arg(format!("{}{}", embedded, text));
+        command
+    }
+}
+
+/// eSpeak NG (or the older eSpeak), common on Linux and the Raspberry Pi.
+pub struct Espeak {
+    program: &'static str,
+}
+
+impl SpeechBackend for Espeak {
+    fn name(&self) -> &'static str {
+        self.program
+    }
+
+    fn command(
+        &self,
+        text: &str,
+        rate_wpm: i64,
+        voice: Option<&str>,
+        pitch: Option<i64>,
+        volume: Option<f32>,
+    ) -> Command {
+        self.build(text, rate_wpm, voice, pitch, volume, None)
+    }
+
+    fn wav_command(
+        &self,
+        text: &str,
+        rate_wpm: i64,
+        voice: Option<&str>,
+        pitch: Option<i64>,
+        volume: Option<f32>,
+        path: &Path,
+    ) -> Option<Command> {
+        Some(self.build(text, rate_wpm, voice, pitch, volume, Some(path)))
+    }
+}
+
+impl Espeak {
+    fn build(
+        &self,
+        text: &str,
+        rate_wpm: i64,
+        voice: Option<&str>,
+        pitch: Option<i64>,
+        volume: Option<f32>,
+        wav: Option<&Path>,
+    ) -> Command {
+        let mut command = Command::new(self.program);
+        command.arg("-s").arg(rate_wpm.to_string());
+        if let Some(path) = wav {
+            command.arg("-w").arg(path);
+        }
+        if let Some(voice) = voice {
+            command.arg("-v").arg(voice);
+        }
+        if let Some(pitch) = pitch {
+            // eSpeak pitches run from 0 to 99, with 50 as usual.
+            command
+                .arg("-p")
+                .arg((50 + pitch / 2).clamp(0, 99).to_string());
+        }
+        if let Some(volume) = volume {
+            // eSpeak amplitudes run from 0 to 200, with 100 as usual.
+            let amplitude = (volume.clamp(0.0, 1.0) * 100.0).round() as i64;
+            command.arg("-a").arg(amplitude.to_string());
+        }
+        // Stop option parsing, so text starting with a dash isn't a flag.
+        command.arg("--").arg(text);
+        command
+    }
+}
+
+/// speech-dispatcher, through its `spd-say` client.
+pub struct SpeechDispatcher;
+
+impl SpeechBackend for SpeechDispatcher {
+    fn name(&self) -> &'static str {
+        "spd-say"
+    }
+
+    fn command(
+        &self,
+        text: &str,
+        rate_wpm: i64,
+        voice: Option<&str>,
+        pitch: Option<i64>,
+        volume: Option<f32>,
+    ) -> Command {
+        let mut command = Command::new("spd-say");
+        // spd-say rates run from -100 to 100, with 0 at roughly 175 wpm.
+        let rate = ((rate_wpm - 175) * 100 / 275).clamp(-100, 100);
+        command.arg("--wait").arg("-r").arg(rate.to_string());
+        if let Some(pitch) = pitch {
+            command.arg("-p").arg(pitch.clamp(-100, 100).to_string());
+        }
+        if let Some(volume) = volume {
+            // spd-say volumes run from -100 to 100, with 0 as usual.
+            let volume = (volume.clamp(0.0, 1.0) * 100.0).round() as i64 - 100;
+            command.arg("-i").arg(volume.to_string());
+        }
+        match voice {
+            // Short names like "de" or "pt-BR" are languages; anything else
+            // is a synthesizer voice name.
+            Some(voice) if voice.len() <= 5 => {
+                command.arg("-l").arg(voice);
+            }
+            Some(voice) => {
+                command.arg("-y").arg(voice);
+            }
+            None => (),
+        }
+        command.arg("--").arg(text);
+        command
+    }
+
+    fn stop_command(&self) -> Option<Command> {
+        // Killing the client leaves the daemon talking; cancel it instead.
+        let mut command = Command::new("spd-say");
+        command.arg("--cancel");
+        Some(command)
+    }
+}
+
+/// Windows SAPI, driven through PowerShell's System.Speech.
+pub struct Sapi;
+
+impl SpeechBackend for Sapi {
+    fn name(&self) -> &'static str {
+        "sapi"
+    }
+
+    fn command(
+        &self,
+        text: &str,
+        rate_wpm: i64,
+        voice: Option<&str>,
+        // System.Speech only sets pitch through SSML, so it's left alone.
+        _pitch: Option<i64>,
+        volume: Option<f32>,
+    ) -> Command {
+        self.build(text, rate_wpm, voice, volume, None)
+    }
+
+    fn wav_command(
+        &self,
+        text: &str,
+        rate_wpm: i64,
+        voice: Option<&str>,
+        _pitch: Option<i64>,
+        volume: Option<f32>,
+        path: &Path,
+    ) -> Option<Command> {
+        Some(self.build(text, rate_wpm, voice, volume, Some(path)))
+    }
+}
+
+impl Sapi {
+    fn build(
+        &self,
+        text: &str,
+        rate_wpm: i64,
+        voice: Option<&str>,
+        volume: Option<f32>,
+        wav: Option<&Path>,
+    ) -> Command {
+        // SAPI rates run from -10 to 10, with 0 at roughly 180 wpm.
+        let rate = ((rate_wpm - 180) / 20).clamp(-10, 10);
+        let mut command = Command::new("powershell");
+        // The text, voice and path reach the script through the environment
+        // rather than being spliced into it, so nothing in them is ever run.
+        let mut script = format!(
+            "Add-Type -AssemblyName System.Speech; \
+             $s = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+             $s.Rate = {};",
+            rate
+        );
+        if let Some(voice) = voice {
+            command.env("CLACK_VOICE", voice);
+            script.push_str(" $s.SelectVoice($env:CLACK_VOICE);");
+        }
+        if let Some(volume) = volume {
+            // SAPI volumes run from 0 to 100, with 100 as usual.
+            let volume = (volume.clamp(0.0, 1.0) * 100.0).round() as i64;
+            script.push_str(&format!(" $s.Volume = {};", volume));
+        }
+        if let Some(path) = wav {
+            command.env("CLACK_WAV", path);
+            script.push_str(" $s.SetOutputToWaveFile($env:CLACK_WAV);");
+        }
+        command.env("CLACK_TEXT", text);
+        script.push_str(" $s.Speak($env:CLACK_TEXT)");
+        command
+            .arg("-NoProfile")
+            .arg("-NonInteractive")
+            .arg("-Command")
+            .arg(script);
+        command
+    }
+}
+
+/// Pick the speech backend named in the config, or detect one.
+///
+/// # Arguments
+///
+/// * `name` - The configured backend name; "auto" (or an unknown name)
+///   detects the best backend available on this machine.
+///
+/// # Returns
+///
+/// The speech backend.
+///
+pub fn select_backend(name: &str) -> Box<dyn SpeechBackend> {
+    match name {
+        "say" => Box::new(Say),
+        "espeak-ng" => Box::new(Espeak {
+            program: "espeak-ng",
+        }),
+        "espeak" => Box::new(Espeak { program: "espeak" }),
+        "spd-say" | "speech-dispatcher" => Box::new(SpeechDispatcher),
+        "sapi" => Box::new(Sapi),
+        #[cfg(feature = "native-speech")]
+        "tts" => match NativeSpeech::new() {
+            Some(speech) => Box::new(speech),
+            None => detect_backend(),
+        },
+        _ => detect_backend(),
+    }
+}
+
+/// Detect the best speech backend available on this machine.
+pub fn detect_backend() -> Box<dyn SpeechBackend> {
+    if cfg!(target_os = "windows") {
+        return Box::new(Sapi);
+    }
+    if cfg!(target_os = "macos") || is_on_path("say") {
+        return Box::new(Say);
+    }
+    if is_on_path("espeak-ng") {
+        return Box::new(Espeak {
+            program: "espeak-ng",
+        });
+    }
+    if is_on_path("spd-say") {
+        return Box::new(SpeechDispatcher);
+    }
+    if is_on_path("espeak") {
+        return Box::new(Espeak { program: "espeak" });
+    }
+    // Nothing found: fall back to `say`, which fails quietly if missing.
+    Box::new(Say)
+}
+
+/// The rate the `tts` crate's engines speak at by default, in words per
+/// minute (roughly: each engine has its own).
+#[cfg(feature = "native-speech")]
+const NATIVE_NORMAL_RATE_WPM: f32 = 180.0;
+
+/// Speech through the `tts` crate, which talks to the platform's speech
+/// service (AVFoundation, Speech Dispatcher or WinRT) from inside clack.
+///
+/// Speech starts sooner than with a program per utterance, stops the moment
+/// it's interrupted, and the engine says when each utterance ends. Speech to
+/// be panned has to be written to a file, which the service can't do, so it
+/// goes through the program-driven backend found on the machine instead.
+#[cfg(feature = "native-speech")]
+pub struct NativeSpeech {
+    tts: tts::Tts,
+    /// The voice the service speaks with when none is asked for.
+    default_voice: Option<tts::Voice>,
+    /// Whether each utterance given to the engine and not yet ended has
+    /// ended, oldest first. The engine says when an utterance ends but not
+    /// in a way that can be matched up on every platform (utterance ids
+    /// can't be compared on macOS), so each ending marks the oldest.
+    pending: Arc<Mutex<VecDeque<Arc<AtomicBool>>>>,
+    /// Whether the engine says when utterances end. If not, it's asked
+    /// whether it's still speaking.
+    callbacks: bool,
+    fallback: Box<dyn SpeechBackend>,
+}
+
+#[cfg(feature = "native-speech")]
+impl NativeSpeech {
+    /// Connect to the platform's speech service.
+    ///
+    /// # Returns
+    ///
+    /// The backend, or None if there's no speech service to connect to.
+    ///
+    pub fn new() -> Option<Self> {
+        let tts = tts::Tts::default().ok()?;
+        let pending = Arc::new(Mutex::new(VecDeque::new()));
+        let record = |pending: &Arc<Mutex<VecDeque<Arc<AtomicBool>>>>| {
+            let pending = Arc::clone(pending);
+            Box::new(move |_: tts::UtteranceId| {
+                let ended = pending
+                    .lock()
+                    .ok()
+                    .and_then(|mut pending| pending.pop_front());
+                if let Some(ended) = ended {
+                    ended.store(true, Ordering::Relaxed);
+                }
+            }) as Box<dyn FnMut(tts::UtteranceId)>
+        };
+        let callbacks = tts.supported_features().utterance_callbacks
+            && tts.on_utterance_end(Some(record(&pending))).is_ok()
+            && tts.on_utterance_stop(Some(record(&pending))).is_ok();
+        Some(Self {
+            default_voice: tts.voice().ok().flatten(),
+            tts,
+            pending,
+            callbacks,
+            fallback: detect_backend(),
+        })
+    }
+
+    /// Find a voice by its name or id, or else by its language ("en" or
+    /// "en-GB").
+    fn find_voice(&self, name: &str) -> Option<tts::Voice> {
+        let voices = self.tts.voices().ok()?;
+        let name = name.to_lowercase();
+        voices
+            .iter()
+            .find(|voice| voice.name().to_lowercase() == name || voice.id().to_lowercase() == name)
+            .or_else(|| {
+                voices
+                    .iter()
+                    .find(|voice| voice.language().as_str().to_lowercase().starts_with(&name))
+            })
+            .cloned()
+    }
+}
+
+#[cfg(feature = "native-speech")]
+impl SpeechBackend for NativeSpeech {
+    fn name(&self) -> &'static str {
+        "tts"
+    }
+
+    fn command(
+        &self,
+        text: &str,
+        rate_wpm: i64,
+        voice: Option<&str>,
+        pitch: Option<i64>,
+        volume: Option<f32>,
+    ) -> Command {
+        self.fallback.command(text, rate_wpm, voice, pitch, volume)
+    }
+
+    fn wav_command(
+        &self,
+        text: &str,
+        rate_wpm: i64,
+        voice: Option<&str>,
+        pitch: Option<i64>,
+        volume: Option<f32>,
+        path: &Path,
+    ) -> Option<Command> {
+        self.fallback
+            .wav_command(text, rate_wpm, voice, pitch, volume, path)
+    }
+
+    fn stop_command(&self) -> Option<Command> {
+        self.fallback.stop_command()
+    }
+
+    fn speak(
+        &self,
+        text: &str,
+        rate_wpm: i64,
+        voice: Option<&str>,
+        pitch: Option<i64>,
+        volume: Option<f32>,
+    ) -> Option<Box<dyn SpeechHandle>> {
+        // Settings apply to the engine as a whole, so each utterance sets
+        // all of them, back to the usual ones where it asks for nothing.
+        let mut tts = self.tts.clone();
+        let features = tts.supported_features();
+        if features.rate {
+            let rate = tts.normal_rate() * rate_wpm as f32 / NATIVE_NORMAL_RATE_WPM;
+            let _ = tts.set_rate(rate.clamp(tts.min_rate(), tts.max_rate()));
+        }
+        if features.pitch {
+            let pitch = pitch.unwrap_or(0).clamp(-100, 100) as f32 / 100.0;
+            let normal = tts.normal_pitch();
+            let extent = if pitch < 0.0 {
+                normal - tts.min_pitch()
+            } else {
+                tts.max_pitch() - normal
+            };
+            let _ = tts.set_pitch(normal + extent * pitch);
+        }
+        if features.volume {
+            let volume = tts.normal_volume() * volume.unwrap_or(1.0).clamp(0.0, 1.0);
+            let _ = tts.set_volume(volume.clamp(tts.min_volume(), tts.max_volume()));
+        }
+        if features.voice {
+            let voice = voice
+                .and_then(|name| self.find_voice(name))
+                .or_else(|| self.default_voice.clone());
+            if let Some(voice) = voice {
+                let _ = tts.set_voice(&voice);
+            }
+        }
+        if !self.callbacks {
+            tts.speak(text, false).ok()?;
+            return Some(Box::new(NativeSpeechHandle {
+                tts,
+                ended: None,
+                pending: Arc::clone(&self.pending),
+            }));
+        }
+        // Queued first, in case the engine is done before `speak` returns.
+        let ended = Arc::new(AtomicBool::new(false));
+        let mut pending = self.pending.lock().ok()?;
+        pending.push_back(Arc::clone(&ended));
+        drop(pending);
+        if tts.speak(text, false).is_err() {
+            if let Ok(mut pending) = self.pending.lock() {
+                pending.retain(|queued| !Arc::ptr_eq(queued, &ended));
+            }
+            return None;
+        }
+        Some(Box::new(NativeSpeechHandle {
+            tts,
+            ended: Some(ended),
+            pending: Arc::clone(&self.pending),
+        }))
+    }
+}
+
+/// An utterance being spoken by the `tts` crate.
+#[cfg(feature = "native-speech")]
+struct NativeSpeechHandle {
+    tts: tts::Tts,
+    /// Whether the utterance has ended, if the engine says when utterances
+    /// end.
+    ended: Option<Arc<AtomicBool>>,
+    pending: Arc<Mutex<VecDeque<Arc<AtomicBool>>>>,
+}
+
+#[cfg(feature = "native-speech")]
+impl SpeechHandle for NativeSpeechHandle {
+    fn is_finished(&mut self) -> bool {
+        match &self.ended {
+            Some(ended) => ended.load(Ordering::Relaxed),
+            None => !self.tts.is_speaking().unwrap_or(false),
+        }
+    }
+
+    fn stop(&mut self) {
+        let _ = self.tts.stop();
+        // Stopping ends everything the engine had queued, whether or not it
+        // says so.
+        if let Ok(mut pending) = self.pending.lock() {
+            for ended in pending.drain(..) {
+                ended.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+}
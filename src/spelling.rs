@@ -0,0 +1,219 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::utils::SearchDirection;
+use crate::{Document, Position, Row};
+
+/// Word lists tried, in order, when the config file doesn't name one. A
+/// hunspell `.dic` file lists stems without their inflections, so it's
+/// only used when there's no full word list.
+const DEFAULT_DICTIONARIES: &[&str] = &[
+    "/usr/share/dict/words",
+    "/usr/share/hunspell/en_US.dic",
+    "/usr/share/myspell/en_US.dic",
+];
+
+/// The extensions of files whose spelling is checked.
+const PROSE_EXTENSIONS: &[&str] = &["txt", "md", "markdown", "rst", "org", "tex", "adoc"];
+
+/// The most suggestions offered for a misspelled word.
+const MAX_SUGGESTIONS: usize = 8;
+
+/// A list of correctly spelled words.
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    /// Load a word list with one word per line, like `/usr/share/dict/words`,
+    /// or a hunspell `.dic` file. A `.dic` file's word count and affix flags
+    /// are skipped, so only the forms it lists in full are known.
+    pub fn load(path: &Path) -> Result<Self, std::io::Error> {
+        let contents = fs::read_to_string(path)?;
+        let words = contents
+            .lines()
+            .map(|line| line.split('/').next().unwrap_or_default().trim())
+            .filter(|word| !word.is_empty() && !word.chars().all(|c| c.is_ascii_digit()))
+            .map(str::to_lowercase)
+            .collect();
+        Ok(Self { words })
+    }
+
+    /// Find the first of the usual system word lists that exists.
+    pub fn default_path() -> Option<PathBuf> {
+        DEFAULT_DICTIONARIES
+            .iter()
+            .map(PathBuf::from)
+            .find(|path| path.is_file())
+    }
+
+    /// Whether a word is spelled correctly. Words that aren't checked, like
+    /// acronyms and identifiers, always are.
+    pub fn is_correct(&self, word: &str) -> bool {
+        if !is_checkable(word) {
+            return true;
+        }
+        let word = word.to_lowercase().replace('\u{2019}', "'");
+        self.words.contains(&word)
+            || word
+                .strip_suffix("'s")
+                .is_some_and(|stem| self.words.contains(stem))
+    }
+
+    /// Get the known words one edit away from a misspelled word, likeliest
+    /// first, capitalized like the word.
+    pub fn suggestions(&self, word: &str) -> Vec<String> {
+        let mut suggestions: Vec<String> = Vec::new();
+        for candidate in edits(&word.to_lowercase()) {
+            if self.words.contains(&candidate) && !suggestions.contains(&candidate) {
+                suggestions.push(candidate);
+                if suggestions.len() == MAX_SUGGESTIONS {
+                    break;
+                }
+            }
+        }
+        suggestions
+            .into_iter()
+            .map(|suggestion| match_case(word, &suggestion))
+            .collect()
+    }
+}
+
+/// Whether a file's spelling is checked: prose files, by extension, and new
+/// files that haven't been named yet.
+pub fn is_prose(file_name: Option<&str>) -> bool {
+    let file_name = match file_name {
+        Some(file_name) => file_name,
+        None => return true,
+    };
+    Path::new(file_name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| PROSE_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+/// Whether a word is worth checking. Single letters, acronyms, and anything
+/// with digits, underscores or capitals after the first letter (like
+/// `getUserName`) are left alone.
+fn is_checkable(word: &str) -> bool {
+    let mut letters = word.chars().filter(|c| c.is_alphabetic());
+    letters.next().is_some()
+        && letters.next().is_some()
+        && word
+            .chars()
+            .all(|c| c.is_alphabetic() || c == '\'' || c == '\u{2019}')
+        && !word.chars().skip(1).any(char::is_uppercase)
+}
+
+/// Every string one edit away from a word, with swapped and missing
+/// letters (the likeliest typos) first, then wrong and extra letters.
+fn edits(word: &str) -> Vec<String> {
+    let chars: Vec<char> = word.chars().collect();
+    let splice = |start: usize, middle: &[char], end: usize| -> String {
+        chars[..start]
+            .iter()
+            .chain(middle)
+            .chain(&chars[end..])
+            .collect()
+    };
+    let mut edits = Vec::new();
+    for i in 1..chars.len() {
+        edits.push(splice(i - 1, &[chars[i], chars[i - 1]], i + 1));
+    }
+    for i in 0..=chars.len() {
+        for letter in 'a'..='z' {
+            edits.push(splice(i, &[letter], i));
+        }
+    }
+    for (i, &c) in chars.iter().enumerate() {
+        for letter in ('a'..='z').filter(|&letter| letter != c) {
+            edits.push(splice(i, &[letter], i + 1));
+        }
+    }
+    for i in 0..chars.len() {
+        edits.push(splice(i, &[], i + 1));
+    }
+    edits
+}
+
+/// Capitalize a suggestion like the word it replaces.
+fn match_case(word: &str, suggestion: &str) -> String {
+    if !word.starts_with(char::is_uppercase) {
+        return suggestion.to_string();
+    }
+    let mut chars = suggestion.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Find the misspelled word a column is in.
+///
+/// # Returns
+///
+/// The index of the word's first character, and the word.
+///
+pub fn misspelling_at<'a>(
+    row: &'a Row,
+    x: usize,
+    dictionary: &Dictionary,
+) -> Option<(usize, &'a str)> {
+    row.words()
+        .into_iter()
+        .find(|(start, word)| *start <= x && x < start + word.graphemes(true).count())
+        .filter(|(_, word)| !dictionary.is_correct(word))
+}
+
+/// Find the next misspelled word from a position, going round to the other
+/// end of the document if there are no more in that direction.
+///
+/// # Returns
+///
+/// The start of the word, and whether the search went round to find it; or
+/// None if nothing is misspelled.
+///
+pub fn find_misspelling(
+    document: &Document,
+    at: &Position,
+    direction: SearchDirection,
+    dictionary: &Dictionary,
+) -> Option<(Position, bool)> {
+    let count = document.row_count();
+    if count == 0 {
+        return None;
+    }
+    for step in 0..=count {
+        let (y, wrapped) = match direction {
+            SearchDirection::Forward => ((at.y + step) % count, at.y + step >= count),
+            SearchDirection::Backward => ((at.y + count - step % count) % count, step > at.y),
+        };
+        let row = match document.get_row(y) {
+            Some(row) => row,
+            None => continue,
+        };
+        let mut words = row.words();
+        if direction == SearchDirection::Backward {
+            words.reverse();
+        }
+        // The cursor's own row is searched twice: once from the cursor, and
+        // again up to it after going round.
+        let found = words.into_iter().find(|(start, word)| {
+            let in_range = match (direction, step) {
+                (SearchDirection::Forward, 0) => *start > at.x,
+                (SearchDirection::Forward, _) if step == count => *start <= at.x,
+                (SearchDirection::Backward, 0) => *start < at.x,
+                (SearchDirection::Backward, _) if step == count => *start >= at.x,
+                _ => true,
+            };
+            in_range && !dictionary.is_correct(word)
+        });
+        if let Some((x, _)) = found {
+            return Some((Position { x, y }, wrapped));
+        }
+    }
+    None
+}
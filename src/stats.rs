@@ -0,0 +1,100 @@
+/// Summary statistics of some numbers.
+pub struct Summary {
+    pub count: usize,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+impl Summary {
+    /// Summarize some numbers.
+    ///
+    /// # Returns
+    ///
+    /// The summary, or None if there are no numbers.
+    ///
+    pub fn of(numbers: &[f64]) -> Option<Self> {
+        let first = *numbers.first()?;
+        let sum: f64 = numbers.iter().sum();
+        Some(Self {
+            count: numbers.len(),
+            sum,
+            min: numbers.iter().copied().fold(first, f64::min),
+            max: numbers.iter().copied().fold(first, f64::max),
+            #[allow(clippy::cast_precision_loss)]
+            mean: sum / numbers.len() as f64,
+        })
+    }
+
+    /// Describe the summary for speech, e.g. "3 numbers. Sum 12, minimum 2,
+    /// maximum 6, mean 4."
+    pub fn describe(&self) -> String {
+        if self.count == 1 {
+            return format!("1 number: {}.", format_number(self.sum));
+        }
+        format!(
+            "{} numbers. Sum {}, minimum {}, maximum {}, mean {}.",
+            self.count,
+            format_number(self.sum),
+            format_number(self.min),
+            format_number(self.max),
+            format_number(self.mean)
+        )
+    }
+}
+
+/// Find the numbers in some text, like `12`, `-3` or `4.5`.
+///
+/// A minus sign only counts when it isn't joined to a word, so the `-` in
+/// `2022-09-14` is not read as making 09 negative. Digits inside words,
+/// like `x2`, are skipped.
+///
+pub fn numbers_in(text: &str) -> Vec<f64> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut numbers = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        // Skip words like `x2`, whose digits aren't a number on their own.
+        if chars[i].is_alphabetic() || chars[i] == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            continue;
+        }
+        let is_minus_sign = chars[i] == '-'
+            && chars.get(i + 1).is_some_and(char::is_ascii_digit)
+            && (i == 0 || !chars[i - 1].is_alphanumeric());
+        if !chars[i].is_ascii_digit() && !is_minus_sign {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+        while i < chars.len() && chars[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i + 1 < chars.len() && chars[i] == '.' && chars[i + 1].is_ascii_digit() {
+            i += 1;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+        }
+        let token: String = chars[start..i].iter().collect();
+        if let Ok(number) = token.parse() {
+            numbers.push(number);
+        }
+    }
+    numbers
+}
+
+/// Format a number for speech, with at most two decimal places.
+fn format_number(number: f64) -> String {
+    let rounded = format!("{:.2}", number);
+    let trimmed = rounded.trim_end_matches('0').trim_end_matches('.');
+    if trimmed == "-0" {
+        String::from("0")
+    } else {
+        trimmed.to_string()
+    }
+}
@@ -0,0 +1,44 @@
+use crate::atomic;
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Write a document's text to its crash-recovery file, `.<name>.clack-swp`
+/// next to it.
+///
+/// The recovery file is removed when the document is saved or clack quits,
+/// so one that's still there when the file is opened again holds changes
+/// from a session that ended without saving.
+///
+pub fn write(file_name: &str, contents: &[u8]) -> Result<(), io::Error> {
+    // Write beside the recovery file and rename over it, so that dying
+    // part of the way through a write can't leave a truncated copy.
+    let path = swap_path_for(file_name);
+    let partial = path.with_extension("clack-swp-partial");
+    // The recovery file holds the document's text, so it's kept as private
+    // as the document.
+    let _ = fs::remove_file(&partial);
+    atomic::create_like(&partial, Path::new(file_name))?.write_all(contents)?;
+    fs::rename(&partial, &path)
+}
+
+/// Read a document's recovery file, if it has one.
+pub fn read(file_name: &str) -> Option<String> {
+    fs::read_to_string(swap_path_for(file_name)).ok()
+}
+
+/// Remove a document's recovery file, if it has one.
+pub fn remove(file_name: &str) {
+    let _ = fs::remove_file(swap_path_for(file_name));
+}
+
+fn swap_path_for(file_name: &str) -> PathBuf {
+    let path = Path::new(file_name);
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.clack-swp", name))
+}
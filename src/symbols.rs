@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::OnceLock;
+
+use crate::utils::PunctuationLevel;
+
+/// Unicode character databases tried, in order, for the names of emoji
+/// that aren't in `EMOJI_NAMES`.
+const UNICODE_DATA_FILES: &[&str] = &[
+    "/usr/share/unicode/UnicodeData.txt",
+    "/usr/share/unicode-data/UnicodeData.txt",
+];
+
+/// Spoken names for common emoji, said with "emoji" after them.
+const EMOJI_NAMES: &[(char, &str)] = &[
+    ('😀', "grinning face"),
+    ('😃', "smiling face with open mouth"),
+    ('😄', "smiling face with smiling eyes"),
+    ('😁', "beaming face"),
+    ('😂', "tears of joy"),
+    ('🤣', "rolling on the floor laughing"),
+    ('😊', "smiling face"),
+    ('🙂', "slightly smiling face"),
+    ('😉', "winking face"),
+    ('😍', "heart eyes"),
+    ('😘', "blowing a kiss"),
+    ('😎', "sunglasses face"),
+    ('🤔', "thinking face"),
+    ('😐', "neutral face"),
+    ('😅', "sweat smile"),
+    ('😢', "crying face"),
+    ('😭', "loudly crying face"),
+    ('😡', "angry face"),
+    ('😱', "screaming face"),
+    ('🙃', "upside down face"),
+    ('🙄', "rolling eyes"),
+    ('😴', "sleeping face"),
+    ('🤯', "exploding head"),
+    ('👍', "thumbs up"),
+    ('👎', "thumbs down"),
+    ('👏', "clapping hands"),
+    ('🙏', "folded hands"),
+    ('👋', "waving hand"),
+    ('👌', "OK hand"),
+    ('💪', "flexed biceps"),
+    ('👀', "eyes"),
+    ('🧠', "brain"),
+    ('👨', "man"),
+    ('👩', "woman"),
+    ('❤', "red heart"),
+    ('💔', "broken heart"),
+    ('💯', "hundred points"),
+    ('🔥', "fire"),
+    ('✨', "sparkles"),
+    ('⭐', "star"),
+    ('🌟', "glowing star"),
+    ('⚡', "lightning"),
+    ('🎉', "party popper"),
+    ('🎊', "confetti ball"),
+    ('🚀', "rocket"),
+    ('💡', "light bulb"),
+    ('📝', "memo"),
+    ('📌', "pushpin"),
+    ('📎', "paperclip"),
+    ('📦', "package"),
+    ('📁', "folder"),
+    ('📄', "page"),
+    ('🔗', "link"),
+    ('🔒', "locked"),
+    ('🔓', "unlocked"),
+    ('🔑', "key"),
+    ('🔧', "wrench"),
+    ('🔨', "hammer"),
+    ('🛠', "hammer and wrench"),
+    ('⚙', "gear"),
+    ('🐛', "bug"),
+    ('🐞', "lady beetle"),
+    ('🧪', "test tube"),
+    ('💻', "laptop"),
+    ('🖥', "desktop computer"),
+    ('📱', "mobile phone"),
+    ('⏰', "alarm clock"),
+    ('⏳', "hourglass"),
+    ('⌛', "hourglass done"),
+    ('📅', "calendar"),
+    ('✅', "check mark button"),
+    ('❌', "cross mark"),
+    ('❎', "cross mark button"),
+    ('⚠', "warning"),
+    ('⛔', "no entry"),
+    ('🚫', "prohibited"),
+    ('❗', "exclamation mark"),
+    ('❓', "question mark"),
+    ('💬', "speech balloon"),
+    ('🔔', "bell"),
+    ('🎵', "musical note"),
+    ('🌍', "globe"),
+    ('☀', "sun"),
+    ('🌙', "crescent moon"),
+    ('☕', "hot beverage"),
+    ('🍕', "pizza"),
+    ('🐍', "snake"),
+    ('🦀', "crab"),
+    ('🐳', "whale"),
+    ('🐧', "penguin"),
+    ('🏁', "chequered flag"),
+    ('🚧', "construction"),
+    ('♻', "recycling symbol"),
+    ('🆕', "new button"),
+    ('🆗', "OK button"),
+    ('🔴', "red circle"),
+    ('🟢', "green circle"),
+    ('🟡', "yellow circle"),
+    ('🔵', "blue circle"),
+];
+
+/// Spoken names for arrows, bullets and other symbols that speech engines
+/// tend to skip, which are only named when some punctuation is.
+const SYMBOL_NAMES: &[(char, &str)] = &[
+    ('←', "left arrow"),
+    ('→', "right arrow"),
+    ('↑', "up arrow"),
+    ('↓', "down arrow"),
+    ('↔', "left right arrow"),
+    ('↕', "up down arrow"),
+    ('↩', "return arrow"),
+    ('⇐', "double left arrow"),
+    ('⇒', "double right arrow"),
+    ('⇔', "double left right arrow"),
+    ('➜', "right arrow"),
+    ('➡', "right arrow"),
+    ('⬅', "left arrow"),
+    ('⬆', "up arrow"),
+    ('⬇', "down arrow"),
+    ('▶', "right triangle"),
+    ('◀', "left triangle"),
+    ('▲', "up triangle"),
+    ('▼', "down triangle"),
+    ('•', "bullet"),
+    ('◦', "white bullet"),
+    ('▪', "small square"),
+    ('■', "square"),
+    ('□', "white square"),
+    ('●', "circle"),
+    ('○', "white circle"),
+    ('✓', "check"),
+    ('✔', "check"),
+    ('✗', "cross"),
+    ('✘', "cross"),
+    ('★', "star"),
+    ('☆', "white star"),
+    ('…', "ellipsis"),
+    ('≠', "not equal to"),
+    ('≤', "less than or equal to"),
+    ('≥', "greater than or equal to"),
+    ('≈', "approximately"),
+    ('±', "plus or minus"),
+    ('×', "times"),
+    ('÷', "divided by"),
+    ('°', "degrees"),
+    ('∞', "infinity"),
+    ('©', "copyright"),
+    ('®', "registered"),
+    ('™', "trademark"),
+    ('§', "section"),
+    ('¶', "pilcrow"),
+];
+
+/// Spoken names for box-drawing and block characters, which are only named
+/// when some punctuation is.
+const BOX_DRAWING_NAMES: &[(char, &str)] = &[
+    ('─', "horizontal line"),
+    ('━', "heavy horizontal line"),
+    ('═', "double horizontal line"),
+    ('│', "vertical line"),
+    ('┃', "heavy vertical line"),
+    ('║', "double vertical line"),
+    ('┌', "top left corner"),
+    ('┐', "top right corner"),
+    ('└', "bottom left corner"),
+    ('┘', "bottom right corner"),
+    ('╭', "top left corner"),
+    ('╮', "top right corner"),
+    ('╰', "bottom left corner"),
+    ('╯', "bottom right corner"),
+    ('╔', "double top left corner"),
+    ('╗', "double top right corner"),
+    ('╚', "double bottom left corner"),
+    ('╝', "double bottom right corner"),
+    ('├', "left tee"),
+    ('┤', "right tee"),
+    ('┬', "top tee"),
+    ('┴', "bottom tee"),
+    ('┼', "cross"),
+    ('╠', "double left tee"),
+    ('╣', "double right tee"),
+    ('╦', "double top tee"),
+    ('╩', "double bottom tee"),
+    ('╬', "double cross"),
+    ('█', "full block"),
+    ('▀', "upper half block"),
+    ('▄', "lower half block"),
+    ('░', "light shade"),
+    ('▒', "medium shade"),
+    ('▓', "dark shade"),
+];
+
+/// Runs of one symbol at least this long are named once, with a count.
+const MIN_COUNTED_RUN: usize = 3;
+
+/// Characters that shape the emoji next to them, like skin tones and the
+/// joiners between the parts of a family, and aren't spoken themselves.
+fn is_emoji_modifier(c: char) -> bool {
+    matches!(
+        c,
+        '\u{FE0E}' | '\u{FE0F}' | '\u{200D}' | '\u{1F3FB}'..='\u{1F3FF}'
+    )
+}
+
+/// Whether a character is an emoji (roughly: the pictographic blocks).
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c,
+        '\u{1F000}'..='\u{1FAFF}'
+            | '\u{2600}'..='\u{27BF}'
+            | '\u{2B50}'
+            | '\u{2B55}'
+            | '\u{231A}'..='\u{231B}'
+            | '\u{23E9}'..='\u{23FA}'
+    )
+}
+
+/// Get what to say for a character, if it's one speech engines don't read
+/// well.
+fn spoken_name(c: char, punctuation: PunctuationLevel) -> Option<String> {
+    let find = |table: &[(char, &'static str)]| {
+        table
+            .iter()
+            .find(|(symbol, _)| *symbol == c)
+            .map(|(_, name)| *name)
+    };
+    if punctuation != PunctuationLevel::None {
+        if let Some(name) = find(SYMBOL_NAMES) {
+            return Some(name.to_string());
+        }
+    }
+    if let Some(name) = find(EMOJI_NAMES) {
+        return Some(format!("{} emoji", name));
+    }
+    if is_emoji(c) {
+        return Some(match unicode_name(c) {
+            Some(name) => format!("{} emoji", name),
+            None => String::from("emoji"),
+        });
+    }
+    let is_box_drawing = matches!(c, '\u{2500}'..='\u{259F}');
+    if is_box_drawing {
+        if punctuation == PunctuationLevel::None {
+            return Some(String::new());
+        }
+        return Some(find(BOX_DRAWING_NAMES).unwrap_or("box drawing").to_string());
+    }
+    if punctuation != PunctuationLevel::None && matches!(c, '\u{2190}'..='\u{21FF}') {
+        return Some(String::from("arrow"));
+    }
+    None
+}
+
+/// Get the Unicode name of an emoji, in lowercase ("hedgehog"), from the
+/// system's character database. The database is read the first time it's
+/// needed.
+fn unicode_name(c: char) -> Option<String> {
+    static NAMES: OnceLock<HashMap<char, String>> = OnceLock::new();
+    NAMES.get_or_init(load_emoji_names).get(&c).cloned()
+}
+
+/// Read the names of the emoji from the first Unicode character database
+/// found, or none if there isn't one.
+fn load_emoji_names() -> HashMap<char, String> {
+    let contents = UNICODE_DATA_FILES
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default();
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(';');
+            let c = char::from_u32(u32::from_str_radix(fields.next()?, 16).ok()?)?;
+            let name = fields.next()?;
+            // Ranges, like "<CJK Ideograph, First>", have no names.
+            (is_emoji(c) && !name.starts_with('<')).then(|| (c, name.to_lowercase()))
+        })
+        .collect()
+}
+
+/// The shortest run of a typed symbol, like `=` or `-`, that's counted
+/// rather than named each time. Shorter runs like `===` and `--` have names
+/// of their own.
+const MIN_COUNTED_SYMBOL_RUN: usize = 4;
+
+/// Replace each run of one symbol, like a line of `=` under a heading, with
+/// its name and how many there are ("equals 12 times"). The count is
+/// written without punctuation, so that it isn't named in turn.
+///
+/// # Arguments
+///
+/// * `text` - The text to read.
+/// * `name` - The name of a symbol, or None if it isn't named.
+///
+pub fn count_symbol_runs<'a>(text: &str, name: impl Fn(char) -> Option<&'a str>) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let mut run = 1;
+        if c.is_ascii_punctuation() {
+            while chars.next_if_eq(&c).is_some() {
+                run += 1;
+            }
+        }
+        match name(c) {
+            Some(name) if run >= MIN_COUNTED_SYMBOL_RUN => {
+                result.push_str(&format!(" {} {} times ", name, run));
+            }
+            _ => result.extend(std::iter::repeat_n(c, run)),
+        }
+    }
+    result
+}
+
+/// Replace emoji with their names ("rocket emoji"), and arrows and
+/// box-drawing characters with words, since speech engines tend to skip
+/// them or read them oddly. A run of one symbol, like a line of `─`, is
+/// named once with how many there are.
+pub fn name_symbols(text: &str, punctuation: PunctuationLevel) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars().filter(|c| !is_emoji_modifier(*c)).peekable();
+    while let Some(c) = chars.next() {
+        let name = match spoken_name(c, punctuation) {
+            Some(name) => name,
+            None => {
+                result.push(c);
+                continue;
+            }
+        };
+        let mut run = 1;
+        while chars.next_if_eq(&c).is_some() {
+            run += 1;
+        }
+        if name.is_empty() {
+            result.push(' ');
+        } else if run >= MIN_COUNTED_RUN {
+            result.push_str(&format!(" {}, {} times, ", name, run));
+        } else {
+            for _ in 0..run {
+                result.push_str(&format!(" {} ", name));
+            }
+        }
+    }
+    result
+}
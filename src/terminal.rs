@@ -1,9 +1,84 @@
 use crate::Position;
 use std::io::{self, stdout, Write};
-use termion::color;
-use termion::event::Key;
-use termion::input::TermRead;
-use termion::raw::{IntoRawMode, RawTerminal};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+#[cfg(any(windows, feature = "crossterm"))]
+mod crossterm_backend;
+#[cfg(all(unix, not(feature = "crossterm")))]
+mod termion_backend;
+
+#[cfg(any(windows, feature = "crossterm"))]
+type ActiveBackend = crossterm_backend::Crossterm;
+#[cfg(all(unix, not(feature = "crossterm")))]
+type ActiveBackend = termion_backend::Termion;
+
+/// A key press, whichever terminal library read it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Backspace,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    BackTab,
+    Delete,
+    Insert,
+    /// A function key, like F1.
+    F(u8),
+    /// A character, including `\n` for Enter and `\t` for Tab.
+    Char(char),
+    /// A character typed with Alt held. Alt-Shift-L is `Alt('L')`.
+    Alt(char),
+    /// A character typed with Ctrl held, in lowercase.
+    Ctrl(char),
+    /// Up and Down with Alt held.
+    AltUp,
+    AltDown,
+    /// The arrows with Shift held, which extend the selection.
+    ShiftUp,
+    ShiftDown,
+    ShiftLeft,
+    ShiftRight,
+    /// Ctrl-Space, which terminals send as a null byte.
+    Null,
+    Esc,
+}
+
+/// A 24-bit color.
+#[derive(Clone, Copy)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+/// What clack needs from a terminal library. Exactly one backend is built
+/// in: crossterm on Windows or with the `crossterm` feature, and termion
+/// everywhere else.
+trait Backend {
+    /// Restores the terminal's normal mode when dropped.
+    type RawMode;
+
+    fn enter_raw_mode() -> Result<Self::RawMode, io::Error>;
+    /// Get the terminal's width and height, in cells.
+    fn size() -> Result<(u16, u16), io::Error>;
+    fn clear_screen();
+    fn clear_current_line();
+    /// Move the cursor to a cell, counting from 0.
+    fn move_cursor(x: u16, y: u16);
+    fn hide_cursor();
+    fn show_cursor();
+    fn set_bg_color(color: Rgb);
+    fn reset_bg_color();
+    fn set_fg_color(color: Rgb);
+    fn reset_fg_color();
+    /// Read keys from stdin until it closes or the receiver hangs up. Runs
+    /// on the key thread.
+    fn read_keys(sender: &Sender<Result<Key, io::Error>>);
+}
 
 pub struct Size {
     pub width: u16,
@@ -11,65 +86,90 @@ pub struct Size {
 }
 pub struct Terminal {
     size: Size,
-    _stdout: RawTerminal<std::io::Stdout>,
+    _raw_mode: <ActiveBackend as Backend>::RawMode,
 }
 
 impl Terminal {
     pub fn default() -> Result<Self, std::io::Error> {
-        let size = termion::terminal_size()?;
+        let size = ActiveBackend::size()?;
         Ok(Self {
             size: Size {
                 width: size.0,
                 height: size.1.saturating_sub(2),
             },
-            _stdout: stdout().into_raw_mode()?,
+            _raw_mode: ActiveBackend::enter_raw_mode()?,
         })
     }
     pub fn size(&self) -> &Size {
         &self.size
     }
     pub fn clear_screen() {
-        print!("{}", termion::clear::All);
+        ActiveBackend::clear_screen();
     }
 
     #[allow(clippy::cast_possible_truncation)]
     pub fn cursor_position(position: &Position) {
-        let Position { mut x, mut y } = position;
-        x = x.saturating_add(1);
-        y = y.saturating_add(1);
-        let x = x as u16;
-        let y = y as u16;
-        print!("{}", termion::cursor::Goto(x, y));
+        let Position { x, y } = position;
+        ActiveBackend::move_cursor(*x as u16, *y as u16);
     }
     pub fn flush() -> Result<(), std::io::Error> {
-        io::stdout().flush()
+        stdout().flush()
     }
     pub fn read_key() -> Result<Key, std::io::Error> {
-        loop {
-            if let Some(key) = io::stdin().lock().keys().next() {
-                return key;
-            }
+        let keys = keys().lock().map_err(|_| closed_input())?;
+        keys.recv().unwrap_or_else(|_| Err(closed_input()))
+    }
+    /// Wait up to `timeout` for a key.
+    ///
+    /// # Returns
+    ///
+    /// The key, or None if none was pressed in time.
+    ///
+    pub fn poll_key(timeout: Duration) -> Option<Result<Key, std::io::Error>> {
+        let keys = match keys().lock() {
+            Ok(keys) => keys,
+            Err(_) => return Some(Err(closed_input())),
+        };
+        match keys.recv_timeout(timeout) {
+            Ok(key) => Some(key),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => Some(Err(closed_input())),
         }
     }
     pub fn cursor_hide() {
-        print!("{}", termion::cursor::Hide);
+        ActiveBackend::hide_cursor();
     }
     pub fn cursor_show() {
-        print!("{}", termion::cursor::Show);
+        ActiveBackend::show_cursor();
     }
     pub fn clear_current_line() {
-        print!("{}", termion::clear::CurrentLine);
+        ActiveBackend::clear_current_line();
     }
-    pub fn set_bg_color(color: color::Rgb) {
-        print!("{}", color::Bg(color));
+    pub fn set_bg_color(color: Rgb) {
+        ActiveBackend::set_bg_color(color);
     }
     pub fn reset_bg_color() {
-        print!("{}", color::Bg(color::Reset));
+        ActiveBackend::reset_bg_color();
     }
-    pub fn set_fg_color(color: color::Rgb) {
-        print!("{}", color::Fg(color));
+    pub fn set_fg_color(color: Rgb) {
+        ActiveBackend::set_fg_color(color);
     }
     pub fn reset_fg_color() {
-        print!("{}", color::Fg(color::Reset));
+        ActiveBackend::reset_fg_color();
     }
 }
+
+/// Keys read from stdin by a background thread, so that the editor can wait
+/// for a key and for something else (like speech finishing) at once.
+fn keys() -> &'static Mutex<Receiver<Result<Key, io::Error>>> {
+    static KEYS: OnceLock<Mutex<Receiver<Result<Key, io::Error>>>> = OnceLock::new();
+    KEYS.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || ActiveBackend::read_keys(&sender));
+        Mutex::new(receiver)
+    })
+}
+
+fn closed_input() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "stdin was closed")
+}
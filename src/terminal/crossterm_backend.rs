@@ -0,0 +1,131 @@
+use super::{Backend, Key, Rgb};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Color, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType};
+use crossterm::{Command, QueueableCommand};
+use std::io::{self, stdout};
+use std::sync::mpsc::Sender;
+
+/// The crossterm backend, used on Windows, where termion doesn't build.
+pub struct Crossterm;
+
+/// Leaves raw mode when dropped.
+pub struct RawMode;
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        terminal::disable_raw_mode().ok();
+    }
+}
+
+impl Backend for Crossterm {
+    type RawMode = RawMode;
+
+    fn enter_raw_mode() -> Result<Self::RawMode, io::Error> {
+        terminal::enable_raw_mode()?;
+        Ok(RawMode)
+    }
+    fn size() -> Result<(u16, u16), io::Error> {
+        terminal::size()
+    }
+    fn clear_screen() {
+        queue(Clear(ClearType::All));
+    }
+    fn clear_current_line() {
+        queue(Clear(ClearType::CurrentLine));
+    }
+    fn move_cursor(x: u16, y: u16) {
+        queue(MoveTo(x, y));
+    }
+    fn hide_cursor() {
+        queue(Hide);
+    }
+    fn show_cursor() {
+        queue(Show);
+    }
+    fn set_bg_color(Rgb(r, g, b): Rgb) {
+        queue(SetBackgroundColor(Color::Rgb { r, g, b }));
+    }
+    fn reset_bg_color() {
+        queue(SetBackgroundColor(Color::Reset));
+    }
+    fn set_fg_color(Rgb(r, g, b): Rgb) {
+        queue(SetForegroundColor(Color::Rgb { r, g, b }));
+    }
+    fn reset_fg_color() {
+        queue(SetForegroundColor(Color::Reset));
+    }
+
+    fn read_keys(sender: &Sender<Result<Key, io::Error>>) {
+        loop {
+            let key = match event::read() {
+                // Windows reports releases too; only presses are keys.
+                Ok(Event::Key(key)) if key.kind != KeyEventKind::Release => {
+                    match convert_key(key) {
+                        Some(key) => Ok(key),
+                        None => continue,
+                    }
+                }
+                Ok(_) => continue,
+                Err(error) => Err(error),
+            };
+            let failed = key.is_err();
+            if sender.send(key).is_err() || failed {
+                return;
+            }
+        }
+    }
+}
+
+/// Queue a command on stdout, to be written at the next flush. Like
+/// `print!`, which the termion backend uses, it's fire and forget.
+fn queue(command: impl Command) {
+    stdout().queue(command).ok();
+}
+
+/// Read a crossterm key the way termion would have, so that bindings are
+/// the same on every platform.
+fn convert_key(event: KeyEvent) -> Option<Key> {
+    let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = event.modifiers.contains(KeyModifiers::ALT);
+    let shift = event.modifiers.contains(KeyModifiers::SHIFT);
+    Some(match event.code {
+        // AltGr arrives as Ctrl-Alt, and types a plain character.
+        KeyCode::Char(c) if ctrl && alt => Key::Char(c),
+        KeyCode::Char(' ') if ctrl => Key::Null,
+        // Ctrl-] arrives as the byte 0x1D, which crossterm reads as Ctrl-5
+        // (except on Windows, which sees the key itself).
+        KeyCode::Char('5') if ctrl => Key::Ctrl(']'),
+        KeyCode::Char(c) if ctrl => Key::Ctrl(c.to_ascii_lowercase()),
+        KeyCode::Char(c) if alt => Key::Alt(c),
+        KeyCode::Char(c) => Key::Char(c),
+        KeyCode::Enter => Key::Char('\n'),
+        KeyCode::Tab => Key::Char('\t'),
+        // Ctrl- and Alt-Left/Right jump by word, like Alt-B and Alt-F.
+        KeyCode::Left if ctrl || alt => Key::Alt('b'),
+        KeyCode::Right if ctrl || alt => Key::Alt('f'),
+        KeyCode::Up if alt => Key::AltUp,
+        KeyCode::Down if alt => Key::AltDown,
+        KeyCode::Up if shift => Key::ShiftUp,
+        KeyCode::Down if shift => Key::ShiftDown,
+        KeyCode::Left if shift => Key::ShiftLeft,
+        KeyCode::Right if shift => Key::ShiftRight,
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Left => Key::Left,
+        KeyCode::Right => Key::Right,
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        KeyCode::Home => Key::Home,
+        KeyCode::End => Key::End,
+        KeyCode::PageUp => Key::PageUp,
+        KeyCode::PageDown => Key::PageDown,
+        KeyCode::BackTab => Key::BackTab,
+        KeyCode::Delete => Key::Delete,
+        KeyCode::Insert => Key::Insert,
+        KeyCode::F(n) => Key::F(n),
+        KeyCode::Null => Key::Null,
+        KeyCode::Esc => Key::Esc,
+        _ => return None,
+    })
+}
@@ -0,0 +1,120 @@
+use super::{Backend, Key, Rgb};
+use std::io::{self, stdout, Stdout};
+use std::sync::mpsc::Sender;
+use termion::color;
+use termion::event::{self, Event};
+use termion::input::TermRead;
+use termion::raw::{IntoRawMode, RawTerminal};
+
+/// Escape sequences for modified arrow keys, which termion doesn't parse,
+/// and the keys to read them as. Ctrl- and Alt-Left/Right become the
+/// Emacs-style Alt-B and Alt-F that many terminals send for them anyway.
+/// Alt-Up, Alt-Down and the Shift arrows are kept as they are.
+const MODIFIED_ARROW_KEYS: &[(&[u8], Key)] = &[
+    (b"\x1b[1;5C", Key::Alt('f')),
+    (b"\x1b[1;5D", Key::Alt('b')),
+    (b"\x1b[1;3C", Key::Alt('f')),
+    (b"\x1b[1;3D", Key::Alt('b')),
+    (b"\x1b[5C", Key::Alt('f')),
+    (b"\x1b[5D", Key::Alt('b')),
+    (b"\x1b[1;3A", Key::AltUp),
+    (b"\x1b[1;3B", Key::AltDown),
+    (b"\x1b[1;2A", Key::ShiftUp),
+    (b"\x1b[1;2B", Key::ShiftDown),
+    (b"\x1b[1;2C", Key::ShiftRight),
+    (b"\x1b[1;2D", Key::ShiftLeft),
+];
+
+/// The termion backend, used on Unix.
+pub struct Termion;
+
+impl Backend for Termion {
+    type RawMode = RawTerminal<Stdout>;
+
+    fn enter_raw_mode() -> Result<Self::RawMode, io::Error> {
+        stdout().into_raw_mode()
+    }
+    fn size() -> Result<(u16, u16), io::Error> {
+        termion::terminal_size()
+    }
+    fn clear_screen() {
+        print!("{}", termion::clear::All);
+    }
+    fn clear_current_line() {
+        print!("{}", termion::clear::CurrentLine);
+    }
+    fn move_cursor(x: u16, y: u16) {
+        // termion counts cells from 1.
+        print!(
+            "{}",
+            termion::cursor::Goto(x.saturating_add(1), y.saturating_add(1))
+        );
+    }
+    fn hide_cursor() {
+        print!("{}", termion::cursor::Hide);
+    }
+    fn show_cursor() {
+        print!("{}", termion::cursor::Show);
+    }
+    fn set_bg_color(Rgb(r, g, b): Rgb) {
+        print!("{}", color::Bg(color::Rgb(r, g, b)));
+    }
+    fn reset_bg_color() {
+        print!("{}", color::Bg(color::Reset));
+    }
+    fn set_fg_color(Rgb(r, g, b): Rgb) {
+        print!("{}", color::Fg(color::Rgb(r, g, b)));
+    }
+    fn reset_fg_color() {
+        print!("{}", color::Fg(color::Reset));
+    }
+
+    fn read_keys(sender: &Sender<Result<Key, io::Error>>) {
+        for event in io::stdin().events() {
+            let key = match event {
+                Ok(Event::Key(key)) => match convert_key(key) {
+                    Some(key) => Ok(key),
+                    None => continue,
+                },
+                Ok(Event::Unsupported(bytes)) => match MODIFIED_ARROW_KEYS
+                    .iter()
+                    .find(|(sequence, _)| *sequence == bytes.as_slice())
+                {
+                    Some((_, key)) => Ok(*key),
+                    None => continue,
+                },
+                Ok(_) => continue,
+                Err(error) => Err(error),
+            };
+            if sender.send(key).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+fn convert_key(key: event::Key) -> Option<Key> {
+    Some(match key {
+        event::Key::Backspace => Key::Backspace,
+        event::Key::Left => Key::Left,
+        event::Key::Right => Key::Right,
+        event::Key::Up => Key::Up,
+        event::Key::Down => Key::Down,
+        event::Key::Home => Key::Home,
+        event::Key::End => Key::End,
+        event::Key::PageUp => Key::PageUp,
+        event::Key::PageDown => Key::PageDown,
+        event::Key::BackTab => Key::BackTab,
+        event::Key::Delete => Key::Delete,
+        event::Key::Insert => Key::Insert,
+        event::Key::F(n) => Key::F(n),
+        event::Key::Char(c) => Key::Char(c),
+        event::Key::Alt(c) => Key::Alt(c),
+        // Ctrl-] arrives as the byte 0x1D, which termion reads as Ctrl-5.
+        event::Key::Ctrl('5') => Key::Ctrl(']'),
+        event::Key::Ctrl(c) => Key::Ctrl(c),
+        event::Key::Null => Key::Null,
+        event::Key::Esc => Key::Esc,
+        _ => return None,
+    })
+}
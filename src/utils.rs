@@ -1,3 +1,11 @@
+use std::{env, path::Path};
+
+use serde::Deserialize;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::config::DEFAULT_RATE_WPM;
+use crate::symbols::{count_symbol_runs, name_symbols};
+
 #[derive(PartialEq, Clone, Copy)]
 
 pub enum SearchDirection {
@@ -5,71 +13,732 @@ pub enum SearchDirection {
     Backward,
 }
 
+/// How URLs and filesystem paths are read aloud.
+#[derive(PartialEq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkReadingMode {
+    /// Speak only the host or file name, e.g. "link to github dot com".
+    Terse,
+    /// Spell the whole link out, symbol by symbol.
+    Full,
+    /// Leave links out of speech entirely.
+    Skip,
+}
+
+/// How many symbols are spoken by name when text is read, like a screen
+/// reader's punctuation level. Symbols that aren't named are left to the
+/// speech engine, which usually just pauses for them.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PunctuationLevel {
+    /// Name no symbols.
+    None,
+    /// Name operators and rarer symbols, like `=`, `&` and `#`.
+    Some,
+    /// Also name brackets, slashes, dashes and underscores.
+    Most,
+    /// Name every symbol, including quotes and sentence punctuation.
+    All,
+}
+
+impl PunctuationLevel {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Some => "some",
+            Self::Most => "most",
+            Self::All => "all",
+        }
+    }
+
+    /// The next level, wrapping from `All` back to `None`.
+    pub fn next(self) -> Self {
+        match self {
+            Self::None => Self::Some,
+            Self::Some => Self::Most,
+            Self::Most => Self::All,
+            Self::All => Self::None,
+        }
+    }
+}
+
+/// How numbers are read.
+#[derive(PartialEq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NumberReading {
+    /// Read numbers as values: "two hundred fifty-five".
+    Values,
+    /// Read numbers digit by digit: "two five five".
+    Digits,
+}
+
+impl NumberReading {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Values => "values",
+            Self::Digits => "digits",
+        }
+    }
+
+    /// The other mode, for switching with a hotkey.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Values => Self::Digits,
+            Self::Digits => Self::Values,
+        }
+    }
+}
+
+/// How identifiers like `getUserName` and `get_user_name` are read.
+#[derive(PartialEq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentifierReading {
+    /// Leave identifiers to the speech engine, which reads them as one word.
+    Whole,
+    /// Read each word of an identifier separately: "get user name".
+    Split,
+    /// Split identifiers and name their style first: "camel case get user
+    /// name".
+    Announce,
+}
+
+/// How indentation is heard when a line is read.
+#[derive(PartialEq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndentReading {
+    /// A tone for each level of indentation, before every line.
+    Tones,
+    /// "Entering block, depth 3" when a line is indented deeper than the
+    /// line read before it, and "Leaving block, depth 1" when it's indented
+    /// less.
+    Blocks,
+    /// Both tones and block announcements.
+    Both,
+    /// "Indent 3" before every indented line.
+    Level,
+    /// "Indent 3" (or "No indent") only when a line is indented differently
+    /// from the line read before it, so reading a block is quick.
+    Changes,
+    /// Indentation isn't heard at all.
+    None,
+}
+
+impl IndentReading {
+    pub fn plays_tones(self) -> bool {
+        matches!(self, Self::Tones | Self::Both)
+    }
+
+    pub fn announces_blocks(self) -> bool {
+        matches!(self, Self::Blocks | Self::Both)
+    }
+}
+
+/// What is read of a long line when it's soft wrapped across several
+/// screen lines.
+#[derive(PartialEq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WrappedLineReading {
+    /// Read the whole line.
+    Line,
+    /// Read just the screen line the cursor is on.
+    Segment,
+}
+
+/// What is spoken while typing.
+#[derive(PartialEq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TypingEcho {
+    /// Speak each word when a separator finishes it, and each symbol.
+    Words,
+    /// Speak every character typed, letters included.
+    Characters,
+    /// Speak every character, and each word when it's finished.
+    Both,
+    /// Speak nothing while typing.
+    None,
+}
+
+impl TypingEcho {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Words => "words",
+            Self::Characters => "characters",
+            Self::Both => "characters and words",
+            Self::None => "none",
+        }
+    }
+
+    /// The next mode, for cycling through them with a hotkey.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Words => Self::Characters,
+            Self::Characters => Self::Both,
+            Self::Both => Self::None,
+            Self::None => Self::Words,
+        }
+    }
+
+    pub fn speaks_words(self) -> bool {
+        matches!(self, Self::Words | Self::Both)
+    }
+
+    pub fn speaks_letters(self) -> bool {
+        matches!(self, Self::Characters | Self::Both)
+    }
+}
+
+/// How capital letters are pointed out when text is spelled or typed.
+#[derive(PartialEq, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CapitalReading {
+    /// Say "cap" before each capital.
+    Word,
+    /// Speak each capital at a higher pitch.
+    Pitch,
+    /// Leave capitals to the speech engine.
+    None,
+}
+
+/// Settings that control how text is turned into speech.
+#[derive(Clone)]
+pub struct SpeechOptions {
+    pub links: LinkReadingMode,
+    pub punctuation: PunctuationLevel,
+    pub identifiers: IdentifierReading,
+    pub numbers: NumberReading,
+    /// The speech rate, in words per minute.
+    pub rate_wpm: i64,
+    /// How many spaces make one level of indentation.
+    pub tab_width: usize,
+    /// Whether indentation is played as tones, announced as blocks, or both.
+    pub indentation: IndentReading,
+    /// The voice (or language) to speak with, or None for the default.
+    pub voice: Option<String>,
+    /// How string literals and comments sound, when a whole line is read.
+    pub token_voices: TokenVoices,
+    /// How the editor's messages, the document's text, and spelling sound.
+    pub context_voices: ContextVoices,
+    /// How far the document's text is panned right, and everything else
+    /// left, from 0.0 (both centred) to 1.0.
+    pub stereo_speech: f32,
+    /// Whether a run of one symbol, like a line of `=`, is named once with
+    /// how many there are.
+    pub count_repeated_symbols: bool,
+    /// How capital letters are pointed out when text is spelled or typed.
+    pub capitals: CapitalReading,
+}
+
+impl Default for SpeechOptions {
+    fn default() -> Self {
+        Self {
+            links: LinkReadingMode::Terse,
+            punctuation: PunctuationLevel::All,
+            identifiers: IdentifierReading::Split,
+            numbers: NumberReading::Values,
+            rate_wpm: DEFAULT_RATE_WPM,
+            tab_width: 4,
+            indentation: IndentReading::Tones,
+            voice: None,
+            token_voices: TokenVoices::default(),
+            context_voices: ContextVoices::default(),
+            stereo_speech: 0.0,
+            count_repeated_symbols: true,
+            capitals: CapitalReading::None,
+        }
+    }
+}
+
+/// Voice settings that override the defaults for some kind of text.
+/// Anything left out keeps the default.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct VoiceParameters {
+    /// The voice (or language) to speak with.
+    pub voice: Option<String>,
+    /// The speech rate, in words per minute.
+    pub rate_wpm: Option<i64>,
+    /// The pitch, from -100 to 100, where 0 is the voice's usual pitch.
+    pub pitch: Option<i64>,
+}
+
+/// The voice settings for each class of token, from the `[token_voices]`
+/// table in the config file. A class without settings is read in the
+/// line's voice, and with no classes set a line is read in one go.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TokenVoices {
+    pub string: Option<VoiceParameters>,
+    pub comment: Option<VoiceParameters>,
+}
+
+impl TokenVoices {
+    pub fn is_empty(&self) -> bool {
+        self.string.is_none() && self.comment.is_none()
+    }
+
+    /// Get the settings for a class of token, if it has any.
+    pub fn get(&self, class: TokenClass) -> Option<&VoiceParameters> {
+        match class {
+            TokenClass::Code => None,
+            TokenClass::String => self.string.as_ref(),
+            TokenClass::Comment => self.comment.as_ref(),
+        }
+    }
+}
+
+/// The voice settings for each kind of speech, from the `[context_voices]`
+/// table in the config file, so the editor talking sounds apart from the
+/// file being read. Anything left out speaks in the usual voice.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ContextVoices {
+    /// Status and other messages from the editor.
+    pub messages: Option<VoiceParameters>,
+    /// The document's own text: lines, words and selections.
+    pub content: Option<VoiceParameters>,
+    /// Characters, as they're typed or moved over, and spelled words.
+    pub spelling: Option<VoiceParameters>,
+}
+
+/// The kinds of text in a line of code that can be read in their own voice.
+#[derive(PartialEq, Clone, Copy)]
+pub enum TokenClass {
+    Code,
+    /// A quoted string, quotes included.
+    String,
+    /// A comment, from its marker to the end of the line (or the end of a
+    /// block comment).
+    Comment,
+}
+
+/// Terse names spoken when a symbol is typed.
+///
+/// This is deliberately separate from the reading replace map in
+/// `string_to_speakable_tokens`: when typing, short names keep up with the
+/// keyboard, whereas reading a line can afford longer descriptions. Entries
+/// can be overridden with the `[echo]` table in the config file.
+pub const DEFAULT_ECHO_MAP: &[(&str, &str)] = &[
+    ("[", "bracket"),
+    ("]", "close bracket"),
+    ("(", "paren"),
+    (")", "close paren"),
+    ("{", "brace"),
+    ("}", "close brace"),
+    ("<", "less"),
+    (">", "greater"),
+    (".", "dot"),
+    ("&", "and"),
+    ("!", "bang"),
+    ("#", "hash"),
+    ("$", "dollar"),
+    ("%", "percent"),
+    ("^", "caret"),
+    ("*", "star"),
+    ("+", "plus"),
+    ("-", "dash"),
+    ("=", "equals"),
+    ("\\", "backslash"),
+    ("|", "pipe"),
+    ("/", "slash"),
+    ("`", "tick"),
+    ("'", "quote"),
+    (",", "comma"),
+    (";", "semi"),
+    (":", "colon"),
+    ("\"", "double quote"),
+    ("?", "question"),
+    ("_", "underscore"),
+    ("~", "tilde"),
+    ("@", "at"),
+];
+
+/// The NATO phonetic alphabet, from a to z.
+const PHONETIC_ALPHABET: &[&str] = &[
+    "alfa", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliett",
+    "kilo", "lima", "mike", "november", "oscar", "papa", "quebec", "romeo", "sierra", "tango",
+    "uniform", "victor", "whiskey", "x-ray", "yankee", "zulu",
+];
+
+/// Get how a character is spelled phonetically: "f as in foxtrot", or
+/// "capital F as in foxtrot". Other characters are named like typed
+/// symbols, or left as they are.
+pub fn phonetic_name(c: char) -> String {
+    if !c.is_ascii_alphabetic() {
+        let symbol = c.to_string();
+        return default_echo_name(&symbol).map_or(symbol, String::from);
+    }
+    let word = PHONETIC_ALPHABET[(c.to_ascii_lowercase() as u8 - b'a') as usize];
+    if c.is_ascii_uppercase() {
+        format!("capital {} as in {}", c, word)
+    } else {
+        format!("{} as in {}", c, word)
+    }
+}
+
+/// Get the default spoken name for a typed symbol.
+pub fn default_echo_name(symbol: &str) -> Option<&'static str> {
+    DEFAULT_ECHO_MAP
+        .iter()
+        .find(|(candidate, _)| *candidate == symbol)
+        .map(|(_, name)| *name)
+}
+
 /// Create a speakable sentence from a string.
 /// This handles the following:
 /// - Replacing symbols with their spoken equivalent
 /// - Replacing diacritics with their spoken equivalent
 /// - Speaking common operations like [i] as "index at i"
+/// - Shortening or skipping URLs and paths, per `options.links`
+/// - Naming only as many symbols as `options.punctuation` asks for
+/// - Reading numbers as values or digits, per `options.numbers`, and hex,
+///   binary and octal literals digit by digit
+/// - Splitting camelCase and snake_case identifiers, per `options.identifiers`
+/// - Naming emoji, arrows and box-drawing characters
 ///
-pub fn string_to_speakable_tokens(text: &str, _: Option<usize>) -> String {
-    // pub fn string_to_speakable_tokens(text: &str, cursor_position: Option<usize>) -> String {
+pub fn string_to_speakable_tokens(text: &str, options: &SpeechOptions) -> String {
     let replace_map = vec![
-        ("===", "triple equals"),
-        ("```", "triple backtick"),
-        ("<=", "less than or equal to"),
-        (">=", "greater than or equal to"),
-        ("<>", "not equal to"),
-        ("<<", "left shift"),
-        (">>", "right shift"),
-        ("__", "dunder"),
-        ("==", "double equals"),
-        ("++", "plus plus"),
-        ("--", "minus minus"),
-        ("+=", "plus equals"),
-        ("-=", "minus equals"),
-        ("[", "square bracket"),
-        ("]", "close bracket"),
-        ("(", "open paren"),
-        (")", "close paren"),
-        ("{", "open curly brace"),
-        ("}", "close curly brace"),
-        ("<", "open angle bracket"),
-        (">", "close angle bracket"),
-        (".", "dot"),
-        ("&", "ref"),
-        ("!", "bang"),
-        ("#", "hash"),
-        ("$", "dollarsign"),
-        ("%", "percent"),
-        ("^", "caret"),
-        ("*", "asterisk"),
-        ("+", "plus"),
-        ("-", "minus"),
-        ("=", "equals"),
-        ("\\", "backslash"),
-        ("|", "pipe"),
-        ("/", "slash"),
-        ("`", "backtick"),
-        ("'", "single-quote"),
-        (",", "comma"),
-        (";", "semicolon"),
-        (":", "colon"),
-        ("\"", "double-quote"),
-        ("?", "question-mark"),
-        ("_", "underscore"),
-        ("~", "tilde"),
-        ("@", "at-sign"),
-        ("€", "euro"),
-        ("£", "pound"),
-        ("¥", "yen"),
+        ("===", "triple equals", PunctuationLevel::Some),
+        ("```", "triple backtick", PunctuationLevel::Most),
+        ("<=", "less than or equal to", PunctuationLevel::Some),
+        (">=", "greater than or equal to", PunctuationLevel::Some),
+        ("<>", "not equal to", PunctuationLevel::Some),
+        ("<<", "left shift", PunctuationLevel::Some),
+        (">>", "right shift", PunctuationLevel::Some),
+        ("__", "dunder", PunctuationLevel::Most),
+        ("==", "double equals", PunctuationLevel::Some),
+        ("++", "plus plus", PunctuationLevel::Some),
+        ("--", "minus minus", PunctuationLevel::Some),
+        ("+=", "plus equals", PunctuationLevel::Some),
+        ("-=", "minus equals", PunctuationLevel::Some),
+        ("[", "square bracket", PunctuationLevel::Most),
+        ("]", "close bracket", PunctuationLevel::Most),
+        ("(", "open paren", PunctuationLevel::Most),
+        (")", "close paren", PunctuationLevel::Most),
+        ("{", "open curly brace", PunctuationLevel::Most),
+        ("}", "close curly brace", PunctuationLevel::Most),
+        ("<", "open angle bracket", PunctuationLevel::Some),
+        (">", "close angle bracket", PunctuationLevel::Some),
+        (".", "dot", PunctuationLevel::All),
+        ("&", "ref", PunctuationLevel::Some),
+        ("!", "bang", PunctuationLevel::All),
+        ("#", "hash", PunctuationLevel::Some),
+        ("$", "dollarsign", PunctuationLevel::Some),
+        ("%", "percent", PunctuationLevel::Some),
+        ("^", "caret", PunctuationLevel::Some),
+        ("*", "asterisk", PunctuationLevel::Some),
+        ("+", "plus", PunctuationLevel::Some),
+        ("-", "minus", PunctuationLevel::Most),
+        ("=", "equals", PunctuationLevel::Some),
+        ("\\", "backslash", PunctuationLevel::Some),
+        ("|", "pipe", PunctuationLevel::Some),
+        ("/", "slash", PunctuationLevel::Most),
+        ("`", "backtick", PunctuationLevel::Most),
+        ("'", "single-quote", PunctuationLevel::All),
+        (",", "comma", PunctuationLevel::All),
+        (";", "semicolon", PunctuationLevel::All),
+        (":", "colon", PunctuationLevel::All),
+        ("\"", "double-quote", PunctuationLevel::All),
+        ("?", "question-mark", PunctuationLevel::All),
+        ("_", "underscore", PunctuationLevel::Most),
+        ("~", "tilde", PunctuationLevel::Some),
+        ("@", "at-sign", PunctuationLevel::Some),
+        ("€", "euro", PunctuationLevel::Some),
+        ("£", "pound", PunctuationLevel::Some),
+        ("¥", "yen", PunctuationLevel::Some),
     ];
 
-    let mut text_copy = text.clone().to_string();
-    for (symbol, replacement) in replace_map {
+    let mut text_copy = replace_links(text, options.links);
+    // Before identifiers are split, which would break 0xFF in two, and
+    // before symbol runs are counted, so that their counts aren't spelled
+    // out as digits.
+    text_copy = read_numbers(&text_copy, options.numbers);
+    if options.count_repeated_symbols {
+        text_copy = count_symbol_runs(&text_copy, |c| {
+            replace_map
+                .iter()
+                .find(|(symbol, _, level)| {
+                    *level <= options.punctuation && symbol.chars().eq(std::iter::once(c))
+                })
+                .map(|(_, name, _)| *name)
+        });
+    }
+    if options.identifiers != IdentifierReading::Whole {
+        text_copy = split_identifiers(
+            &text_copy,
+            options.identifiers == IdentifierReading::Announce,
+        );
+    }
+    for (symbol, replacement, level) in replace_map {
+        if level > options.punctuation {
+            continue;
+        }
         text_copy = text_copy
             .replace(symbol, format!(" {} ", replacement).as_str())
             .to_string();
     }
+    // Named last, so that the commas in "horizontal line, 40 times" are
+    // left as pauses.
+    text_copy = name_symbols(&text_copy, options.punctuation);
 
     return text_copy.to_string();
 }
+
+/// Spell out the digits of hex, binary and octal literals after their base
+/// ("hex f f" for `0xff`), and of every number when numbers are read as
+/// digits, leaving the rest of the text as it is.
+fn read_numbers(text: &str, mode: NumberReading) -> String {
+    let mut result = String::new();
+    for token in text.split_word_bounds() {
+        let prefixed = token.get(..2).map(str::to_ascii_lowercase);
+        let (base, digits, radix) = match prefixed.as_deref() {
+            Some("0x") => ("hex ", &token[2..], 16),
+            Some("0b") => ("binary ", &token[2..], 2),
+            Some("0o") => ("octal ", &token[2..], 8),
+            _ => ("", token, 10),
+        };
+        // A decimal point is read as "point" among the digits.
+        let is_number = digits.chars().any(|c| c.is_digit(radix))
+            && digits
+                .chars()
+                .all(|c| c.is_digit(radix) || c == '_' || (c == '.' && radix == 10));
+        if !is_number || (radix == 10 && mode == NumberReading::Values) {
+            result.push_str(token);
+            continue;
+        }
+        let spelled: Vec<String> = digits
+            .chars()
+            .filter(|c| *c != '_')
+            .map(|c| match c {
+                '.' => String::from("point"),
+                c => c.to_ascii_lowercase().to_string(),
+            })
+            .collect();
+        result.push_str(&format!(" {}{} ", base, spelled.join(" ")));
+    }
+    result
+}
+
+/// Split the identifiers in text into their words, leaving everything else
+/// as it is. Leading and trailing underscores are kept, to be named.
+fn split_identifiers(text: &str, announce_style: bool) -> String {
+    let mut result = String::new();
+    for token in text.split_word_bounds() {
+        let core = token.trim_matches('_');
+        match split_identifier(core) {
+            Some((style, words)) => {
+                if announce_style {
+                    result.push_str(style);
+                    result.push_str(" case ");
+                }
+                let leading = token.len() - token.trim_start_matches('_').len();
+                result.push_str(&token[..leading]);
+                result.push_str(&words.join(" "));
+                result.push_str(&token[leading + core.len()..]);
+            }
+            None => result.push_str(token),
+        }
+    }
+    result
+}
+
+/// Split an identifier into its words.
+///
+/// # Returns
+///
+/// The identifier's style ("camel", "pascal", "snake" or "constant") and its
+/// words, or None if it isn't made of more than one word.
+///
+pub fn split_identifier(word: &str) -> Option<(&'static str, Vec<&str>)> {
+    if word.is_empty() || !word.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    if word.contains('_') {
+        let words: Vec<&str> = word.split('_').collect();
+        if words.iter().any(|word| word.is_empty()) {
+            return None;
+        }
+        let style = if word.chars().any(char::is_lowercase) {
+            "snake"
+        } else {
+            "constant"
+        };
+        return Some((style, words));
+    }
+    // A word starts at a capital after a lowercase letter or digit, or at the
+    // last capital of a run followed by lowercase, as in "HTTPServer".
+    let chars: Vec<(usize, char)> = word.char_indices().collect();
+    let mut starts = vec![0];
+    for (n, &(i, c)) in chars.iter().enumerate().skip(1) {
+        let previous = chars[n - 1].1;
+        let next_is_lowercase = chars
+            .get(n + 1)
+            .is_some_and(|&(_, next)| next.is_lowercase());
+        if c.is_uppercase()
+            && (previous.is_lowercase()
+                || previous.is_numeric()
+                || (previous.is_uppercase() && next_is_lowercase))
+        {
+            starts.push(i);
+        }
+    }
+    if starts.len() < 2 {
+        return None;
+    }
+    let style = if chars[0].1.is_lowercase() {
+        "camel"
+    } else {
+        "pascal"
+    };
+    starts.push(word.len());
+    Some((
+        style,
+        starts
+            .windows(2)
+            .map(|pair| &word[pair[0]..pair[1]])
+            .collect(),
+    ))
+}
+
+/// Split text into words and symbols, dropping whitespace.
+pub fn split_tokens(text: &str) -> Vec<&str> {
+    text.split_word_bounds()
+        .filter(|token| !token.trim().is_empty())
+        .collect()
+}
+
+/// Split a line into runs of code, quoted strings and comments.
+///
+/// This is a guess that works across languages rather than a parser: strings
+/// are in double quotes or backticks, and comments start with `//`, `/*`,
+/// or a `#` or `--` at the start of the line or after a space (but not a
+/// Rust `#[` attribute). A string or
+/// block comment that isn't closed runs to the end of the line.
+///
+/// # Returns
+///
+/// The runs in order, each with its class. Together they make up the line.
+///
+pub fn split_token_classes(line: &str) -> Vec<(TokenClass, &str)> {
+    let mut runs = Vec::new();
+    let mut run_start = 0;
+    let mut chars = line.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let rest = &line[i..];
+        let after_space = line[..i].chars().last().is_none_or(char::is_whitespace);
+        let end = if c == '"' || c == '`' {
+            let mut escaped = false;
+            let close = chars.find(|&(_, next)| {
+                let closes = next == c && !escaped;
+                escaped = next == '\\' && !escaped;
+                closes
+            });
+            close.map_or(line.len(), |(j, _)| j + 1)
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            comment.find("*/").map_or(line.len(), |j| i + j + 4)
+        } else if rest.starts_with("//")
+            || (after_space && rest.starts_with('#') && !rest.starts_with("#["))
+            || (after_space && rest.starts_with("--"))
+        {
+            line.len()
+        } else {
+            continue;
+        };
+        let class = if c == '"' || c == '`' {
+            TokenClass::String
+        } else {
+            TokenClass::Comment
+        };
+        if run_start < i {
+            runs.push((TokenClass::Code, &line[run_start..i]));
+        }
+        runs.push((class, &line[i..end]));
+        run_start = end;
+        while chars.peek().is_some_and(|&(j, _)| j < end) {
+            chars.next();
+        }
+    }
+    if run_start < line.len() {
+        runs.push((TokenClass::Code, &line[run_start..]));
+    }
+    runs
+}
+
+/// Characters that commonly follow a link in prose without being part of it.
+const LINK_TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', ')', ']', '}', '\'', '"', '>'];
+
+/// Rewrite URLs and filesystem paths in a line according to a reading mode.
+///
+/// # Arguments
+///
+/// * `text` - The text to rewrite.
+/// * `mode` - How links should be read.
+///
+/// # Returns
+///
+/// The text with each link replaced by its spoken form.
+///
+pub fn replace_links(text: &str, mode: LinkReadingMode) -> String {
+    if mode == LinkReadingMode::Full {
+        return text.to_string();
+    }
+    text.split(' ')
+        .map(|word| {
+            let link = word.trim_end_matches(LINK_TRAILING_PUNCTUATION);
+            let trailing = &word[link.len()..];
+            let link = link.trim_start_matches(['(', '[', '<', '\'', '"']);
+            let leading = &word[..word.len() - trailing.len() - link.len()];
+            let spoken = if let Some(host) = url_host(link) {
+                format!("link to {}", host)
+            } else if let Some(file) = path_file_name(link) {
+                format!("path to {}", file)
+            } else {
+                return word.to_string();
+            };
+            match mode {
+                LinkReadingMode::Skip => String::new(),
+                _ => format!("{}{}{}", leading, spoken, trailing),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn url_host(word: &str) -> Option<&str> {
+    let rest = match word.find("://") {
+        Some(index) => &word[index + 3..],
+        None if word.starts_with("www.") => word,
+        None => return None,
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = host.rsplit('@').next().unwrap_or_default();
+    let host = host.trim_start_matches("www.");
+    if host.is_empty() {
+        None
+    } else {
+        Some(host)
+    }
+}
+
+fn path_file_name(word: &str) -> Option<&str> {
+    let is_relative = ["~/", "./", "../"]
+        .iter()
+        .any(|prefix| word.starts_with(prefix));
+    // A lone leading slash is too often an operator or comment to count, so
+    // absolute paths need at least two components.
+    let is_absolute = word.starts_with('/') && word[1..].trim_end_matches('/').contains('/');
+    let is_windows = word.get(1..).is_some_and(|rest| rest.starts_with(":\\"));
+    if !(is_relative || is_absolute || is_windows) {
+        return None;
+    }
+    word.trim_end_matches(['/', '\\'])
+        .rsplit(['/', '\\'])
+        .next()
+        .filter(|name| name.chars().any(char::is_alphanumeric))
+}
+
+/// Whether an executable with this name is on the PATH.
+pub fn is_on_path(program: &str) -> bool {
+    env::var_os("PATH").is_some_and(|paths| {
+        env::split_paths(&paths).any(|dir| Path::new(&dir).join(program).is_file())
+    })
+}
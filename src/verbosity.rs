@@ -0,0 +1,106 @@
+use serde::Deserialize;
+
+/// The kinds of announcement the editor makes, each with its own verbosity.
+#[derive(Clone, Copy, PartialEq)]
+pub enum AnnouncementClass {
+    /// Where the cursor is, or where it moved to.
+    Navigation,
+    /// The result of a change to the document, like saving.
+    Editing,
+    /// Prompts that ask the user for input.
+    Prompts,
+    /// Things that went wrong.
+    Errors,
+}
+
+impl AnnouncementClass {
+    pub const ALL: [Self; 4] = [Self::Navigation, Self::Editing, Self::Prompts, Self::Errors];
+
+    /// The name used for this class in the config file and in speech.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Navigation => "navigation",
+            Self::Editing => "editing",
+            Self::Prompts => "prompts",
+            Self::Errors => "errors",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verbosity {
+    Terse,
+    Verbose,
+}
+
+impl Verbosity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Terse => "terse",
+            Self::Verbose => "verbose",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Terse => Self::Verbose,
+            Self::Verbose => Self::Terse,
+        }
+    }
+}
+
+/// The verbosity chosen for each announcement class.
+#[derive(Clone, Copy)]
+pub struct VerbositySettings {
+    levels: [Verbosity; 4],
+}
+
+impl Default for VerbositySettings {
+    fn default() -> Self {
+        Self {
+            levels: [Verbosity::Verbose; 4],
+        }
+    }
+}
+
+impl VerbositySettings {
+    fn index(class: AnnouncementClass) -> usize {
+        AnnouncementClass::ALL
+            .iter()
+            .position(|candidate| *candidate == class)
+            .unwrap_or_default()
+    }
+
+    pub fn get(&self, class: AnnouncementClass) -> Verbosity {
+        self.levels[Self::index(class)]
+    }
+
+    pub fn set(&mut self, class: AnnouncementClass, verbosity: Verbosity) {
+        self.levels[Self::index(class)] = verbosity;
+    }
+
+    /// Pick the phrasing of an announcement for its class's verbosity.
+    ///
+    /// # Arguments
+    ///
+    /// * `class` - The kind of announcement.
+    /// * `verbose` - The full phrasing.
+    /// * `terse` - The short phrasing.
+    ///
+    /// # Returns
+    ///
+    /// The phrasing to speak.
+    ///
+    pub fn format<'a>(
+        &self,
+        class: AnnouncementClass,
+        verbose: &'a str,
+        terse: &'a str,
+    ) -> &'a str {
+        match self.get(class) {
+            Verbosity::Verbose => verbose,
+            Verbosity::Terse => terse,
+        }
+    }
+}